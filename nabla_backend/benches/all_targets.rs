@@ -0,0 +1,90 @@
+//! Measures the conversion of one large value into all four targets.
+//!
+//! The converters borrow the value, so it is not cloned upfront
+//! and every target allocates only its own output tree.
+//!
+//! Run with `cargo bench -p nabla_backend --bench all_targets`.
+
+use nabla_backend::{to_json_value, to_toml_value, to_xml_value, to_yaml_value};
+use nabla_frontend::eval::Value;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+/// Number of servers in the converted value
+const SERVERS: usize = 100_000;
+
+/// Allocator that keeps track of the allocated bytes.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run the conversion and print its time and the bytes it allocated.
+fn measure<T>(name: &str, convert: impl FnOnce() -> T) {
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    let start = Instant::now();
+    let output = convert();
+    let elapsed = start.elapsed();
+    let allocated = ALLOCATED.load(Ordering::SeqCst) - before;
+    println!(
+        "{:<6} {:>8.1} MiB allocated {:>8.1} ms",
+        name,
+        mib(allocated),
+        elapsed.as_secs_f64() * 1000.0
+    );
+    drop(output);
+}
+
+fn main() {
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    let servers: Vec<_> = (0..SERVERS)
+        .map(|index| {
+            Value::from([
+                ("host", Value::from(format!("server-{}.example.com", index))),
+                ("port", Value::from(8080)),
+                ("enabled", Value::Bool(index % 2 == 0)),
+            ])
+        })
+        .collect();
+    let value = Value::from([("servers", Value::List(servers))]);
+    println!(
+        "value  {:>8.1} MiB allocated",
+        mib(ALLOCATED.load(Ordering::SeqCst) - before)
+    );
+
+    measure("json", || {
+        to_json_value(&value).expect("Conversion must succeed")
+    });
+    measure("yaml", || {
+        to_yaml_value(&value).expect("Conversion must succeed")
+    });
+    measure("toml", || {
+        to_toml_value(&value).expect("Conversion must succeed")
+    });
+    measure("xml", || {
+        to_xml_value(&value, "root").expect("Conversion must succeed")
+    });
+}
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
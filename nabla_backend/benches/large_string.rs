@@ -0,0 +1,81 @@
+//! Measures the memory used to convert a config with a large embedded string.
+//!
+//! Run with `cargo bench -p nabla_backend --bench large_string`.
+
+use nabla_backend::to_json_value;
+use nabla_frontend::{
+    lexer::{self, LexerResult},
+    parser::{self, ParserResult},
+    semantics::{self, SemanticsResult},
+    GlobalIdent, ModuleAst,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+/// Size of the embedded string in bytes
+const BLOB_SIZE: usize = 10 * 1024 * 1024;
+
+/// Allocator that keeps track of the currently and maximally allocated bytes.
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let src = format!(
+        "def Config = {{\n    blob: String\n}}\nConfig {{\n    blob = \"{}\"\n}}\n",
+        "x".repeat(BLOB_SIZE)
+    );
+    let baseline = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    let start = Instant::now();
+
+    let LexerResult { tokens, errors } = lexer::lex(&src);
+    assert!(errors.is_empty());
+    let ParserResult { ast, errors } = parser::parse(&tokens);
+    assert!(errors.is_empty());
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert!(errors.is_empty());
+    let init = inits.first().expect("Init must exist");
+    let json = to_json_value(init).expect("Conversion must succeed");
+
+    let elapsed = start.elapsed();
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+    let retained = CURRENT.load(Ordering::SeqCst) - baseline;
+    println!("blob size:      {:>6.1} MiB", mib(BLOB_SIZE));
+    println!(
+        "peak memory:    {:>6.1} MiB ({:.1}x blob size)",
+        mib(peak),
+        peak as f64 / BLOB_SIZE as f64
+    );
+    println!("retained:       {:>6.1} MiB", mib(retained));
+    println!("time:           {:>6.1} ms", elapsed.as_secs_f64() * 1000.0);
+    drop((json, inits, module_ast, tokens));
+}
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
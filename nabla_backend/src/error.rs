@@ -1,13 +1,32 @@
+use nabla_frontend::eval::ValuePath;
 use std::num::{ParseFloatError, ParseIntError};
 
 use thiserror::Error;
 
+/// Error of a conversion, located by the path of the value that could not be converted.
+#[derive(Debug, Error)]
+#[error("{path}: {error}")]
+pub struct ConversionError<E> {
+    pub path: ValuePath,
+    pub error: E,
+}
+
+impl<E> ConversionError<E> {
+    pub const fn new(path: ValuePath, error: E) -> Self {
+        Self { path, error }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum JsonValueError {
     #[error(transparent)]
     UnknownValueError(#[from] UnknownValueError),
     #[error(transparent)]
     NumberParseError(#[from] serde_json::Error),
+    #[error("`{0}` is not a finite number")]
+    NonFiniteNumber(String),
+    #[error("writing JSON failed: {0}")]
+    WriteError(std::io::Error),
 }
 
 #[derive(Debug, Error)]
@@ -16,6 +35,8 @@ pub enum YamlValueError {
     UnknownValueError(#[from] UnknownValueError),
     #[error(transparent)]
     NumberParseError(#[from] serde_yaml::Error),
+    #[error("`{0}` is not a finite number")]
+    NonFiniteNumber(String),
 }
 
 #[derive(Debug, Error)]
@@ -26,6 +47,14 @@ pub enum TomlValueError {
     ParseIntError(#[from] ParseIntError),
     #[error(transparent)]
     ParseFloatError(#[from] ParseFloatError),
+    #[error("`{0}` is not a finite number")]
+    NonFiniteNumber(String),
+    #[error("null cannot be represented, because TOML has no null")]
+    NullNotRepresentable(ValuePath),
+    #[error("top-level value must be a struct, because TOML documents are tables")]
+    StructlessRoot,
+    #[error("top-level value is null, so there is nothing to emit")]
+    NothingToEmit,
 }
 
 #[derive(Debug, Error)]
@@ -34,8 +63,24 @@ pub enum XmlValueError {
     UnknownValueError(#[from] UnknownValueError),
     #[error("list must be contained inside a struct")]
     StructlessList,
+    #[error("attribute `{0}` must be a primitive value")]
+    InvalidAttributeValue(String),
+    #[error("text of an element cannot be mixed with child elements")]
+    MixedContent,
+    #[error("`{0}` is not a finite number")]
+    NonFiniteNumber(String),
 }
 
 #[derive(Clone, Copy, Debug, Error)]
 #[error("value is (partially) unknown")]
 pub struct UnknownValueError;
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("mapping keys must be strings, numbers or booleans")]
+    UnsupportedKey,
+    #[error(transparent)]
+    UnknownValueError(#[from] UnknownValueError),
+    #[error("`{0}` cannot be written as a nabla number")]
+    InvalidNumber(String),
+}
@@ -0,0 +1,77 @@
+use crate::error::ImportError;
+use nabla_frontend::eval::Value;
+
+/// Convert a JSON value into a nabla value.
+pub fn import_json(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Bool(b),
+        serde_json::Value::Number(n) => Value::Number(n.to_string()),
+        serde_json::Value::String(s) => Value::from(s),
+        serde_json::Value::Array(array) => {
+            Value::List(array.into_iter().map(import_json).collect())
+        }
+        serde_json::Value::Object(object) => Value::Struct(
+            object
+                .into_iter()
+                .map(|(k, v)| (k, import_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert a YAML value into a nabla value.
+/// Tags are ignored and only the tagged value is converted.
+///
+/// Fails, if a mapping key is not a scalar.
+pub fn import_yaml(value: serde_yaml::Value) -> Result<Value, ImportError> {
+    match value {
+        serde_yaml::Value::Null => Ok(Value::Null),
+        serde_yaml::Value::Bool(b) => Ok(Value::Bool(b)),
+        serde_yaml::Value::Number(n) => Ok(Value::Number(n.to_string())),
+        serde_yaml::Value::String(s) => Ok(Value::from(s)),
+        serde_yaml::Value::Sequence(sequence) => {
+            let list = sequence
+                .into_iter()
+                .map(import_yaml)
+                .collect::<Result<Vec<_>, ImportError>>()?;
+            Ok(Value::List(list))
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            let map = mapping
+                .into_iter()
+                .map(|(k, v)| {
+                    let key = match k {
+                        serde_yaml::Value::String(s) => s,
+                        serde_yaml::Value::Number(n) => n.to_string(),
+                        serde_yaml::Value::Bool(b) => b.to_string(),
+                        _ => Err(ImportError::UnsupportedKey)?,
+                    };
+                    import_yaml(v).map(|v| (key, v))
+                })
+                .collect::<Result<_, ImportError>>()?;
+            Ok(Value::Struct(map))
+        }
+        serde_yaml::Value::Tagged(tagged) => import_yaml(tagged.value),
+    }
+}
+
+/// Convert a TOML value into a nabla value.
+/// Dates and times are converted to strings in their TOML notation.
+pub fn import_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::from(s),
+        toml::Value::Integer(i) => Value::Number(i.to_string()),
+        // the debug notation keeps the decimal point of whole floats, e.g. `1.0`
+        toml::Value::Float(f) => Value::Number(format!("{:?}", f)),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::from(datetime.to_string()),
+        toml::Value::Array(array) => Value::List(array.into_iter().map(import_toml).collect()),
+        toml::Value::Table(table) => Value::Struct(
+            table
+                .into_iter()
+                .map(|(k, v)| (k, import_toml(v)))
+                .collect(),
+        ),
+    }
+}
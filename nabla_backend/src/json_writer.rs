@@ -0,0 +1,141 @@
+use crate::{
+    error::{ConversionError, JsonValueError, UnknownValueError},
+    non_finite, ConversionErrors, ConvertOptions,
+};
+use nabla_frontend::eval::{Value, ValuePath};
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
+use std::{cell::RefCell, io, str::FromStr};
+
+/// Write the value as JSON, compact or indented by two spaces.
+///
+/// The JSON is serialized straight from the value without building a `serde_json::Value`,
+/// so the output is the same as of [`to_json_value`](crate::to_json_value).
+/// Nothing is written, if the value cannot be converted.
+pub fn write_json<W: io::Write>(
+    value: &Value,
+    writer: W,
+    pretty: bool,
+) -> Result<(), JsonValueError> {
+    let options = ConvertOptions::default();
+    let result = if pretty {
+        write_json_with_formatter(value, writer, PrettyFormatter::new(), &options)
+    } else {
+        write_json_with_formatter(value, writer, CompactFormatter, &options)
+    };
+    result.map_err(crate::first_error)
+}
+
+/// Serialize the value into a JSON string, see [`write_json`].
+pub fn to_json_string(value: &Value, pretty: bool) -> Result<String, JsonValueError> {
+    let mut json = Vec::new();
+    write_json(value, &mut json, pretty)?;
+    Ok(String::from_utf8(json).expect("JSON is valid UTF-8"))
+}
+
+/// Write the value as JSON in the layout of the formatter
+/// and report all errors with their paths.
+///
+/// The whole value is checked before anything is written,
+/// so the writer only receives complete JSON.
+pub fn write_json_with_formatter<W: io::Write, F: Formatter>(
+    value: &Value,
+    writer: W,
+    formatter: F,
+    options: &ConvertOptions,
+) -> Result<(), ConversionErrors<JsonValueError>> {
+    serialize(value, io::sink(), CompactFormatter, options)?;
+    serialize(value, writer, formatter, options)
+}
+
+fn serialize<W: io::Write, F: Formatter>(
+    value: &Value,
+    writer: W,
+    formatter: F,
+    options: &ConvertOptions,
+) -> Result<(), ConversionErrors<JsonValueError>> {
+    let errors = RefCell::new(Vec::new());
+    let json = Json {
+        value,
+        path: ValuePath::root(),
+        options,
+        errors: &errors,
+    };
+    let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
+    let result = json.serialize(&mut serializer);
+    let mut errors = errors.into_inner();
+    if let Err(error) = result {
+        // conversion errors are recorded and written as null, so only writing can fail
+        errors.push(ConversionError::new(
+            ValuePath::root(),
+            JsonValueError::WriteError(error.into()),
+        ));
+    }
+    crate::finish((), errors)
+}
+
+/// Value at its path, that records its conversion errors and is serialized as null instead.
+struct Json<'a> {
+    value: &'a Value,
+    path: ValuePath,
+    options: &'a ConvertOptions,
+    errors: &'a RefCell<ConversionErrors<JsonValueError>>,
+}
+
+impl Json<'_> {
+    fn child<'a>(&'a self, value: &'a Value, path: ValuePath) -> Json<'a> {
+        Json {
+            value,
+            path,
+            options: self.options,
+            errors: self.errors,
+        }
+    }
+
+    fn record<S: Serializer>(
+        &self,
+        error: JsonValueError,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.errors
+            .borrow_mut()
+            .push(ConversionError::new(self.path.clone(), error));
+        serializer.serialize_unit()
+    }
+}
+
+impl Serialize for Json<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            Value::Unknown if self.options.unknown_as_null => serializer.serialize_unit(),
+            Value::Unknown => self.record(UnknownValueError.into(), serializer),
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Number(n) if non_finite(n).is_some() => {
+                self.record(JsonValueError::NonFiniteNumber(n.clone()), serializer)
+            }
+            Value::Number(n) => match serde_json::Number::from_str(n) {
+                Ok(number) => number.serialize(serializer),
+                Err(error) => self.record(error.into(), serializer),
+            },
+            Value::String(s) => serializer.serialize_str(s),
+            Value::List(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for (index, value) in list.iter().enumerate() {
+                    seq.serialize_element(&self.child(value, self.path.join_index(index)))?;
+                }
+                seq.end()
+            }
+            Value::Struct(s) => {
+                let mut map = serializer.serialize_map(Some(s.len()))?;
+                for (key, value) in s {
+                    map.serialize_entry(key, &self.child(value, self.path.join_key(key)))?;
+                }
+                map.end()
+            }
+        }
+    }
+}
@@ -1,130 +1,532 @@
-use error::{JsonValueError, TomlValueError, UnknownValueError, XmlValueError, YamlValueError};
-use nabla_frontend::eval::Value;
+use error::{
+    ConversionError, JsonValueError, TomlValueError, UnknownValueError, XmlValueError,
+    YamlValueError,
+};
+pub use import::{import_json, import_toml, import_yaml};
+pub use json_writer::{to_json_string, write_json, write_json_with_formatter};
+pub use nabla_document::{from_json_value, from_toml_value, from_yaml_value, to_nabla_string};
+use nabla_frontend::eval::{Value, ValuePath};
 use std::str::FromStr;
+pub use toml_document::{
+    to_toml_string, to_toml_string_with_docs, to_toml_table, NullBehavior, TomlOptions,
+};
 use xml_builder::XMLElement;
+pub use xml_document::{
+    to_xml_document, to_xml_document_all_errors, XmlListStyle, XmlOptions, XmlVersion,
+};
+pub use yaml_document::{to_yaml_documents, to_yaml_string_with_docs};
 
 pub mod error;
+mod import;
+mod json_writer;
+mod nabla_document;
+#[cfg(test)]
+mod tests;
+mod toml_document;
+mod xml_document;
+mod yaml_document;
 
-pub fn to_json_value(value: Value) -> Result<serde_json::Value, JsonValueError> {
+/// Options of all converters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConvertOptions {
+    /// Convert unknown values as null instead of reporting them, which loses the unknown parts.
+    /// JSON and YAML emit `null`, TOML leaves the key out
+    /// and XML emits an empty element with a comment.
+    pub unknown_as_null: bool,
+}
+
+/// Comment of an unknown value in XML, if it is converted as null
+const XML_UNKNOWN_COMMENT: &str = "<!-- unknown value -->";
+
+/// Prefix of the fields, that are emitted as attributes of their element in XML, e.g. `@host`
+const XML_ATTRIBUTE_PREFIX: char = '@';
+
+/// Name of the field, that is emitted as text of its element in XML
+const XML_TEXT_KEY: &str = "#text";
+
+/// Paths of all unknown values in the order of the converters,
+/// e.g. to report the values that are converted as null.
+pub fn unknown_paths(value: &Value) -> Vec<ValuePath> {
+    let mut paths = Vec::new();
+    collect_unknown_paths(value, &ValuePath::root(), &mut paths);
+    paths
+}
+
+fn collect_unknown_paths(value: &Value, path: &ValuePath, paths: &mut Vec<ValuePath>) {
     match value {
-        Value::Unknown => Err(UnknownValueError)?,
-        Value::Null => Ok(serde_json::Value::Null),
-        Value::Bool(b) => Ok(serde_json::Value::Bool(b)),
-        Value::Number(n) => {
-            let number = serde_json::Number::from_str(&n)?;
-            Ok(serde_json::Value::Number(number))
-        }
-        Value::String(s) => Ok(serde_json::Value::String(s)),
+        Value::Unknown => paths.push(path.clone()),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {}
         Value::List(list) => {
-            let array = list
-                .into_iter()
-                .map(to_json_value)
-                .collect::<Result<Vec<_>, JsonValueError>>()?;
-            Ok(serde_json::Value::Array(array))
+            for (index, value) in list.iter().enumerate() {
+                collect_unknown_paths(value, &path.join_index(index), paths);
+            }
         }
         Value::Struct(s) => {
-            let object = s
-                .into_iter()
-                .map(|(k, v)| to_json_value(v).map(|v| (k, v)))
-                .collect::<Result<serde_json::Map<_, _>, JsonValueError>>()?;
-            Ok(serde_json::Value::Object(object))
+            for (key, value) in s {
+                collect_unknown_paths(value, &path.join_key(key), paths);
+            }
         }
     }
 }
 
-pub fn to_yaml_value(value: Value) -> Result<serde_yaml::Value, YamlValueError> {
+/// Parse numbers, that are infinite or not a number, e.g. `inf`, `-.inf` (as in YAML) or `1e999`.
+fn non_finite(n: &str) -> Option<f64> {
+    let (sign, unsigned) = match n.strip_prefix('-') {
+        Some(unsigned) => (-1.0, unsigned),
+        None => (1.0, n.strip_prefix('+').unwrap_or(n)),
+    };
+    let unsigned = unsigned.strip_prefix('.').unwrap_or(unsigned);
+    f64::from_str(unsigned)
+        .ok()
+        .filter(|n| !n.is_finite())
+        .map(|n| sign * n)
+}
+
+/// Errors of a conversion, that traversed the whole value.
+pub type ConversionErrors<E> = Vec<ConversionError<E>>;
+
+/// Collect the error of a failed conversion step.
+fn record<T, E>(
+    result: Result<T, E>,
+    path: &ValuePath,
+    errors: &mut ConversionErrors<E>,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(error) => {
+            errors.push(ConversionError::new(path.clone(), error));
+            None
+        }
+    }
+}
+
+/// Only return the converted value, if no error occurred anywhere.
+fn finish<T, E>(value: T, errors: ConversionErrors<E>) -> Result<T, ConversionErrors<E>> {
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(errors)
+    }
+}
+
+fn first_error<E>(errors: ConversionErrors<E>) -> E {
+    errors
+        .into_iter()
+        .next()
+        .expect("Failed conversion has at least one error")
+        .error
+}
+
+/// Convert the value to JSON.
+///
+/// Empty structs and lists are emitted as `{}` and `[]`, null as `null`,
+/// at the top level as well as nested.
+pub fn to_json_value(value: &Value) -> Result<serde_json::Value, JsonValueError> {
+    to_json_value_all_errors(value).map_err(first_error)
+}
+
+/// Convert the owned value to JSON, see [`to_json_value`].
+pub fn into_json_value(value: Value) -> Result<serde_json::Value, JsonValueError> {
+    to_json_value(&value)
+}
+
+/// Convert the whole value and report all errors with their paths.
+pub fn to_json_value_all_errors(
+    value: &Value,
+) -> Result<serde_json::Value, ConversionErrors<JsonValueError>> {
+    to_json_value_with_options(value, &ConvertOptions::default())
+}
+
+/// Convert the whole value with the given options and report all errors with their paths.
+pub fn to_json_value_with_options(
+    value: &Value,
+    options: &ConvertOptions,
+) -> Result<serde_json::Value, ConversionErrors<JsonValueError>> {
+    let mut errors = Vec::new();
+    let json = json_value(value, &ValuePath::root(), options, &mut errors);
+    finish(json, errors)
+}
+
+fn json_value(
+    value: &Value,
+    path: &ValuePath,
+    options: &ConvertOptions,
+    errors: &mut ConversionErrors<JsonValueError>,
+) -> serde_json::Value {
     match value {
-        Value::Unknown => Err(UnknownValueError)?,
-        Value::Null => Ok(serde_yaml::Value::Null),
-        Value::Bool(b) => Ok(serde_yaml::Value::Bool(b)),
+        Value::Unknown if options.unknown_as_null => serde_json::Value::Null,
+        Value::Unknown => record(Err(UnknownValueError.into()), path, errors).unwrap_or_default(),
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Number(n) if non_finite(n).is_some() => record(
+            Err(JsonValueError::NonFiniteNumber(n.clone())),
+            path,
+            errors,
+        )
+        .unwrap_or_default(),
         Value::Number(n) => {
-            let number = serde_yaml::Number::from_str(&n)?;
-            Ok(serde_yaml::Value::Number(number))
-        }
-        Value::String(s) => Ok(serde_yaml::Value::String(s)),
-        Value::List(list) => {
-            let array = list
-                .into_iter()
-                .map(to_yaml_value)
-                .collect::<Result<Vec<_>, YamlValueError>>()?;
-            Ok(serde_yaml::Value::Sequence(array))
+            let number = serde_json::Number::from_str(n)
+                .map(serde_json::Value::Number)
+                .map_err(JsonValueError::from);
+            record(number, path, errors).unwrap_or_default()
         }
-        Value::Struct(s) => {
-            let object = s
-                .into_iter()
-                .map(|(k, v)| to_yaml_value(v).map(|v| (serde_yaml::Value::String(k), v)))
-                .collect::<Result<serde_yaml::Mapping, YamlValueError>>()?;
-            Ok(serde_yaml::Value::Mapping(object))
+        // serde values own their strings, so only the leaves and keys are copied
+        Value::String(s) => serde_json::Value::String(s.to_string()),
+        Value::List(list) => serde_json::Value::Array(
+            list.iter()
+                .enumerate()
+                .map(|(index, value)| json_value(value, &path.join_index(index), options, errors))
+                .collect(),
+        ),
+        Value::Struct(s) => serde_json::Value::Object(
+            s.iter()
+                .map(|(k, v)| {
+                    let v = json_value(v, &path.join_key(k), options, errors);
+                    (k.clone(), v)
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Convert the value to YAML.
+///
+/// Empty structs and lists are emitted as `{}` and `[]`, null as `null`,
+/// at the top level as well as nested.
+pub fn to_yaml_value(value: &Value) -> Result<serde_yaml::Value, YamlValueError> {
+    to_yaml_value_all_errors(value).map_err(first_error)
+}
+
+/// Convert the owned value to YAML, see [`to_yaml_value`].
+pub fn into_yaml_value(value: Value) -> Result<serde_yaml::Value, YamlValueError> {
+    to_yaml_value(&value)
+}
+
+/// Convert the whole value and report all errors with their paths.
+pub fn to_yaml_value_all_errors(
+    value: &Value,
+) -> Result<serde_yaml::Value, ConversionErrors<YamlValueError>> {
+    to_yaml_value_with_options(value, &ConvertOptions::default())
+}
+
+/// Convert the whole value with the given options and report all errors with their paths.
+pub fn to_yaml_value_with_options(
+    value: &Value,
+    options: &ConvertOptions,
+) -> Result<serde_yaml::Value, ConversionErrors<YamlValueError>> {
+    let mut errors = Vec::new();
+    let yaml = yaml_value(value, &ValuePath::root(), options, &mut errors);
+    finish(yaml, errors)
+}
+
+fn yaml_value(
+    value: &Value,
+    path: &ValuePath,
+    options: &ConvertOptions,
+    errors: &mut ConversionErrors<YamlValueError>,
+) -> serde_yaml::Value {
+    match value {
+        Value::Unknown if options.unknown_as_null => serde_yaml::Value::Null,
+        Value::Unknown => record(Err(UnknownValueError.into()), path, errors).unwrap_or_default(),
+        Value::Null => serde_yaml::Value::Null,
+        Value::Bool(b) => serde_yaml::Value::Bool(*b),
+        Value::Number(n) if non_finite(n).is_some() => record(
+            Err(YamlValueError::NonFiniteNumber(n.clone())),
+            path,
+            errors,
+        )
+        .unwrap_or_default(),
+        Value::Number(n) => {
+            let number = serde_yaml::Number::from_str(n)
+                .map(serde_yaml::Value::Number)
+                .map_err(YamlValueError::from);
+            record(number, path, errors).unwrap_or_default()
         }
+        Value::String(s) => serde_yaml::Value::String(s.to_string()),
+        Value::List(list) => serde_yaml::Value::Sequence(
+            list.iter()
+                .enumerate()
+                .map(|(index, value)| yaml_value(value, &path.join_index(index), options, errors))
+                .collect(),
+        ),
+        Value::Struct(s) => serde_yaml::Value::Mapping(
+            s.iter()
+                .map(|(k, v)| {
+                    let v = yaml_value(v, &path.join_key(k), options, errors);
+                    (serde_yaml::Value::String(k.clone()), v)
+                })
+                .collect(),
+        ),
     }
 }
 
-pub fn to_toml_value(value: Value) -> Result<Option<toml::Value>, TomlValueError> {
+/// Convert the value to TOML.
+///
+/// Empty structs and lists are converted to empty tables and arrays.
+/// TOML has no null, so null is `None` and left out of structs and lists,
+/// see [`NullBehavior`] to report it instead.
+/// The keys of every table are ordered simple keys first, then arrays of tables and tables,
+/// keeping the order of the struct within each group.
+/// A document must be a table, see [`to_toml_table`] for the top-level value.
+pub fn to_toml_value(value: &Value) -> Result<Option<toml::Value>, TomlValueError> {
+    to_toml_value_all_errors(value).map_err(first_error)
+}
+
+/// Convert the owned value to TOML, see [`to_toml_value`].
+pub fn into_toml_value(value: Value) -> Result<Option<toml::Value>, TomlValueError> {
+    to_toml_value(&value)
+}
+
+/// Convert the whole value and report all errors with their paths.
+pub fn to_toml_value_all_errors(
+    value: &Value,
+) -> Result<Option<toml::Value>, ConversionErrors<TomlValueError>> {
+    to_toml_value_with_options(value, &TomlOptions::default())
+}
+
+/// Convert the whole value with the given options and report all errors with their paths.
+pub fn to_toml_value_with_options(
+    value: &Value,
+    options: &TomlOptions,
+) -> Result<Option<toml::Value>, ConversionErrors<TomlValueError>> {
+    let mut errors = Vec::new();
+    let toml = toml_value(value, &ValuePath::root(), options, &mut errors);
+    finish(toml, errors)
+}
+
+/// Null values, values with errors and unknown values converted as null are `None`,
+/// as toml has no null. Null values are errors, if the options do not skip them.
+/// Infinite numbers and NaN are only converted, if the options allow them.
+fn toml_value(
+    value: &Value,
+    path: &ValuePath,
+    options: &TomlOptions,
+    errors: &mut ConversionErrors<TomlValueError>,
+) -> Option<toml::Value> {
     match value {
-        Value::Unknown => Err(UnknownValueError)?,
-        Value::Null => Ok(None),
-        Value::Bool(b) => Ok(Some(toml::Value::Boolean(b))),
+        Value::Unknown if options.convert.unknown_as_null => None,
+        Value::Unknown => record(Err(UnknownValueError.into()), path, errors),
+        Value::Null => match options.null_behavior {
+            NullBehavior::Skip => None,
+            NullBehavior::Error => record(
+                Err(TomlValueError::NullNotRepresentable(path.clone())),
+                path,
+                errors,
+            ),
+        },
+        Value::Bool(b) => Some(toml::Value::Boolean(*b)),
         Value::Number(n) => {
-            if n.contains('.') {
-                let float = f64::from_str(&n)?;
-                Ok(Some(toml::Value::Float(float)))
+            let number = if let Some(non_finite) = non_finite(n) {
+                if options.allow_non_finite {
+                    Ok(toml::Value::Float(non_finite))
+                } else {
+                    Err(TomlValueError::NonFiniteNumber(n.clone()))
+                }
+            } else if n.contains(['.', 'e', 'E']) {
+                f64::from_str(n)
+                    .map(toml::Value::Float)
+                    .map_err(TomlValueError::from)
             } else {
-                let int = i64::from_str(&n)?;
-                Ok(Some(toml::Value::Integer(int)))
-            }
+                i64::from_str(n)
+                    .map(toml::Value::Integer)
+                    .map_err(TomlValueError::from)
+            };
+            record(number, path, errors)
         }
-        Value::String(s) => Ok(Some(toml::Value::String(s))),
-        Value::List(list) => {
-            let array = list
-                .into_iter()
-                .map(to_toml_value)
-                .filter_map(|r| match r {
-                    Ok(Some(v)) => Some(Ok(v)),
-                    Ok(None) => None,
-                    Err(err) => Some(Err(err)),
+        Value::String(s) => Some(toml::Value::String(s.to_string())),
+        Value::List(list) => Some(toml::Value::Array(
+            list.iter()
+                .enumerate()
+                .filter_map(|(index, value)| {
+                    toml_value(value, &path.join_index(index), options, errors)
                 })
-                .collect::<Result<Vec<_>, TomlValueError>>()?;
-            Ok(Some(toml::Value::Array(array)))
-        }
+                .collect(),
+        )),
         Value::Struct(s) => {
-            let object: toml::map::Map<_, _> = s
-                .into_iter()
-                .map(|(k, v)| to_toml_value(v).map(|v| (k, v)))
-                .filter_map(|r| match r {
-                    Ok((k, Some(v))) => Some(Ok((k, v))),
-                    Ok((_, None)) => None,
-                    Err(err) => Some(Err(err)),
+            let mut entries: Vec<_> = s
+                .iter()
+                .filter_map(|(k, v)| {
+                    let v = toml_value(v, &path.join_key(k), options, errors)?;
+                    Some((k.clone(), v))
                 })
-                .collect::<Result<toml::map::Map<_, _>, TomlValueError>>()?;
-            Ok(Some(toml::Value::Table(object)))
+                .collect();
+            // keys after a table header belong to the table, so simple keys must come first
+            entries.sort_by_key(|(_, v)| toml_section(v));
+            Some(toml::Value::Table(entries.into_iter().collect()))
+        }
+    }
+}
+
+/// Order of the value in its table: simple keys, arrays of tables and tables.
+fn toml_section(value: &toml::Value) -> u8 {
+    match value {
+        toml::Value::Table(_) => 2,
+        toml::Value::Array(array)
+            if !array.is_empty() && array.iter().all(toml::Value::is_table) =>
+        {
+            1
         }
+        _ => 0,
     }
 }
 
-pub fn to_xml_value(value: Value, name: &str) -> Result<XMLElement, XmlValueError> {
+/// Convert the value to an XML element with the given name.
+///
+/// Empty structs and null are empty elements, e.g. `<root />`.
+/// Fields named with an `@` prefix are attributes of their element (e.g. `host as "@host"`)
+/// and a field named `#text` is its text, both must be primitive values.
+/// Lists are emitted as one element per item with the name of their field,
+/// so an empty list is left out of its struct
+/// and a list, that is not a field of a struct, is a `StructlessList` error.
+/// See [`XmlListStyle`] for lists wrapped in an element of their own.
+pub fn to_xml_value(value: &Value, name: &str) -> Result<XMLElement, XmlValueError> {
+    to_xml_value_all_errors(value, name).map_err(first_error)
+}
+
+/// Convert the owned value to an XML element, see [`to_xml_value`].
+pub fn into_xml_value(value: Value, name: &str) -> Result<XMLElement, XmlValueError> {
+    to_xml_value(&value, name)
+}
+
+/// Convert the whole value and report all errors with their paths.
+pub fn to_xml_value_all_errors(
+    value: &Value,
+    name: &str,
+) -> Result<XMLElement, ConversionErrors<XmlValueError>> {
+    to_xml_value_with_options(value, name, &ConvertOptions::default())
+}
+
+/// Convert the whole value with the given options and report all errors with their paths.
+pub fn to_xml_value_with_options(
+    value: &Value,
+    name: &str,
+    options: &ConvertOptions,
+) -> Result<XMLElement, ConversionErrors<XmlValueError>> {
+    to_xml_element(value, name, options, &XmlListStyle::default())
+}
+
+/// Convert the value to an XML element, emitting its lists in the style.
+fn to_xml_element(
+    value: &Value,
+    name: &str,
+    options: &ConvertOptions,
+    list_style: &XmlListStyle,
+) -> Result<XMLElement, ConversionErrors<XmlValueError>> {
+    let mut errors = Vec::new();
+    let element = xml_value(
+        value,
+        name,
+        &ValuePath::root(),
+        options,
+        list_style,
+        &mut errors,
+    );
+    finish(element, errors)
+}
+
+fn xml_value(
+    value: &Value,
+    name: &str,
+    path: &ValuePath,
+    options: &ConvertOptions,
+    list_style: &XmlListStyle,
+    errors: &mut ConversionErrors<XmlValueError>,
+) -> XMLElement {
     let mut element = XMLElement::new(name);
     match value {
-        Value::Unknown => Err(UnknownValueError)?,
+        // the text of an element is not escaped, so it can be a comment
+        Value::Unknown if options.unknown_as_null => element
+            .add_text(XML_UNKNOWN_COMMENT.to_string())
+            .expect("Element is empty"),
+        Value::Unknown => {
+            record::<(), _>(Err(UnknownValueError.into()), path, errors);
+        }
         Value::Null => {}
         Value::Bool(b) => element.add_text(b.to_string()).expect("Element is empty"),
-        Value::Number(n) => element.add_text(n).expect("Element is empty"),
-        Value::String(s) => element.add_text(s).expect("Element is empty"),
-        Value::List(_) => Err(XmlValueError::StructlessList)?,
+        Value::Number(n) if non_finite(n).is_some() => {
+            record::<(), _>(Err(XmlValueError::NonFiniteNumber(n.clone())), path, errors);
+        }
+        Value::Number(n) => element.add_text(n.clone()).expect("Element is empty"),
+        Value::String(s) => element.add_text(s.to_string()).expect("Element is empty"),
+        Value::List(list) => match list_style {
+            XmlListStyle::RepeatKey => {
+                record::<(), _>(Err(XmlValueError::StructlessList), path, errors);
+            }
+            XmlListStyle::Wrapped { item_name } => {
+                for (index, value) in list.iter().enumerate() {
+                    let path = path.join_index(index);
+                    element
+                        .add_child(xml_value(
+                            value, item_name, &path, options, list_style, errors,
+                        ))
+                        .expect("Element is no text element");
+                }
+            }
+        },
         Value::Struct(s) => {
+            let mut text = None;
+            let mut children = Vec::new();
             for (key, value) in s {
-                if let Value::List(list) = value {
-                    for value in list {
+                let path = path.join_key(key);
+                if let Some(attribute) = key.strip_prefix(XML_ATTRIBUTE_PREFIX) {
+                    let invalid = || XmlValueError::InvalidAttributeValue(attribute.to_string());
+                    if let Some(text) = xml_text(value, &path, options, errors, invalid) {
+                        element.add_attribute(attribute, &text);
+                    }
+                } else if key == XML_TEXT_KEY {
+                    text = xml_text(value, &path, options, errors, || {
+                        XmlValueError::MixedContent
+                    })
+                    .map(|text| (text, path));
+                } else if let (Value::List(list), XmlListStyle::RepeatKey) = (value, list_style) {
+                    children.extend(list.iter().enumerate().map(|(index, value)| {
+                        let path = path.join_index(index);
+                        xml_value(value, key, &path, options, list_style, errors)
+                    }));
+                } else {
+                    children.push(xml_value(value, key, &path, options, list_style, errors));
+                }
+            }
+            match text {
+                Some((_, path)) if !children.is_empty() => {
+                    record::<(), _>(Err(XmlValueError::MixedContent), &path, errors);
+                }
+                Some((text, _)) => element.add_text(text).expect("Element is empty"),
+                None => {
+                    for child in children {
                         element
-                            .add_child(to_xml_value(value, &key)?)
+                            .add_child(child)
                             .expect("Element is no text element");
                     }
-                } else {
-                    element
-                        .add_child(to_xml_value(value, &key)?)
-                        .expect("Element is no text element");
                 }
             }
         }
     };
-    Ok(element)
+    element
+}
+
+/// Text of a primitive value, that is emitted as attribute or text of an element.
+///
+/// Null and unknown values converted as null are left out.
+/// Structs and lists are reported with the error of `invalid`.
+fn xml_text(
+    value: &Value,
+    path: &ValuePath,
+    options: &ConvertOptions,
+    errors: &mut ConversionErrors<XmlValueError>,
+    invalid: impl FnOnce() -> XmlValueError,
+) -> Option<String> {
+    let text = match value {
+        Value::Unknown if options.unknown_as_null => return None,
+        Value::Unknown => Err(UnknownValueError.into()),
+        Value::Null => return None,
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Number(n) if non_finite(n).is_some() => {
+            Err(XmlValueError::NonFiniteNumber(n.clone()))
+        }
+        Value::Number(n) => Ok(n.clone()),
+        Value::String(s) => Ok(s.to_string()),
+        Value::List(_) | Value::Struct(_) => Err(invalid()),
+    };
+    record(text, path, errors)
 }
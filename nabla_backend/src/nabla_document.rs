@@ -0,0 +1,136 @@
+use crate::{
+    error::{ImportError, UnknownValueError},
+    import_json, import_toml, import_yaml, non_finite,
+};
+use nabla_frontend::{
+    eval::Value,
+    lexer::{self, LexerResult},
+    parser::{self, ParserResult},
+    token::{self, TokenType},
+};
+use std::collections::HashSet;
+
+/// Write the value as nabla source, whose init evaluates to the value.
+///
+/// Keys, that are no valid identifiers, become fields with an alias,
+/// e.g. `content_type = "json" as "content-type"`.
+/// The source is laid out by the formatter.
+pub fn to_nabla_string(value: &Value) -> Result<String, ImportError> {
+    let mut src = String::new();
+    write_value(value, &mut src)?;
+    src.push('\n');
+    let LexerResult { tokens, .. } = lexer::lex(&src);
+    let ParserResult { ast, .. } = parser::parse(&tokens);
+    Ok(nabla_frontend::format(&ast, &src))
+}
+
+/// Write the JSON value as nabla source, see [`to_nabla_string`].
+pub fn from_json_value(value: serde_json::Value) -> Result<String, ImportError> {
+    to_nabla_string(&import_json(value))
+}
+
+/// Write the YAML value as nabla source, see [`to_nabla_string`].
+pub fn from_yaml_value(value: serde_yaml::Value) -> Result<String, ImportError> {
+    to_nabla_string(&import_yaml(value)?)
+}
+
+/// Write the TOML value as nabla source, see [`to_nabla_string`].
+pub fn from_toml_value(value: toml::Value) -> Result<String, ImportError> {
+    to_nabla_string(&import_toml(value))
+}
+
+fn write_value(value: &Value, src: &mut String) -> Result<(), ImportError> {
+    match value {
+        Value::Unknown => return Err(UnknownValueError.into()),
+        Value::Null => src.push_str(token::NULL),
+        Value::Bool(b) => src.push_str(&b.to_string()),
+        Value::Number(n) if non_finite(n).is_some() || !is_number(n) => {
+            return Err(ImportError::InvalidNumber(n.clone()));
+        }
+        Value::Number(n) => src.push_str(n),
+        Value::String(s) => write_string(s, src),
+        Value::List(list) => {
+            src.push('[');
+            for (index, value) in list.iter().enumerate() {
+                if index > 0 {
+                    src.push(' ');
+                }
+                write_value(value, src)?;
+            }
+            src.push(']');
+        }
+        Value::Struct(fields) => {
+            src.push('{');
+            let mut names = FieldNames::new(fields.keys());
+            for (key, value) in fields {
+                let name = names.name(key);
+                src.push(' ');
+                src.push_str(&name);
+                src.push_str(" = ");
+                write_value(value, src)?;
+                if name != *key {
+                    src.push_str(" as ");
+                    write_string(key, src);
+                }
+            }
+            src.push_str(" }");
+        }
+    }
+    Ok(())
+}
+
+fn write_string(s: &str, src: &mut String) {
+    src.push('"');
+    src.push_str(&lexer::escape(s));
+    src.push('"');
+}
+
+/// Whether the number can be written as a literal, that may be negated, e.g. `-2.5e-3`.
+fn is_number(n: &str) -> bool {
+    let LexerResult { tokens, errors } = lexer::lex(n.strip_prefix('-').unwrap_or(n));
+    errors.is_empty()
+        && matches!(&tokens[..], [number, _] if matches!(number.token_type, TokenType::Number(_)))
+}
+
+/// Whether the key can be written as the name of a field, e.g. `port` but not `max-size` or `true`.
+fn is_ident(key: &str) -> bool {
+    let LexerResult { tokens, errors } = lexer::lex(key);
+    errors.is_empty()
+        && matches!(&tokens[..], [ident, _] if ident.token_type == TokenType::Ident(key.to_string()))
+}
+
+/// Names of the fields of a struct, that are distinct identifiers.
+/// Keys, that are identifiers, keep their name.
+struct FieldNames {
+    used: HashSet<String>,
+}
+
+impl FieldNames {
+    fn new<'a>(keys: impl Iterator<Item = &'a String>) -> Self {
+        Self {
+            used: keys.filter(|key| is_ident(key)).cloned().collect(),
+        }
+    }
+
+    /// Name of the field with the key, e.g. `max_size` for `max-size`.
+    fn name(&mut self, key: &str) -> String {
+        if is_ident(key) {
+            return key.to_string();
+        }
+        let mut base: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            base.insert(0, '_');
+        }
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while !is_ident(&name) || self.used.contains(&name) {
+            suffix += 1;
+            name = format!("{}_{}", base, suffix);
+        }
+        self.used.insert(name.clone());
+        name
+    }
+}
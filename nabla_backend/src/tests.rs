@@ -0,0 +1,1109 @@
+use crate::{
+    error::{ImportError, JsonValueError, TomlValueError, XmlValueError, YamlValueError},
+    from_json_value, from_toml_value, from_yaml_value, import_json, import_yaml, into_json_value,
+    into_toml_value, into_xml_value, into_yaml_value, to_json_string, to_json_value,
+    to_json_value_all_errors, to_json_value_with_options, to_nabla_string, to_toml_string,
+    to_toml_string_with_docs, to_toml_table, to_toml_value, to_toml_value_all_errors,
+    to_xml_document, to_xml_value, to_xml_value_all_errors, to_xml_value_with_options,
+    to_yaml_documents, to_yaml_string_with_docs, to_yaml_value, unknown_paths, write_json,
+    write_json_with_formatter, ConvertOptions, NullBehavior, TomlOptions, XmlListStyle, XmlOptions,
+    XmlVersion,
+};
+use nabla_frontend::{
+    compile::compile,
+    eval::{Fields, Value, ValuePath},
+    semantics::docs::Docs,
+};
+
+#[test]
+fn import_json_document() {
+    let json = serde_json::json!({
+        "name": "test",
+        "version": 1.5,
+        "enabled": true,
+        "tags": ["a", null],
+    });
+    assert_eq!(
+        Value::from([
+            ("name", Value::from("test")),
+            ("version", Value::from(1.5)),
+            ("enabled", Value::from(true)),
+            ("tags", Value::List(vec![Value::from("a"), Value::Null])),
+        ]),
+        import_json(json)
+    );
+}
+
+#[test]
+fn import_yaml_document() {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(
+        "
+name: test
+1: one
+nested:
+  - x: !tag 2
+",
+    )
+    .unwrap();
+    assert_eq!(
+        Value::from([
+            ("name", Value::from("test")),
+            ("1", Value::from("one")),
+            ("nested", Value::from([Value::from([("x", 2)])])),
+        ]),
+        import_yaml(yaml).unwrap()
+    );
+}
+
+#[test]
+fn import_yaml_complex_key() {
+    let yaml: serde_yaml::Value = serde_yaml::from_str("[a]: b").unwrap();
+    assert!(import_yaml(yaml).is_err());
+}
+
+#[test]
+fn struct_fields_in_order() {
+    let value = Value::from([("c", 1), ("a", 2), ("b", 3)]);
+    assert_eq!(
+        "{\"c\":1,\"a\":2,\"b\":3}",
+        to_json_string(&value, false).unwrap()
+    );
+    assert_eq!(
+        "{\"c\":1,\"a\":2,\"b\":3}",
+        serde_json::to_string(&to_json_value(&value).unwrap()).unwrap()
+    );
+    let yaml = serde_yaml::to_string(&to_yaml_value(&value).unwrap()).unwrap();
+    assert_eq!("c: 1\na: 2\nb: 3\n", yaml);
+    let toml = to_toml_string(&value, &TomlOptions::default()).unwrap();
+    assert_eq!("c = 1\na = 2\nb = 3\n", toml);
+    let mut xml = xml_builder::XMLBuilder::new().build();
+    xml.set_root_element(to_xml_value(&value, "root").unwrap());
+    let mut output = Vec::new();
+    xml.generate(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let positions: Vec<_> = ["<c>", "<a>", "<b>"]
+        .iter()
+        .map(|tag| output.find(tag).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+}
+
+fn invalid_leaves() -> Value {
+    Value::from([
+        ("a", Value::Unknown),
+        (
+            "b",
+            Value::from([("c", Value::Number("1.2.3".to_string()))]),
+        ),
+        ("d", Value::List(vec![Value::from(1), Value::Unknown])),
+        ("e", Value::from("valid")),
+    ])
+}
+
+fn expected_paths() -> Vec<ValuePath> {
+    vec![
+        ValuePath::root().join_key("a"),
+        ValuePath::root().join_key("b").join_key("c"),
+        ValuePath::root().join_key("d").join_index(1),
+    ]
+}
+
+#[test]
+fn json_all_errors() {
+    let errors = to_json_value_all_errors(&invalid_leaves()).unwrap_err();
+    let paths: Vec<_> = errors.into_iter().map(|error| error.path).collect();
+    assert_eq!(expected_paths(), paths);
+}
+
+#[test]
+fn written_json_all_errors() {
+    let mut json = Vec::new();
+    let errors = write_json_with_formatter(
+        &invalid_leaves(),
+        &mut json,
+        serde_json::ser::CompactFormatter,
+        &ConvertOptions::default(),
+    )
+    .unwrap_err();
+    let paths: Vec<_> = errors.into_iter().map(|error| error.path).collect();
+    assert_eq!(expected_paths(), paths);
+    assert!(json.is_empty());
+}
+
+#[test]
+fn toml_all_errors() {
+    let errors = to_toml_value_all_errors(&invalid_leaves()).unwrap_err();
+    let paths: Vec<_> = errors.into_iter().map(|error| error.path).collect();
+    assert_eq!(expected_paths(), paths);
+}
+
+#[test]
+fn toml_empty_struct_and_list() {
+    let value = Value::from([
+        ("empty", Value::Struct(Fields::new())),
+        ("list", Value::List(Vec::new())),
+    ]);
+    let options = TomlOptions {
+        prefer_array_of_tables: true,
+        inline_threshold: None,
+        allow_non_finite: false,
+        null_behavior: NullBehavior::Skip,
+        convert: ConvertOptions::default(),
+        root_key: None,
+    };
+    assert_eq!(
+        "list = []\n\n[empty]\n",
+        to_toml_string(&value, &options).unwrap()
+    );
+}
+
+#[test]
+fn toml_string_structless_root() {
+    let errors = to_toml_string(&Value::from(1), &TomlOptions::default()).unwrap_err();
+    let paths: Vec<_> = errors.into_iter().map(|error| error.path).collect();
+    assert_eq!(vec![ValuePath::root()], paths);
+}
+
+fn empty_struct() -> Value {
+    Value::Struct(Fields::new())
+}
+
+fn empty_list() -> Value {
+    Value::List(Vec::new())
+}
+
+fn xml_string(value: Value) -> Result<String, XmlValueError> {
+    let mut xml = xml_builder::XMLBuilder::new().build();
+    xml.set_root_element(to_xml_value(&value, "root")?);
+    let mut output = Vec::new();
+    xml.generate(&mut output).unwrap();
+    Ok(String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn json_empty_struct() {
+    assert_eq!(
+        serde_json::json!({}),
+        to_json_value(&empty_struct()).unwrap()
+    );
+}
+
+#[test]
+fn json_empty_list() {
+    assert_eq!(serde_json::json!([]), to_json_value(&empty_list()).unwrap());
+}
+
+#[test]
+fn json_null() {
+    assert_eq!(
+        serde_json::Value::Null,
+        to_json_value(&Value::Null).unwrap()
+    );
+}
+
+#[test]
+fn yaml_empty_struct() {
+    let yaml = to_yaml_value(&empty_struct()).unwrap();
+    assert_eq!(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()), yaml);
+    assert_eq!("{}\n", serde_yaml::to_string(&yaml).unwrap());
+}
+
+#[test]
+fn yaml_empty_list() {
+    let yaml = to_yaml_value(&empty_list()).unwrap();
+    assert_eq!(serde_yaml::Value::Sequence(Vec::new()), yaml);
+    assert_eq!("[]\n", serde_yaml::to_string(&yaml).unwrap());
+}
+
+#[test]
+fn yaml_null() {
+    let yaml = to_yaml_value(&Value::Null).unwrap();
+    assert_eq!(serde_yaml::Value::Null, yaml);
+    assert_eq!("null\n", serde_yaml::to_string(&yaml).unwrap());
+}
+
+#[test]
+fn toml_empty_struct() {
+    assert_eq!(
+        Some(toml::Value::Table(toml::Table::new())),
+        to_toml_value(&empty_struct()).unwrap()
+    );
+    let root = to_toml_table(&empty_struct(), &TomlOptions::default()).unwrap();
+    assert_eq!("", toml::to_string_pretty(&root).unwrap());
+    assert_eq!(
+        "",
+        to_toml_string(&empty_struct(), &TomlOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn toml_empty_list() {
+    assert_eq!(
+        Some(toml::Value::Array(Vec::new())),
+        to_toml_value(&empty_list()).unwrap()
+    );
+    let errors = to_toml_table(&empty_list(), &TomlOptions::default()).unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [error] if matches!(error.error, TomlValueError::StructlessRoot)
+    ));
+}
+
+#[test]
+fn toml_null() {
+    assert_eq!(None, to_toml_value(&Value::Null).unwrap());
+    let errors = to_toml_table(&Value::Null, &TomlOptions::default()).unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [error] if matches!(error.error, TomlValueError::NothingToEmit)
+    ));
+    let errors = to_toml_string(&Value::Null, &TomlOptions::default()).unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [error] if matches!(error.error, TomlValueError::NothingToEmit)
+    ));
+}
+
+#[test]
+fn toml_tables_after_simple_keys() {
+    let value = Value::from([
+        ("server", Value::from([("host", "localhost")])),
+        ("name", Value::from("app")),
+        ("workers", Value::from([[("id", 1)], [("id", 2)]])),
+        ("port", Value::from(80)),
+    ]);
+    let Some(toml::Value::Table(table)) = to_toml_value(&value).unwrap() else {
+        panic!("struct must be a table");
+    };
+    assert_eq!(
+        vec!["name", "port", "workers", "server"],
+        table.keys().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        "name = \"app\"\nport = 80\n\n[[workers]]\nid = 1\n\n[[workers]]\nid = 2\n\n[server]\nhost = \"localhost\"\n",
+        toml::to_string_pretty(&table).unwrap()
+    );
+}
+
+#[test]
+fn toml_strict_nulls() {
+    let value = Value::from([
+        ("name", Value::from("app")),
+        ("tags", Value::List(vec![Value::from("a"), Value::Null])),
+        ("comment", Value::Null),
+    ]);
+    let options = TomlOptions {
+        null_behavior: NullBehavior::Error,
+        ..TomlOptions::default()
+    };
+    let errors = to_toml_string(&value, &options).unwrap_err();
+    let paths: Vec<_> = errors
+        .into_iter()
+        .map(|error| match error.error {
+            TomlValueError::NullNotRepresentable(path) => {
+                assert_eq!(error.path, path);
+                path.to_string()
+            }
+            error => panic!("unexpected error: {}", error),
+        })
+        .collect();
+    assert_eq!(vec!["/tags/1", "/comment"], paths);
+    assert_eq!(
+        "name = \"app\"\n",
+        to_toml_string(&Value::from([("name", "app")]), &options).unwrap()
+    );
+}
+
+fn root_key_options() -> TomlOptions {
+    TomlOptions {
+        root_key: Some("value".to_string()),
+        ..TomlOptions::default()
+    }
+}
+
+#[test]
+fn list_root_every_target() {
+    let list = || Value::List(vec![Value::from(1), Value::from(2)]);
+    assert_eq!(serde_json::json!([1, 2]), to_json_value(&list()).unwrap());
+    assert_eq!(
+        serde_yaml::Value::Sequence(vec![1.into(), 2.into()]),
+        to_yaml_value(&list()).unwrap()
+    );
+    let errors = to_toml_string(&list(), &TomlOptions::default()).unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [error] if matches!(error.error, TomlValueError::StructlessRoot)
+    ));
+    assert_eq!(
+        "value = [1, 2]\n",
+        to_toml_string(&list(), &root_key_options()).unwrap()
+    );
+    assert!(matches!(
+        xml_string(list()),
+        Err(XmlValueError::StructlessList)
+    ));
+}
+
+#[test]
+fn number_root_every_target() {
+    let number = || Value::from(42);
+    assert_eq!(serde_json::json!(42), to_json_value(&number()).unwrap());
+    assert_eq!(
+        serde_yaml::Value::from(42),
+        to_yaml_value(&number()).unwrap()
+    );
+    let errors = to_toml_table(&number(), &TomlOptions::default()).unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [error] if matches!(error.error, TomlValueError::StructlessRoot)
+    ));
+    assert_eq!(
+        "value = 42\n",
+        to_toml_string(&number(), &root_key_options()).unwrap()
+    );
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root>42</root>\n",
+        xml_string(number()).unwrap()
+    );
+}
+
+#[test]
+fn root_key_keeps_struct_and_null() {
+    assert_eq!(
+        "a = 1\n",
+        to_toml_string(&Value::from([("a", 1)]), &root_key_options()).unwrap()
+    );
+    let errors = to_toml_table(&Value::Null, &root_key_options()).unwrap_err();
+    assert!(matches!(
+        errors.as_slice(),
+        [error] if matches!(error.error, TomlValueError::NothingToEmit)
+    ));
+}
+
+#[test]
+fn xml_empty_struct() {
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root />\n",
+        xml_string(empty_struct()).unwrap()
+    );
+}
+
+#[test]
+fn xml_empty_list() {
+    assert!(matches!(
+        xml_string(empty_list()),
+        Err(XmlValueError::StructlessList)
+    ));
+    // an empty list field has no elements
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root />\n",
+        xml_string(Value::from([("items", empty_list())])).unwrap()
+    );
+}
+
+#[test]
+fn xml_null() {
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root />\n",
+        xml_string(Value::Null).unwrap()
+    );
+}
+
+#[test]
+fn xml_attributes_and_text() {
+    let value = Value::from([
+        (
+            "server",
+            Value::from([
+                ("@host", Value::from("x")),
+                ("@port", Value::from(1)),
+                ("@comment", Value::Null),
+                ("name", Value::from("main")),
+            ]),
+        ),
+        (
+            "greeting",
+            Value::from([
+                ("@lang", Value::from("en")),
+                ("#text", Value::from("hello")),
+            ]),
+        ),
+    ]);
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <root>\n\
+        \t<server host=\"x\" port=\"1\">\n\
+        \t\t<name>main</name>\n\
+        \t</server>\n\
+        \t<greeting lang=\"en\">hello</greeting>\n\
+        </root>\n",
+        xml_string(value).unwrap()
+    );
+}
+
+#[test]
+fn xml_invalid_attributes_and_text() {
+    let value = Value::from([
+        ("@tags", Value::from(["a", "b"])),
+        ("#text", Value::from("hello")),
+        ("child", Value::from(1)),
+    ]);
+    let Err(errors) = to_xml_value_all_errors(&value, "root") else {
+        panic!("attributes and text must be reported");
+    };
+    let errors: Vec<_> = errors
+        .into_iter()
+        .map(|error| format!("{}: {}", error.path, error.error))
+        .collect();
+    assert_eq!(
+        vec![
+            "/@tags: attribute `tags` must be a primitive value",
+            "/#text: text of an element cannot be mixed with child elements",
+        ],
+        errors
+    );
+}
+
+/// Numbers, that are infinite or not a number, as they could be written or imported.
+fn non_finite_numbers() -> Vec<String> {
+    let overflow = format!("1{}", "0".repeat(400));
+    ["inf", "-inf", "nan", ".inf", "-.inf", ".nan", &overflow]
+        .map(str::to_string)
+        .to_vec()
+}
+
+#[test]
+fn non_finite_json() {
+    for n in non_finite_numbers() {
+        let error = to_json_value(&Value::Number(n.clone())).unwrap_err();
+        assert!(
+            matches!(&error, JsonValueError::NonFiniteNumber(number) if *number == n),
+            "{}: {:?}",
+            n,
+            error
+        );
+    }
+}
+
+#[test]
+fn non_finite_yaml() {
+    for n in non_finite_numbers() {
+        let error = to_yaml_value(&Value::Number(n.clone())).unwrap_err();
+        assert!(
+            matches!(&error, YamlValueError::NonFiniteNumber(number) if *number == n),
+            "{}: {:?}",
+            n,
+            error
+        );
+    }
+}
+
+#[test]
+fn non_finite_toml() {
+    for n in non_finite_numbers() {
+        let error = to_toml_value(&Value::Number(n.clone())).unwrap_err();
+        assert!(
+            matches!(&error, TomlValueError::NonFiniteNumber(number) if *number == n),
+            "{}: {:?}",
+            n,
+            error
+        );
+    }
+}
+
+#[test]
+fn non_finite_toml_allowed() {
+    let value = Value::from([
+        ("a", Value::Number("inf".to_string())),
+        ("b", Value::Number("-.inf".to_string())),
+        ("c", Value::Number("nan".to_string())),
+    ]);
+    let options = TomlOptions {
+        allow_non_finite: true,
+        ..TomlOptions::default()
+    };
+    assert_eq!(
+        "a = inf\nb = -inf\nc = nan\n",
+        to_toml_string(&value, &options).unwrap()
+    );
+}
+
+#[test]
+fn non_finite_xml() {
+    for n in non_finite_numbers() {
+        let Err(error) = to_xml_value(&Value::Number(n.clone()), "root") else {
+            panic!("{} must not be converted", n);
+        };
+        assert!(
+            matches!(&error, XmlValueError::NonFiniteNumber(number) if *number == n),
+            "{}: {:?}",
+            n,
+            error
+        );
+    }
+}
+
+#[test]
+fn overflowing_evaluation() {
+    // evaluations, that overflow, are unknown instead of infinite
+    let value = Value::from_f64(f64::MAX * 2.0);
+    assert!(to_json_value(&value).is_err());
+    assert!(to_yaml_value(&value).is_err());
+    assert!(to_toml_value(&value).is_err());
+    assert!(to_xml_value(&value, "root").is_err());
+}
+
+fn documented_value() -> Value {
+    Value::from([
+        ("empty", empty_struct()),
+        ("name", Value::from("multi\nline")),
+        (
+            "server",
+            Value::from([
+                ("host", Value::from("localhost")),
+                ("port", Value::from(80)),
+            ]),
+        ),
+        (
+            "servers",
+            Value::List(vec![
+                Value::from([("port", 1), ("weight", 2)]),
+                Value::from([("port", 3), ("weight", 4)]),
+            ]),
+        ),
+        (
+            "tags",
+            Value::List(vec![Value::from("a"), Value::List(vec![Value::from(1)])]),
+        ),
+    ])
+}
+
+fn docs() -> Docs {
+    let root = ValuePath::root();
+    Docs::from([
+        (
+            root.join_key("name"),
+            "the name\n\nof the config".to_string(),
+        ),
+        (root.join_key("server"), "the server".to_string()),
+        (
+            root.join_key("server").join_key("port"),
+            "the port".to_string(),
+        ),
+        (root.join_key("servers"), "all servers".to_string()),
+        (
+            root.join_key("servers").join_index(0).join_key("port"),
+            "first port".to_string(),
+        ),
+    ])
+}
+
+#[test]
+fn yaml_without_docs() {
+    let value = documented_value();
+    assert_eq!(
+        serde_yaml::to_string(&to_yaml_value(&value).unwrap()).unwrap(),
+        to_yaml_string_with_docs(&value, &ConvertOptions::default(), &Docs::new()).unwrap()
+    );
+}
+
+#[test]
+fn yaml_docs() {
+    assert_eq!(
+        "empty: {}
+# the name
+#
+# of the config
+name: |-
+  multi
+  line
+# the server
+server:
+  host: localhost
+  # the port
+  port: 80
+# all servers
+servers:
+- # first port
+  port: 1
+  weight: 2
+- port: 3
+  weight: 4
+tags:
+- a
+- - 1
+",
+        to_yaml_string_with_docs(&documented_value(), &ConvertOptions::default(), &docs()).unwrap()
+    );
+}
+
+#[test]
+fn toml_without_docs() {
+    let options = TomlOptions::default();
+    assert_eq!(
+        to_toml_string(&documented_value(), &options).unwrap(),
+        to_toml_string_with_docs(&documented_value(), &options, &Docs::new()).unwrap()
+    );
+}
+
+#[test]
+fn toml_docs() {
+    let options = TomlOptions {
+        prefer_array_of_tables: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        r#"# the name
+#
+# of the config
+name = """
+multi
+line"""
+tags = ["a", [1]]
+
+# all servers
+[[servers]]
+# first port
+port = 1
+weight = 2
+
+[[servers]]
+port = 3
+weight = 4
+
+[empty]
+
+# the server
+[server]
+host = "localhost"
+# the port
+port = 80
+"#,
+        to_toml_string_with_docs(&documented_value(), &options, &docs()).unwrap()
+    );
+}
+
+fn partially_unknown() -> Value {
+    Value::from([
+        ("hosts", Value::List(vec![Value::from("a"), Value::Unknown])),
+        ("name", Value::from("prod")),
+        ("port", Value::Unknown),
+    ])
+}
+
+fn unknown_as_null() -> ConvertOptions {
+    ConvertOptions {
+        unknown_as_null: true,
+    }
+}
+
+#[test]
+fn unknown_paths_in_order() {
+    assert_eq!(
+        vec![
+            ValuePath::root().join_key("hosts").join_index(1),
+            ValuePath::root().join_key("port"),
+        ],
+        unknown_paths(&partially_unknown())
+    );
+    assert_eq!(vec![ValuePath::root()], unknown_paths(&Value::Unknown));
+    assert_eq!(Vec::<ValuePath>::new(), unknown_paths(&documented_value()));
+}
+
+#[test]
+fn json_unknown_as_null() {
+    assert_eq!(
+        serde_json::json!({"hosts": ["a", null], "name": "prod", "port": null}),
+        to_json_value_with_options(&partially_unknown(), &unknown_as_null()).unwrap()
+    );
+}
+
+#[test]
+fn yaml_unknown_as_null() {
+    assert_eq!(
+        "hosts:\n- a\n- null\nname: prod\nport: null\n",
+        to_yaml_string_with_docs(&partially_unknown(), &unknown_as_null(), &Docs::new()).unwrap()
+    );
+}
+
+#[test]
+fn yaml_documents() {
+    let values = [
+        Value::from([("a", Value::from(1))]),
+        Value::List(vec![Value::from("b")]),
+    ];
+    assert_eq!(
+        "a: 1\n---\n- b\n",
+        to_yaml_documents(&values, &ConvertOptions::default()).unwrap()
+    );
+    assert_eq!(
+        "",
+        to_yaml_documents(&[], &ConvertOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn yaml_document_errors() {
+    let values = [Value::from(1), invalid_leaves()];
+    let errors = to_yaml_documents(&values, &ConvertOptions::default()).unwrap_err();
+    let paths: Vec<_> = errors.into_iter().map(|error| error.path).collect();
+    let document = ValuePath::root().join_index(1);
+    let expected = vec![
+        document.join_key("a"),
+        document.join_key("b").join_key("c"),
+        document.join_key("d").join_index(1),
+    ];
+    assert_eq!(expected, paths);
+}
+
+#[test]
+fn toml_unknown_as_null() {
+    let options = TomlOptions {
+        convert: unknown_as_null(),
+        ..TomlOptions::default()
+    };
+    assert_eq!(
+        "hosts = [\"a\"]\nname = \"prod\"\n",
+        to_toml_string(&partially_unknown(), &options).unwrap()
+    );
+}
+
+#[test]
+fn xml_unknown_as_null() {
+    let element =
+        to_xml_value_with_options(&partially_unknown(), "root", &unknown_as_null()).unwrap();
+    let mut xml = xml_builder::XMLBuilder::new().build();
+    xml.set_root_element(element);
+    let mut output = Vec::new();
+    xml.generate(&mut output).unwrap();
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root>\n\t<hosts>a</hosts>\n\t<hosts><!-- unknown value --></hosts>\n\t<name>prod</name>\n\t<port><!-- unknown value --></port>\n</root>\n",
+        String::from_utf8(output).unwrap()
+    );
+}
+
+#[test]
+fn unknown_without_option() {
+    let errors = to_json_value_all_errors(&partially_unknown()).unwrap_err();
+    let paths: Vec<_> = errors.into_iter().map(|error| error.path).collect();
+    assert_eq!(unknown_paths(&partially_unknown()), paths);
+}
+
+#[test]
+fn one_value_every_target() {
+    let value = Value::from([("a", Value::from([1, 2])), ("b", Value::from("c"))]);
+    assert_eq!(
+        into_json_value(value.clone()).unwrap(),
+        to_json_value(&value).unwrap()
+    );
+    assert_eq!(
+        into_yaml_value(value.clone()).unwrap(),
+        to_yaml_value(&value).unwrap()
+    );
+    assert_eq!(
+        into_toml_value(value.clone()).unwrap(),
+        to_toml_value(&value).unwrap()
+    );
+    let xml = |element| {
+        let mut xml = xml_builder::XMLBuilder::new().build();
+        xml.set_root_element(element);
+        let mut output = Vec::new();
+        xml.generate(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    };
+    assert_eq!(
+        xml(into_xml_value(value.clone(), "root").unwrap()),
+        xml(to_xml_value(&value, "root").unwrap())
+    );
+}
+
+#[test]
+fn written_json_equals_json_value() {
+    let value = Value::from([
+        ("a", Value::from([Value::from(1), Value::from("-1.5e3")])),
+        (
+            "b",
+            Value::from([("d", Value::Null), ("c", Value::Bool(true))]),
+        ),
+        ("e", Value::Number("-1.5e3".to_string())),
+        ("f", Value::List(Vec::new())),
+        ("g", Value::from("quote \" and \n newline")),
+    ]);
+    let json = to_json_value(&value).unwrap();
+    assert_eq!(
+        serde_json::to_string(&json).unwrap(),
+        to_json_string(&value, false).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_string_pretty(&json).unwrap(),
+        to_json_string(&value, true).unwrap()
+    );
+}
+
+#[test]
+fn written_json_errors() {
+    let mut json = Vec::new();
+    assert!(matches!(
+        write_json(&Value::from([("a", Value::Unknown)]), &mut json, false),
+        Err(JsonValueError::UnknownValueError(_))
+    ));
+    assert!(matches!(
+        write_json(&Value::Number("1.2.3".to_string()), &mut json, false),
+        Err(JsonValueError::NumberParseError(_))
+    ));
+    assert!(matches!(
+        write_json(&Value::Number("inf".to_string()), &mut json, false),
+        Err(JsonValueError::NonFiniteNumber(_))
+    ));
+    assert!(json.is_empty());
+    let options = ConvertOptions {
+        unknown_as_null: true,
+    };
+    write_json_with_formatter(
+        &Value::from([("a", Value::Unknown)]),
+        &mut json,
+        serde_json::ser::CompactFormatter,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(b"{\"a\":null}", json.as_slice());
+}
+
+#[test]
+fn xml_document_declaration() {
+    let options = XmlOptions {
+        root_name: "config".to_string(),
+        version: XmlVersion::V1_1,
+        encoding: "ISO-8859-1".to_string(),
+        standalone: true,
+        ..XmlOptions::default()
+    };
+    let xml = to_xml_document(&Value::from([("a", 1)]), &options).unwrap();
+    let mut output = Vec::new();
+    xml.generate(&mut output).unwrap();
+    assert_eq!(
+        "<?xml version=\"1.1\" encoding=\"ISO-8859-1\" standalone=\"yes\"?>\n<config>\n\t<a>1</a>\n</config>\n",
+        String::from_utf8(output).unwrap()
+    );
+}
+
+#[test]
+fn xml_document_structless_list() {
+    let result = to_xml_document(&Value::from([1, 2]), &XmlOptions::default());
+    assert!(matches!(result, Err(XmlValueError::StructlessList)));
+}
+
+fn xml_document_string(value: &Value, options: &XmlOptions) -> Result<String, XmlValueError> {
+    let xml = to_xml_document(value, options)?;
+    let mut output = Vec::new();
+    xml.generate(&mut output).unwrap();
+    Ok(String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn xml_nested_lists_repeat_key() {
+    let value = Value::from([(
+        "matrix",
+        Value::List(vec![Value::from([1, 2]), Value::from([3])]),
+    )]);
+    let Err(errors) = to_xml_value_all_errors(&value, "root") else {
+        panic!("nested lists must be reported");
+    };
+    let paths: Vec<_> = errors
+        .iter()
+        .map(|error| {
+            assert!(matches!(error.error, XmlValueError::StructlessList));
+            error.path.to_string()
+        })
+        .collect();
+    assert_eq!(vec!["/matrix/0", "/matrix/1"], paths);
+}
+
+#[test]
+fn xml_nested_lists_wrapped() {
+    let options = XmlOptions {
+        list_style: XmlListStyle::Wrapped {
+            item_name: "item".to_string(),
+        },
+        ..XmlOptions::default()
+    };
+    let value = Value::from([
+        (
+            "matrix",
+            Value::List(vec![Value::from([1, 2]), Value::from([3])]),
+        ),
+        ("empty", empty_list()),
+    ]);
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <root>\n\
+        \t<matrix>\n\
+        \t\t<item>\n\
+        \t\t\t<item>1</item>\n\
+        \t\t\t<item>2</item>\n\
+        \t\t</item>\n\
+        \t\t<item>\n\
+        \t\t\t<item>3</item>\n\
+        \t\t</item>\n\
+        \t</matrix>\n\
+        \t<empty />\n\
+        </root>\n",
+        xml_document_string(&value, &options).unwrap()
+    );
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root>\n\t<item>1</item>\n\t<item>2</item>\n</root>\n",
+        xml_document_string(&Value::from([1, 2]), &options).unwrap()
+    );
+}
+
+#[test]
+fn xml_list_style_from_str() {
+    assert_eq!(Ok(XmlListStyle::RepeatKey), "repeat-key".parse());
+    assert_eq!(
+        Ok(XmlListStyle::Wrapped {
+            item_name: "item".to_string()
+        }),
+        "wrapped".parse()
+    );
+    assert_eq!(
+        Ok(XmlListStyle::Wrapped {
+            item_name: "entry".to_string()
+        }),
+        "wrapped:entry".parse()
+    );
+    assert!("wrapped:".parse::<XmlListStyle>().is_err());
+    assert!("flat".parse::<XmlListStyle>().is_err());
+}
+
+#[test]
+fn negative_numbers() {
+    let value = Value::from([
+        ("offset", Value::Number("-5".to_string())),
+        ("ratio", Value::Number("-0.5".to_string())),
+    ]);
+    assert_eq!(
+        serde_json::json!({ "offset": -5, "ratio": -0.5 }),
+        to_json_value(&value).unwrap()
+    );
+    assert_eq!(
+        "offset = -5\nratio = -0.5\n",
+        to_toml_string(&value, &TomlOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn negative_list_elements() {
+    let value = compiled_init("{ offsets = [-1 -2] ratios = [0.5 -0.5] }");
+    assert_eq!(
+        serde_json::json!({ "offsets": [-1, -2], "ratios": [0.5, -0.5] }),
+        to_json_value(&value).unwrap()
+    );
+    assert_eq!(
+        "offsets = [-1, -2]\nratios = [0.5, -0.5]\n",
+        to_toml_string(&value, &TomlOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn toml_exponent_is_float() {
+    let value = Value::from([
+        ("large", Value::Number("1e9".to_string())),
+        ("small", Value::Number("2.5E-3".to_string())),
+    ]);
+    assert_eq!(
+        "large = 1000000000.0\nsmall = 0.0025\n",
+        to_toml_string(&value, &TomlOptions::default()).unwrap()
+    );
+}
+
+/// Value of the init of the nabla source, which must compile without errors
+fn compiled_init(src: &str) -> Value {
+    let result = compile(src);
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    result.init().cloned().expect("The source has an init")
+}
+
+#[test]
+fn nabla_string() {
+    let value = Value::from([
+        ("name", Value::from("app \"one\"\n")),
+        ("max-size", Value::from(10)),
+        ("max_size", Value::Number("-2.5e-3".to_string())),
+        ("true", Value::Bool(true)),
+        ("tags", Value::from(["a", "b"])),
+        (
+            "servers",
+            Value::List(vec![Value::from([("port", 80)]), empty_struct()]),
+        ),
+        ("nothing", Value::Null),
+        ("empty", empty_list()),
+    ]);
+    let src = to_nabla_string(&value).unwrap();
+    assert_eq!(
+        r#"{
+    name = "app \"one\"\n"
+    max_size_2 = 10 as "max-size"
+    max_size = -2.5e-3
+    true_2 = true as "true"
+    tags = ["a" "b"]
+    servers = [
+        {
+            port = 80
+        }
+        {}
+    ]
+    nothing = null
+    empty = []
+}
+"#,
+        src
+    );
+    assert_eq!(value, compiled_init(&src));
+}
+
+#[test]
+fn nabla_string_of_primitive() {
+    assert_eq!("\"a\"\n", to_nabla_string(&Value::from("a")).unwrap());
+    assert!(matches!(
+        to_nabla_string(&Value::Unknown),
+        Err(ImportError::UnknownValueError(_))
+    ));
+    assert!(matches!(
+        to_nabla_string(&Value::Number(".inf".to_string())),
+        Err(ImportError::InvalidNumber(number)) if number == ".inf"
+    ));
+}
+
+#[test]
+fn json_round_trip() {
+    let json = serde_json::json!({
+        "name": "app",
+        "content-type": "json",
+        "1st": { "": -1.0, "big": 12345678901234567890u64, "ratio": 1e-7 },
+        "hosts": [{ "host": "a", "port": 80 }, null, [true, false]],
+    });
+    let src = from_json_value(json.clone()).unwrap();
+    assert_eq!(json, to_json_value(&compiled_init(&src)).unwrap());
+}
+
+#[test]
+fn yaml_round_trip() {
+    let yaml: serde_yaml::Value =
+        serde_yaml::from_str("name: app\nports:\n  - 80\n  - 443\nmax size: 1.5\n").unwrap();
+    let src = from_yaml_value(yaml.clone()).unwrap();
+    assert_eq!(yaml, to_yaml_value(&compiled_init(&src)).unwrap());
+}
+
+#[test]
+fn toml_round_trip() {
+    let toml: toml::Value = toml::from_str(
+        "name = \"app\"\nratio = 1.0\nstarted = 1979-05-27T07:32:00Z\n\n[server]\nport = 80\n",
+    )
+    .unwrap();
+    let src = from_toml_value(toml.clone()).unwrap();
+    let value = to_toml_value(&compiled_init(&src)).unwrap().unwrap();
+    // dates are imported as strings
+    let mut expected = toml;
+    expected["started"] = toml::Value::String("1979-05-27T07:32:00Z".to_string());
+    assert_eq!(expected, value);
+}
@@ -0,0 +1,188 @@
+use crate::{
+    error::TomlValueError, record, to_toml_value_with_options, ConversionErrors, ConvertOptions,
+};
+use nabla_frontend::{
+    eval::{Value, ValuePath},
+    semantics::docs::Docs,
+};
+use toml_edit::{Array, ArrayOfTables, Document, InlineTable, Item, Table};
+
+/// How null values are converted, since TOML has no null
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NullBehavior {
+    /// Leave null values out of their struct or list
+    #[default]
+    Skip,
+    /// Report every null value as a `NullNotRepresentable` error
+    Error,
+}
+
+/// Options of the TOML representation of structs and lists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TomlOptions {
+    /// Emit lists, that only contain structs, as array of tables (e.g. `[[servers]]`)
+    /// instead of an array of inline tables.
+    pub prefer_array_of_tables: bool,
+    /// Emit structs with less fields than the threshold as inline tables.
+    /// Without a threshold, structs are only inlined inside of arrays.
+    pub inline_threshold: Option<usize>,
+    /// Emit infinite numbers and NaN as `inf` and `nan` instead of reporting them.
+    pub allow_non_finite: bool,
+    /// Leave null values out or report them
+    pub null_behavior: NullBehavior,
+    /// Options of all converters, e.g. to leave out unknown values
+    pub convert: ConvertOptions,
+    /// Key, under which a top-level value, that is not a struct, is emitted,
+    /// e.g. `value = [1, 2]` for the key `value`.
+    /// Without a key, such a value is a `StructlessRoot` error, because TOML documents are tables.
+    pub root_key: Option<String>,
+}
+
+/// Convert the value into a TOML document with the given table representation.
+///
+/// Unlike `toml::to_string_pretty`, the representation of every struct is decided
+/// by the options and not by the serializer.
+pub fn to_toml_string(
+    value: &Value,
+    options: &TomlOptions,
+) -> Result<String, ConversionErrors<TomlValueError>> {
+    to_toml_string_with_docs(value, options, &Docs::new())
+}
+
+/// Convert the value into a TOML document with the docs as comments above their keys.
+///
+/// Comments are placed above keys and table headers.
+/// Inline tables cannot contain comments, so the docs of their fields are left out.
+pub fn to_toml_string_with_docs(
+    value: &Value,
+    options: &TomlOptions,
+    docs: &Docs,
+) -> Result<String, ConversionErrors<TomlValueError>> {
+    let root = to_toml_table(value, options)?;
+    let document = Document::from(table(root, options, &ValuePath::root(), docs));
+    Ok(document.to_string())
+}
+
+/// Convert the top-level value into the root table of a TOML document.
+///
+/// An empty struct is an empty document.
+/// Null is a `NothingToEmit` error and any other value, e.g. a list, is emitted under the root key
+/// of the options or is a `StructlessRoot` error without one.
+pub fn to_toml_table(
+    value: &Value,
+    options: &TomlOptions,
+) -> Result<toml::Table, ConversionErrors<TomlValueError>> {
+    let error = match to_toml_value_with_options(value, options)? {
+        Some(toml::Value::Table(root)) => return Ok(root),
+        Some(value) => match &options.root_key {
+            Some(key) => return Ok(toml::Table::from_iter([(key.clone(), value)])),
+            None => TomlValueError::StructlessRoot,
+        },
+        None => TomlValueError::NothingToEmit,
+    };
+    let mut errors = Vec::new();
+    record::<(), _>(Err(error), &ValuePath::root(), &mut errors);
+    Err(errors)
+}
+
+fn table(map: toml::Table, options: &TomlOptions, path: &ValuePath, docs: &Docs) -> Table {
+    let mut table = Table::new();
+    for (key, value) in map {
+        let path = path.join_key(&key);
+        let mut item = item(value, options, &path, docs);
+        let doc = docs.get(&path).map(|doc| comments(doc));
+        match (&mut item, doc) {
+            (Item::Table(nested), Some(doc)) => {
+                // the header must be emitted to carry the comment
+                nested.set_implicit(false);
+                nested.decor_mut().set_prefix(format!("\n{}", doc));
+            }
+            (Item::ArrayOfTables(array_of_tables), Some(doc)) => {
+                if let Some(first) = array_of_tables.get_mut(0) {
+                    first.decor_mut().set_prefix(format!("\n{}", doc));
+                }
+            }
+            (_, Some(doc)) => {
+                table.insert(&key, item);
+                if let Some(mut key) = table.key_mut(&key) {
+                    key.leaf_decor_mut().set_prefix(doc);
+                }
+                continue;
+            }
+            (_, None) => {}
+        }
+        table.insert(&key, item);
+    }
+    // only emit a header for tables with own key/value pairs or without any content
+    if !table
+        .iter()
+        .any(|(key, _)| docs.contains_key(&path.join_key(key)))
+    {
+        table.set_implicit(!table.is_empty());
+    }
+    table
+}
+
+/// Lines of the doc as TOML comments, each ending with a line break.
+fn comments(doc: &str) -> String {
+    doc.lines()
+        .map(|line| {
+            if line.is_empty() {
+                "#\n".to_string()
+            } else {
+                format!("# {}\n", line)
+            }
+        })
+        .collect()
+}
+
+fn item(value: toml::Value, options: &TomlOptions, path: &ValuePath, docs: &Docs) -> Item {
+    match value {
+        toml::Value::Table(map) => {
+            let is_small = options
+                .inline_threshold
+                .is_some_and(|threshold| map.len() < threshold);
+            if is_small {
+                Item::Value(inline_table(map).into())
+            } else {
+                Item::Table(table(map, options, path, docs))
+            }
+        }
+        toml::Value::Array(elements)
+            if options.prefer_array_of_tables
+                && !elements.is_empty()
+                && elements.iter().all(toml::Value::is_table) =>
+        {
+            let mut array_of_tables = ArrayOfTables::new();
+            for (index, element) in elements.into_iter().enumerate() {
+                if let toml::Value::Table(map) = element {
+                    array_of_tables.push(table(map, options, &path.join_index(index), docs));
+                }
+            }
+            Item::ArrayOfTables(array_of_tables)
+        }
+        value => Item::Value(inline_value(value)),
+    }
+}
+
+fn inline_table(map: toml::Table) -> InlineTable {
+    map.into_iter()
+        .map(|(key, value)| (key, inline_value(value)))
+        .collect()
+}
+
+fn inline_value(value: toml::Value) -> toml_edit::Value {
+    match value {
+        toml::Value::String(s) => s.into(),
+        toml::Value::Integer(i) => i.into(),
+        toml::Value::Float(f) => f.into(),
+        toml::Value::Boolean(b) => b.into(),
+        toml::Value::Datetime(datetime) => datetime.into(),
+        toml::Value::Array(elements) => elements
+            .into_iter()
+            .map(inline_value)
+            .collect::<Array>()
+            .into(),
+        toml::Value::Table(map) => inline_table(map).into(),
+    }
+}
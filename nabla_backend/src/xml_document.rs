@@ -0,0 +1,136 @@
+use crate::{error::XmlValueError, first_error, to_xml_element, ConversionErrors, ConvertOptions};
+use nabla_frontend::eval::Value;
+use std::{fmt, str::FromStr};
+use xml_builder::{XMLBuilder, XMLVersion, XML};
+
+/// Version of the XML declaration
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum XmlVersion {
+    #[default]
+    V1_0,
+    V1_1,
+}
+
+impl fmt::Display for XmlVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V1_0 => write!(f, "1.0"),
+            Self::V1_1 => write!(f, "1.1"),
+        }
+    }
+}
+
+impl FromStr for XmlVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1.0" => Ok(Self::V1_0),
+            "1.1" => Ok(Self::V1_1),
+            _ => Err(format!(
+                "unsupported XML version `{}`, expected `1.0` or `1.1`",
+                s
+            )),
+        }
+    }
+}
+
+/// How lists are emitted in XML
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum XmlListStyle {
+    /// One element per item named after the field of the list, e.g. `<port>1</port><port>2</port>`.
+    /// Lists, that are not fields of a struct, are `StructlessList` errors.
+    #[default]
+    RepeatKey,
+    /// One element per list, that contains an element with the item name per item,
+    /// e.g. `<ports><item>1</item><item>2</item></ports>`, so lists can be nested.
+    Wrapped { item_name: String },
+}
+
+impl FromStr for XmlListStyle {
+    type Err = String;
+
+    /// Parses `repeat-key` or `wrapped`, whose items are named `item` unless given,
+    /// e.g. `wrapped:port`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "repeat-key" => Ok(Self::RepeatKey),
+            None if s == "wrapped" => Ok(Self::Wrapped {
+                item_name: DEFAULT_ITEM_NAME.to_string(),
+            }),
+            Some(("wrapped", item_name)) if !item_name.is_empty() => Ok(Self::Wrapped {
+                item_name: item_name.to_string(),
+            }),
+            _ => Err(format!(
+                "unsupported XML list style `{}`, expected `repeat-key`, `wrapped` or `wrapped:<item name>`",
+                s
+            )),
+        }
+    }
+}
+
+/// Name of the item elements of wrapped lists, unless it is given
+const DEFAULT_ITEM_NAME: &str = "item";
+
+/// Options of the XML document and its declaration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XmlOptions {
+    /// Name of the root element
+    pub root_name: String,
+    /// Version of the declaration
+    pub version: XmlVersion,
+    /// Encoding of the declaration.
+    /// It is only declared, the generated document is always UTF-8.
+    pub encoding: String,
+    /// Declare the document as `standalone="yes"`
+    pub standalone: bool,
+    /// Repeat the key of a list per item or wrap its items
+    pub list_style: XmlListStyle,
+    /// Options of all converters, e.g. to emit unknown values as empty elements
+    pub convert: ConvertOptions,
+}
+
+impl Default for XmlOptions {
+    fn default() -> Self {
+        Self {
+            root_name: "root".to_string(),
+            version: XmlVersion::default(),
+            encoding: "UTF-8".to_string(),
+            standalone: false,
+            list_style: XmlListStyle::default(),
+            convert: ConvertOptions::default(),
+        }
+    }
+}
+
+/// Convert the value into an XML document with the declaration of the options.
+///
+/// The value is the root element, see [`to_xml_value`](crate::to_xml_value),
+/// so a list at the top level is a `StructlessList` error.
+pub fn to_xml_document(value: &Value, options: &XmlOptions) -> Result<XML, XmlValueError> {
+    to_xml_document_all_errors(value, options).map_err(first_error)
+}
+
+/// Convert the whole value into an XML document and report all errors with their paths.
+pub fn to_xml_document_all_errors(
+    value: &Value,
+    options: &XmlOptions,
+) -> Result<XML, ConversionErrors<XmlValueError>> {
+    let root = to_xml_element(
+        value,
+        &options.root_name,
+        &options.convert,
+        &options.list_style,
+    )?;
+    let version = match options.version {
+        XmlVersion::V1_0 => XMLVersion::XML1_0,
+        XmlVersion::V1_1 => XMLVersion::XML1_1,
+    };
+    let mut xml = XMLBuilder::new()
+        .version(version)
+        .encoding(options.encoding.clone())
+        .standalone(options.standalone.then_some(true))
+        .build();
+    xml.set_root_element(root);
+    Ok(xml)
+}
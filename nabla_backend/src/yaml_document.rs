@@ -0,0 +1,130 @@
+use crate::{
+    error::YamlValueError, finish, to_yaml_value_with_options, yaml_value, ConversionErrors,
+    ConvertOptions,
+};
+use nabla_frontend::{
+    eval::{Value, ValuePath},
+    semantics::docs::Docs,
+};
+
+/// Width of a nested mapping
+const INDENT: &str = "  ";
+
+/// Convert the value into a YAML document with the docs as comments above their keys.
+///
+/// serde_yaml cannot emit comments, so the document is written by hand.
+/// Only the scalars and keys are serialized by serde_yaml,
+/// so the document has the same layout as `serde_yaml::to_string` apart from the comments.
+pub fn to_yaml_string_with_docs(
+    value: &Value,
+    options: &ConvertOptions,
+    docs: &Docs,
+) -> Result<String, ConversionErrors<YamlValueError>> {
+    let yaml = to_yaml_value_with_options(value, options)?;
+    let mut document = yaml_lines(&yaml, &ValuePath::root(), docs).join("\n");
+    document.push('\n');
+    Ok(document)
+}
+
+/// Convert the values into a stream of YAML documents, which are separated by `---`.
+///
+/// The errors are reported with the index of their document,
+/// e.g. `/1/port` for the `port` of the second value.
+pub fn to_yaml_documents(
+    values: &[Value],
+    options: &ConvertOptions,
+) -> Result<String, ConversionErrors<YamlValueError>> {
+    let mut errors = Vec::new();
+    let documents: Vec<_> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            yaml_value(
+                value,
+                &ValuePath::root().join_index(index),
+                options,
+                &mut errors,
+            )
+        })
+        .collect();
+    let documents = finish(documents, errors)?;
+    Ok(documents
+        .iter()
+        .map(|document| {
+            serde_yaml::to_string(document).expect("Converting value to yaml string failed")
+        })
+        .collect::<Vec<_>>()
+        .join("---\n"))
+}
+
+/// Lines of the value, as if it started at the beginning of a line.
+fn yaml_lines(value: &serde_yaml::Value, path: &ValuePath, docs: &Docs) -> Vec<String> {
+    match value {
+        serde_yaml::Value::Mapping(mapping) if !mapping.is_empty() => {
+            let mut lines = Vec::new();
+            for (key, value) in mapping {
+                // structs only have string keys
+                let path = key
+                    .as_str()
+                    .map_or_else(|| path.clone(), |key| path.join_key(key));
+                let key = scalar(key);
+                if let Some(doc) = docs.get(&path) {
+                    lines.extend(doc.lines().map(comment));
+                }
+                let mut nested = yaml_lines(value, &path, docs).into_iter();
+                match value {
+                    serde_yaml::Value::Mapping(mapping) if !mapping.is_empty() => {
+                        lines.push(format!("{}:", key));
+                        lines.extend(nested.map(|line| indent(INDENT, &line)));
+                    }
+                    // sequences are not indented below their key
+                    serde_yaml::Value::Sequence(sequence) if !sequence.is_empty() => {
+                        lines.push(format!("{}:", key));
+                        lines.extend(nested);
+                    }
+                    _ => {
+                        let first = nested.next().unwrap_or_default();
+                        lines.push(format!("{}: {}", key, first));
+                        lines.extend(nested);
+                    }
+                }
+            }
+            lines
+        }
+        serde_yaml::Value::Sequence(sequence) if !sequence.is_empty() => {
+            let mut lines = Vec::new();
+            for (index, element) in sequence.iter().enumerate() {
+                let mut nested = yaml_lines(element, &path.join_index(index), docs).into_iter();
+                let first = nested.next().unwrap_or_default();
+                lines.push(format!("- {}", first));
+                lines.extend(nested.map(|line| indent(INDENT, &line)));
+            }
+            lines
+        }
+        _ => scalar(value).lines().map(str::to_string).collect(),
+    }
+}
+
+/// Scalars, empty mappings and empty sequences as serialized by serde_yaml,
+/// e.g. quoted strings or block scalars, without the final line break.
+fn scalar(value: &serde_yaml::Value) -> String {
+    let yaml = serde_yaml::to_string(value).expect("Converting value to yaml string failed");
+    yaml.strip_suffix('\n').unwrap_or(&yaml).to_string()
+}
+
+fn comment(line: &str) -> String {
+    if line.is_empty() {
+        "#".to_string()
+    } else {
+        format!("# {}", line)
+    }
+}
+
+/// Indent the line, unless it is empty, so no trailing whitespace is written.
+fn indent(indent: &str, line: &str) -> String {
+    if line.is_empty() {
+        String::new()
+    } else {
+        format!("{}{}", indent, line)
+    }
+}
@@ -0,0 +1,323 @@
+use std::io::IsTerminal;
+
+#[cfg(test)]
+mod tests;
+
+const RESET: &str = "\x1b[0m";
+const KEY: &str = "\x1b[34m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[36m";
+/// Color of booleans and null
+const LITERAL: &str = "\x1b[33m";
+/// Color of doc comments, e.g. of `--emit-comments`
+const COMMENT: &str = "\x1b[90m";
+
+/// When to colorize the output
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Colorize, if stdout is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Auto => std::io::stdout().is_terminal(),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+/// Format of the serialized output
+#[derive(Clone, Copy, Debug)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+/// Colorize keys, strings, numbers and literals of the serialized output.
+///
+/// The output is only scanned, not parsed, and ANSI codes are inserted around its tokens.
+/// Removing them always gives back the output, even if a token is colored wrongly.
+pub fn colorize(output: &str, format: Format) -> String {
+    let mut painter = Painter::default();
+    match format {
+        Format::Json => painter.json(output),
+        Format::Yaml => painter.yaml(output),
+        Format::Toml => painter.toml(output),
+    }
+    painter.output
+}
+
+#[derive(Default)]
+struct Painter {
+    output: String,
+}
+
+impl Painter {
+    fn paint(&mut self, color: &str, text: &str) {
+        if !text.is_empty() {
+            self.output.push_str(color);
+            self.output.push_str(text);
+            self.output.push_str(RESET);
+        }
+    }
+
+    fn plain(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn json(&mut self, mut rest: &str) {
+        while let Some(c) = rest.chars().next() {
+            let len = match c {
+                '"' => {
+                    let len = quoted_len(rest, '"');
+                    let color = if rest[len..].trim_start().starts_with(':') {
+                        KEY
+                    } else {
+                        STRING
+                    };
+                    self.paint(color, &rest[..len]);
+                    len
+                }
+                '-' | '0'..='9' => self.number(rest),
+                'a'..='z' => self.word(rest),
+                _ => {
+                    self.plain(&rest[..c.len_utf8()]);
+                    c.len_utf8()
+                }
+            };
+            rest = &rest[len..];
+        }
+    }
+
+    /// Colorize the output line by line.
+    ///
+    /// Serialized YAML has a key or a scalar per line, possibly after sequence markers (`- `).
+    /// The lines of a block scalar (after `|-`) are more indented than its key.
+    fn yaml(&mut self, output: &str) {
+        let mut block_indent = None;
+        for line in output.split_inclusive('\n') {
+            let content = line.trim_end_matches('\n');
+            let indent = content.len() - content.trim_start_matches(' ').len();
+            if block_indent
+                .is_some_and(|block_indent| indent > block_indent || content.trim().is_empty())
+            {
+                self.plain(&content[..indent]);
+                self.paint(STRING, &content[indent..]);
+                self.plain(&line[content.len()..]);
+                continue;
+            }
+            block_indent = None;
+            self.plain(&content[..indent]);
+            let mut rest = &content[indent..];
+            while let Some(marker) = ["- ", "-"]
+                .into_iter()
+                .find(|marker| rest.starts_with(marker) && (rest.len() > 1 || *marker == "-"))
+            {
+                self.plain(marker);
+                rest = &rest[marker.len()..];
+            }
+            if rest.starts_with('#') {
+                self.paint(COMMENT, rest);
+                self.plain(&line[content.len()..]);
+                continue;
+            }
+            if let Some(len) = yaml_key_len(rest) {
+                self.paint(KEY, &rest[..len]);
+                let value = rest[len + 1..].trim_start();
+                self.plain(&rest[len..rest.len() - value.len()]);
+                rest = value;
+            }
+            if rest.starts_with('|') || rest.starts_with('>') {
+                block_indent = Some(indent);
+                self.plain(rest);
+            } else {
+                self.yaml_scalar(rest);
+            }
+            self.plain(&line[content.len()..]);
+        }
+    }
+
+    fn yaml_scalar(&mut self, scalar: &str) {
+        let color = match scalar {
+            "" | "{}" | "[]" => {
+                self.plain(scalar);
+                return;
+            }
+            "true" | "false" | "null" | "~" => LITERAL,
+            ".inf" | "-.inf" | ".nan" => NUMBER,
+            _ if scalar.parse::<f64>().is_ok() => NUMBER,
+            _ => STRING,
+        };
+        self.paint(color, scalar);
+    }
+
+    /// Colorize the output line by line.
+    ///
+    /// Lines starting with `[` are table headers, lines starting with `#` comments
+    /// and other lines contain a key or values.
+    /// Multi-line strings (`"""`) may continue over several lines.
+    fn toml(&mut self, output: &str) {
+        let mut open_string: Option<&str> = None;
+        for line in output.split_inclusive('\n') {
+            let content = line.trim_end_matches('\n');
+            let mut rest = content;
+            if let Some(delimiter) = open_string {
+                let Some(end) = rest.find(delimiter) else {
+                    self.paint(STRING, rest);
+                    self.plain(&line[content.len()..]);
+                    continue;
+                };
+                let len = end + delimiter.len();
+                self.paint(STRING, &rest[..len]);
+                rest = &rest[len..];
+            } else if rest.starts_with('#') {
+                self.paint(COMMENT, rest);
+                self.plain(&line[content.len()..]);
+                continue;
+            } else if rest.starts_with('[') {
+                self.paint(KEY, rest);
+                self.plain(&line[content.len()..]);
+                continue;
+            }
+            open_string = self.toml_values(rest);
+            self.plain(&line[content.len()..]);
+        }
+    }
+
+    /// Colorize keys and values of a line.
+    ///
+    /// Returns the delimiter of a multi-line string, that is not closed in the line.
+    fn toml_values(&mut self, mut rest: &str) -> Option<&'static str> {
+        while let Some(c) = rest.chars().next() {
+            let len = if let Some(len) = toml_key_len(rest) {
+                self.paint(KEY, &rest[..len]);
+                len
+            } else if let Some(delimiter) = ["\"\"\"", "'''"]
+                .into_iter()
+                .find(|delimiter| rest.starts_with(delimiter))
+            {
+                match rest[delimiter.len()..].find(delimiter) {
+                    Some(end) => {
+                        let len = 2 * delimiter.len() + end;
+                        self.paint(STRING, &rest[..len]);
+                        len
+                    }
+                    None => {
+                        self.paint(STRING, rest);
+                        return Some(delimiter);
+                    }
+                }
+            } else {
+                match c {
+                    '"' | '\'' => {
+                        let len = quoted_len(rest, c);
+                        self.paint(STRING, &rest[..len]);
+                        len
+                    }
+                    '-' | '+' | '0'..='9' => self.number(rest),
+                    'a'..='z' => self.word(rest),
+                    _ => {
+                        self.plain(&rest[..c.len_utf8()]);
+                        c.len_utf8()
+                    }
+                }
+            };
+            rest = &rest[len..];
+        }
+        None
+    }
+
+    /// Colorize a number at the start and return its length.
+    fn number(&mut self, rest: &str) -> usize {
+        let len = token_len(rest, |c| {
+            c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-' | '_')
+        });
+        self.paint(NUMBER, &rest[..len]);
+        len
+    }
+
+    /// Colorize a literal (e.g. `true`) at the start and return the length of the word.
+    fn word(&mut self, rest: &str) -> usize {
+        let len = token_len(rest, |c| c.is_ascii_alphanumeric() || c == '_');
+        match &rest[..len] {
+            "true" | "false" | "null" => self.paint(LITERAL, &rest[..len]),
+            "inf" | "nan" => self.paint(NUMBER, &rest[..len]),
+            word => self.plain(word),
+        }
+        len
+    }
+}
+
+/// Length of the first character and all following, that match the predicate.
+fn token_len(text: &str, predicate: impl Fn(char) -> bool) -> usize {
+    text.char_indices()
+        .skip(1)
+        .find(|(_, c)| !predicate(*c))
+        .map_or(text.len(), |(index, _)| index)
+}
+
+/// Length of the quoted string at the start, including its quotes.
+///
+/// Double quoted strings may contain escaped quotes (`\"`),
+/// single quoted strings doubled quotes (`''`).
+/// An unclosed string reaches to the end.
+fn quoted_len(text: &str, quote: char) -> usize {
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' && quote == '"' {
+            chars.next();
+        } else if c == quote {
+            if quote == '\'' && text[index + 1..].starts_with('\'') {
+                chars.next();
+            } else {
+                return index + 1;
+            }
+        }
+    }
+    text.len()
+}
+
+/// Length of the mapping key at the start of the YAML line, without the colon.
+fn yaml_key_len(line: &str) -> Option<usize> {
+    let len = match line.chars().next()? {
+        quote @ ('"' | '\'') => quoted_len(line, quote),
+        '[' | '{' => return None,
+        _ => line
+            .match_indices(':')
+            .map(|(index, _)| index)
+            .find(|index| matches!(line[index + 1..].chars().next(), None | Some(' ')))?,
+    };
+    let after = &line[len..];
+    (after == ":" || after.starts_with(": ")).then_some(len)
+}
+
+/// Length of the (dotted) TOML key at the start, if it is followed by ` = `.
+fn toml_key_len(text: &str) -> Option<usize> {
+    let mut len = 0;
+    loop {
+        let rest = &text[len..];
+        len += match rest.chars().next()? {
+            quote @ ('"' | '\'') if !rest.starts_with("\"\"\"") && !rest.starts_with("'''") => {
+                quoted_len(rest, quote)
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '-' => {
+                token_len(rest, |c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            }
+            _ => return None,
+        };
+        if text[len..].starts_with(" = ") {
+            return Some(len);
+        }
+        if !text[len..].starts_with('.') {
+            return None;
+        }
+        len += 1;
+    }
+}
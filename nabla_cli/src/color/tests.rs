@@ -0,0 +1,142 @@
+use super::*;
+use pretty_assertions::assert_eq;
+
+/// Remove the ANSI codes, that `colorize` inserted.
+fn strip(colored: &str) -> String {
+    [RESET, KEY, STRING, NUMBER, LITERAL, COMMENT]
+        .into_iter()
+        .fold(colored.to_string(), |output, code| output.replace(code, ""))
+}
+
+fn sample() -> serde_json::Value {
+    serde_json::json!({
+        "name": "cluster: \"main\"",
+        "it's": "don't",
+        "empty": "",
+        "port": 8080,
+        "ratio": -0.25,
+        "big": 1e300,
+        "port string": "80",
+        "bool string": "true",
+        "enabled": true,
+        "nothing": null,
+        "multi": "first line\nsecond line\n  indented",
+        "unicode": "grüße ✓",
+        "hosts": ["alpha", "beta: b", "- gamma", "#delta"],
+        "nested": [[1, 2], [], {}],
+        "owner": {
+            "name": "ops",
+            "contact": { "mail": "ops@example.com", "url": "https://example.com" }
+        },
+        "servers": [
+            { "host": "alpha", "port": 80, "tls": { "enabled": false } },
+            { "host": "beta", "port": 8080, "tls": { "enabled": true } }
+        ]
+    })
+}
+
+/// The sample without null, which TOML does not support
+fn toml_sample() -> toml::Value {
+    let mut sample = sample();
+    sample
+        .as_object_mut()
+        .expect("Sample is an object")
+        .remove("nothing");
+    toml::Value::try_from(sample).expect("Sample is valid TOML")
+}
+
+#[test]
+fn json_strips_to_plain() {
+    let json = serde_json::to_string_pretty(&sample()).unwrap();
+    assert_eq!(json, strip(&colorize(&json, Format::Json)));
+}
+
+#[test]
+fn yaml_strips_to_plain() {
+    let yaml = serde_yaml::to_string(&sample()).unwrap();
+    assert_eq!(yaml, strip(&colorize(&yaml, Format::Yaml)));
+}
+
+#[test]
+fn toml_strips_to_plain() {
+    let toml = toml::to_string_pretty(&toml_sample()).unwrap();
+    assert_eq!(toml, strip(&colorize(&toml, Format::Toml)));
+    let toml = toml::to_string(&toml_sample()).unwrap();
+    assert_eq!(toml, strip(&colorize(&toml, Format::Toml)));
+}
+
+#[test]
+fn json_tokens() {
+    assert_eq!(
+        format!(
+            "{{{KEY}\"a\"{RESET}: {STRING}\"b: c\"{RESET}, {KEY}\"n\"{RESET}: [{NUMBER}-1.5e3{RESET}, \
+             {LITERAL}true{RESET}, {LITERAL}null{RESET}]}}"
+        ),
+        colorize(r#"{"a": "b: c", "n": [-1.5e3, true, null]}"#, Format::Json)
+    );
+}
+
+#[test]
+fn yaml_tokens() {
+    assert_eq!(
+        format!(
+            "{KEY}port{RESET}: {NUMBER}80{RESET}\n\
+             {KEY}port string{RESET}: {STRING}'80'{RESET}\n\
+             {KEY}hosts{RESET}:\n\
+             - {STRING}alpha{RESET}\n\
+             - {KEY}tls{RESET}: {LITERAL}false{RESET}\n\
+             {KEY}text{RESET}: |-\n  {STRING}a: b{RESET}\n  {STRING}c{RESET}\n\
+             {KEY}empty{RESET}: {{}}\n"
+        ),
+        colorize(
+            "port: 80\nport string: '80'\nhosts:\n- alpha\n- tls: false\ntext: |-\n  a: b\n  c\nempty: {}\n",
+            Format::Yaml
+        )
+    );
+}
+
+#[test]
+fn toml_tokens() {
+    assert_eq!(
+        format!(
+            "{KEY}name{RESET} = {STRING}\"a = b\"{RESET}\n\
+             {KEY}ports{RESET} = [{NUMBER}80{RESET}, {NUMBER}-inf{RESET}]\n\
+             {KEY}text{RESET} = {STRING}'''{RESET}\n{STRING}first{RESET}\n{STRING}'''{RESET}\n\n\
+             {KEY}[servers.tls]{RESET}\n\
+             {KEY}\"quoted key\"{RESET} = {{ {KEY}enabled{RESET} = {LITERAL}true{RESET} }}\n"
+        ),
+        colorize(
+            "name = \"a = b\"\nports = [80, -inf]\ntext = '''\nfirst\n'''\n\n\
+             [servers.tls]\n\"quoted key\" = { enabled = true }\n",
+            Format::Toml
+        )
+    );
+}
+
+#[test]
+fn comments() {
+    assert_eq!(
+        format!(
+            "{COMMENT}# the port: 80{RESET}\n\
+             {KEY}port{RESET}: {NUMBER}80{RESET}\n\
+             {KEY}servers{RESET}:\n\
+             - {COMMENT}# first{RESET}\n  {KEY}tls{RESET}: {LITERAL}true{RESET}\n"
+        ),
+        colorize(
+            "# the port: 80\nport: 80\nservers:\n- # first\n  tls: true\n",
+            Format::Yaml
+        )
+    );
+    assert_eq!(
+        format!(
+            "{COMMENT}# the port = 80{RESET}\n\
+             {KEY}port{RESET} = {NUMBER}80{RESET}\n\n\
+             {COMMENT}# the server{RESET}\n\
+             {KEY}[server]{RESET}\n"
+        ),
+        colorize(
+            "# the port = 80\nport = 80\n\n# the server\n[server]\n",
+            Format::Toml
+        )
+    );
+}
@@ -0,0 +1,39 @@
+use nabla_frontend::semantics::graph::{Graph, GraphNodeKind};
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// Render the dependency graph as Graphviz DOT.
+///
+/// Defs are boxes, lets ellipses and inits diamonds.
+/// Nodes, whose value is not fully known, are red.
+pub fn to_dot(graph: &Graph) -> String {
+    let mut output = String::from("digraph dependencies {\n");
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let shape = match node.kind {
+            GraphNodeKind::Def => "box",
+            GraphNodeKind::Let => "ellipse",
+            GraphNodeKind::Init => "diamond",
+        };
+        let color = if node.is_known { "" } else { ", color=red" };
+        writeln!(
+            output,
+            "    {} [label=\"{}\", shape={}{}];",
+            index,
+            escape(&node.name),
+            shape,
+            color
+        )
+        .expect("Writing to string");
+    }
+    for (from, to) in &graph.edges {
+        writeln!(output, "    {} -> {};", from, to).expect("Writing to string");
+    }
+    output.push_str("}\n");
+    output
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,44 @@
+use super::to_dot;
+use nabla_frontend::semantics::graph::{Graph, GraphNode, GraphNodeKind};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn empty_graph() {
+    assert_eq!("digraph dependencies {\n}\n", to_dot(&Graph::default()));
+}
+
+#[test]
+fn shapes_and_colors() {
+    let graph = Graph {
+        nodes: vec![
+            GraphNode {
+                name: "Config".to_string(),
+                kind: GraphNodeKind::Def,
+                is_known: true,
+            },
+            GraphNode {
+                name: "port".to_string(),
+                kind: GraphNodeKind::Let,
+                is_known: false,
+            },
+            GraphNode {
+                name: "<init>".to_string(),
+                kind: GraphNodeKind::Init,
+                is_known: true,
+            },
+        ],
+        edges: vec![(2, 0), (2, 1)],
+    };
+    assert_eq!(
+        "\
+digraph dependencies {
+    0 [label=\"Config\", shape=box];
+    1 [label=\"port\", shape=ellipse, color=red];
+    2 [label=\"<init>\", shape=diamond];
+    2 -> 0;
+    2 -> 1;
+}
+",
+        to_dot(&graph)
+    );
+}
@@ -1,27 +1,55 @@
 use clap::Parser;
-use nabla_backend::{to_json_value, to_toml_value, to_xml_value, to_yaml_value};
+use color::ColorChoice;
+use color_eyre::eyre::{bail, WrapErr};
+use nabla_backend::{
+    import_json, import_toml, import_yaml, to_nabla_string, to_toml_string_with_docs,
+    to_toml_table, to_xml_document_all_errors, to_yaml_documents, to_yaml_string_with_docs,
+    to_yaml_value_with_options, unknown_paths, ConversionErrors, ConvertOptions, NullBehavior,
+    TomlOptions, XmlListStyle, XmlOptions, XmlVersion,
+};
 use nabla_frontend::{
+    compile::{self, CompileError, CompileOptions, CompileResult, Uses},
+    diagnostics::{Diagnostic, Severity},
+    eval::{Value, ValuePath},
     lexer::{self, LexerResult},
+    limits,
+    node::ToNode,
     parser::{self, ParserResult},
-    semantics::{self, SemanticsResult},
-    token::TextRange,
-    GlobalIdent, ModuleAst,
+    semantics::{
+        self, compat,
+        directives::FileDirectives,
+        docs::{self, Docs},
+        error::{Error as SemanticError, ErrorMessage},
+        graph::dependency_graph,
+        structure::GlobalRanges,
+        SemanticsResult,
+    },
+    token::Token,
+    GlobalIdent,
 };
-use std::path::PathBuf;
-
-macro_rules! printerr {
-    ($errors:expr, $src:expr, $tokens:expr) => {
-        for error in $errors {
-            let text_range =
-                $tokens[error.range.start].range.start..$tokens[error.range.end].range.end;
-            let range = convert_text_range(&$src, &text_range);
-            println!(
-                "Line {}, Char {}: {}",
-                range.start.line, range.start.char, error
-            );
-        }
-    };
-}
+use report::render_diagnostics;
+use resolver::{CaseSensitivity, FileResolver};
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use style::{Style, StyleOptions};
+
+/// Name of the root element, if neither a flag nor a directive sets it
+const DEFAULT_ROOT: &str = "root";
+/// File argument, that reads the source from stdin
+const STDIN: &str = "-";
+/// Name of stdin in diagnostics. Uses are resolved in the working directory.
+const STDIN_NAME: &str = "<stdin>";
+
+mod color;
+mod graph;
+mod report;
+mod resolver;
+mod style;
+mod symbols;
+mod test_runner;
 
 #[derive(Clone, Debug, Default, clap::ValueEnum)]
 enum Target {
@@ -30,114 +58,1131 @@ enum Target {
     Yaml,
     Toml,
     Xml,
+    /// Every other target, each written into its own file
+    All,
+}
+
+impl Target {
+    /// Targets of `All` in the order, in which they are emitted
+    const FORMATS: [Self; 4] = [Self::Json, Self::Yaml, Self::Toml, Self::Xml];
+
+    const fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Xml => "xml",
+            Self::All => "",
+        }
+    }
+
+    const fn color_format(&self) -> Option<color::Format> {
+        match self {
+            Self::Json => Some(color::Format::Json),
+            Self::Yaml => Some(color::Format::Yaml),
+            Self::Toml => Some(color::Format::Toml),
+            Self::Xml | Self::All => None,
+        }
+    }
+
+    /// Target of the compatibility check, `All` is not checked against a single target.
+    const fn compat(&self) -> Option<compat::Target> {
+        match self {
+            Self::Json => Some(compat::Target::Json),
+            Self::Yaml => Some(compat::Target::Yaml),
+            Self::Toml => Some(compat::Target::Toml),
+            Self::Xml => Some(compat::Target::Xml),
+            Self::All => None,
+        }
+    }
+}
+
+impl From<compat::Target> for Target {
+    fn from(target: compat::Target) -> Self {
+        match target {
+            compat::Target::Json => Self::Json,
+            compat::Target::Yaml => Self::Yaml,
+            compat::Target::Toml => Self::Toml,
+            compat::Target::Xml => Self::Xml,
+        }
+    }
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+enum DataFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+#[derive(Clone, Debug, Default, clap::ValueEnum)]
+enum AstFormat {
+    #[default]
+    Debug,
+    Json,
+}
+
+#[derive(Clone, Debug, Default, clap::ValueEnum)]
+enum GraphFormat {
+    #[default]
+    Dot,
 }
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    convert: ConvertArgs,
+}
+
+#[derive(Debug, clap::Args)]
+struct ConvertArgs {
+    /// The nabla file to convert. Read from stdin, if it is `-` or not given.
+    file: Option<PathBuf>,
+    /// Defaults to the `target` directive of the file or json.
+    /// `all` writes every format into its own file, see `--output`.
+    #[clap(short, long)]
+    target: Option<Target>,
+    /// Write the output into this file instead of stdout.
+    /// With `--target all`, the files are named after its stem with the extension of each format
+    /// (e.g. `out/config.json`) and default to the stem of the input file.
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+    /// Name of the xml root element and of the `--wrap-root` key.
+    /// An alias of the init takes precedence.
+    /// Defaults to the `root` directive of the file or `root`.
+    #[clap(long, visible_alias = "root-name")]
+    xml_root: Option<String>,
+    /// Version of the xml declaration, `1.0` or `1.1`
+    #[clap(long, default_value_t)]
+    xml_version: XmlVersion,
+    /// Encoding of the xml declaration. The output is always UTF-8.
+    #[clap(long, default_value = "UTF-8")]
+    xml_encoding: String,
+    /// Declare the xml document as `standalone="yes"`
+    #[clap(long)]
+    xml_standalone: bool,
+    /// Emit an xml element per list item named after the field (`repeat-key`),
+    /// or wrap the items of every list in an element named after the field (`wrapped`),
+    /// which allows top-level and nested lists. Items are named `item` or e.g. `wrapped:entry`.
+    #[clap(long, default_value = "repeat-key")]
+    xml_list_style: XmlListStyle,
+    /// Wrap the output in an object with the root name as its only key.
+    /// Ignored for xml, which always has a named root element.
+    /// Also set by the `wrap-root` directive of the file.
+    #[clap(long)]
+    wrap_root: bool,
+    /// Emit the keys of every struct sorted instead of in the order of their declaration.
+    /// Also set by the `sort-keys` directive of the file.
+    #[clap(long)]
+    sort_keys: bool,
+    /// Warn about references to globals, that are declared later in the file
+    #[clap(long)]
+    strict_order: bool,
+    /// Emit the default value of the def given by `--type` instead of the init.
+    /// Fails, if a field of the def has no default.
+    #[clap(long, requires = "type_name")]
+    defaults_only: bool,
+    /// The name of the def, whose defaults are emitted
+    #[clap(short = 'T', long = "type")]
+    type_name: Option<String>,
+    /// Accept any number of inits and emit all of them instead of only the first:
+    /// json as a top-level array and yaml as documents separated by `---`.
+    /// toml and xml documents cannot hold more than one value.
+    #[clap(long, conflicts_with_all = ["defaults_only", "emit_comments", "preserve_comments"])]
+    all_inits: bool,
+    /// Emit lists of structs as TOML array of tables (e.g. `[[servers]]`)
+    #[clap(long)]
+    toml_array_of_tables: bool,
+    /// Emit structs with less fields than the threshold as TOML inline tables
+    #[clap(long)]
+    toml_inline_threshold: Option<usize>,
+    /// Emit infinite numbers and NaN as TOML `inf` and `nan` instead of reporting them
+    #[clap(long)]
+    toml_allow_non_finite: bool,
+    /// Report null values as errors instead of leaving them out of the TOML output,
+    /// because TOML has no null
+    #[clap(long)]
+    strict_nulls: bool,
+    /// Emit a top-level value, that is not a struct (e.g. a list), under this TOML key
+    /// instead of failing, because TOML documents must be tables
+    #[clap(long)]
+    toml_root_key: Option<String>,
+    /// Emit the doc comments (`///`) of struct fields as yaml and toml comments above their keys.
+    /// Ignored for json and xml.
+    #[clap(long)]
+    emit_comments: bool,
+    /// Like `--emit-comments`, but emit the plain comments (`//`) of struct fields as well,
+    /// so all comments of the source are kept in the yaml and toml output.
+    #[clap(long)]
+    preserve_comments: bool,
+    /// Emit values, that are not fully initialized, e.g. because of a use, as warnings
+    /// and their unknown parts as null instead of failing. The paths of the unknown parts are
+    /// listed on stderr. TOML leaves them out and XML emits an empty element with a comment.
+    #[clap(long)]
+    allow_unknown: bool,
+    /// Spaces per indentation level of the json output.
+    /// This and the other style flags default to the nearest `.nabla-fmt.toml`.
+    #[clap(long)]
+    indent_width: Option<usize>,
+    /// Indent the json output with tabs
+    #[clap(long)]
+    use_tabs: Option<bool>,
+    /// Line width, after which long lists are wrapped by the formatter
+    #[clap(long)]
+    max_width: Option<usize>,
+    /// End the json output with a newline
+    #[clap(long)]
+    newline_at_eof: Option<bool>,
+    /// Refuse input files larger than this number of bytes
+    #[clap(long, default_value_t = limits::MAX_FILE_SIZE)]
+    max_file_size: usize,
+    /// Colorize json, yaml and toml output. `auto` colorizes, if stdout is a terminal.
+    #[clap(long, value_enum, default_value_t)]
+    color: ColorChoice,
+}
+
+impl ConvertArgs {
+    /// The file to convert, `None` for stdin
+    fn input_file(&self) -> Option<&Path> {
+        self.file
+            .as_deref()
+            .filter(|file| *file != Path::new(STDIN))
+    }
+
+    /// Whether comments are written into the yaml and toml output
+    const fn emits_comments(&self) -> bool {
+        self.emit_comments || self.preserve_comments
+    }
+
+    fn style_options(&self) -> StyleOptions {
+        StyleOptions {
+            indent_width: self.indent_width,
+            use_tabs: self.use_tabs,
+            max_width: self.max_width,
+            newline_at_eof: self.newline_at_eof,
+        }
+    }
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Convert a nabla file into the target format (the default command)
+    Convert(ConvertArgs),
+    /// Validate a JSON, YAML or TOML file against a def of a nabla schema
+    Validate(ValidateArgs),
+    /// Print the tokens of a nabla file
+    Tokens(TokensArgs),
+    /// Print the syntax tree of a nabla file
+    Ast(AstArgs),
+    /// Print the type rules and assertions of a nabla file as Graphviz DOT
+    DebugTypes(DebugTypesArgs),
+    /// Print the globals of a nabla file with their binding and value, sorted by name
+    Symbols(SymbolsArgs),
+    /// Print which defs, lets and inits of a nabla file reference which
+    Graph(GraphArgs),
+    /// Check the `test` blocks of a nabla file and report, which of them fail
+    Test(TestArgs),
+    /// Rewrite a nabla file in the canonical layout
+    Fmt(FmtArgs),
+    /// Translate a JSON, YAML or TOML file into a nabla file, whose init is its content
+    Import(ImportArgs),
+    /// Run the language server over stdio
+    Lsp,
+}
+
+#[derive(Debug, clap::Args)]
+struct ValidateArgs {
+    /// The data file to validate
+    #[clap(required_unless_present = "input")]
+    file: Option<PathBuf>,
+    /// The data file to validate, instead of the positional argument
+    #[clap(short, long, conflicts_with = "file")]
+    input: Option<PathBuf>,
+    /// The nabla file containing the def
+    #[clap(short, long)]
+    schema: PathBuf,
+    /// The name of the def to validate against
+    #[clap(short = 'T', long = "type")]
+    type_name: String,
+    /// Format of the data file. Derived from the file extension, if not given.
+    #[clap(short, long)]
+    format: Option<DataFormat>,
+    /// Warn about references to globals, that are declared later in the schema
+    #[clap(long)]
+    strict_order: bool,
+    /// Refuse schema and data files larger than this number of bytes
+    #[clap(long, default_value_t = limits::MAX_FILE_SIZE)]
+    max_file_size: usize,
+}
+
+#[derive(Debug, clap::Args)]
+struct TokensArgs {
     file: PathBuf,
-    #[clap(short, long, default_value = "json")]
-    target: Target,
 }
 
-fn main() -> color_eyre::Result<()> {
+#[derive(Debug, clap::Args)]
+struct DebugTypesArgs {
+    file: PathBuf,
+}
+
+#[derive(Debug, clap::Args)]
+struct SymbolsArgs {
+    file: PathBuf,
+}
+
+#[derive(Debug, clap::Args)]
+struct GraphArgs {
+    file: PathBuf,
+    /// `dot` prints a Graphviz graph with unknown values in red
+    #[clap(short, long, default_value = "dot")]
+    format: GraphFormat,
+}
+
+#[derive(Debug, clap::Args)]
+struct TestArgs {
+    file: PathBuf,
+}
+
+#[derive(Debug, clap::Args)]
+struct FmtArgs {
+    file: PathBuf,
+    /// Only check, that the file is formatted, and fail otherwise, without writing it
+    #[clap(long)]
+    check: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct ImportArgs {
+    /// The data file to translate
+    file: PathBuf,
+    /// Format of the data file. Derived from the file extension, if not given.
+    #[clap(short, long)]
+    format: Option<DataFormat>,
+    /// Write the nabla file instead of printing it to stdout
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+    /// Refuse data files larger than this number of bytes
+    #[clap(long, default_value_t = limits::MAX_FILE_SIZE)]
+    max_file_size: usize,
+}
+
+#[derive(Debug, clap::Args)]
+struct AstArgs {
+    file: PathBuf,
+    /// `debug` prints the AST, `json` prints the node tree
+    #[clap(short, long, default_value = "debug")]
+    format: AstFormat,
+}
+
+fn main() -> color_eyre::Result<ExitCode> {
     let args = Args::parse();
-    let src = std::fs::read_to_string(args.file).expect("Could not open file");
-    let mut valid = true;
-    let LexerResult { tokens, errors } = lexer::lex(&src);
-    if !errors.is_empty() {
-        valid = false
+    match args.command {
+        Some(Command::Validate(args)) => validate(args),
+        Some(Command::Tokens(args)) => tokens(args),
+        Some(Command::Ast(args)) => ast(args),
+        Some(Command::DebugTypes(args)) => debug_types(args),
+        Some(Command::Symbols(args)) => symbols(args),
+        Some(Command::Graph(args)) => graph(args),
+        Some(Command::Test(args)) => test(args),
+        Some(Command::Fmt(args)) => fmt(args),
+        Some(Command::Import(args)) => import(args),
+        Some(Command::Lsp) => {
+            nabla_ls::run();
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Command::Convert(args)) => convert(args),
+        None => convert(args.convert),
     }
-    for error in errors {
-        let range = convert_text_range(&src, &error.range);
-        println!(
-            "Line {}, Char {}: {}",
-            range.start.line, range.start.char, error
+}
+
+/// Read the file, unless it is larger than `max_file_size` bytes.
+fn read_source(path: &Path, max_file_size: usize) -> color_eyre::Result<String> {
+    let size = std::fs::metadata(path)
+        .wrap_err_with(|| format!("Could not open file {}", path.display()))?
+        .len();
+    if size > max_file_size as u64 {
+        bail!(
+            "{} is too large for analysis ({} bytes), the limit is {} bytes (`--max-file-size`)",
+            path.display(),
+            size,
+            max_file_size
         );
     }
-    let ParserResult { ast, errors } = parser::parse(&tokens);
-    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
-    if !errors.is_empty() {
-        valid = false
+    std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not open file {}", path.display()))
+}
+
+/// Read stdin to its end, unless it is larger than `max_file_size` bytes.
+fn read_stdin(max_file_size: usize) -> color_eyre::Result<String> {
+    let mut src = Vec::new();
+    std::io::stdin()
+        .lock()
+        .take(max_file_size as u64 + 1)
+        .read_to_end(&mut src)
+        .wrap_err("Could not read stdin")?;
+    if src.len() > max_file_size {
+        bail!(
+            "stdin is too large for analysis, the limit is {} bytes (`--max-file-size`)",
+            max_file_size
+        );
     }
-    printerr!(errors, src, tokens);
-    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
-    if !errors.is_empty() {
-        valid = false
+    String::from_utf8(src).wrap_err("stdin is not valid UTF-8")
+}
+
+/// Analyze the source together with the modules of its uses and print all errors.
+///
+/// The modules are the files next to the file,
+/// e.g. `use shared::Port` imports `Port` of `shared.nabla`.
+/// The `target` closure gets the directives of the file and returns the target with its options,
+/// in which the init is checked to be representable, if there is one.
+/// With `allow_unknown`, values, that are not fully initialized, are only warnings.
+///
+/// The errors are printed to stderr, so they do not mix with the output.
+/// Returns the semantics result and whether the source is valid.
+fn analyze(
+    src: &str,
+    file: &Path,
+    options: &semantics::Options,
+    allow_unknown: bool,
+    target: impl FnOnce(&FileDirectives) -> Option<(compat::Target, compat::Options)>,
+) -> (SemanticsResult, bool) {
+    // a file without directory, e.g. stdin, is in the working directory
+    let dir = file
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let resolver = FileResolver::new(dir.to_path_buf(), CaseSensitivity::default());
+    let CompileResult {
+        tokens,
+        module_ast,
+        semantics: semantics_result,
+        errors,
+    } = compile::compile_with_options(
+        src,
+        &CompileOptions {
+            semantics: options.clone(),
+            uses: Uses::Loaded(&resolver),
+        },
+    );
+    let globals = GlobalRanges::new(&module_ast.ast);
+    let mut diagnostics: Vec<_> = errors
+        .iter()
+        .map(|error| {
+            let mut diagnostic = to_diagnostic(&tokens, &globals, error);
+            let is_uninitialized = matches!(
+                error,
+                CompileError::Semantic(
+                    SemanticError {
+                        message: ErrorMessage::UninitializedDefault
+                            | ErrorMessage::UninitializedLet
+                            | ErrorMessage::UninitializedInit,
+                        ..
+                    },
+                    _
+                )
+            );
+            if allow_unknown && is_uninitialized {
+                diagnostic.severity = Severity::Warning;
+            }
+            diagnostic
+        })
+        .collect();
+    if let (Some((target, compat_options)), Some(init)) = (
+        target(&semantics_result.directives),
+        semantics_result.inits.first(),
+    ) {
+        diagnostics.extend(
+            compat::check_with_options(&module_ast, init, target, &compat_options)
+                .iter()
+                .map(|error| {
+                    Diagnostic::from_semantic_error(&tokens, error)
+                        .with_context(globals.enclosing(&error.range))
+                }),
+        );
     }
-    printerr!(errors, src, tokens);
+    let valid = !diagnostics.iter().any(Diagnostic::is_error);
+    eprint!(
+        "{}",
+        render_diagnostics(diagnostics, src, &file.display().to_string())
+    );
+    (semantics_result, valid)
+}
+
+/// Diagnostic of the error with the enclosing global as context.
+fn to_diagnostic(tokens: &[Token], globals: &GlobalRanges, error: &CompileError) -> Diagnostic {
+    error.to_diagnostic(tokens).with_context(
+        error
+            .token_range()
+            .and_then(|range| globals.enclosing(range)),
+    )
+}
+
+/// Output preferences of a conversion.
+struct Output {
+    target: Target,
+    root: String,
+    wrap_root: bool,
+    /// Sort the keys instead of emitting them in the order of their declaration
+    sort_keys: bool,
+    /// Key of a top-level toml value, that is not a struct
+    toml_root_key: Option<String>,
+    /// The items of xml lists are wrapped in an element per list
+    xml_wrapped_lists: bool,
+}
+
+impl Output {
+    /// Combine the flags with the directives of the file.
+    ///
+    /// Flags take precedence over directives, which take precedence over the defaults.
+    /// Returns a note for every directive, that is overridden by a flag.
+    fn resolve(args: &ConvertArgs, directives: &FileDirectives) -> (Self, Vec<String>) {
+        let mut notes = Vec::new();
+        let target = match (&args.target, directives.target) {
+            (Some(flag), Some(directive)) => {
+                if flag.compat() != Some(directive) {
+                    notes.push(format!(
+                        "`--target` overrides the directive `target={}`",
+                        directive.as_str().to_lowercase()
+                    ));
+                }
+                flag.clone()
+            }
+            (Some(flag), None) => flag.clone(),
+            (None, Some(directive)) => Target::from(directive),
+            (None, None) => Target::default(),
+        };
+        let root = match (&args.xml_root, &directives.root) {
+            (Some(flag), Some(directive)) => {
+                if flag != directive {
+                    notes.push(format!(
+                        "`--xml-root` overrides the directive `root={}`",
+                        directive
+                    ));
+                }
+                flag.clone()
+            }
+            (Some(root), None) | (None, Some(root)) => root.clone(),
+            (None, None) => DEFAULT_ROOT.to_string(),
+        };
+        let output = Self {
+            target,
+            root,
+            wrap_root: args.wrap_root || directives.wrap_root,
+            sort_keys: args.sort_keys || directives.sort_keys,
+            toml_root_key: args.toml_root_key.clone(),
+            xml_wrapped_lists: matches!(args.xml_list_style, XmlListStyle::Wrapped { .. }),
+        };
+        (output, notes)
+    }
+
+    /// Target of the compatibility check and how the init is placed in it.
+    fn compat_target(&self) -> Option<(compat::Target, compat::Options)> {
+        // xml always has a named root element and is never wrapped,
+        // a toml root key wraps every top-level value, that is not a struct
+        let is_wrapped = match self.target {
+            Target::Xml => false,
+            Target::Toml => self.wrap_root || self.toml_root_key.is_some(),
+            Target::Json | Target::Yaml | Target::All => self.wrap_root,
+        };
+        let options = compat::Options {
+            is_wrapped,
+            xml_wrapped_lists: self.xml_wrapped_lists,
+        };
+        self.target.compat().map(|target| (target, options))
+    }
+}
+
+fn convert(args: ConvertArgs) -> color_eyre::Result<ExitCode> {
+    let src = match args.input_file() {
+        Some(file) => read_source(file, args.max_file_size)?,
+        None => read_stdin(args.max_file_size)?,
+    };
+    let file = args.input_file().unwrap_or(Path::new(STDIN_NAME));
+    let (
+        SemanticsResult {
+            inits,
+            init_name,
+            defaults,
+            directives,
+            docs,
+            ..
+        },
+        valid,
+    ) = analyze(
+        &src,
+        file,
+        &semantics::Options {
+            strict_order: args.strict_order,
+            multiple_inits: args.all_inits,
+            plain_comments: args.preserve_comments,
+            ..Default::default()
+        },
+        args.allow_unknown,
+        |directives| Output::resolve(&args, directives).0.compat_target(),
+    );
+    let (output, notes) = Output::resolve(&args, &directives);
+    let style = Style::resolve(args.style_options(), file)?;
+    let colored = args.color.enabled();
+    for note in notes {
+        eprintln!("note: {}", note);
+    }
+    if let (true, Target::Toml | Target::Xml | Target::All) = (args.all_inits, &output.target) {
+        bail!("`--all-inits` is only supported by json and yaml, which can hold several values");
+    }
+    // the inits are emitted as one list, which json writes as array and yaml as documents
+    let all_inits = args
+        .all_inits
+        .then(|| Value::List(inits.clone()))
+        .filter(|_| !inits.is_empty());
     if valid {
-        if let Some(init) = inits.first() {
-            match args.target {
-                Target::Json => {
-                    let json = to_json_value(init.clone())?;
-                    let pretty_json = serde_json::to_string_pretty(&json)
-                        .expect("Converting value to json string failed");
-                    println!("{}", pretty_json);
+        let value = match &args.type_name {
+            Some(type_name) if args.defaults_only => {
+                match defaults.get(&GlobalIdent::default().extend(type_name.clone())) {
+                    Some(value @ Value::Struct(_)) => Some(value),
+                    Some(_) => bail!("`{}` is not a struct in {}", type_name, file.display()),
+                    None => bail!("`{}` is not defined in {}", type_name, file.display()),
+                }
+            }
+            _ if args.all_inits => all_inits.as_ref(),
+            _ => inits.first(),
+        };
+        if let Some(init) = value {
+            let sorted = output.sort_keys.then(|| {
+                let mut sorted = init.clone();
+                sorted.sort_keys();
+                sorted
+            });
+            let init = sorted.as_ref().unwrap_or(init);
+            // the alias of the init does not name the defaults of a def
+            let root_name = init_name
+                .filter(|_| !args.defaults_only)
+                .unwrap_or(output.root);
+            // only wrapping copies the init, the converters borrow it
+            let wrap = |init: &Value| Value::from([(root_name.as_str(), init.clone())]);
+            let wrapped = output.wrap_root.then(|| match init {
+                Value::List(inits) if args.all_inits => {
+                    Value::List(inits.iter().map(wrap).collect())
+                }
+                _ => wrap(init),
+            });
+            // the docs belong to the init, not to the defaults of a def
+            let docs = match (
+                args.emits_comments() && !args.defaults_only,
+                output.wrap_root,
+            ) {
+                (true, true) => docs::nest(docs, &root_name),
+                (true, false) => docs,
+                (false, _) => Docs::new(),
+            };
+            let emitter = Emitter {
+                args: &args,
+                init,
+                wrapped: wrapped.as_ref().unwrap_or(init),
+                root_name: &root_name,
+                docs: &docs,
+                style: &style,
+                toml_root_key: output.toml_root_key,
+            };
+            if let Target::All = output.target {
+                return emit_all(&emitter, &args);
+            }
+            if let (Target::Json, None, false) = (&output.target, &args.output, colored) {
+                // without color, the JSON is written straight to stdout
+                emitter.report_unknown_values(&Target::Json);
+                let mut stdout = std::io::BufWriter::new(std::io::stdout().lock());
+                if emitter.write_json(&mut stdout).is_none() {
+                    return Ok(ExitCode::FAILURE);
+                }
+                stdout.flush().wrap_err("Could not write to stdout")?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let Some(emitted) = emitter.emit(&output.target)? else {
+                return Ok(ExitCode::FAILURE);
+            };
+            match &args.output {
+                Some(path) => write_output(path, &emitted)?,
+                None => match output.target.color_format() {
+                    Some(color_format) if colored => {
+                        print!("{}", color::colorize(&emitted, color_format))
+                    }
+                    _ => print!("{}", emitted),
+                },
+            }
+        } else if args.target.is_some() || args.output.is_some() {
+            eprintln!("error: {} has no init to convert", file.display());
+            return Ok(ExitCode::FAILURE);
+        } else {
+            eprintln!("No errors detected.");
+        }
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Write every format into its own file,
+/// named after the stem of `--output` or else of the input file.
+///
+/// A failed conversion does not stop the other formats.
+fn emit_all(emitter: &Emitter, args: &ConvertArgs) -> color_eyre::Result<ExitCode> {
+    let Some(base) = args.output.as_deref().or(args.input_file()) else {
+        bail!("`--target all` needs `--output`, if the source is read from stdin");
+    };
+    let mut exit_code = ExitCode::SUCCESS;
+    for target in &Target::FORMATS {
+        match emitter.emit(target)? {
+            Some(emitted) => write_output(&base.with_extension(target.extension()), &emitted)?,
+            None => exit_code = ExitCode::FAILURE,
+        }
+    }
+    Ok(exit_code)
+}
+
+/// Write the file atomically, so it is never left half written.
+///
+/// The output is written into a temporary file next to it, which then replaces it.
+fn write_output(path: &Path, emitted: &str) -> color_eyre::Result<()> {
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+    let result = std::fs::write(&temp, emitted).and_then(|_| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result.wrap_err_with(|| format!("Could not write file {}", path.display()))
+}
+
+/// Converts the emitted value into the formats.
+struct Emitter<'a> {
+    args: &'a ConvertArgs,
+    /// Value, that is converted into xml, which is never wrapped
+    init: &'a Value,
+    /// Value, that is converted into the other formats
+    wrapped: &'a Value,
+    root_name: &'a str,
+    docs: &'a Docs,
+    style: &'a Style,
+    toml_root_key: Option<String>,
+}
+
+impl Emitter<'_> {
+    /// Serialize the value into the target without color, as it is printed.
+    ///
+    /// Returns `None`, if the conversion failed and its errors were printed.
+    fn emit(&self, target: &Target) -> color_eyre::Result<Option<String>> {
+        let args = self.args;
+        let convert_options = self.convert_options();
+        self.report_unknown_values(target);
+        let emitted = match target {
+            Target::All => unreachable!("Every target of `all` is emitted on its own"),
+            Target::Json => {
+                let mut json = Vec::new();
+                if self.write_json(&mut json).is_none() {
+                    return Ok(None);
                 }
-                Target::Yaml => {
-                    let yaml = to_yaml_value(init.clone())?;
-                    let pretty_yaml = serde_yaml::to_string(&yaml)
-                        .expect("Converting value to yaml string failed");
-                    println!("{}", pretty_yaml);
+                String::from_utf8(json).wrap_err("Generated JSON is not UTF-8")?
+            }
+            Target::Yaml if args.all_inits => {
+                let Value::List(inits) = self.wrapped else {
+                    unreachable!("All inits are emitted as list");
+                };
+                match to_yaml_documents(inits, &convert_options) {
+                    Ok(yaml) => format!("{}\n", yaml),
+                    Err(errors) => return Ok(report_conversion_errors(errors)),
                 }
-                Target::Toml => {
-                    let toml = to_toml_value(init.clone())?;
-                    let pretty_toml = toml::to_string_pretty(&toml)
-                        .expect("Converting value to yaml string failed");
-                    println!("{}", pretty_toml);
+            }
+            Target::Yaml if args.emits_comments() => {
+                match to_yaml_string_with_docs(self.wrapped, &convert_options, self.docs) {
+                    Ok(yaml) => format!("{}\n", yaml),
+                    Err(errors) => return Ok(report_conversion_errors(errors)),
                 }
-                Target::Xml => {
-                    let element = to_xml_value(init.clone(), "root")?;
-                    let mut xml = xml_builder::XMLBuilder::new().build();
-                    xml.set_root_element(element);
-                    xml.generate(std::io::stdout())
-                        .expect("Generation XML failed");
+            }
+            Target::Yaml => {
+                let yaml = match to_yaml_value_with_options(self.wrapped, &convert_options) {
+                    Ok(yaml) => yaml,
+                    Err(errors) => return Ok(report_conversion_errors(errors)),
+                };
+                let pretty_yaml = serde_yaml::to_string(&yaml)
+                    .wrap_err("Converting value to yaml string failed")?;
+                format!("{}\n", pretty_yaml)
+            }
+            Target::Toml
+                if args.toml_array_of_tables
+                    || args.toml_inline_threshold.is_some()
+                    || args.toml_allow_non_finite
+                    || args.emits_comments() =>
+            {
+                let options = TomlOptions {
+                    prefer_array_of_tables: args.toml_array_of_tables,
+                    inline_threshold: args.toml_inline_threshold,
+                    allow_non_finite: args.toml_allow_non_finite,
+                    null_behavior: self.null_behavior(),
+                    convert: convert_options,
+                    root_key: self.toml_root_key.clone(),
+                };
+                match to_toml_string_with_docs(self.wrapped, &options, self.docs) {
+                    Ok(toml) => format!("{}\n", toml),
+                    Err(errors) => return Ok(report_conversion_errors(errors)),
                 }
             }
+            Target::Toml => {
+                let options = TomlOptions {
+                    null_behavior: self.null_behavior(),
+                    convert: convert_options,
+                    root_key: self.toml_root_key.clone(),
+                    ..TomlOptions::default()
+                };
+                let toml = match to_toml_table(self.wrapped, &options) {
+                    Ok(toml) => toml,
+                    Err(errors) => return Ok(report_conversion_errors(errors)),
+                };
+                let pretty_toml = toml::to_string_pretty(&toml)
+                    .wrap_err("Converting value to toml string failed")?;
+                format!("{}\n", pretty_toml)
+            }
+            Target::Xml => {
+                let options = XmlOptions {
+                    root_name: self.root_name.to_string(),
+                    version: args.xml_version,
+                    encoding: args.xml_encoding.clone(),
+                    standalone: args.xml_standalone,
+                    list_style: args.xml_list_style.clone(),
+                    convert: convert_options,
+                };
+                let xml = match to_xml_document_all_errors(self.init, &options) {
+                    Ok(xml) => xml,
+                    Err(errors) => return Ok(report_conversion_errors(errors)),
+                };
+                let mut output = Vec::new();
+                xml.generate(&mut output)
+                    .wrap_err("Generating XML failed")?;
+                String::from_utf8(output).wrap_err("Generated XML is not UTF-8")?
+            }
+        };
+        Ok(Some(emitted))
+    }
+
+    /// Write the value as JSON without building a JSON value first.
+    ///
+    /// Returns `None`, if the conversion failed and its errors were printed.
+    fn write_json(&self, writer: impl Write) -> Option<()> {
+        match self
+            .style
+            .write_json(self.wrapped, writer, &self.convert_options())
+        {
+            Ok(()) => Some(()),
+            Err(errors) => report_conversion_errors(errors),
+        }
+    }
+
+    fn convert_options(&self) -> ConvertOptions {
+        ConvertOptions {
+            unknown_as_null: self.args.allow_unknown,
+        }
+    }
+
+    /// TOML leaves null values out, unless they are reported (`--strict-nulls`).
+    const fn null_behavior(&self) -> NullBehavior {
+        if self.args.strict_nulls {
+            NullBehavior::Error
         } else {
-            println!("No errors detected.");
+            NullBehavior::Skip
         }
     }
-    Ok(())
+
+    /// Print the unknown values, that the target emits as placeholders (`--allow-unknown`).
+    fn report_unknown_values(&self, target: &Target) {
+        if !self.args.allow_unknown {
+            return;
+        }
+        let (emitted, placeholder) = match target {
+            Target::Json | Target::Yaml | Target::All => (self.wrapped, "emitted as null"),
+            Target::Toml => (self.wrapped, "left out"),
+            Target::Xml => (self.init, "emitted as empty elements"),
+        };
+        report_unknown_values(&unknown_paths(emitted), placeholder);
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct Pos {
-    line: usize,
-    char: usize,
+/// Print the paths of the unknown values and how they are emitted instead.
+fn report_unknown_values(paths: &[ValuePath], placeholder: &str) {
+    if paths.is_empty() {
+        return;
+    }
+    eprintln!(
+        "note: {} unknown value(s) {} (`--allow-unknown`):",
+        paths.len(),
+        placeholder
+    );
+    for path in paths {
+        eprintln!("  {}", path);
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct PosRange {
-    start: Pos,
-    end: Pos,
+/// Print all errors of a failed conversion, which has no output.
+fn report_conversion_errors<E: std::fmt::Display, T>(errors: ConversionErrors<E>) -> Option<T> {
+    for error in errors {
+        eprintln!("{}", error);
+    }
+    None
 }
 
-fn convert_text_range(text: &str, range: &TextRange) -> PosRange {
-    let before_range = &text[..range.start];
-    let start = before_range
-        .split('\n')
-        .enumerate()
-        .last()
-        .map(|(line_number, last_line)| Pos {
-            line: line_number,
-            char: last_line.len(),
-        })
-        .expect("Split must yield at least one element");
-    let in_range = &text[range.clone()];
-    let end = in_range
-        .split('\n')
-        .enumerate()
-        .last()
-        .map(|(line_number, last_line)| Pos {
-            line: line_number + start.line,
-            char: if line_number == 0 {
-                // end is on the same line as start, therefore the char positions must be added
-                start.char + last_line.len()
-            } else {
-                last_line.len()
-            },
+fn validate(args: ValidateArgs) -> color_eyre::Result<ExitCode> {
+    let schema = read_source(&args.schema, args.max_file_size)?;
+    let (SemanticsResult { types, .. }, valid) = analyze(
+        &schema,
+        &args.schema,
+        &semantics::Options {
+            strict_order: args.strict_order,
+            ..Default::default()
+        },
+        false,
+        |_| None,
+    );
+    if !valid {
+        return Ok(ExitCode::FAILURE);
+    }
+    let file = args
+        .file
+        .or(args.input)
+        .expect("clap requires the file or the input");
+    let value = read_data(&file, args.format, args.max_file_size)?;
+    let ident = GlobalIdent::default().extend(args.type_name.clone());
+    let Some(errors) = types.validate_ident(&ident, &value) else {
+        bail!(
+            "`{}` is not defined in {}",
+            args.type_name,
+            args.schema.display()
+        );
+    };
+    if errors.is_empty() {
+        println!("No errors detected.");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        for error in errors {
+            println!("{}", error);
+        }
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Read the data file in the given format or the format of its extension.
+fn read_data(
+    path: &Path,
+    format: Option<DataFormat>,
+    max_file_size: usize,
+) -> color_eyre::Result<Value> {
+    let format = match format {
+        Some(format) => format,
+        None => match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => DataFormat::Json,
+            Some("yaml" | "yml") => DataFormat::Yaml,
+            Some("toml") => DataFormat::Toml,
+            _ => bail!("Could not derive data format, please specify `--format`"),
+        },
+    };
+    let data = read_source(path, max_file_size)?;
+    Ok(match format {
+        DataFormat::Json => import_json(serde_json::from_str(&data)?),
+        DataFormat::Yaml => import_yaml(serde_yaml::from_str(&data)?)?,
+        DataFormat::Toml => import_toml(toml::from_str(&data)?),
+    })
+}
+
+/// Translate the data file into nabla source and print or write it.
+fn import(args: ImportArgs) -> color_eyre::Result<ExitCode> {
+    let value = read_data(&args.file, args.format, args.max_file_size)?;
+    let src = to_nabla_string(&value)
+        .wrap_err_with(|| format!("Could not translate {}", args.file.display()))?;
+    match &args.output {
+        Some(path) => write_output(path, &src)?,
+        None => print!("{}", src),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print all tokens, even if there are lexical errors.
+/// Errors are printed to stderr.
+fn tokens(args: TokensArgs) -> color_eyre::Result<ExitCode> {
+    let src = read_source(&args.file, limits::MAX_FILE_SIZE)?;
+    let LexerResult { tokens, errors } = lexer::lex(&src);
+    for token in &tokens {
+        println!("{:?} {:?}", token.range, token.token_type);
+    }
+    let diagnostics = errors.iter().map(Diagnostic::from_lexer_error).collect();
+    eprint!(
+        "{}",
+        render_diagnostics(diagnostics, &src, &args.file.display().to_string())
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print the (partial) syntax tree, even if there are lexical or parse errors.
+/// Errors are printed to stderr.
+fn ast(args: AstArgs) -> color_eyre::Result<ExitCode> {
+    let src = read_source(&args.file, limits::MAX_FILE_SIZE)?;
+    let LexerResult { tokens, errors } = lexer::lex(&src);
+    let mut diagnostics: Vec<_> = errors.iter().map(Diagnostic::from_lexer_error).collect();
+    let ParserResult { ast, errors } = parser::parse(&tokens);
+    diagnostics.extend(
+        errors
+            .iter()
+            .map(|error| Diagnostic::from_parser_error(&tokens, error)),
+    );
+    match args.format {
+        AstFormat::Debug => println!("{:#?}", ast),
+        AstFormat::Json => {
+            let json = serde_json::to_string_pretty(&ast.to_node())
+                .wrap_err("Converting node to json string failed")?;
+            println!("{}", json);
+        }
+    }
+    eprint!(
+        "{}",
+        render_diagnostics(diagnostics, &src, &args.file.display().to_string())
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Format the file in place, unless it is already formatted.
+///
+/// Files with lexical or parse errors are not formatted, their errors are printed to stderr.
+/// With `--check`, the file is never written and an unformatted file fails.
+fn fmt(args: FmtArgs) -> color_eyre::Result<ExitCode> {
+    let src = read_source(&args.file, limits::MAX_FILE_SIZE)?;
+    let LexerResult { tokens, errors } = lexer::lex(&src);
+    let mut diagnostics: Vec<_> = errors
+        .iter()
+        .filter(|error| error.message.severity() == Severity::Error)
+        .map(Diagnostic::from_lexer_error)
+        .collect();
+    let ParserResult { ast, errors } = parser::parse(&tokens);
+    diagnostics.extend(
+        errors
+            .iter()
+            .map(|error| Diagnostic::from_parser_error(&tokens, error)),
+    );
+    if !diagnostics.is_empty() {
+        eprint!(
+            "{}",
+            render_diagnostics(diagnostics, &src, &args.file.display().to_string())
+        );
+        eprintln!(
+            "error: {} is not formatted, because it does not parse",
+            args.file.display()
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+    let formatted = nabla_frontend::format(&ast, &src);
+    if formatted == src {
+        return Ok(ExitCode::SUCCESS);
+    }
+    if args.check {
+        eprintln!("{} is not formatted", args.file.display());
+        return Ok(ExitCode::FAILURE);
+    }
+    write_output(&args.file, &formatted)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print the type rules as DOT graph, even if there are errors.
+/// Errors are printed to stderr.
+fn debug_types(args: DebugTypesArgs) -> color_eyre::Result<ExitCode> {
+    let src = read_source(&args.file, limits::MAX_FILE_SIZE)?;
+    let result = compile::compile(&src);
+    let diagnostics = result
+        .errors
+        .iter()
+        .map(|error| error.to_diagnostic(&result.tokens))
+        .collect();
+    print!("{}", result.semantics.types.to_dot());
+    eprint!(
+        "{}",
+        render_diagnostics(diagnostics, &src, &args.file.display().to_string())
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print the symbol table, even if there are errors.
+/// Errors are printed to stderr.
+fn symbols(args: SymbolsArgs) -> color_eyre::Result<ExitCode> {
+    let src = read_source(&args.file, limits::MAX_FILE_SIZE)?;
+    let result = compile::compile(&src);
+    let diagnostics = result
+        .errors
+        .iter()
+        .map(|error| error.to_diagnostic(&result.tokens))
+        .collect();
+    print!(
+        "{}",
+        symbols::render(result.symbol_table(), &result.semantics.bindings)
+    );
+    eprint!(
+        "{}",
+        render_diagnostics(diagnostics, &src, &args.file.display().to_string())
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print the dependency graph, even if there are errors.
+/// Errors are printed to stderr.
+fn graph(args: GraphArgs) -> color_eyre::Result<ExitCode> {
+    let src = read_source(&args.file, limits::MAX_FILE_SIZE)?;
+    let result = compile::compile(&src);
+    let diagnostics = result
+        .errors
+        .iter()
+        .map(|error| error.to_diagnostic(&result.tokens))
+        .collect();
+    let dependencies = dependency_graph(&result.module_ast, &result.semantics);
+    match args.format {
+        GraphFormat::Dot => print!("{}", graph::to_dot(&dependencies)),
+    }
+    eprint!(
+        "{}",
+        render_diagnostics(diagnostics, &src, &args.file.display().to_string())
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Check the test blocks of a file.
+///
+/// Errors outside of the test blocks are written to stderr.
+/// Fails, if any test or the rest of the file has errors.
+fn test(args: TestArgs) -> color_eyre::Result<ExitCode> {
+    let src = read_source(&args.file, limits::MAX_FILE_SIZE)?;
+    let result = compile::compile(&src);
+    let globals = GlobalRanges::new(&result.module_ast.ast);
+    let diagnostics: Vec<_> = result
+        .errors
+        .iter()
+        .map(|error| to_diagnostic(&result.tokens, &globals, error))
+        .collect();
+    let blocks = test_runner::collect(&result.module_ast.ast, &result.tokens);
+    let outcomes = test_runner::run(&blocks, &diagnostics);
+    print!("{}", test_runner::render(&outcomes, &src));
+    let outside: Vec<_> = diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            !blocks
+                .iter()
+                .any(|block| test_runner::contains(&block.range, diagnostic))
         })
-        .expect("Split must yield at least one element");
-    PosRange { start, end }
+        .collect();
+    let has_errors = outside
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error);
+    eprint!(
+        "{}",
+        render_diagnostics(outside, &src, &args.file.display().to_string())
+    );
+    if has_errors || outcomes.iter().any(|outcome| !outcome.passed()) {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
 }
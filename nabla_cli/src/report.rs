@@ -0,0 +1,76 @@
+use nabla_frontend::{
+    diagnostics::{self, Diagnostic, Severity, Stage},
+    line_index::LineIndex,
+};
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// Render diagnostics grouped by compilation stage and severity and sorted by position,
+/// followed by summary lines.
+/// Duplicate diagnostics are only rendered once.
+/// Notes are rendered indented below their diagnostic.
+/// Diagnostics inside of a global are prefixed by it, e.g. `[def Config] Line 3, Char 4: ...`.
+///
+/// Returns an empty string, if there are no diagnostics.
+pub fn render_diagnostics(mut diagnostics: Vec<Diagnostic>, src: &str, file_name: &str) -> String {
+    diagnostics::normalize(&mut diagnostics);
+    let line_index = LineIndex::new(src);
+    let mut output = String::new();
+    for (severity, name) in [(Severity::Error, "error"), (Severity::Warning, "warning")] {
+        let mut counts = Vec::new();
+        for stage in [Stage::Lex, Stage::Parse, Stage::Semantics] {
+            let group: Vec<_> = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.stage == stage && diagnostic.severity == severity)
+                .collect();
+            if group.is_empty() {
+                continue;
+            }
+            writeln!(output, "{} {}s:", capitalize(stage.as_str()), name)
+                .expect("Writing to string");
+            for diagnostic in &group {
+                let start = line_index.position(diagnostic.range.start);
+                if let Some(context) = &diagnostic.context {
+                    write!(output, "[{}] ", context).expect("Writing to string");
+                }
+                writeln!(
+                    output,
+                    "Line {}, Char {}: {}",
+                    start.line, start.col, diagnostic.message
+                )
+                .expect("Writing to string");
+                for note in &diagnostic.notes {
+                    let start = line_index.position(note.range.start);
+                    writeln!(
+                        output,
+                        "  Line {}, Char {}: note: {}",
+                        start.line, start.col, note.message
+                    )
+                    .expect("Writing to string");
+                }
+            }
+            counts.push(format!(
+                "{} {} {}{}",
+                group.len(),
+                stage.as_str(),
+                name,
+                if group.len() == 1 { "" } else { "s" }
+            ));
+        }
+        if !counts.is_empty() {
+            writeln!(output, "{}: {} in {}", name, counts.join(", "), file_name)
+                .expect("Writing to string");
+        }
+    }
+    output
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,153 @@
+use super::*;
+use nabla_frontend::diagnostics::Note;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn no_diagnostics() {
+    assert_eq!("", render_diagnostics(Vec::new(), "", "empty.nabla"));
+}
+
+#[test]
+fn grouped_by_stage() {
+    let src = "def x = @\nlet y = z\n";
+    let diagnostics = vec![
+        Diagnostic::new(Stage::Semantics, "`z` is not defined".to_string(), 18..19),
+        Diagnostic::new(Stage::Lex, "Unknown character".to_string(), 8..9),
+        Diagnostic::new(Stage::Parse, "unexpected tokens".to_string(), 8..9),
+        Diagnostic::new(Stage::Semantics, "unknown type".to_string(), 4..5),
+    ];
+    assert_eq!(
+        "\
+Lexical errors:
+Line 0, Char 8: Unknown character
+Parse errors:
+Line 0, Char 8: unexpected tokens
+Semantic errors:
+Line 0, Char 4: unknown type
+Line 1, Char 8: `z` is not defined
+error: 1 lexical error, 1 parse error, 2 semantic errors in config.nabla
+",
+        render_diagnostics(diagnostics, src, "config.nabla")
+    );
+}
+
+#[test]
+fn duplicates_are_removed() {
+    let src = "Config {}";
+    let diagnostics = vec![
+        Diagnostic::new(Stage::Semantics, "missing field: `a`".to_string(), 7..9),
+        Diagnostic::new(Stage::Semantics, "missing field: `a`".to_string(), 7..9),
+        Diagnostic::new(Stage::Semantics, "missing field: `b`".to_string(), 7..9),
+    ];
+    assert_eq!(
+        "\
+Semantic errors:
+Line 0, Char 7: missing field: `a`
+Line 0, Char 7: missing field: `b`
+error: 2 semantic errors in config.nabla
+",
+        render_diagnostics(diagnostics, src, "config.nabla")
+    );
+}
+
+#[test]
+fn warnings_after_errors() {
+    let src = "def A\nlet x: A = y\n";
+    let diagnostics = vec![
+        Diagnostic::new(Stage::Semantics, "`A` is opaque".to_string(), 13..14)
+            .with_severity(Severity::Warning),
+        Diagnostic::new(Stage::Semantics, "`y` is not defined".to_string(), 17..18),
+    ];
+    assert_eq!(
+        "\
+Semantic errors:
+Line 1, Char 11: `y` is not defined
+error: 1 semantic error in config.nabla
+Semantic warnings:
+Line 1, Char 7: `A` is opaque
+warning: 1 semantic warning in config.nabla
+",
+        render_diagnostics(diagnostics, src, "config.nabla")
+    );
+}
+
+#[test]
+fn crlf_and_bom() {
+    let src = "\u{feff}def x = @\r\nlet y = z\r\n";
+    let diagnostics = vec![
+        Diagnostic::new(Stage::Lex, "Unknown character".to_string(), 11..12),
+        Diagnostic::new(Stage::Semantics, "`z` is not defined".to_string(), 22..23),
+    ];
+    assert_eq!(
+        "\
+Lexical errors:
+Line 0, Char 8: Unknown character
+Semantic errors:
+Line 1, Char 8: `z` is not defined
+error: 1 lexical error, 1 semantic error in config.nabla
+",
+        render_diagnostics(diagnostics, src, "config.nabla")
+    );
+}
+
+#[test]
+fn emoji_columns() {
+    // the column counts UTF-16 code units, like editors do
+    let src = "let s = \"🦀\" @\r\nlet t = \"é\" + z";
+    let diagnostics = vec![
+        Diagnostic::new(Stage::Lex, "Unknown character".to_string(), 15..16),
+        Diagnostic::new(Stage::Semantics, "`z` is not defined".to_string(), 33..34),
+    ];
+    assert_eq!(
+        "\
+Lexical errors:
+Line 0, Char 13: Unknown character
+Semantic errors:
+Line 1, Char 14: `z` is not defined
+error: 1 lexical error, 1 semantic error in config.nabla
+",
+        render_diagnostics(diagnostics, src, "config.nabla")
+    );
+}
+
+#[test]
+fn notes_below_diagnostic() {
+    let src = "def a = {}\ndef a = {}\n";
+    let mut diagnostic = Diagnostic::new(
+        Stage::Semantics,
+        "`a` was alreay declared".to_string(),
+        15..16,
+    );
+    diagnostic.notes = vec![Note {
+        message: "first declared here".to_string(),
+        range: 4..5,
+    }];
+    assert_eq!(
+        "\
+Semantic errors:
+Line 1, Char 4: `a` was alreay declared
+  Line 0, Char 4: note: first declared here
+error: 1 semantic error in config.nabla
+",
+        render_diagnostics(vec![diagnostic], src, "config.nabla")
+    );
+}
+
+#[test]
+fn context_prefix() {
+    let src = "def A = { a: Number = true }\n{}\n";
+    let diagnostics = vec![
+        Diagnostic::new(Stage::Semantics, "types do not match".to_string(), 22..26)
+            .with_context(Some("def A")),
+        Diagnostic::new(Stage::Semantics, "no context".to_string(), 29..31),
+    ];
+    assert_eq!(
+        "\
+Semantic errors:
+[def A] Line 0, Char 22: types do not match
+Line 1, Char 0: no context
+error: 2 semantic errors in config.nabla
+",
+        render_diagnostics(diagnostics, src, "config.nabla")
+    );
+}
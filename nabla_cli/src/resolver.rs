@@ -0,0 +1,112 @@
+use nabla_frontend::{
+    resolver::{module_files, validate_module_name, ModuleResolver, ModuleSource, ResolveError},
+    GlobalIdent,
+};
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+pub const EXTENSION: &str = "nabla";
+
+/// How module names are compared to file names.
+///
+/// The comparison is done on the directory entries,
+/// so the result does not depend on the case sensitivity of the file system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseSensitivity {
+    /// Module names must match file names exactly.
+    #[default]
+    Sensitive,
+    /// Module names match file names regardless of case.
+    /// Files that only differ in case are reported as ambiguous.
+    // the CLI always resolves case sensitively, only the tests select it
+    #[cfg_attr(not(test), allow(dead_code))]
+    Insensitive,
+}
+
+impl CaseSensitivity {
+    fn matches(&self, name: &str, file_name: &str) -> bool {
+        match self {
+            Self::Sensitive => name == file_name,
+            Self::Insensitive => name.to_lowercase() == file_name.to_lowercase(),
+        }
+    }
+}
+
+/// Resolves modules from the file system.
+///
+/// The module `root::a::b` is mapped to the file `<root_dir>/a/b.nabla`.
+/// File names, that are not valid UTF-8, never match a module.
+#[derive(Clone, Debug)]
+pub struct FileResolver {
+    root_dir: PathBuf,
+    case_sensitivity: CaseSensitivity,
+}
+
+impl FileResolver {
+    pub fn new(root_dir: PathBuf, case_sensitivity: CaseSensitivity) -> Self {
+        Self {
+            root_dir,
+            case_sensitivity,
+        }
+    }
+
+    /// Find the single directory entry matching the name.
+    fn find_entry(
+        &self,
+        ident: &GlobalIdent,
+        dir: &Path,
+        name: &str,
+    ) -> Result<PathBuf, ResolveError> {
+        let entries = std::fs::read_dir(dir).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => ResolveError::NotFound(ident.clone()),
+            _ => ResolveError::Io(ident.clone(), err.to_string()),
+        })?;
+        let mut candidates = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|err| ResolveError::Io(ident.clone(), err.to_string()))?;
+            if let Some(file_name) = entry.file_name().to_str() {
+                if self.case_sensitivity.matches(name, file_name) {
+                    candidates.push(entry.path());
+                }
+            }
+        }
+        candidates.sort();
+        match candidates.len() {
+            0 => Err(ResolveError::NotFound(ident.clone())),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(ResolveError::Ambiguous(
+                ident.clone(),
+                candidates
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl ModuleResolver for FileResolver {
+    fn resolve(&self, ident: &GlobalIdent) -> Result<ModuleSource, ResolveError> {
+        validate_module_name(ident)?;
+        let (file, dirs) = ident.path.split_last().expect("Module name is not empty");
+        let mut path = self.root_dir.clone();
+        for dir in dirs {
+            path = self.find_entry(ident, &path, dir)?;
+        }
+        let path = self.find_entry(ident, &path, &format!("{}.{}", file, EXTENSION))?;
+        let src = std::fs::read_to_string(&path)
+            .map_err(|err| ResolveError::Io(ident.clone(), err.to_string()))?;
+        Ok(ModuleSource {
+            name: ident.clone(),
+            origin: path.display().to_string(),
+            src,
+        })
+    }
+
+    /// All module files below the root directory, in the tree `root`.
+    fn modules(&self) -> Vec<GlobalIdent> {
+        module_files(&self.root_dir, EXTENSION)
+    }
+}
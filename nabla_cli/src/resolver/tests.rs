@@ -0,0 +1,102 @@
+use super::*;
+use pretty_assertions::assert_eq;
+
+/// Temporary module tree, that is removed on drop.
+struct TempTree(PathBuf);
+
+impl TempTree {
+    fn new(name: &str, files: &[&str]) -> Self {
+        let root =
+            std::env::temp_dir().join(format!("nabla-resolver-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, format!("// {}", file)).unwrap();
+        }
+        Self(root)
+    }
+}
+
+impl Drop for TempTree {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn module(path: &[&str]) -> GlobalIdent {
+    GlobalIdent::default().extend_multiple(path.iter().map(|s| s.to_string()).collect())
+}
+
+#[test]
+fn resolve_nested_file() {
+    let tree = TempTree::new("nested", &["dir/file.nabla"]);
+    let resolver = FileResolver::new(tree.0.clone(), CaseSensitivity::Sensitive);
+    let source = resolver.resolve(&module(&["dir", "file"])).unwrap();
+    assert_eq!("// dir/file.nabla", source.src);
+    assert_eq!(
+        tree.0.join("dir").join("file.nabla").display().to_string(),
+        source.origin
+    );
+}
+
+#[test]
+fn case_sensitive() {
+    let tree = TempTree::new("sensitive", &["dir/file.nabla"]);
+    let resolver = FileResolver::new(tree.0.clone(), CaseSensitivity::Sensitive);
+    assert_eq!(
+        Err(ResolveError::NotFound(module(&["Dir", "File"]))),
+        resolver.resolve(&module(&["Dir", "File"]))
+    );
+}
+
+#[test]
+fn case_insensitive() {
+    let tree = TempTree::new("insensitive", &["dir/file.nabla"]);
+    let resolver = FileResolver::new(tree.0.clone(), CaseSensitivity::Insensitive);
+    let source = resolver.resolve(&module(&["Dir", "File"])).unwrap();
+    assert_eq!("// dir/file.nabla", source.src);
+}
+
+#[test]
+fn case_insensitive_ambiguous() {
+    let tree = TempTree::new("ambiguous", &["file.nabla", "FILE.nabla"]);
+    // Case insensitive file systems cannot contain both files.
+    if std::fs::read_dir(&tree.0).unwrap().count() < 2 {
+        return;
+    }
+    let resolver = FileResolver::new(tree.0.clone(), CaseSensitivity::Insensitive);
+    assert_eq!(
+        Err(ResolveError::Ambiguous(
+            module(&["file"]),
+            vec![
+                tree.0.join("FILE.nabla").display().to_string(),
+                tree.0.join("file.nabla").display().to_string(),
+            ]
+        )),
+        resolver.resolve(&module(&["file"]))
+    );
+}
+
+#[test]
+fn invalid_name() {
+    let tree = TempTree::new("invalid", &["file.nabla"]);
+    let resolver = FileResolver::new(tree.0.join("dir"), CaseSensitivity::Sensitive);
+    assert_eq!(
+        Err(ResolveError::InvalidName(module(&["..", "file"]))),
+        resolver.resolve(&module(&["..", "file"]))
+    );
+}
+
+#[test]
+fn list_modules() {
+    let tree = TempTree::new(
+        "list",
+        &["a.nabla", "dir/b.nabla", "dir/notes.txt", "in valid.nabla"],
+    );
+    let resolver = FileResolver::new(tree.0.clone(), CaseSensitivity::Sensitive);
+    assert_eq!(
+        vec![module(&["a"]), module(&["dir", "b"])],
+        resolver.modules()
+    );
+}
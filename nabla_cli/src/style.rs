@@ -0,0 +1,162 @@
+use color_eyre::eyre::{bail, eyre, WrapErr};
+use nabla_backend::{
+    error::{ConversionError, JsonValueError},
+    write_json_with_formatter, ConversionErrors, ConvertOptions,
+};
+use nabla_frontend::eval::{Value, ValuePath};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Name of the style file, that is discovered upward from the input file
+pub const FILE_NAME: &str = ".nabla-fmt.toml";
+
+/// Layout of the emitted output and of formatted nabla files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Style {
+    /// Number of spaces per indentation level, ignored if `use_tabs` is set
+    pub indent_width: usize,
+    /// Indent with one tab per level
+    pub use_tabs: bool,
+    /// Width, after which the formatter wraps long lists.
+    /// The emitted JSON is not wrapped.
+    pub max_width: usize,
+    /// End the output with a newline
+    pub newline_at_eof: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            use_tabs: false,
+            max_width: 100,
+            newline_at_eof: true,
+        }
+    }
+}
+
+/// Style options, that are set explicitly, e.g. by a flag or in a style file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyleOptions {
+    pub indent_width: Option<usize>,
+    pub use_tabs: Option<bool>,
+    pub max_width: Option<usize>,
+    pub newline_at_eof: Option<bool>,
+}
+
+impl StyleOptions {
+    /// Parse the content of a style file, e.g. `indent_width = 4`.
+    ///
+    /// Unknown keys and values of the wrong type are errors.
+    pub fn parse(content: &str) -> color_eyre::Result<Self> {
+        let table: toml::Table = content.parse()?;
+        let mut options = Self::default();
+        for (key, value) in table {
+            match key.as_str() {
+                "indent_width" => options.indent_width = Some(parse_width(&key, &value)?),
+                "use_tabs" => options.use_tabs = Some(parse_bool(&key, &value)?),
+                "max_width" => options.max_width = Some(parse_width(&key, &value)?),
+                "newline_at_eof" => options.newline_at_eof = Some(parse_bool(&key, &value)?),
+                _ => bail!("Unknown style option `{}`", key),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Fill the options, that are not set, from the other options.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            indent_width: self.indent_width.or(other.indent_width),
+            use_tabs: self.use_tabs.or(other.use_tabs),
+            max_width: self.max_width.or(other.max_width),
+            newline_at_eof: self.newline_at_eof.or(other.newline_at_eof),
+        }
+    }
+
+    /// Fill the options, that are not set, with the defaults.
+    pub fn into_style(self) -> Style {
+        let default = Style::default();
+        Style {
+            indent_width: self.indent_width.unwrap_or(default.indent_width),
+            use_tabs: self.use_tabs.unwrap_or(default.use_tabs),
+            max_width: self.max_width.unwrap_or(default.max_width),
+            newline_at_eof: self.newline_at_eof.unwrap_or(default.newline_at_eof),
+        }
+    }
+}
+
+fn parse_width(key: &str, value: &toml::Value) -> color_eyre::Result<usize> {
+    value
+        .as_integer()
+        .and_then(|width| usize::try_from(width).ok())
+        .ok_or_else(|| eyre!("Style option `{}` must be a non-negative integer", key))
+}
+
+fn parse_bool(key: &str, value: &toml::Value) -> color_eyre::Result<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| eyre!("Style option `{}` must be a boolean", key))
+}
+
+impl Style {
+    /// Combine the flags with the nearest style file of the input file.
+    ///
+    /// Flags take precedence over the style file, which takes precedence over the defaults.
+    pub fn resolve(flags: StyleOptions, file: &Path) -> color_eyre::Result<Self> {
+        let options = match discover(file) {
+            Some(path) => {
+                let content = std::fs::read_to_string(&path)
+                    .wrap_err_with(|| format!("Could not open style file {}", path.display()))?;
+                let options = StyleOptions::parse(&content)
+                    .wrap_err_with(|| format!("Invalid style file {}", path.display()))?;
+                flags.or(options)
+            }
+            None => flags,
+        };
+        Ok(options.into_style())
+    }
+
+    /// Whitespace of one indentation level.
+    pub fn indent(&self) -> String {
+        if self.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent_width)
+        }
+    }
+
+    /// Write the value as indented JSON.
+    pub fn write_json(
+        &self,
+        value: &Value,
+        mut writer: impl Write,
+        options: &ConvertOptions,
+    ) -> Result<(), ConversionErrors<JsonValueError>> {
+        let indent = self.indent();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        write_json_with_formatter(value, &mut writer, formatter, options)?;
+        if self.newline_at_eof {
+            writer.write_all(b"\n").map_err(|error| {
+                vec![ConversionError::new(
+                    ValuePath::root(),
+                    JsonValueError::WriteError(error),
+                )]
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the style file in the directory of the file or the nearest of its ancestors.
+pub fn discover(file: &Path) -> Option<PathBuf> {
+    let file = std::path::absolute(file).unwrap_or_else(|_| file.to_path_buf());
+    file.ancestors()
+        .skip(1)
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|path| path.is_file())
+}
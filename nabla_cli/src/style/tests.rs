@@ -0,0 +1,136 @@
+use super::*;
+use pretty_assertions::assert_eq;
+
+/// Temporary directory tree, that is removed on drop.
+struct TempTree(PathBuf);
+
+impl TempTree {
+    fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let root =
+            std::env::temp_dir().join(format!("nabla-style-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        for (file, content) in files {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        Self(root)
+    }
+}
+
+impl Drop for TempTree {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn discover_nearest() {
+    let tree = TempTree::new(
+        "nearest",
+        &[
+            (FILE_NAME, "indent_width = 4"),
+            ("a/.nabla-fmt.toml", "indent_width = 8"),
+            ("a/b/c.nabla", ""),
+            ("d/e.nabla", ""),
+        ],
+    );
+    assert_eq!(
+        Some(tree.0.join("a").join(FILE_NAME)),
+        discover(&tree.0.join("a/b/c.nabla"))
+    );
+    assert_eq!(
+        Some(tree.0.join(FILE_NAME)),
+        discover(&tree.0.join("d/e.nabla"))
+    );
+}
+
+#[test]
+fn nearest_file_takes_precedence() {
+    let tree = TempTree::new(
+        "precedence",
+        &[
+            (FILE_NAME, "indent_width = 4\nuse_tabs = true"),
+            ("a/.nabla-fmt.toml", "indent_width = 8"),
+            ("a/b.nabla", ""),
+        ],
+    );
+    let style = Style::resolve(StyleOptions::default(), &tree.0.join("a/b.nabla")).unwrap();
+    // only the nearest file is read
+    assert_eq!(
+        Style {
+            indent_width: 8,
+            ..Style::default()
+        },
+        style
+    );
+}
+
+#[test]
+fn flags_take_precedence() {
+    let tree = TempTree::new(
+        "flags",
+        &[
+            (FILE_NAME, "indent_width = 4\nnewline_at_eof = false"),
+            ("a.nabla", ""),
+        ],
+    );
+    let flags = StyleOptions {
+        indent_width: Some(3),
+        ..StyleOptions::default()
+    };
+    let style = Style::resolve(flags, &tree.0.join("a.nabla")).unwrap();
+    assert_eq!(
+        Style {
+            indent_width: 3,
+            newline_at_eof: false,
+            ..Style::default()
+        },
+        style
+    );
+}
+
+#[test]
+fn invalid_style_file() {
+    assert!(StyleOptions::parse("indent = 4").is_err());
+    assert!(StyleOptions::parse("indent_width = -1").is_err());
+    assert!(StyleOptions::parse("use_tabs = 1").is_err());
+    assert_eq!(
+        StyleOptions {
+            max_width: Some(80),
+            ..StyleOptions::default()
+        },
+        StyleOptions::parse("max_width = 80").unwrap()
+    );
+}
+
+#[test]
+fn json_indent() {
+    let value = Value::from([("a", Value::from([1]))]);
+    let to_json_string = |style: &Style| {
+        let mut json = Vec::new();
+        style
+            .write_json(&value, &mut json, &ConvertOptions::default())
+            .unwrap();
+        String::from_utf8(json).unwrap()
+    };
+    assert_eq!(
+        "{\n  \"a\": [\n    1\n  ]\n}\n",
+        to_json_string(&Style::default())
+    );
+    let style = Style {
+        indent_width: 4,
+        newline_at_eof: false,
+        ..Style::default()
+    };
+    assert_eq!(
+        "{\n    \"a\": [\n        1\n    ]\n}",
+        to_json_string(&style)
+    );
+    let style = Style {
+        use_tabs: true,
+        ..Style::default()
+    };
+    assert_eq!("{\n\t\"a\": [\n\t\t1\n\t]\n}\n", to_json_string(&style));
+}
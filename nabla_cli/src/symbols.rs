@@ -0,0 +1,49 @@
+use nabla_frontend::semantics::{namespace::Binding, BindingMap, SymbolTable};
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// Values longer than this number of characters are truncated
+pub const MAX_VALUE_WIDTH: usize = 60;
+
+/// Render every entry of the symbol table on its own line, sorted by its identifier.
+///
+/// A line contains the identifier, the binding (`def` or `let`),
+/// whether the value is fully known and the value, e.g. `root::Config  def  known    { port = 80 }`.
+/// The columns are aligned and long values truncated to `MAX_VALUE_WIDTH` characters.
+pub fn render(symbol_table: &SymbolTable, bindings: &BindingMap) -> String {
+    let mut entries: Vec<_> = symbol_table.iter().collect();
+    entries.sort_by_key(|(ident, _)| *ident);
+    let idents: Vec<_> = entries.iter().map(|(ident, _)| ident.to_string()).collect();
+    let width = idents.iter().map(String::len).max().unwrap_or_default();
+    let mut output = String::new();
+    for (ident, (global_ident, value)) in idents.iter().zip(entries) {
+        let binding = match bindings.get(global_ident) {
+            Some(Binding::Def) => "def",
+            Some(Binding::Let) => "let",
+            None => "?",
+        };
+        let known = if value.is_known() { "known" } else { "unknown" };
+        writeln!(
+            output,
+            "{:width$}  {}  {:7}  {}",
+            ident,
+            binding,
+            known,
+            truncate(&value.to_string(), MAX_VALUE_WIDTH)
+        )
+        .expect("Writing to string");
+    }
+    output
+}
+
+/// Cut the text to the number of characters, marking the cut with `...`.
+pub fn truncate(text: &str, max_width: usize) -> String {
+    const ELLIPSIS: &str = "...";
+    if text.chars().count() <= max_width {
+        return text.to_string();
+    }
+    let kept = max_width.saturating_sub(ELLIPSIS.len());
+    text.chars().take(kept).chain(ELLIPSIS.chars()).collect()
+}
@@ -0,0 +1,39 @@
+use super::{render, truncate};
+use nabla_frontend::{
+    eval::Value,
+    semantics::{namespace::Binding, BindingMap, SymbolTable},
+    GlobalIdent,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn short_text() {
+    assert_eq!("{ a = 1 }", truncate("{ a = 1 }", 9));
+}
+
+#[test]
+fn long_text() {
+    assert_eq!("{ a...", truncate("{ a = 1 }", 6));
+}
+
+#[test]
+fn multi_byte_text() {
+    assert_eq!("äö...", truncate("äöüäöü", 5));
+}
+
+#[test]
+fn sorted_and_aligned() {
+    let ident = |name: &str| GlobalIdent::default().extend(name.to_string());
+    let symbol_table = SymbolTable::from([
+        (ident("value"), Value::Unknown),
+        (ident("Config"), Value::from([("port", Value::from(80))])),
+    ]);
+    let bindings = BindingMap::from([
+        (ident("value"), Binding::Let),
+        (ident("Config"), Binding::Def),
+    ]);
+    assert_eq!(
+        "root::Config  def  known    { port = 80 }\nroot::value   let  unknown  ?\n",
+        render(&symbol_table, &bindings)
+    );
+}
@@ -0,0 +1,102 @@
+use nabla_frontend::{
+    ast::{Ast, Global},
+    diagnostics::{self, Diagnostic, Severity},
+    line_index::LineIndex,
+    token::{TextRange, Token},
+};
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// Named `test` block of a nabla file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestBlock {
+    pub name: String,
+    pub range: TextRange,
+}
+
+/// Result of a single test block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestOutcome {
+    pub name: String,
+    /// Errors inside of the block
+    pub failures: Vec<Diagnostic>,
+}
+
+impl TestOutcome {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Test blocks of the file in their order.
+/// Blocks without a name are already reported by the parser.
+pub fn collect(ast: &Ast, tokens: &[Token]) -> Vec<TestBlock> {
+    ast.globals
+        .iter()
+        .filter_map(|global| match global {
+            Global::Test(test) => Some(TestBlock {
+                name: test.name.as_ref()?.name.clone(),
+                range: diagnostics::to_text_range(tokens, &test.info.range),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A test fails, if any error starts inside of its block.
+/// Warnings never fail a test.
+pub fn run(blocks: &[TestBlock], diagnostics: &[Diagnostic]) -> Vec<TestOutcome> {
+    blocks
+        .iter()
+        .map(|block| TestOutcome {
+            name: block.name.clone(),
+            failures: diagnostics
+                .iter()
+                .filter(|diagnostic| {
+                    diagnostic.severity == Severity::Error && contains(&block.range, diagnostic)
+                })
+                .cloned()
+                .collect(),
+        })
+        .collect()
+}
+
+/// Whether the diagnostic starts inside of the range.
+/// An empty diagnostic at the end of the range, e.g. of a missing `}`, belongs to it.
+pub fn contains(range: &TextRange, diagnostic: &Diagnostic) -> bool {
+    range.contains(&diagnostic.range.start)
+        || (diagnostic.range.is_empty() && diagnostic.range.start == range.end)
+}
+
+/// Render one line per test, e.g. `test local ... ok`,
+/// with the errors of failed tests indented below it, followed by a summary line.
+pub fn render(outcomes: &[TestOutcome], src: &str) -> String {
+    let line_index = LineIndex::new(src);
+    let mut output = String::new();
+    for outcome in outcomes {
+        let status = if outcome.passed() { "ok" } else { "FAILED" };
+        writeln!(output, "test {} ... {}", outcome.name, status).expect("Writing to string");
+        for failure in &outcome.failures {
+            let start = line_index.position(failure.range.start);
+            writeln!(
+                output,
+                "  Line {}, Char {}: {}",
+                start.line, start.col, failure.message
+            )
+            .expect("Writing to string");
+        }
+    }
+    let failed = outcomes.iter().filter(|outcome| !outcome.passed()).count();
+    let status = if failed == 0 { "ok" } else { "FAILED" };
+    writeln!(
+        output,
+        "test result: {}. {} passed; {} failed",
+        status,
+        outcomes.len() - failed,
+        failed
+    )
+    .expect("Writing to string");
+    output
+}
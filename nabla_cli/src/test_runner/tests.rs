@@ -0,0 +1,106 @@
+use super::*;
+use nabla_frontend::{
+    diagnostics::Stage,
+    lexer::{self, LexerResult},
+    parser::{self, ParserResult},
+};
+use pretty_assertions::assert_eq;
+
+fn blocks(src: &str) -> Vec<TestBlock> {
+    let LexerResult { tokens, .. } = lexer::lex(src);
+    let ParserResult { ast, .. } = parser::parse(&tokens);
+    collect(&ast, &tokens)
+}
+
+#[test]
+fn collect_named_blocks() {
+    let src = "def X = Number\ntest first { 1 }\ntest { 2 }\ntest second { 3 }\n";
+    assert_eq!(
+        vec![
+            TestBlock {
+                name: "first".to_string(),
+                range: 15..31,
+            },
+            TestBlock {
+                name: "second".to_string(),
+                range: 43..60,
+            },
+        ],
+        blocks(src)
+    );
+}
+
+#[test]
+fn errors_fail_their_block() {
+    let blocks = vec![
+        TestBlock {
+            name: "first".to_string(),
+            range: 0..10,
+        },
+        TestBlock {
+            name: "second".to_string(),
+            range: 10..20,
+        },
+    ];
+    let error = Diagnostic::new(Stage::Semantics, "mismatch".to_string(), 12..14);
+    let mut warning = Diagnostic::new(Stage::Semantics, "unused".to_string(), 2..3);
+    warning.severity = Severity::Warning;
+    let outside = Diagnostic::new(Stage::Semantics, "undefined".to_string(), 25..26);
+    let outcomes = run(&blocks, &[error.clone(), warning, outside]);
+    assert_eq!(
+        vec![
+            TestOutcome {
+                name: "first".to_string(),
+                failures: Vec::new(),
+            },
+            TestOutcome {
+                name: "second".to_string(),
+                failures: vec![error],
+            },
+        ],
+        outcomes
+    );
+}
+
+#[test]
+fn missing_token_at_end() {
+    let diagnostic = Diagnostic::new(Stage::Parse, "missing `}`".to_string(), 10..10);
+    assert!(contains(&(0..10), &diagnostic));
+    assert!(!contains(
+        &(10..20),
+        &Diagnostic::new(Stage::Parse, String::new(), 9..10)
+    ));
+}
+
+#[test]
+fn render_outcomes() {
+    let src = "test a { 1 }\ntest b { x }\n";
+    let outcomes = vec![
+        TestOutcome {
+            name: "a".to_string(),
+            failures: Vec::new(),
+        },
+        TestOutcome {
+            name: "b".to_string(),
+            failures: vec![Diagnostic::new(
+                Stage::Semantics,
+                "`x` is not defined".to_string(),
+                22..23,
+            )],
+        },
+    ];
+    assert_eq!(
+        "\
+test a ... ok
+test b ... FAILED
+  Line 1, Char 9: `x` is not defined
+test result: FAILED. 1 passed; 1 failed
+",
+        render(&outcomes, src)
+    );
+}
+
+#[test]
+fn render_without_tests() {
+    assert_eq!("test result: ok. 0 passed; 0 failed\n", render(&[], ""));
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::process::Output;
+
+fn convert(args: &[&str]) -> Output {
+    common::convert(&common::fixture("multiple_inits.nabla"), args)
+}
+
+fn stdout(output: Output) -> String {
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+#[test]
+fn multiple_inits_fail_without_flag() {
+    let output = convert(&["-t", "yaml"]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(
+        stderr.contains("more than one initialization"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn json_array() {
+    assert_eq!(
+        r#"[
+  {
+    "host": "a.example.com",
+    "port": 80
+  },
+  {
+    "host": "b.example.com",
+    "port": 8080
+  }
+]
+"#,
+        stdout(convert(&["--all-inits"]))
+    );
+}
+
+#[test]
+fn yaml_documents() {
+    assert_eq!(
+        "host: a.example.com\nport: 80\n---\nhost: b.example.com\nport: 8080\n\n",
+        stdout(convert(&["--all-inits", "-t", "yaml"]))
+    );
+}
+
+#[test]
+fn every_document_is_wrapped() {
+    assert_eq!(
+        "server:\n  host: a.example.com\n  port: 80\n---\nserver:\n  host: b.example.com\n  port: 8080\n\n",
+        stdout(convert(&[
+            "--all-inits",
+            "-t",
+            "yaml",
+            "--wrap-root",
+            "--root-name",
+            "server"
+        ]))
+    );
+}
+
+#[test]
+fn toml_and_xml_are_unsupported() {
+    for target in ["toml", "xml", "all"] {
+        let output = convert(&["--all-inits", "-t", target]);
+        assert!(!output.status.success());
+        let stderr = common::stderr(&output);
+        assert!(
+            stderr.contains("`--all-inits` is only supported by json and yaml"),
+            "{}",
+            stderr
+        );
+    }
+}
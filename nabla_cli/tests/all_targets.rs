@@ -0,0 +1,89 @@
+mod common;
+
+use common::{convert, fixture, temp_dir};
+use std::path::Path;
+
+fn stdout(file: &Path, target: &str) -> String {
+    let output = convert(file, &["-t", target]);
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+#[test]
+fn one_file_per_target() {
+    let dir = temp_dir("all-targets");
+    let file = fixture("servers.nabla");
+    let output = convert(
+        &file,
+        &["-t", "all", "-o", &dir.join("config").display().to_string()],
+    );
+    assert!(output.status.success());
+    for target in ["json", "yaml", "toml", "xml"] {
+        let written = std::fs::read_to_string(dir.join("config").with_extension(target))
+            .expect("Output file is missing");
+        assert_eq!(stdout(&file, target), written, "{}", target);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn stem_of_input_file() {
+    let dir = temp_dir("all-targets-input");
+    let file = dir.join("servers.nabla");
+    std::fs::copy(fixture("servers.nabla"), &file).expect("Copying the fixture failed");
+    assert!(convert(&file, &["-t", "all"]).status.success());
+    let mut written: Vec<_> = std::fs::read_dir(&dir)
+        .expect("Reading the directory failed")
+        .map(|entry| entry.expect("Reading the entry failed").file_name())
+        .collect();
+    written.sort();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(
+        vec![
+            "servers.json",
+            "servers.nabla",
+            "servers.toml",
+            "servers.xml",
+            "servers.yaml"
+        ],
+        written
+    );
+}
+
+#[test]
+fn failed_target_keeps_the_others() {
+    let dir = temp_dir("all-targets-failed");
+    let output = convert(
+        &fixture("list_init.nabla"),
+        &["-t", "all", "-o", &dir.join("list").display().to_string()],
+    );
+    assert!(!output.status.success());
+    for target in ["json", "yaml"] {
+        assert!(
+            dir.join("list").with_extension(target).is_file(),
+            "{}",
+            target
+        );
+    }
+    for target in ["toml", "xml"] {
+        assert!(
+            !dir.join("list").with_extension(target).exists(),
+            "{}",
+            target
+        );
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn output_of_single_target() {
+    let dir = temp_dir("single-target-output");
+    let path = dir.join("out.yaml");
+    let file = fixture("servers.nabla");
+    let output = convert(&file, &["-t", "yaml", "-o", &path.display().to_string()]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let written = std::fs::read_to_string(&path).expect("Output file is missing");
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(stdout(&file, "yaml"), written);
+}
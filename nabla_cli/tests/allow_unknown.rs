@@ -0,0 +1,72 @@
+mod common;
+
+use std::process::Output;
+
+fn convert(args: &[&str]) -> Output {
+    common::convert(&common::fixture("unknown.nabla"), args)
+}
+
+/// Converts with `--allow-unknown` and returns stdout and stderr.
+fn convert_allowed(target: &str) -> (String, String) {
+    let output = convert(&["-t", target, "--allow-unknown"]);
+    assert!(output.status.success());
+    (common::stdout(&output), common::stderr(&output))
+}
+
+fn report(placeholder: &str) -> String {
+    format!(
+        "note: 2 unknown value(s) {} (`--allow-unknown`):\n  /port\n  /hosts/1\n",
+        placeholder
+    )
+}
+
+#[test]
+fn unknown_values_fail_without_flag() {
+    let output = convert(&["-t", "json"]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("error: 3 semantic errors"));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn json_nulls() {
+    let (stdout, stderr) = convert_allowed("json");
+    // the missing module `shared` is a warning as well
+    assert!(stderr.contains("warning: 4 semantic warnings"));
+    assert!(stdout.ends_with(
+        r#"{
+  "name": "prod",
+  "port": null,
+  "hosts": [
+    "a",
+    null
+  ]
+}
+"#
+    ));
+    assert!(stderr.ends_with(&report("emitted as null")));
+}
+
+#[test]
+fn yaml_nulls() {
+    let (stdout, stderr) = convert_allowed("yaml");
+    assert!(stdout.ends_with("name: prod\nport: null\nhosts:\n- a\n- null\n\n"));
+    assert!(stderr.ends_with(&report("emitted as null")));
+}
+
+#[test]
+fn toml_left_out() {
+    let (stdout, stderr) = convert_allowed("toml");
+    assert!(stdout.ends_with("name = \"prod\"\nhosts = [\"a\"]\n\n"));
+    assert!(stderr.ends_with(&report("left out")));
+}
+
+#[test]
+fn xml_empty_elements() {
+    let (stdout, stderr) = convert_allowed("xml");
+    assert!(stdout.ends_with(
+        "<root>\n\t<name>prod</name>\n\t<port><!-- unknown value --></port>\n\t<hosts>a</hosts>\n\t<hosts><!-- unknown value --></hosts>\n</root>\n"
+    ));
+    assert!(stderr.ends_with(&report("emitted as empty elements")));
+}
@@ -0,0 +1,24 @@
+mod common;
+
+#[test]
+fn derived_numbers_in_json() {
+    let output = common::convert(&common::fixture("arithmetic.nabla"), &["-t", "json"]);
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output is not JSON");
+    assert_eq!(serde_json::json!({"heap": 3072, "workers": 3}), json);
+}
+
+#[test]
+fn comment_at_end_of_file() {
+    let file = common::temp_file(
+        "arithmetic",
+        "comment.nabla",
+        "{\n    half = 8 / 2\n} // no line break",
+    );
+    let output = common::convert(&file, &["-t", "json"]);
+    assert!(output.status.success(), "{}", common::stderr(&output));
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output is not JSON");
+    assert_eq!(serde_json::json!({"half": 4}), json);
+}
@@ -0,0 +1,11 @@
+mod common;
+
+#[test]
+fn bare_let_init_emits_let() {
+    let output = common::convert(&common::fixture("bare_let.nabla"), &["-t", "json"]);
+    assert!(output.status.success());
+    assert_eq!(
+        "{\n  \"name\": \"app\",\n  \"ports\": [\n    80,\n    443\n  ]\n}\n",
+        common::stdout(&output)
+    );
+}
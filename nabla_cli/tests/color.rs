@@ -0,0 +1,48 @@
+mod common;
+
+fn convert(args: &[&str]) -> String {
+    let output = common::convert(&common::fixture("servers.nabla"), args);
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+/// Remove the ANSI escape codes, e.g. `\x1b[34m`.
+fn strip_ansi(colored: &str) -> String {
+    let mut output = String::new();
+    let mut rest = colored;
+    while let Some(start) = rest.find('\x1b') {
+        output.push_str(&rest[..start]);
+        let end = rest[start..].find('m').expect("Escape code is terminated");
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[test]
+fn colored_strips_to_plain() {
+    for target_args in [
+        &["-t", "json"][..],
+        &["-t", "yaml"],
+        &["-t", "toml"],
+        &["-t", "toml", "--toml-array-of-tables"],
+        &["-t", "toml", "--toml-inline-threshold", "2"],
+    ] {
+        let plain = convert(&[target_args, &["--color", "never"]].concat());
+        let colored = convert(&[target_args, &["--color", "always"]].concat());
+        assert!(colored.contains('\x1b'), "{:?} is not colored", target_args);
+        assert_eq!(plain, strip_ansi(&colored), "{:?}", target_args);
+    }
+}
+
+#[test]
+fn auto_without_terminal_is_plain() {
+    for target in ["json", "yaml", "toml", "xml"] {
+        assert!(!convert(&["-t", target]).contains('\x1b'), "{}", target);
+    }
+}
+
+#[test]
+fn xml_is_never_colored() {
+    assert!(!convert(&["-t", "xml", "--color", "always"]).contains('\x1b'));
+}
@@ -0,0 +1,53 @@
+//! Helpers shared by the integration tests of the CLI.
+
+// every test binary only uses some of the helpers
+#![allow(dead_code)]
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// Path of a file in `tests/fixtures`
+pub fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Command of the CLI binary under test
+pub fn cli() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_nabla_cli"))
+}
+
+/// Run the CLI on the file with the arguments
+pub fn convert(file: &Path, args: &[&str]) -> Output {
+    cli()
+        .arg(file)
+        .args(args)
+        .output()
+        .expect("Running the CLI failed")
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("Output is not UTF-8")
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).expect("Output is not UTF-8")
+}
+
+/// Empty directory, which is unique to the test
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("nabla-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Creating the directory failed");
+    dir
+}
+
+/// Write the content into a fresh temporary directory
+pub fn temp_file(name: &str, file_name: &str, content: &str) -> PathBuf {
+    let path = temp_dir(name).join(file_name);
+    std::fs::write(&path, content).expect("Writing the file failed");
+    path
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{stderr, stdout};
+use std::process::Output;
+
+fn convert(args: &[&str]) -> Output {
+    common::convert(&common::fixture("defaults.nabla"), args)
+}
+
+#[test]
+fn defaults_of_def() {
+    let output = convert(&["-T", "Config", "--defaults-only", "-t", "yaml"]);
+    assert!(output.status.success());
+    assert_eq!(
+        "host: localhost\nport: 80\ntls:\n  enabled: false\n\n",
+        stdout(&output)
+    );
+}
+
+#[test]
+fn required_field_without_default() {
+    let output = convert(&["-T", "Server", "--defaults-only"]);
+    assert!(!output.status.success());
+    assert_eq!("", stdout(&output));
+    assert_eq!("/name: value is (partially) unknown\n", stderr(&output));
+}
+
+#[test]
+fn undefined_or_non_struct_def() {
+    for type_name in ["Port", "Missing"] {
+        let output = convert(&["-T", type_name, "--defaults-only"]);
+        assert!(!output.status.success());
+        assert_eq!("", stdout(&output));
+    }
+}
+
+#[test]
+fn defaults_only_requires_type() {
+    let output = convert(&["--defaults-only"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,23 @@
+mod common;
+
+#[test]
+fn prefixed_by_enclosing_global() {
+    let fixture = common::fixture("two_defs.nabla");
+    let output = common::convert(&fixture, &[]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert_eq!(
+        format!(
+            "\
+Semantic errors:
+[def ServerConfig] Line 1, Char 19: `80` does not match type Number
+[def ClientConfig] Line 5, Char 22: `true` does not match type Number
+[let timeout] Line 8, Char 22: `slow` does not match type Number
+[<init>] Line 10, Char 22: `false` does not match type Number
+error: 4 semantic errors in {}
+",
+            fixture.display()
+        ),
+        stderr
+    );
+}
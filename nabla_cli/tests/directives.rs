@@ -0,0 +1,38 @@
+mod common;
+
+use common::{stderr, stdout};
+use std::process::Output;
+
+fn convert(args: &[&str]) -> Output {
+    let output = common::convert(&common::fixture("directives.nabla"), args);
+    assert!(output.status.success());
+    output
+}
+
+#[test]
+fn directives_without_flags() {
+    let output = convert(&[]);
+    assert_eq!("config:\n  name: cluster\n  port: 80\n\n", stdout(&output));
+    assert_eq!("", stderr(&output));
+}
+
+#[test]
+fn flag_overrides_directive() {
+    let output = convert(&["-t", "json", "--xml-root", "cluster"]);
+    assert_eq!(
+        "{\n  \"cluster\": {\n    \"name\": \"cluster\",\n    \"port\": 80\n  }\n}\n",
+        stdout(&output)
+    );
+    assert_eq!(
+        "note: `--target` overrides the directive `target=yaml`\n\
+         note: `--xml-root` overrides the directive `root=config`\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn flag_equal_to_directive() {
+    let output = convert(&["-t", "yaml"]);
+    assert_eq!("config:\n  name: cluster\n  port: 80\n\n", stdout(&output));
+    assert_eq!("", stderr(&output));
+}
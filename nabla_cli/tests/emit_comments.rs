@@ -0,0 +1,88 @@
+mod common;
+
+fn convert(args: &[&str]) -> String {
+    let output = common::convert(&common::fixture("documented.nabla"), args);
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+#[test]
+fn yaml_comments() {
+    assert_eq!(
+        r#"# Name of the deployment
+name: prod
+# The main server
+server:
+  host: example.com
+  # Port to listen on,
+  # any free port if 0
+  port: 8080
+
+"#,
+        convert(&["-t", "yaml", "--emit-comments"])
+    );
+}
+
+#[test]
+fn toml_comments() {
+    assert_eq!(
+        r#"# Name of the deployment
+name = "prod"
+
+# The main server
+[server]
+host = "example.com"
+# Port to listen on,
+# any free port if 0
+port = 8080
+
+"#,
+        convert(&["-t", "toml", "--emit-comments"])
+    );
+}
+
+#[test]
+fn no_comments_without_flag() {
+    assert!(!convert(&["-t", "yaml"]).contains('#'));
+    assert!(!convert(&["-t", "toml"]).contains('#'));
+}
+
+#[test]
+fn json_ignores_flag() {
+    assert_eq!(
+        convert(&["-t", "json"]),
+        convert(&["-t", "json", "--emit-comments"])
+    );
+}
+
+#[test]
+fn preserve_plain_comments() {
+    let output = common::convert(
+        &common::fixture("commented.nabla"),
+        &["-t", "yaml", "--preserve-comments"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        r#"# Name of the deployment
+name: prod
+# the server is public
+# Port to listen on
+port: 8080
+
+"#,
+        common::stdout(&output)
+    );
+}
+
+#[test]
+fn emit_comments_skips_plain_comments() {
+    let output = common::convert(
+        &common::fixture("commented.nabla"),
+        &["-t", "toml", "--emit-comments"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        "name = \"prod\"\n# Port to listen on\nport = 8080\n\n",
+        common::stdout(&output)
+    );
+}
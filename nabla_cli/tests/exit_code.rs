@@ -0,0 +1,60 @@
+mod common;
+
+use common::{convert, fixture};
+
+#[test]
+fn errors_fail() {
+    let output = convert(&fixture("two_defs.nabla"), &[]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn schema_without_init_is_valid() {
+    let output = convert(&fixture("defaults.nabla"), &[]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = common::stderr(&output);
+    assert_eq!("No errors detected.\n", stderr);
+}
+
+#[test]
+fn conversion_requires_init() {
+    let output = convert(&fixture("defaults.nabla"), &["-t", "json"]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("has no init to convert"), "{}", stderr);
+}
+
+#[test]
+fn output_replaces_file() {
+    let dir = common::temp_dir("exit-code");
+    let path = dir.join("config.yaml");
+    std::fs::write(&path, "old").expect("Writing the file failed");
+    let output = convert(
+        &fixture("no_alias.nabla"),
+        &["-t", "yaml", "-o", path.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        "name: app\n\n",
+        std::fs::read_to_string(&path).expect("Reading the file failed")
+    );
+    // the temporary file is renamed, so only the output is left
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .expect("Reading the directory failed")
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(vec!["config.yaml"], files);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unwritable_output_fails() {
+    let path = fixture("missing/config.json");
+    let output = convert(&fixture("no_alias.nabla"), &["-o", path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("Could not write file"), "{}", stderr);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+fn convert(args: &[&str]) -> String {
+    let output = common::convert(
+        &common::fixture("servers.nabla"),
+        &[&["-t", "yaml"], args].concat(),
+    );
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+#[test]
+fn declaration_order() {
+    assert_eq!(
+        r#"name: cluster
+servers:
+- host: alpha
+  port: 80
+  tls:
+    enabled: false
+- host: beta
+  port: 8080
+  tls:
+    enabled: true
+owner:
+  name: ops
+  contact:
+    mail: ops@example.com
+
+"#,
+        convert(&[])
+    );
+}
+
+#[test]
+fn sorted_keys() {
+    assert_eq!(
+        r#"name: cluster
+owner:
+  contact:
+    mail: ops@example.com
+  name: ops
+servers:
+- host: alpha
+  port: 80
+  tls:
+    enabled: false
+- host: beta
+  port: 8080
+  tls:
+    enabled: true
+
+"#,
+        convert(&["--sort-keys"])
+    );
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::{
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+fn fmt(file: &Path, args: &[&str]) -> Output {
+    common::cli()
+        .arg("fmt")
+        .arg(file)
+        .args(args)
+        .output()
+        .expect("Running the CLI failed")
+}
+
+/// Write the source into a fresh temporary file
+fn temp_file(name: &str, src: &str) -> PathBuf {
+    common::temp_file(&format!("fmt-{}", name), "config.nabla", src)
+}
+
+const UNFORMATTED: &str = "let port=80\n{ port = port }";
+const FORMATTED: &str = "let port = 80\n\n{\n    port = port\n}\n";
+
+#[test]
+fn rewrites_file() {
+    let path = temp_file("rewrite", UNFORMATTED);
+    let output = fmt(&path, &[]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        FORMATTED,
+        std::fs::read_to_string(&path).expect("Reading the file failed")
+    );
+}
+
+#[test]
+fn check() {
+    let path = temp_file("check", UNFORMATTED);
+    let output = fmt(&path, &["--check"]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("is not formatted"), "{}", stderr);
+    assert_eq!(
+        UNFORMATTED,
+        std::fs::read_to_string(&path).expect("Reading the file failed")
+    );
+
+    let path = temp_file("check-formatted", FORMATTED);
+    assert!(fmt(&path, &["--check"]).status.success());
+}
+
+#[test]
+fn syntax_errors_are_not_formatted() {
+    let src = "let port = \n{ port = }";
+    let path = temp_file("syntax-errors", src);
+    let output = fmt(&path, &[]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("does not parse"), "{}", stderr);
+    assert_eq!(
+        src,
+        std::fs::read_to_string(&path).expect("Reading the file failed")
+    );
+}
@@ -0,0 +1,18 @@
+mod common;
+
+#[test]
+fn dot_golden() {
+    let output = common::cli()
+        .arg("graph")
+        .arg(common::fixture("graph.nabla"))
+        .args(["--format", "dot"])
+        .output()
+        .expect("Running the CLI failed");
+    assert!(output.status.success());
+    let golden =
+        std::fs::read_to_string(common::fixture("graph.dot")).expect("Reading golden file failed");
+    assert_eq!(golden, common::stdout(&output));
+    // the cycle of `a` and `b` is still reported
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("value cannot be initialized recursively"));
+}
@@ -0,0 +1,11 @@
+mod common;
+
+#[test]
+fn hidden_field_is_not_emitted() {
+    let output = common::convert(&common::fixture("hidden_field.nabla"), &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        "{\n  \"host\": \"localhost\",\n  \"port\": 8080\n}\n",
+        common::stdout(&output)
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::{
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+fn run(args: &[&str]) -> Output {
+    common::cli()
+        .args(args)
+        .output()
+        .expect("Running the CLI failed")
+}
+
+fn temp_file(name: &str, file_name: &str, content: &str) -> PathBuf {
+    common::temp_file(&format!("import-{}", name), file_name, content)
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().expect("The path is valid UTF-8")
+}
+
+const JSON: &str =
+    r#"{"name":"app","content-type":"json","ports":[80,443],"limits":{"ratio":-0.5}}"#;
+
+#[test]
+fn prints_nabla_source() {
+    let path = temp_file("print", "config.json", JSON);
+    let output = run(&["import", path_str(&path)]);
+    assert!(output.status.success());
+    assert_eq!(
+        "{\n    name = \"app\"\n    content_type = \"json\" as \"content-type\"\n    ports = [80 443]\n    \
+         limits = {\n        ratio = -0.5\n    }\n}\n",
+        common::stdout(&output)
+    );
+}
+
+#[test]
+fn converts_back_to_the_same_json() {
+    let path = temp_file("round-trip", "config.json", JSON);
+    let nabla = path.with_extension("nabla");
+    let output = run(&["import", path_str(&path), "-o", path_str(&nabla)]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let output = run(&[path_str(&nabla)]);
+    assert!(output.status.success());
+    let converted: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(JSON).unwrap(),
+        converted
+    );
+}
+
+#[test]
+fn toml_by_extension() {
+    let path = temp_file("toml", "config.toml", "[server]\nport = 80\n");
+    let output = run(&["import", path_str(&path)]);
+    assert!(output.status.success());
+    assert_eq!(
+        "{\n    server = {\n        port = 80\n    }\n}\n",
+        common::stdout(&output)
+    );
+}
+
+#[test]
+fn unknown_extension() {
+    let path = temp_file("unknown", "config.txt", "{}");
+    let output = run(&["import", path_str(&path)]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("please specify `--format`"), "{}", stderr);
+    let output = run(&["import", path_str(&path), "--format", "json"]);
+    assert!(output.status.success());
+    assert_eq!("{}\n", common::stdout(&output));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    process::{ChildStdout, Stdio},
+};
+
+fn send(stdin: &mut impl Write, message: &str) {
+    write!(
+        stdin,
+        "Content-Length: {}\r\n\r\n{}",
+        message.len(),
+        message
+    )
+    .expect("Writing to the server failed");
+    stdin.flush().expect("Flushing the server input failed");
+}
+
+fn receive(stdout: &mut BufReader<ChildStdout>) -> String {
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        stdout
+            .read_line(&mut header)
+            .expect("Reading a header failed");
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length: ") {
+            content_length = length.parse().expect("Content length is a number");
+        }
+    }
+    let mut content = vec![0; content_length];
+    stdout
+        .read_exact(&mut content)
+        .expect("Reading the content failed");
+    String::from_utf8(content).expect("Content is not UTF-8")
+}
+
+/// Skip messages, e.g. logs, until the next one containing the pattern
+fn receive_containing(stdout: &mut BufReader<ChildStdout>, pattern: &str) -> String {
+    loop {
+        let message = receive(stdout);
+        if message.contains(pattern) {
+            return message;
+        }
+    }
+}
+
+#[test]
+fn lsp_subcommand_answers_initialize() {
+    let mut child = common::cli()
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Running the CLI failed");
+    let mut stdin = child.stdin.take().expect("Stdin is piped");
+    let mut stdout = BufReader::new(child.stdout.take().expect("Stdout is piped"));
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#,
+    );
+    let response = receive(&mut stdout);
+    assert!(response.contains(r#""id":1"#));
+    assert!(response.contains(r#""name":"Nabla LS""#));
+    assert!(response.contains(r#""inlayHintProvider":true"#));
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#,
+    );
+    let response = receive(&mut stdout);
+    assert!(response.contains(r#""id":2"#));
+    send(&mut stdin, r#"{"jsonrpc":"2.0","method":"exit"}"#);
+    drop(stdin);
+    assert!(child.wait().expect("Waiting for the CLI failed").success());
+}
+
+#[test]
+fn configuration_change_republishes_diagnostics() {
+    let mut child = common::cli()
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Running the CLI failed");
+    let mut stdin = child.stdin.take().expect("Stdin is piped");
+    let mut stdout = BufReader::new(child.stdout.take().expect("Stdout is piped"));
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#,
+    );
+    receive(&mut stdout);
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+    );
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///config.nabla","languageId":"nabla","version":1,"text":"{ x = pi }\nlet pi = 3.14\n"}}}"#,
+    );
+    let published =
+        receive_containing(&mut stdout, r#""method":"textDocument/publishDiagnostics""#);
+    assert!(published.contains(r#""diagnostics":[]"#), "{}", published);
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","method":"workspace/didChangeConfiguration","params":{"settings":{"nabla":{"strictOrder":true}}}}"#,
+    );
+    let published =
+        receive_containing(&mut stdout, r#""method":"textDocument/publishDiagnostics""#);
+    assert!(
+        published.contains("`pi` is referenced before its declaration"),
+        "{}",
+        published
+    );
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#,
+    );
+    receive_containing(&mut stdout, r#""id":2"#);
+    send(&mut stdin, r#"{"jsonrpc":"2.0","method":"exit"}"#);
+    drop(stdin);
+    assert!(child.wait().expect("Waiting for the CLI failed").success());
+}
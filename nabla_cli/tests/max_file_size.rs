@@ -0,0 +1,53 @@
+mod common;
+
+use nabla_frontend::limits;
+use std::{
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+/// Write the generated source into a temporary file, which is named after the test.
+fn generated_file(name: &str, src: &str) -> PathBuf {
+    let file = std::env::temp_dir().join(format!("nabla-{}-{}.nabla", name, std::process::id()));
+    std::fs::write(&file, src).expect("Writing the generated file failed");
+    file
+}
+
+fn convert(file: &Path, args: &[&str]) -> Output {
+    let output = common::convert(file, args);
+    let _ = std::fs::remove_file(file);
+    output
+}
+
+#[test]
+fn refuse_file_above_flag() {
+    let src = format!("{{\n{}}}\n", "a = 1\n".repeat(1000));
+    let file = generated_file("max-file-size-flag", &src);
+    let output = convert(&file, &["--max-file-size", "1024"]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(
+        stderr.contains("is too large for analysis (6004 bytes), the limit is 1024 bytes"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn accept_file_below_flag() {
+    let fields: String = (0..10).map(|i| format!("a{} = 1\n", i)).collect();
+    let src = format!("{{\n{}}}\n", fields);
+    let file = generated_file("max-file-size-below", &src);
+    let output = convert(&file, &["--max-file-size", "1024"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn refuse_file_above_default() {
+    let src = " ".repeat(limits::MAX_FILE_SIZE + 1);
+    let file = generated_file("max-file-size-default", &src);
+    let output = convert(&file, &[]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("is too large for analysis"), "{}", stderr);
+}
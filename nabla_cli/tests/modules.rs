@@ -0,0 +1,30 @@
+mod common;
+
+/// Converts the module of the fixture directory and returns stdout and stderr.
+fn convert(module: &str) -> (String, String) {
+    let fixture = common::fixture(&format!("modules/{}.nabla", module));
+    let output = common::convert(&fixture, &[]);
+    (common::stdout(&output), common::stderr(&output))
+}
+
+#[test]
+fn imports_files_next_to_the_file() {
+    // `scheme` is a hidden field of the imported def
+    assert_eq!(
+        "{\n  \"host\": \"example.com\",\n  \"port\": 8080\n}\n",
+        convert("main").0
+    );
+}
+
+#[test]
+fn imported_def_is_type_checked() {
+    let (stdout, stderr) = convert("mismatch");
+    assert!(stderr.contains("Line 2, Char 16: `1` does not match type String"));
+    assert_eq!("", stdout);
+}
+
+#[test]
+fn import_cycle_is_reported() {
+    let (_, stderr) = convert("ping");
+    assert!(stderr.contains("modules import each other in a cycle: pong -> ping -> pong"));
+}
@@ -0,0 +1,17 @@
+mod common;
+
+fn convert(target: &str) -> Vec<u8> {
+    let output = common::convert(&common::fixture("many_fields.nabla"), &["--target", target]);
+    assert!(output.status.success());
+    output.stdout
+}
+
+#[test]
+fn output_is_reproducible() {
+    for target in ["json", "yaml", "toml", "xml"] {
+        let first = convert(target);
+        let second = convert(target);
+        assert!(!first.is_empty());
+        assert_eq!(first, second, "output for {} differs between runs", target);
+    }
+}
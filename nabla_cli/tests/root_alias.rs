@@ -0,0 +1,68 @@
+mod common;
+
+use common::fixture;
+use std::path::Path;
+
+fn convert(fixture: &Path, args: &[&str]) -> String {
+    let output = common::convert(fixture, args);
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+#[test]
+fn alias_wraps_json() {
+    let output = convert(&fixture("root_alias.nabla"), &["-t", "json", "--wrap-root"]);
+    assert_eq!(
+        "{\n  \"application-config\": {\n    \"name\": \"app\"\n  }\n}\n",
+        output
+    );
+}
+
+#[test]
+fn alias_wraps_yaml() {
+    let output = convert(&fixture("root_alias.nabla"), &["-t", "yaml", "--wrap-root"]);
+    assert_eq!("application-config:\n  name: app\n\n", output);
+}
+
+#[test]
+fn alias_wraps_toml() {
+    let output = convert(&fixture("root_alias.nabla"), &["-t", "toml", "--wrap-root"]);
+    assert_eq!("[application-config]\nname = \"app\"\n\n", output);
+}
+
+#[test]
+fn alias_names_xml_root() {
+    let output = convert(
+        &fixture("root_alias.nabla"),
+        &["-t", "xml", "--xml-root", "ignored"],
+    );
+    assert!(output.contains("<application-config>"));
+    assert!(!output.contains("ignored"));
+}
+
+#[test]
+fn alias_without_wrap_root() {
+    let output = convert(&fixture("root_alias.nabla"), &["-t", "json"]);
+    assert_eq!("{\n  \"name\": \"app\"\n}\n", output);
+}
+
+#[test]
+fn wrap_root_without_alias() {
+    let output = convert(
+        &fixture("no_alias.nabla"),
+        &["-t", "json", "--wrap-root", "--xml-root", "config"],
+    );
+    assert_eq!(
+        "{\n  \"config\": {\n    \"name\": \"app\"\n  }\n}\n",
+        output
+    );
+}
+
+#[test]
+fn let_alias_wraps_json() {
+    let output = convert(&fixture("let_alias.nabla"), &["-t", "json", "--wrap-root"]);
+    assert_eq!(
+        "{\n  \"database-config\": {\n    \"host\": \"localhost\"\n  }\n}\n",
+        output
+    );
+}
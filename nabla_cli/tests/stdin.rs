@@ -0,0 +1,77 @@
+mod common;
+
+use std::{
+    io::Write,
+    process::{Output, Stdio},
+};
+
+/// Run the CLI in the fixture directory with the source on stdin.
+fn convert_stdin(src: &str, args: &[&str]) -> Output {
+    let mut child = common::cli()
+        .args(args)
+        .current_dir(common::fixture(""))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Running the CLI failed");
+    child
+        .stdin
+        .take()
+        .expect("Stdin is piped")
+        .write_all(src.as_bytes())
+        .expect("Writing to stdin failed");
+    child.wait_with_output().expect("Running the CLI failed")
+}
+
+#[test]
+fn without_file() {
+    let output = convert_stdin("{ port = 80 }", &["-t", "yaml"]);
+    assert!(output.status.success());
+    assert_eq!("port: 80\n\n", common::stdout(&output));
+}
+
+#[test]
+fn dash() {
+    let output = convert_stdin("{ port = 80 }", &["-", "-t", "yaml"]);
+    assert!(output.status.success());
+    assert_eq!("port: 80\n\n", common::stdout(&output));
+}
+
+#[test]
+fn errors_name_stdin() {
+    let output = convert_stdin("{ port = p }", &[]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("`p` is not defined"), "{}", stderr);
+    assert!(stderr.contains("in <stdin>"), "{}", stderr);
+}
+
+#[test]
+fn uses_resolve_in_working_directory() {
+    let src = "use modules::shared::port\n{ port = port }\n";
+    let output = convert_stdin(src, &["-t", "yaml"]);
+    assert!(output.status.success());
+    assert_eq!("port: 8080\n\n", common::stdout(&output));
+}
+
+#[test]
+fn refuse_large_stdin() {
+    let src = format!("{{\n{}}}\n", "a = 1\n".repeat(1000));
+    let output = convert_stdin(&src, &["--max-file-size", "1024"]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(
+        stderr.contains("stdin is too large for analysis, the limit is 1024 bytes"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn all_targets_need_output() {
+    let output = convert_stdin("{ port = 80 }", &["-t", "all"]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("needs `--output`"), "{}", stderr);
+}
@@ -0,0 +1,22 @@
+mod common;
+
+/// Converts the fixture and returns stdout and stderr.
+fn convert(args: &[&str]) -> (String, String) {
+    let output = common::convert(&common::fixture("forward_reference.nabla"), args);
+    assert!(output.status.success());
+    (common::stdout(&output), common::stderr(&output))
+}
+
+#[test]
+fn forward_references_are_allowed() {
+    let (stdout, stderr) = convert(&[]);
+    assert!(!stderr.contains("warning"));
+    assert!(stdout.contains("\"x\": 3.14"));
+}
+
+#[test]
+fn strict_order_warns_about_forward_references() {
+    let (stdout, stderr) = convert(&["--strict-order"]);
+    assert!(stderr.contains("`pi` is referenced before its declaration"));
+    assert!(stdout.contains("\"x\": 3.14"));
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::fixture;
+use std::path::Path;
+
+fn convert(file: &Path, args: &[&str]) -> String {
+    let output = common::convert(file, args);
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+#[test]
+fn indent_width_flag() {
+    assert_eq!(
+        "{\n    \"host\": \"localhost\",\n    \"port\": 8080\n}\n",
+        convert(&fixture("hidden_field.nabla"), &["--indent-width", "4"])
+    );
+}
+
+#[test]
+fn style_file_next_to_input() {
+    let dir = common::temp_dir("style-cli");
+    let file = dir.join("hidden_field.nabla");
+    std::fs::copy(fixture("hidden_field.nabla"), &file).expect("Copying the fixture failed");
+    std::fs::write(
+        dir.join(".nabla-fmt.toml"),
+        "use_tabs = true\nnewline_at_eof = false\n",
+    )
+    .expect("Writing the style file failed");
+    let from_file = convert(&file, &[]);
+    let with_flag = convert(&file, &["--use-tabs", "false"]);
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(
+        "{\n\t\"host\": \"localhost\",\n\t\"port\": 8080\n}",
+        from_file
+    );
+    assert_eq!(
+        "{\n  \"host\": \"localhost\",\n  \"port\": 8080\n}",
+        with_flag
+    );
+}
@@ -0,0 +1,20 @@
+mod common;
+
+#[test]
+fn sorted_symbols() {
+    let output = common::cli()
+        .arg("symbols")
+        .arg(common::fixture("symbols.nabla"))
+        .output()
+        .expect("Running the CLI failed");
+    assert!(output.status.success());
+    assert_eq!(
+        r#"root::Server  def  unknown  { port = 8080 host = ? }
+root::broken  let  unknown  ?
+root::name    let  known    "prod"
+"#,
+        common::stdout(&output)
+    );
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("`missing` is not defined"));
+}
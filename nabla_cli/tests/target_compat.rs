@@ -0,0 +1,33 @@
+mod common;
+
+/// Converts the fixture and returns stdout and stderr.
+fn convert(target: &str) -> (String, String) {
+    let output = common::convert(&common::fixture("nullable.nabla"), &["-t", target]);
+    assert!(output.status.success());
+    (common::stdout(&output), common::stderr(&output))
+}
+
+#[test]
+fn toml_warns_about_null() {
+    let (stdout, stderr) = convert("toml");
+    assert!(stderr.contains("`null` cannot be represented in TOML"));
+    assert_eq!("name = \"app\"\n\n", stdout);
+}
+
+#[test]
+fn toml_strict_nulls_fail() {
+    let output = common::convert(
+        &common::fixture("nullable.nabla"),
+        &["-t", "toml", "--strict-nulls"],
+    );
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("/comment: null cannot be represented"));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn json_has_no_warning() {
+    let (_, stderr) = convert("json");
+    assert!(!stderr.contains("warning"));
+}
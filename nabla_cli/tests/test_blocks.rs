@@ -0,0 +1,53 @@
+mod common;
+
+use std::process::Output;
+
+fn run(fixture: &str) -> Output {
+    common::cli()
+        .arg("test")
+        .arg(common::fixture(fixture))
+        .output()
+        .expect("Running the CLI failed")
+}
+
+#[test]
+fn passing_tests() {
+    let output = run("tests_passing.nabla");
+    assert!(output.status.success());
+    assert_eq!(
+        "\
+test default_port ... ok
+test custom_port ... ok
+test result: ok. 2 passed; 0 failed
+",
+        common::stdout(&output)
+    );
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn failing_tests() {
+    let output = run("tests_failing.nabla");
+    assert!(!output.status.success());
+    assert_eq!(
+        "\
+test valid ... ok
+test wrong_port ... FAILED
+  Line 5, Char 53: `80` does not match type Number
+test missing_host ... FAILED
+  Line 6, Char 20: initialization must be fully initialized
+test result: FAILED. 1 passed; 2 failed
+",
+        common::stdout(&output)
+    );
+}
+
+#[test]
+fn tests_are_not_emitted() {
+    let output = common::convert(&common::fixture("tests_passing.nabla"), &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        "{\n  \"host\": \"prod\",\n  \"port\": 8080\n}\n",
+        common::stdout(&output)
+    );
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::process::Output;
+
+fn convert(fixture: &str) -> Output {
+    convert_with(fixture, &["-t", "toml"])
+}
+
+fn convert_with(fixture: &str, args: &[&str]) -> Output {
+    common::convert(&common::fixture(fixture), args)
+}
+
+#[test]
+fn null_root_is_nothing_to_emit() {
+    let output = convert("null_init.nabla");
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(
+        stderr.contains("/: top-level value is null, so there is nothing to emit"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn list_root_is_structless() {
+    let output = convert("empty_list.nabla");
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(
+        stderr.contains("/: top-level value must be a struct"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn list_root_under_key() {
+    let output = convert_with(
+        "list_init.nabla",
+        &["-t", "toml", "--toml-root-key", "value"],
+    );
+    assert!(output.status.success());
+    assert_eq!("value = [\n    1,\n    2,\n]\n\n", common::stdout(&output));
+    // the root key makes the list representable
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn number_root_under_key() {
+    let output = convert_with(
+        "number_init.nabla",
+        &["-t", "toml", "--toml-root-key", "value"],
+    );
+    assert!(output.status.success());
+    assert_eq!("value = 42\n\n", common::stdout(&output));
+}
+
+#[test]
+fn non_struct_roots_never_panic() {
+    for (fixture, target, success) in [
+        ("list_init.nabla", "json", true),
+        ("list_init.nabla", "yaml", true),
+        ("list_init.nabla", "toml", false),
+        ("list_init.nabla", "xml", false),
+        ("number_init.nabla", "json", true),
+        ("number_init.nabla", "yaml", true),
+        ("number_init.nabla", "toml", false),
+        ("number_init.nabla", "xml", true),
+    ] {
+        let output = convert_with(fixture, &["-t", target]);
+        let stderr = common::stderr(&output);
+        assert!(
+            !stderr.contains("panicked"),
+            "{} {}: {}",
+            fixture,
+            target,
+            stderr
+        );
+        assert_eq!(
+            success,
+            output.status.success(),
+            "{} {}: {}",
+            fixture,
+            target,
+            stderr
+        );
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+
+fn convert(args: &[&str]) -> String {
+    let output = common::convert(
+        &common::fixture("servers.nabla"),
+        &[&["-t", "toml"], args].concat(),
+    );
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+#[test]
+fn array_of_tables() {
+    assert_eq!(
+        r#"name = "cluster"
+
+[[servers]]
+host = "alpha"
+port = 80
+
+[servers.tls]
+enabled = false
+
+[[servers]]
+host = "beta"
+port = 8080
+
+[servers.tls]
+enabled = true
+
+[owner]
+name = "ops"
+
+[owner.contact]
+mail = "ops@example.com"
+
+"#,
+        convert(&["--toml-array-of-tables"])
+    );
+}
+
+#[test]
+fn array_of_tables_with_inline_threshold() {
+    assert_eq!(
+        r#"name = "cluster"
+
+[[servers]]
+host = "alpha"
+port = 80
+tls = { enabled = false }
+
+[[servers]]
+host = "beta"
+port = 8080
+tls = { enabled = true }
+
+[owner]
+name = "ops"
+contact = { mail = "ops@example.com" }
+
+"#,
+        convert(&["--toml-array-of-tables", "--toml-inline-threshold", "2"])
+    );
+}
+
+#[test]
+fn inline_threshold_without_array_of_tables() {
+    assert_eq!(
+        r#"name = "cluster"
+servers = [{ host = "alpha", port = 80, tls = { enabled = false } }, { host = "beta", port = 8080, tls = { enabled = true } }]
+
+[owner]
+name = "ops"
+contact = { mail = "ops@example.com" }
+
+"#,
+        convert(&["--toml-inline-threshold", "2"])
+    );
+}
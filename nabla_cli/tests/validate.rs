@@ -0,0 +1,50 @@
+mod common;
+
+use std::process::Output;
+
+fn validate(data: &str, file_name: &str, args: &[&str]) -> Output {
+    let path = common::temp_file(&format!("validate-{}", file_name), file_name, data);
+    common::cli()
+        .arg("validate")
+        .arg("--schema")
+        .arg(common::fixture("servers.nabla"))
+        .args(["--type", "Server"])
+        .args(args)
+        .arg(path)
+        .output()
+        .expect("Running the CLI failed")
+}
+
+#[test]
+fn defaults_may_be_missing() {
+    let output = validate(r#"{ "host": "alpha" }"#, "server.json", &[]);
+    assert!(output.status.success());
+    assert_eq!("No errors detected.\n", common::stdout(&output));
+}
+
+#[test]
+fn errors_are_reported_at_their_path() {
+    let output = validate(
+        "host: alpha\nport: eighty\ntls:\n  enabled: true\n  version: 3\n",
+        "server.yaml",
+        &[],
+    );
+    assert!(!output.status.success());
+    let stdout = common::stdout(&output);
+    let mut lines: Vec<_> = stdout.lines().collect();
+    lines.sort();
+    assert_eq!(
+        vec![
+            "/port: `eighty` does not match type Number",
+            "/tls/version: unexpected field: `version`",
+        ],
+        lines
+    );
+}
+
+#[test]
+fn input_option() {
+    let output = validate("host = \"alpha\"\nport = 80\n", "server.toml", &["--input"]);
+    assert!(output.status.success());
+    assert_eq!("No errors detected.\n", common::stdout(&output));
+}
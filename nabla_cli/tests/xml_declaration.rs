@@ -0,0 +1,59 @@
+mod common;
+
+use common::fixture;
+use std::path::Path;
+
+fn convert(fixture: &Path, args: &[&str]) -> String {
+    let output = common::convert(fixture, &[&["-t", "xml"], args].concat());
+    assert!(output.status.success());
+    common::stdout(&output)
+}
+
+#[test]
+fn default_declaration() {
+    let output = convert(&fixture("no_alias.nabla"), &[]);
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root>\n\t<name>app</name>\n</root>\n",
+        output
+    );
+}
+
+#[test]
+fn custom_declaration() {
+    let output = convert(
+        &fixture("no_alias.nabla"),
+        &[
+            "--root-name",
+            "config",
+            "--xml-version",
+            "1.1",
+            "--xml-encoding",
+            "ISO-8859-1",
+            "--xml-standalone",
+        ],
+    );
+    assert_eq!(
+        "<?xml version=\"1.1\" encoding=\"ISO-8859-1\" standalone=\"yes\"?>\n<config>\n\t<name>app</name>\n</config>\n",
+        output
+    );
+}
+
+#[test]
+fn list_init_is_reported() {
+    let output = common::convert(&fixture("list_init.nabla"), &["-t", "xml"]);
+    assert!(!output.status.success());
+    let stderr = common::stderr(&output);
+    assert!(stderr.contains("list must be contained inside a struct"));
+}
+
+#[test]
+fn wrapped_list_init() {
+    let output = convert(
+        &fixture("list_init.nabla"),
+        &["--xml-list-style", "wrapped:port"],
+    );
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root>\n\t<port>1</port>\n\t<port>2</port>\n</root>\n",
+        output
+    );
+}
@@ -0,0 +1,43 @@
+//! Measures the time to parse a config, where every token is preceded by comments.
+//!
+//! Run with `cargo bench -p nabla_frontend --bench comment_heavy`.
+
+use nabla_frontend::{
+    lexer::{self, LexerResult},
+    parser::{self, ParserResult},
+};
+use std::time::Instant;
+
+/// Number of fields in the generated struct
+const FIELDS: usize = 2_000;
+/// Number of comment lines before every token
+const COMMENTS: usize = 8;
+/// Number of parser runs, the fastest is reported
+const RUNS: usize = 5;
+
+fn main() {
+    let comments = "    // some comment\n".repeat(COMMENTS);
+    let mut src = format!("{}def Config = {{\n", comments);
+    for i in 0..FIELDS {
+        src += &format!(
+            "{c}    field{i}\n{c}    :\n{c}    Number\n{c}    =\n{c}    {i}\n",
+            c = comments,
+            i = i
+        );
+    }
+    src += &format!("{}}}\n", comments);
+
+    let LexerResult { tokens, errors } = lexer::lex(&src);
+    assert!(errors.is_empty());
+    let fastest = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            let ParserResult { errors, .. } = parser::parse(&tokens);
+            assert!(errors.is_empty());
+            start.elapsed()
+        })
+        .min()
+        .expect("At least one run");
+    println!("tokens:         {:>8}", tokens.len());
+    println!("parse time:     {:>8.1} ms", fastest.as_secs_f64() * 1000.0);
+}
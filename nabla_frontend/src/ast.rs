@@ -1,4 +1,8 @@
 use crate::token::{self, ToTokenRange, TokenRange};
+use std::sync::Arc;
+
+/// Alias of struct fields, that are not emitted.
+pub const HIDDEN_ALIAS: &str = "_";
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AstInfo {
@@ -12,16 +16,70 @@ impl AstInfo {
     }
 }
 
-impl ToTokenRange for AstInfo {
+/// Node of the AST, that knows the tokens it was parsed from.
+pub trait HasInfo {
+    fn info(&self) -> &AstInfo;
+}
+
+impl<T: HasInfo> ToTokenRange for T {
     fn to_token_range(&self) -> TokenRange {
-        self.range.clone()
+        self.info().range.clone()
     }
 }
 
-pub trait TypedExpr {
+/// Keywords, punctuation and error nodes are only stored as their info.
+impl HasInfo for AstInfo {
+    fn info(&self) -> &AstInfo {
+        self
+    }
+}
+
+/// Implements `HasInfo` for nodes with an `info` field.
+macro_rules! has_info {
+    ($($node:ty),* $(,)?) => {
+        $(
+            impl HasInfo for $node {
+                fn info(&self) -> &AstInfo {
+                    &self.info
+                }
+            }
+        )*
+    };
+}
+
+has_info!(
+    Ast,
+    Use,
+    UseBody,
+    UseItem,
+    UseItemError,
+    UseItems,
+    Def,
+    Let,
+    Init,
+    Union,
+    UnionAlternative,
+    Not,
+    BoolOp,
+    Neg,
+    ArithOp,
+    Struct,
+    StructField,
+    StructFieldError,
+    StructSpread,
+    List,
+    Named,
+    Length,
+    InnerName,
+    Alias,
+    Ident,
+    PrimitiveValue,
+    Bool,
+);
+
+pub trait TypedExpr: HasInfo {
     fn type_expr(&self) -> Option<&Expr>;
     fn expr(&self) -> Option<&Expr>;
-    fn info(&self) -> &AstInfo;
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -39,6 +97,7 @@ impl Prelude {
     }
 }
 
+/// Root of the syntax tree of a file
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Ast {
     pub globals: Vec<Global>,
@@ -50,10 +109,24 @@ pub enum Global {
     Use(Use),
     Def(Def),
     Let(Let),
-    Init(Expr),
+    Init(Init),
+    Test(Test),
     Error(AstInfo),
 }
 
+impl HasInfo for Global {
+    fn info(&self) -> &AstInfo {
+        match self {
+            Self::Use(u) => &u.info,
+            Self::Def(def) => &def.info,
+            Self::Let(l) => &l.info,
+            Self::Init(init) => &init.info,
+            Self::Test(test) => &test.info,
+            Self::Error(info) => info,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Use {
     pub use_kw: AstInfo,
@@ -93,6 +166,16 @@ pub enum UseKind {
     Error(AstInfo),
 }
 
+impl HasInfo for UseKind {
+    fn info(&self) -> &AstInfo {
+        match self {
+            Self::All(info) | Self::Error(info) => info,
+            Self::Single(item) => &item.info,
+            Self::Multiple(items) => &items.info,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UseItem {
     pub name: Ident,
@@ -137,9 +220,18 @@ pub struct Def {
     pub type_expr: Option<Expr>,
     pub eq: Option<AstInfo>,
     pub expr: Option<Expr>,
+    pub alias: Option<Alias>,
     pub info: AstInfo,
 }
 
+impl Def {
+    /// Get the name as which the root of an init, that refers to this def, will be emitted.
+    /// Only returns a name, if a valid alias is set.
+    pub fn emit_name(&self) -> Option<&str> {
+        self.alias.as_ref().and_then(Alias::emit_name)
+    }
+}
+
 impl TypedExpr for Def {
     fn type_expr(&self) -> Option<&Expr> {
         self.type_expr.as_ref()
@@ -148,10 +240,6 @@ impl TypedExpr for Def {
     fn expr(&self) -> Option<&Expr> {
         self.expr.as_ref()
     }
-
-    fn info(&self) -> &AstInfo {
-        &self.info
-    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -162,9 +250,18 @@ pub struct Let {
     pub type_expr: Option<Expr>,
     pub eq: Option<AstInfo>,
     pub expr: Option<Expr>,
+    pub alias: Option<Alias>,
     pub info: AstInfo,
 }
 
+impl Let {
+    /// Get the name as which the root of an init, that refers to this let, will be emitted.
+    /// Only returns a name, if a valid alias is set.
+    pub fn emit_name(&self) -> Option<&str> {
+        self.alias.as_ref().and_then(Alias::emit_name)
+    }
+}
+
 impl TypedExpr for Let {
     fn type_expr(&self) -> Option<&Expr> {
         self.type_expr.as_ref()
@@ -173,12 +270,35 @@ impl TypedExpr for Let {
     fn expr(&self) -> Option<&Expr> {
         self.expr.as_ref()
     }
+}
 
-    fn info(&self) -> &AstInfo {
-        &self.info
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Init {
+    pub expr: Expr,
+    pub alias: Option<Alias>,
+    pub info: AstInfo,
+}
+
+impl Init {
+    /// Get the name as which the root of this init will be emitted.
+    /// Only returns a name, if a valid alias is set.
+    pub fn emit_name(&self) -> Option<&str> {
+        self.alias.as_ref().and_then(Alias::emit_name)
     }
 }
 
+/// Example init of a schema, e.g. `test local { Config { port = 80 } }`.
+/// It is analyzed like an init, but never emitted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Test {
+    pub test_kw: AstInfo,
+    pub name: Option<Ident>,
+    pub lcurly: Option<AstInfo>,
+    pub expr: Option<Expr>,
+    pub rcurly: Option<AstInfo>,
+    pub info: AstInfo,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Expr {
     Union(Union),
@@ -186,8 +306,8 @@ pub enum Expr {
     Error(AstInfo),
 }
 
-impl Expr {
-    pub const fn info(&self) -> &AstInfo {
+impl HasInfo for Expr {
+    fn info(&self) -> &AstInfo {
         match self {
             Self::Union(union) => &union.info,
             Self::Single(single) => single.info(),
@@ -216,23 +336,125 @@ pub enum Single {
     List(List),
     Named(Named),
     Primitive(Primitive),
+    Not(Not),
+    BoolOp(BoolOp),
+    Neg(Neg),
+    ArithOp(ArithOp),
 }
 
-impl Single {
-    pub const fn info(&self) -> &AstInfo {
+impl HasInfo for Single {
+    fn info(&self) -> &AstInfo {
         match self {
             Self::Struct(Struct { info, .. })
             | Self::List(List { info, .. })
-            | Self::Named(Named { info, .. }) => info,
+            | Self::Named(Named { info, .. })
+            | Self::Not(Not { info, .. })
+            | Self::BoolOp(BoolOp { info, .. })
+            | Self::Neg(Neg { info, .. })
+            | Self::ArithOp(ArithOp { info, .. }) => info,
             Self::Primitive(primitive) => primitive.info(),
         }
     }
 }
 
+/// Negation of a boolean, e.g. `!debug`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Not {
+    pub not: AstInfo,
+    pub single: Option<Box<Single>>,
+    pub info: AstInfo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoolOperator {
+    And,
+    Or,
+}
+
+impl BoolOperator {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::And => token::AND,
+            Self::Or => token::OR,
+        }
+    }
+
+    pub const fn apply(&self, lhs: bool, rhs: bool) -> bool {
+        match self {
+            Self::And => lhs && rhs,
+            Self::Or => lhs || rhs,
+        }
+    }
+}
+
+/// Binary boolean operation, e.g. `a && b`.
+/// `&&` binds stronger than `||`, which binds stronger than the union `|`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoolOp {
+    pub lhs: Box<Single>,
+    pub operator: BoolOperator,
+    pub operator_info: AstInfo,
+    pub rhs: Option<Box<Single>>,
+    pub info: AstInfo,
+}
+
+/// Arithmetic negation of a number, e.g. `-offset`.
+/// Negative number literals are negations as well.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Neg {
+    pub minus: AstInfo,
+    pub single: Option<Box<Single>>,
+    pub info: AstInfo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArithOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl ArithOperator {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => token::PLUS,
+            Self::Sub => token::MINUS,
+            Self::Mul => token::STAR,
+            Self::Div => token::SLASH,
+        }
+    }
+
+    /// Applies the operator.
+    /// Returns `None` on a division by zero.
+    pub fn apply(&self, lhs: f64, rhs: f64) -> Option<f64> {
+        match self {
+            Self::Add => Some(lhs + rhs),
+            Self::Sub => Some(lhs - rhs),
+            Self::Mul => Some(lhs * rhs),
+            Self::Div if rhs == 0.0 => None,
+            Self::Div => Some(lhs / rhs),
+        }
+    }
+}
+
+/// Binary arithmetic operation on numbers, e.g. `total_memory * 0.75`.
+/// `*` and `/` bind stronger than `+` and `-`, which bind stronger than `&&`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArithOp {
+    pub lhs: Box<Single>,
+    pub operator: ArithOperator,
+    pub operator_info: AstInfo,
+    pub rhs: Option<Box<Single>>,
+    pub info: AstInfo,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Struct {
     pub lcurly: AstInfo,
     pub fields: Vec<Result<StructField, StructFieldError>>,
+    /// Structs, whose fields are spread into this struct, in their order
+    pub spreads: Vec<StructSpread>,
     pub rcurly: Option<AstInfo>,
     pub info: AstInfo,
 }
@@ -245,6 +467,9 @@ pub struct StructField {
     pub eq: Option<AstInfo>,
     pub expr: Option<Expr>,
     pub alias: Option<Alias>,
+    /// Set by the reserved alias `as _`.
+    /// The field is analyzed as usual, but not emitted.
+    pub hidden: bool,
     pub info: AstInfo,
 }
 
@@ -254,11 +479,7 @@ impl StructField {
     pub fn emit_name(&self) -> &str {
         self.alias
             .as_ref()
-            .and_then(|alias| alias.name.as_ref())
-            .and_then(|alias_name| match alias_name {
-                AliasName::String(name) => Some(&name.value),
-                AliasName::Ident(_) => None,
-            })
+            .and_then(Alias::emit_name)
             .unwrap_or(&self.name.name)
     }
 }
@@ -271,10 +492,6 @@ impl TypedExpr for StructField {
     fn expr(&self) -> Option<&Expr> {
         self.expr.as_ref()
     }
-
-    fn info(&self) -> &AstInfo {
-        &self.info
-    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -282,6 +499,16 @@ pub struct StructFieldError {
     pub info: AstInfo,
 }
 
+/// Struct, whose fields are spread into the enclosing struct, e.g. `...base` in `{ ...base port = 80 }`.
+///
+/// The fields of the enclosing struct override the spread fields, later spreads override earlier ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructSpread {
+    pub spread: AstInfo,
+    pub single: Option<Single>,
+    pub info: AstInfo,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct List {
     pub lbracket: AstInfo,
@@ -294,10 +521,42 @@ pub struct List {
 pub struct Named {
     pub name: Ident,
     pub inner_names: Vec<InnerName>,
+    pub length: Option<Box<Length>>,
     pub expr: Option<StructOrList>,
     pub info: AstInfo,
 }
 
+/// Length constraint of a string type, e.g. the `(1..10)` in `String(1..10)`.
+///
+/// A single number fixes the length, a range includes both of its bounds.
+/// Either bound of a range may be omitted, e.g. `String(..10)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Length {
+    pub lparen: AstInfo,
+    pub min: Option<PrimitiveValue>,
+    pub dot_dot: Option<AstInfo>,
+    pub max: Option<PrimitiveValue>,
+    pub rparen: Option<AstInfo>,
+    pub info: AstInfo,
+}
+
+/// Renders the constraint as written, e.g. `(1..10)`.
+impl std::fmt::Display for Length {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bound = |bound: &Option<PrimitiveValue>| {
+            bound
+                .as_ref()
+                .map(|bound| bound.value.to_string())
+                .unwrap_or_default()
+        };
+        if self.dot_dot.is_some() {
+            write!(f, "({}..{})", bound(&self.min), bound(&self.max))
+        } else {
+            write!(f, "({})", bound(&self.min))
+        }
+    }
+}
+
 impl Named {
     pub fn flatten_name(&self) -> Ident {
         let mut ident = self.name.clone();
@@ -352,6 +611,15 @@ pub enum StructOrList {
     List(List),
 }
 
+impl HasInfo for StructOrList {
+    fn info(&self) -> &AstInfo {
+        match self {
+            Self::Struct(s) => &s.info,
+            Self::List(list) => &list.info,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq)]
 pub enum Primitive {
     String(PrimitiveValue),
@@ -372,8 +640,10 @@ impl Primitive {
             Self::Null(_) => token::NULL,
         }
     }
+}
 
-    pub const fn info(&self) -> &AstInfo {
+impl HasInfo for Primitive {
+    fn info(&self) -> &AstInfo {
         match self {
             Self::String(PrimitiveValue { info, .. })
             | Self::Char(PrimitiveValue { info, .. })
@@ -389,7 +659,13 @@ impl PartialEq for Primitive {
         match (self, other) {
             (Self::String(s1), Self::String(s2)) => s1 == s2,
             (Self::Char(c1), Self::Char(c2)) => c1 == c2,
-            (Self::Number(n1), Self::Number(n2)) => n1 == n2,
+            // numbers are equal by value, e.g. `1e3` and `1000`
+            (Self::Number(n1), Self::Number(n2)) => {
+                match (n1.value.parse::<f64>(), n2.value.parse::<f64>()) {
+                    (Ok(n1), Ok(n2)) => n1 == n2,
+                    _ => n1 == n2,
+                }
+            }
             (Self::Bool(b1), Self::Bool(b2)) => b1 == b2,
             (Self::Null(_), Self::Null(_)) => true,
             _ => false,
@@ -404,12 +680,36 @@ pub struct Alias {
     pub info: AstInfo,
 }
 
+impl Alias {
+    /// Returns whether this is the reserved alias `as _`.
+    pub fn is_hidden(&self) -> bool {
+        matches!(&self.name, Some(AliasName::Ident(ident)) if ident.name == HIDDEN_ALIAS)
+    }
+
+    /// Name to emit instead of the original one, only string aliases are valid.
+    pub fn emit_name(&self) -> Option<&str> {
+        match &self.name {
+            Some(AliasName::String(name)) => Some(&name.value),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AliasName {
     String(PrimitiveValue),
     Ident(Ident),
 }
 
+impl HasInfo for AliasName {
+    fn info(&self) -> &AstInfo {
+        match self {
+            Self::String(value) => &value.info,
+            Self::Ident(ident) => &ident.info,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq)]
 pub struct Ident {
     pub name: String,
@@ -420,6 +720,12 @@ impl Ident {
     pub fn is_flattened(&self) -> bool {
         self.name.contains("::")
     }
+
+    /// Returns the list index, if this ident is an index segment of a named
+    /// (e.g. the `0` in `hosts::0`).
+    pub fn as_index(&self) -> Option<usize> {
+        self.name.parse().ok()
+    }
 }
 
 impl PartialEq for Ident {
@@ -436,16 +742,18 @@ impl std::hash::Hash for Ident {
 
 #[derive(Clone, Debug, Eq)]
 pub struct PrimitiveValue {
-    pub value: String,
+    /// Shared, because string literals can be large and are cloned into values.
+    pub value: Arc<str>,
     pub info: AstInfo,
 }
 
 impl PrimitiveValue {
-    // clippy proposes to make the function const, but the compiler disagrees
-    #[allow(clippy::missing_const_for_fn)]
-    pub(crate) fn new(tuple: (String, AstInfo)) -> Self {
+    pub(crate) fn new(tuple: (impl Into<Arc<str>>, AstInfo)) -> Self {
         let (value, info) = tuple;
-        Self { value, info }
+        Self {
+            value: value.into(),
+            info,
+        }
     }
 }
 
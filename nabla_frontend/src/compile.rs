@@ -0,0 +1,186 @@
+use crate::{
+    diagnostics::{self, Diagnostic, Severity, Stage},
+    eval::Value,
+    lexer::{self, LexerResult},
+    parser::{self, ParserResult},
+    resolver::ModuleResolver,
+    semantics::{self, SemanticModel, SemanticsResult},
+    token::{self, TextRange, Token, TokenRange},
+    GlobalIdent, ModuleAst,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Error of any stage of a compilation, located by its text range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompileError {
+    Lex(token::Error),
+    /// Parse(error, text range of its token range)
+    Parse(parser::Error, TextRange),
+    /// Semantic(error, text range of its token range)
+    Semantic(semantics::error::Error, TextRange),
+}
+
+impl CompileError {
+    fn parse(tokens: &[Token], error: parser::Error) -> Self {
+        let range = diagnostics::to_text_range(tokens, &error.range);
+        Self::Parse(error, range)
+    }
+
+    fn semantic(tokens: &[Token], error: semantics::error::Error) -> Self {
+        let range = diagnostics::to_text_range(tokens, &error.range);
+        Self::Semantic(error, range)
+    }
+
+    pub const fn range(&self) -> &TextRange {
+        match self {
+            Self::Lex(error) => &error.range,
+            Self::Parse(_, range) | Self::Semantic(_, range) => range,
+        }
+    }
+
+    /// Token range, that parse and semantic errors are reported at.
+    /// Lexical errors occur before there are tokens.
+    pub const fn token_range(&self) -> Option<&TokenRange> {
+        match self {
+            Self::Lex(_) => None,
+            Self::Parse(error, _) => Some(&error.range),
+            Self::Semantic(error, _) => Some(&error.range),
+        }
+    }
+
+    pub const fn stage(&self) -> Stage {
+        match self {
+            Self::Lex(_) => Stage::Lex,
+            Self::Parse(_, _) => Stage::Parse,
+            Self::Semantic(_, _) => Stage::Semantics,
+        }
+    }
+
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::Lex(error) => error.message.severity(),
+            Self::Parse(_, _) => Severity::Error,
+            Self::Semantic(error, _) => error.message.severity(),
+        }
+    }
+
+    pub const fn is_error(&self) -> bool {
+        matches!(self.severity(), Severity::Error)
+    }
+
+    /// Diagnostic of the error.
+    /// The tokens locate the secondary spans of semantic errors.
+    pub fn to_diagnostic(&self, tokens: &[Token]) -> Diagnostic {
+        match self {
+            Self::Lex(error) => Diagnostic::from_lexer_error(error),
+            Self::Parse(error, _) => Diagnostic::from_parser_error(tokens, error),
+            Self::Semantic(error, _) => Diagnostic::from_semantic_error(tokens, error),
+        }
+    }
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lex(error) => write!(f, "{}", error),
+            Self::Parse(error, _) => write!(f, "{}", error),
+            Self::Semantic(error, _) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// How the uses of the compiled module are resolved
+#[derive(Clone, Copy, Default)]
+pub enum Uses<'a> {
+    /// Every use is accepted and its items are unknown
+    #[default]
+    Unchecked,
+    /// The uses are checked against the modules of the resolver, see `uses::analyze`
+    Checked(&'a dyn ModuleResolver),
+    /// The used modules are loaded from the resolver, see `semantics::analyze_modules`
+    Loaded(&'a dyn ModuleResolver),
+}
+
+/// Options of a compilation
+#[derive(Clone, Default)]
+pub struct CompileOptions<'a> {
+    pub semantics: semantics::Options,
+    pub uses: Uses<'a>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CompileResult {
+    pub tokens: Vec<Token>,
+    /// The parsed module, named `root`
+    pub module_ast: ModuleAst,
+    /// Result of the semantic analysis, e.g. the inits and the symbol table.
+    /// Its errors are moved to `errors`.
+    pub semantics: SemanticsResult,
+    /// Errors of all stages in the order of the stages
+    pub errors: Vec<CompileError>,
+}
+
+impl CompileResult {
+    /// Value of the first init, which is the emitted one.
+    pub fn init(&self) -> Option<&Value> {
+        self.semantics.inits.first()
+    }
+
+    pub fn inits(&self) -> &[Value] {
+        &self.semantics.inits
+    }
+
+    pub const fn symbol_table(&self) -> &semantics::SymbolTable {
+        &self.semantics.symbol_table
+    }
+
+    /// Whether any stage reported an error, warnings are ignored.
+    pub fn has_errors(&self) -> bool {
+        self.errors.iter().any(CompileError::is_error)
+    }
+}
+
+/// Lex, parse and analyze the source as the module `root`.
+///
+/// Every stage runs, even if an earlier one reported errors,
+/// so the result can be used by tooling while the source is edited.
+pub fn compile(src: &str) -> CompileResult {
+    compile_with_options(src, &CompileOptions::default())
+}
+
+/// Lex, parse and analyze the source with the given options.
+pub fn compile_with_options(src: &str, options: &CompileOptions) -> CompileResult {
+    let LexerResult { tokens, errors } = lexer::lex(src);
+    let mut compile_errors: Vec<_> = errors.into_iter().map(CompileError::Lex).collect();
+    let ParserResult { ast, errors } = parser::parse(&tokens);
+    compile_errors.extend(
+        errors
+            .into_iter()
+            .map(|error| CompileError::parse(&tokens, error)),
+    );
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let mut semantics_result = match options.uses {
+        Uses::Unchecked => semantics::analyze_with_options(&module_ast, &options.semantics),
+        Uses::Checked(resolver) => {
+            SemanticModel::with_options(&module_ast, options.semantics.clone())
+                .with_resolver(resolver)
+                .into_result()
+        }
+        Uses::Loaded(resolver) => {
+            semantics::analyze_modules_with_options(&module_ast, resolver, &options.semantics)
+        }
+    };
+    compile_errors.extend(
+        std::mem::take(&mut semantics_result.errors)
+            .into_iter()
+            .map(|error| CompileError::semantic(&tokens, error)),
+    );
+    CompileResult {
+        tokens,
+        module_ast,
+        semantics: semantics_result,
+        errors: compile_errors,
+    }
+}
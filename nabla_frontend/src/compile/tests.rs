@@ -0,0 +1,127 @@
+use super::*;
+use crate::resolver::MemoryResolver;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn valid_source() {
+    let result = compile("let port = 80\n{ port = port }");
+    assert_empty!(result.errors);
+    assert_eq!(Some(&Value::from([("port", 80)])), result.init());
+    assert_eq!(
+        Some(&Value::from(80)),
+        result
+            .symbol_table()
+            .get(&GlobalIdent::default().extend("port".to_string()))
+    );
+}
+
+#[test]
+fn errors_of_all_stages() {
+    let src = "let a = 1 $\nlet b = c\nlet = 2";
+    let result = compile(src);
+    let errors: Vec<_> = result
+        .errors
+        .iter()
+        .map(|error| (error.stage(), &src[error.range().clone()]))
+        .collect();
+    assert_eq!(
+        vec![
+            (Stage::Lex, "$"),
+            (Stage::Parse, "$"),
+            (Stage::Parse, ""),
+            (Stage::Semantics, "c"),
+            (Stage::Semantics, "let b = c"),
+            (Stage::Semantics, "$"),
+        ],
+        errors
+    );
+    assert!(result.has_errors());
+}
+
+#[test]
+fn diagnostics_keep_the_range() {
+    let result = compile("def A = { a: Number }\nA { a = \"x\" }");
+    let diagnostics: Vec<_> = result
+        .errors
+        .iter()
+        .map(|error| error.to_diagnostic(&result.tokens))
+        .collect();
+    assert_eq!(
+        result
+            .errors
+            .iter()
+            .map(CompileError::range)
+            .collect::<Vec<_>>(),
+        diagnostics
+            .iter()
+            .map(|diagnostic| &diagnostic.range)
+            .collect::<Vec<_>>()
+    );
+    assert!(!diagnostics.is_empty());
+}
+
+#[test]
+fn loaded_uses() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert(
+        GlobalIdent::default().extend("shared".to_string()),
+        "let port = 8080".to_string(),
+    );
+    let options = CompileOptions {
+        uses: Uses::Loaded(&resolver),
+        ..Default::default()
+    };
+    let result = compile_with_options("use shared::port\n{ port = port }", &options);
+    assert_empty!(result.errors);
+    assert_eq!(Some(&Value::from([("port", 8080)])), result.init());
+}
+
+#[test]
+fn error_ranges_stay_inside_of_the_source() {
+    // unfinished sources report errors at the end of the file
+    for src in [
+        "{ a = ",
+        "let a = [1,",
+        "def A = { a: Number }\nA {",
+        "} A {}",
+        "",
+    ] {
+        let result = compile(src);
+        for error in &result.errors {
+            let range = error.range();
+            assert!(
+                range.start <= range.end && range.end <= src.len(),
+                "{:?} of {:?}",
+                error,
+                src
+            );
+            error.to_diagnostic(&result.tokens);
+        }
+    }
+}
+
+#[test]
+fn unterminated_string_ends_at_the_line() {
+    let result = compile("let a = \"abc\nlet b = 1\n{ b = b }");
+    let errors: Vec<_> = result.errors.iter().map(CompileError::stage).collect();
+    assert_eq!(vec![Stage::Lex], errors);
+    assert_eq!(Some(&Value::from([("b", 1)])), result.init());
+}
+
+#[test]
+fn negative_numbers() {
+    let src = "def Offset = Number\nlet a: Offset = -5\nlet b: Number = -0.5\n\
+               let c = -9007199254740993\nlet d = 1 - -2\n{ a = a b = b c = c d = d e = --a }";
+    let result = compile(src);
+    assert_empty!(result.errors);
+    assert_eq!(
+        Some(&Value::from([
+            ("a", Value::Number("-5".to_string())),
+            ("b", Value::Number("-0.5".to_string())),
+            ("c", Value::Number("-9007199254740993".to_string())),
+            ("d", Value::from(3)),
+            ("e", Value::Number("-5".to_string())),
+        ])),
+        result.init()
+    );
+}
@@ -0,0 +1,209 @@
+use crate::{
+    parser, semantics,
+    token::{self, TextRange, Token, TokenRange},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The compilation stage that reported a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    Lex,
+    Parse,
+    Semantics,
+}
+
+impl Stage {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lex => "lexical",
+            Self::Parse => "parse",
+            Self::Semantics => "semantic",
+        }
+    }
+}
+
+/// Severity of a diagnostic.
+/// Only errors make a compilation fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Error or warning of any compilation stage, located by its text range.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub stage: Stage,
+    pub severity: Severity,
+    pub message: String,
+    pub range: TextRange,
+    /// Diagnostics at the same location, that were merged into this one
+    pub related: Vec<Self>,
+    /// Other locations, that are involved in the diagnostic
+    pub notes: Vec<Note>,
+    /// Global, that contains the diagnostic, e.g. `def Config`, see `structure::GlobalRanges`
+    pub context: Option<String>,
+}
+
+/// Location with a message, that explains a diagnostic,
+/// e.g. the first declaration of a redeclared identifier.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Note {
+    pub message: String,
+    pub range: TextRange,
+}
+
+impl Diagnostic {
+    pub const fn new(stage: Stage, message: String, range: TextRange) -> Self {
+        Self {
+            stage,
+            severity: Severity::Error,
+            message,
+            range,
+            related: Vec::new(),
+            notes: Vec::new(),
+            context: None,
+        }
+    }
+
+    pub fn with_context(mut self, context: Option<&str>) -> Self {
+        self.context = context.map(str::to_string);
+        self
+    }
+
+    pub const fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub const fn is_error(&self) -> bool {
+        matches!(self.severity, Severity::Error)
+    }
+
+    pub fn from_lexer_error(error: &token::Error) -> Self {
+        Self::new(Stage::Lex, error.to_string(), error.range.clone())
+            .with_severity(error.message.severity())
+    }
+
+    pub fn from_parser_error(tokens: &[Token], error: &parser::Error) -> Self {
+        Self::new(
+            Stage::Parse,
+            error.to_string(),
+            to_text_range(tokens, &error.range),
+        )
+    }
+
+    pub fn from_semantic_error(tokens: &[Token], error: &semantics::error::Error) -> Self {
+        let mut diagnostic = Self::new(
+            Stage::Semantics,
+            error.to_string(),
+            to_text_range(tokens, &error.range),
+        )
+        .with_severity(error.message.severity());
+        diagnostic.notes = error
+            .secondary
+            .iter()
+            .map(|span| Note {
+                message: span.message.clone(),
+                range: to_text_range(tokens, &span.range),
+            })
+            .collect();
+        diagnostic
+    }
+}
+
+/// Convert a token range into the text range of the tokens, it covers.
+///
+/// Token ranges are end-exclusive.
+/// Zero-width token ranges are located at the start of their token.
+/// Indices past the last token are located at the end of the text.
+pub fn to_text_range(tokens: &[Token], range: &TokenRange) -> TextRange {
+    let text_end = tokens.last().map_or(0, |token| token.range.end);
+    let start = tokens
+        .get(range.start)
+        .map_or(text_end, |token| token.range.start);
+    if range.end <= range.start {
+        return start..start;
+    }
+    let end = tokens
+        .get(range.end - 1)
+        .map_or(text_end, |token| token.range.end);
+    start..end.max(start)
+}
+
+/// Sort diagnostics by stage and position and remove duplicates.
+///
+/// Cascading errors often report the same message at the same range multiple times.
+pub fn normalize(diagnostics: &mut Vec<Diagnostic>) {
+    diagnostics.sort_by(|d1, d2| {
+        (d1.stage, d1.range.start, d1.range.end).cmp(&(d2.stage, d2.range.start, d2.range.end))
+    });
+    let mut seen = std::collections::HashSet::new();
+    diagnostics.retain(|diagnostic| {
+        seen.insert((
+            diagnostic.stage,
+            diagnostic.severity,
+            diagnostic.message.clone(),
+            diagnostic.range.clone(),
+        ))
+    });
+}
+
+/// Minimal overlap of two ranges, for their diagnostics to be merged.
+const MERGE_OVERLAP: f64 = 0.8;
+
+/// Merge diagnostics with (almost) the same range.
+///
+/// Two ranges are merged, if their intersection covers at least 80% of the longer one.
+/// Zero-width ranges are only merged with zero-width ranges at the same position.
+/// The most severe and then most specific (shortest range, earliest stage) diagnostic
+/// of each group becomes the primary one, the others are attached as `related`.
+/// The result is sorted by position.
+pub fn merge(mut diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    diagnostics.sort_by_key(|diagnostic| {
+        (
+            diagnostic.range.start,
+            diagnostic.range.end,
+            diagnostic.stage,
+        )
+    });
+    let mut groups: Vec<Vec<Diagnostic>> = Vec::new();
+    for diagnostic in diagnostics {
+        match groups
+            .iter_mut()
+            .find(|group| overlaps(&group[0].range, &diagnostic.range))
+        {
+            Some(group) => group.push(diagnostic),
+            None => groups.push(vec![diagnostic]),
+        }
+    }
+    let mut merged: Vec<_> = groups
+        .into_iter()
+        .map(|mut group| {
+            group.sort_by_key(|diagnostic| {
+                (
+                    diagnostic.severity,
+                    diagnostic.range.len(),
+                    diagnostic.stage,
+                )
+            });
+            let mut group = group.into_iter();
+            let mut primary = group.next().expect("Group must not be empty");
+            primary.related.extend(group);
+            primary
+        })
+        .collect();
+    merged.sort_by_key(|diagnostic| (diagnostic.range.start, diagnostic.range.end));
+    merged
+}
+
+fn overlaps(r1: &TextRange, r2: &TextRange) -> bool {
+    if r1.is_empty() || r2.is_empty() {
+        return r1 == r2;
+    }
+    let intersection = r1.end.min(r2.end).saturating_sub(r1.start.max(r2.start));
+    let longest = r1.len().max(r2.len());
+    intersection as f64 / longest as f64 >= MERGE_OVERLAP
+}
@@ -0,0 +1,181 @@
+use super::*;
+use crate::lexer::{lex, LexerResult};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn normalize_sorts_by_stage_and_position() {
+    let mut diagnostics = vec![
+        Diagnostic::new(Stage::Semantics, "b".to_string(), 5..6),
+        Diagnostic::new(Stage::Parse, "c".to_string(), 7..8),
+        Diagnostic::new(Stage::Semantics, "a".to_string(), 1..2),
+        Diagnostic::new(Stage::Lex, "d".to_string(), 9..9),
+    ];
+    normalize(&mut diagnostics);
+    assert_eq!(
+        vec![
+            Diagnostic::new(Stage::Lex, "d".to_string(), 9..9),
+            Diagnostic::new(Stage::Parse, "c".to_string(), 7..8),
+            Diagnostic::new(Stage::Semantics, "a".to_string(), 1..2),
+            Diagnostic::new(Stage::Semantics, "b".to_string(), 5..6),
+        ],
+        diagnostics
+    );
+}
+
+#[test]
+fn normalize_removes_duplicates() {
+    let mut diagnostics = vec![
+        Diagnostic::new(Stage::Semantics, "a".to_string(), 1..2),
+        Diagnostic::new(Stage::Semantics, "b".to_string(), 1..2),
+        Diagnostic::new(Stage::Semantics, "a".to_string(), 1..2),
+        Diagnostic::new(Stage::Semantics, "a".to_string(), 1..3),
+    ];
+    normalize(&mut diagnostics);
+    assert_eq!(
+        vec![
+            Diagnostic::new(Stage::Semantics, "a".to_string(), 1..2),
+            Diagnostic::new(Stage::Semantics, "b".to_string(), 1..2),
+            Diagnostic::new(Stage::Semantics, "a".to_string(), 1..3),
+        ],
+        diagnostics
+    );
+}
+
+#[test]
+fn merge_overlapping() {
+    let lex = Diagnostic::new(Stage::Lex, "unknown".to_string(), 10..20);
+    let parse = Diagnostic::new(Stage::Parse, "unexpected".to_string(), 10..21);
+    let semantics = Diagnostic::new(Stage::Semantics, "type".to_string(), 11..20);
+    let other = Diagnostic::new(Stage::Semantics, "other".to_string(), 0..5);
+    let mut primary = semantics.clone();
+    primary.related = vec![lex.clone(), parse.clone()];
+    assert_eq!(
+        vec![other.clone(), primary],
+        merge(vec![parse, semantics, lex, other])
+    );
+}
+
+#[test]
+fn merge_keeps_small_overlap() {
+    let d1 = Diagnostic::new(Stage::Parse, "a".to_string(), 0..10);
+    let d2 = Diagnostic::new(Stage::Semantics, "b".to_string(), 3..10);
+    assert_eq!(vec![d1.clone(), d2.clone()], merge(vec![d2, d1]));
+}
+
+#[test]
+fn merge_zero_width() {
+    let d1 = Diagnostic::new(Stage::Parse, "a".to_string(), 5..5);
+    let d2 = Diagnostic::new(Stage::Lex, "b".to_string(), 5..5);
+    let d3 = Diagnostic::new(Stage::Semantics, "c".to_string(), 5..6);
+    let d4 = Diagnostic::new(Stage::Parse, "d".to_string(), 6..6);
+    let mut primary = d2.clone();
+    primary.related = vec![d1.clone()];
+    assert_eq!(
+        vec![primary, d3.clone(), d4.clone()],
+        merge(vec![d4, d3, d1, d2])
+    );
+}
+
+#[test]
+fn merge_most_severe_wins() {
+    let warning = Diagnostic::new(Stage::Semantics, "warning".to_string(), 0..4)
+        .with_severity(Severity::Warning);
+    let error = Diagnostic::new(Stage::Semantics, "error".to_string(), 0..5);
+    let mut primary = error.clone();
+    primary.related = vec![warning.clone()];
+    assert_eq!(vec![primary], merge(vec![warning, error]));
+}
+
+/// Tokens: `let`, ` `, `x`, ` `, `=`, ` `, `"äöü"`, ` `, `y`, EOF
+const MULTI_BYTE: &str = r#"let x = "äöü" y"#;
+
+fn semantic_range(range: TokenRange) -> TextRange {
+    let LexerResult { tokens, errors } = lex(MULTI_BYTE);
+    assert_empty!(errors);
+    let error = semantics::error::Error::new(semantics::error::ErrorMessage::TypeMismatch, range);
+    Diagnostic::from_semantic_error(&tokens, &error).range
+}
+
+#[test]
+fn text_range_of_single_token() {
+    assert_eq!(4..5, semantic_range(2..3));
+    assert_eq!(8..16, semantic_range(6..7));
+    assert_eq!(17..18, semantic_range(8..9));
+}
+
+#[test]
+fn text_range_of_multiple_tokens() {
+    assert_eq!(0..18, semantic_range(0..10));
+    assert_eq!(6..16, semantic_range(4..7));
+}
+
+#[test]
+fn text_range_of_zero_width_range() {
+    assert_eq!(0..0, semantic_range(0..0));
+    assert_eq!(17..17, semantic_range(8..8));
+    assert_eq!(18..18, semantic_range(9..9));
+}
+
+#[test]
+fn text_range_past_last_token() {
+    assert_eq!(17..18, semantic_range(8..11));
+    assert_eq!(18..18, semantic_range(10..10));
+    assert_eq!(18..18, semantic_range(12..15));
+}
+
+#[test]
+fn text_range_without_tokens() {
+    assert_eq!(0..0, to_text_range(&[], &(0..0)));
+    assert_eq!(0..0, to_text_range(&[], &(0..3)));
+}
+
+#[test]
+fn parser_error_range() {
+    let LexerResult { tokens, errors } = lex(MULTI_BYTE);
+    assert_empty!(errors);
+    let error = parser::Error::new(parser::ErrorMessage::UnexpectedTokens, 6..9);
+    let diagnostic = Diagnostic::from_parser_error(&tokens, &error);
+    assert_eq!(Stage::Parse, diagnostic.stage);
+    assert_eq!(8..18, diagnostic.range);
+    assert_eq!(&MULTI_BYTE[8..18], r#""äöü" y"#);
+}
+
+#[test]
+fn lexer_error_range() {
+    let src = "x = \"ä\" §";
+    let LexerResult { errors, .. } = lex(src);
+    let diagnostics: Vec<_> = errors.iter().map(Diagnostic::from_lexer_error).collect();
+    assert_eq!(1, diagnostics.len());
+    assert_eq!(Stage::Lex, diagnostics[0].stage);
+    assert_eq!("§", &src[diagnostics[0].range.clone()]);
+}
+
+#[test]
+fn semantic_warning_severity() {
+    let LexerResult { tokens, .. } = lex(MULTI_BYTE);
+    let error = semantics::error::Error::new(
+        semantics::error::ErrorMessage::OpaqueUsage("y".to_string()),
+        8..9,
+    );
+    let diagnostic = Diagnostic::from_semantic_error(&tokens, &error);
+    assert_eq!(Severity::Warning, diagnostic.severity);
+    assert!(!diagnostic.is_error());
+}
+
+#[test]
+fn semantic_secondary_span_as_note() {
+    let LexerResult { tokens, .. } = lex(MULTI_BYTE);
+    let error = semantics::error::Error::new(
+        semantics::error::ErrorMessage::Redeclaration("y".to_string()),
+        8..9,
+    )
+    .with_secondary(semantics::error::FIRST_DECLARED, 2..3);
+    let diagnostic = Diagnostic::from_semantic_error(&tokens, &error);
+    assert_eq!(
+        vec![Note {
+            message: "first declared here".to_string(),
+            range: 4..5,
+        }],
+        diagnostic.notes
+    );
+}
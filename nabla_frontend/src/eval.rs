@@ -1,14 +1,22 @@
 use crate::ast::{
-    Bool, Expr, List, Named, Primitive, PrimitiveValue, Single, Struct, StructOrList,
+    ArithOp, Bool, BoolOp, Expr, List, Named, Neg, Not, Primitive, PrimitiveValue, Single, Struct,
+    StructOrList,
 };
-pub use value::Value;
+pub use path::{PathSegment, ValuePath};
+pub use value::{Fields, Value};
 
+mod path;
 mod value;
 
-pub fn eval(expr: &Expr) -> Value {
+/// Evaluate the literal parts of the expression.
+///
+/// References are not resolved, so a bare reference (e.g. an init `config`, that emits a let)
+/// evaluates to `Value::Unknown`. The emitted values are the inits of `semantics::analyze`.
+pub fn eval_literal(expr: &Expr) -> Value {
     expr.eval()
 }
 
+/// Evaluation without resolving references, see [`eval_literal`].
 pub trait Eval {
     fn eval(&self) -> Value;
 }
@@ -29,10 +37,54 @@ impl Eval for Single {
             Self::List(list) => list.eval(),
             Self::Named(named) => named.eval(),
             Self::Primitive(primitive) => primitive.eval(),
+            Self::Not(not) => not.eval(),
+            Self::BoolOp(bool_op) => bool_op.eval(),
+            Self::Neg(neg) => neg.eval(),
+            Self::ArithOp(arith_op) => arith_op.eval(),
         }
     }
 }
 
+impl Eval for Not {
+    fn eval(&self) -> Value {
+        match self.single.as_deref().map(Single::eval) {
+            Some(Value::Bool(b)) => Value::Bool(!b),
+            _ => Value::Unknown,
+        }
+    }
+}
+
+impl Eval for BoolOp {
+    fn eval(&self) -> Value {
+        match (self.lhs.eval(), self.rhs.as_deref().map(Single::eval)) {
+            (Value::Bool(lhs), Some(Value::Bool(rhs))) => {
+                Value::Bool(self.operator.apply(lhs, rhs))
+            }
+            _ => Value::Unknown,
+        }
+    }
+}
+
+impl Eval for Neg {
+    fn eval(&self) -> Value {
+        // negative literals are negations, also as list elements, e.g. `[-1 -2]`
+        self.single
+            .as_deref()
+            .map_or(Value::Unknown, |single| single.eval().negate())
+    }
+}
+
+impl Eval for ArithOp {
+    fn eval(&self) -> Value {
+        let lhs = self.lhs.eval().as_f64();
+        let rhs = self.rhs.as_deref().and_then(|rhs| rhs.eval().as_f64());
+        lhs.zip(rhs)
+            .and_then(|(lhs, rhs)| self.operator.apply(lhs, rhs))
+            .map(Value::from_f64)
+            .unwrap_or(Value::Unknown)
+    }
+}
+
 impl Eval for Named {
     fn eval(&self) -> Value {
         match &self.expr {
@@ -44,11 +96,13 @@ impl Eval for Named {
 }
 
 impl Eval for Struct {
+    /// The own fields override the fields of the spreads, like in `semantics::analyze`.
     fn eval(&self) -> Value {
-        Value::Struct(
+        let own = Value::Struct(
             self.fields
                 .iter()
                 .flatten()
+                .filter(|field| !field.hidden)
                 .map(|field| {
                     let value = field
                         .expr
@@ -58,7 +112,19 @@ impl Eval for Struct {
                     (field.emit_name().to_string(), value)
                 })
                 .collect(),
-        )
+        );
+        if self.spreads.is_empty() {
+            return own;
+        }
+        let mut value = Value::Struct(Fields::new());
+        for spread in self.spreads.iter().map(|spread| spread.single.as_ref()) {
+            match spread.map(Single::eval) {
+                Some(spread @ Value::Struct(_)) => value.override_fields(spread),
+                _ => return Value::Unknown,
+            }
+        }
+        value.override_fields(own);
+        value
     }
 }
 
@@ -73,7 +139,7 @@ impl Eval for Primitive {
         match self {
             Self::String(PrimitiveValue { value, .. }) => Value::String(value.clone()),
             Self::Char(PrimitiveValue { value, .. }) => Value::String(value.clone()),
-            Self::Number(PrimitiveValue { value, .. }) => Value::Number(value.clone()),
+            Self::Number(PrimitiveValue { value, .. }) => Value::Number(value.to_string()),
             Self::Bool(Bool { value, .. }) => Value::Bool(*value),
             Self::Null(_) => Value::Null,
         }
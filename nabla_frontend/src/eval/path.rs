@@ -0,0 +1,55 @@
+/// A single step into a `Value`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Location of a nested value, starting at the root value.
+/// Displayed in JSON-Pointer style, e.g. `/servers/0/host`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ValuePath {
+    pub segments: Vec<PathSegment>,
+}
+
+impl ValuePath {
+    pub const fn root() -> Self {
+        Self {
+            segments: Vec::new(),
+        }
+    }
+
+    pub fn join_key(&self, key: &str) -> Self {
+        let mut path = self.clone();
+        path.segments.push(PathSegment::Key(key.to_string()));
+        path
+    }
+
+    pub fn join_index(&self, index: usize) -> Self {
+        let mut path = self.clone();
+        path.segments.push(PathSegment::Index(index));
+        path
+    }
+
+    pub const fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl std::fmt::Display for ValuePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_root() {
+            return write!(f, "/");
+        }
+        for segment in &self.segments {
+            match segment {
+                // escaping according to RFC 6901
+                PathSegment::Key(key) => {
+                    write!(f, "/{}", key.replace('~', "~0").replace('/', "~1"))?
+                }
+                PathSegment::Index(index) => write!(f, "/{}", index)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
-use std::collections::HashMap;
+use crate::{lexer, token};
+use indexmap::{map::Entry, IndexMap};
+use std::sync::Arc;
+
+/// Fields of a struct value in the order of their declaration
+pub type Fields = IndexMap<String, Value>;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Value {
@@ -6,9 +11,10 @@ pub enum Value {
     Null,
     Bool(bool),
     Number(String),
-    String(String),
-    List(Vec<Value>),
-    Struct(HashMap<String, Value>),
+    /// Shared, because it is cloned in every stage of the compilation.
+    String(Arc<str>),
+    List(Vec<Self>),
+    Struct(Fields),
 }
 
 impl Value {
@@ -22,13 +28,51 @@ impl Value {
         }
     }
 
+    /// Returns the numeric value of a `Value::Number`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Creates a number value in canonical form, e.g. `3072` instead of `3072.0` and `0` instead of `-0`.
+    /// Infinite numbers and NaN cannot be emitted and are therefore unknown.
+    pub fn from_f64(value: f64) -> Self {
+        if value.is_finite() {
+            Self::Number((value + 0.0).to_string())
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Arithmetic negation of a number, unknown for anything else.
+    /// Number literals keep their digits, e.g. `-1.50`,
+    /// so negative integers beyond the precision of `f64` are exact.
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::Number(n)
+                if !n.starts_with('+')
+                    && n.bytes()
+                        .all(|b| b.is_ascii_digit() || b"-+.eE".contains(&b))
+                    && self.as_f64().is_some_and(|n| n.is_finite() && n != 0.0) =>
+            {
+                Self::Number(
+                    n.strip_prefix('-')
+                        .map_or_else(|| format!("-{}", n), str::to_string),
+                )
+            }
+            _ => self.as_f64().map_or(Self::Unknown, |n| Self::from_f64(-n)),
+        }
+    }
+
     /// Merges the field of two struct values.
-    /// Existing fields of `self` are not overwritten by the other value.
+    /// Existing fields of `self` are not overwritten by the other value
+    /// and keep their position, new fields are appended in the order of the other value.
     /// If any of the values is not a `Value::Struct`, nothing happens.
     pub fn merge_fields(&mut self, other: Self) {
         if let (Self::Struct(this), Self::Struct(other)) = (self, other) {
             for (field, value) in other {
-                use std::collections::hash_map::Entry;
                 match this.entry(field) {
                     Entry::Vacant(entry) => {
                         entry.insert(value);
@@ -40,10 +84,64 @@ impl Value {
             }
         }
     }
+
+    /// Overrides the fields of `self` by the fields of the other struct value.
+    /// Existing fields are replaced and keep their position, new fields are appended.
+    /// Unlike [`Value::merge_fields`], nested structs are replaced as a whole.
+    /// If any of the values is not a `Value::Struct`, nothing happens.
+    pub fn override_fields(&mut self, other: Self) {
+        if let (Self::Struct(this), Self::Struct(other)) = (self, other) {
+            this.extend(other);
+        }
+    }
+
+    /// Sorts the fields of this struct value and of all nested struct values by their name.
+    pub fn sort_keys(&mut self) {
+        match self {
+            Self::List(elements) => elements.iter_mut().for_each(Self::sort_keys),
+            Self::Struct(fields) => {
+                fields.sort_keys();
+                fields.values_mut().for_each(Self::sort_keys);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Renders the value in the syntax of expressions, e.g. `{ hosts = ["a" "b"] port = 80 }`.
+/// Fields are rendered in their order and unknown values are rendered as `?`.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "?"),
+            Self::Null => write!(f, "{}", token::NULL),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Number(value) => write!(f, "{}", value),
+            Self::String(value) => write!(f, "\"{}\"", lexer::escape(value)),
+            Self::List(elements) => {
+                let elements: Vec<_> = elements.iter().map(ToString::to_string).collect();
+                write!(f, "[{}]", elements.join(" "))
+            }
+            Self::Struct(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Self::Struct(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}", name, value))
+                    .collect();
+                write!(f, "{{ {} }}", fields.join(" "))
+            }
+        }
+    }
 }
 
 impl From<String> for Value {
     fn from(value: String) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<Arc<str>> for Value {
+    fn from(value: Arc<str>) -> Self {
         Self::String(value)
     }
 }
@@ -62,7 +160,7 @@ impl From<f64> for Value {
 
 impl From<&str> for Value {
     fn from(value: &str) -> Self {
-        Self::from(value.to_string())
+        Self::String(value.into())
     }
 }
 
@@ -89,6 +187,6 @@ where
 {
     fn from(value: [(K, V); N]) -> Self {
         let map: [(String, Self); N] = value.map(|(k, v)| (k.into(), v.into()));
-        Self::Struct(HashMap::from(map))
+        Self::Struct(Fields::from(map))
     }
 }
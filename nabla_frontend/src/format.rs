@@ -0,0 +1,575 @@
+//! Canonical layout of nabla sources, as `nabla fmt` prints them.
+
+use crate::{
+    ast::*,
+    diagnostics, lexer,
+    token::{self, TextRange, Token, TokenRange, TokenType},
+};
+
+#[cfg(test)]
+mod tests;
+
+const INDENT: &str = "    ";
+
+/// Print the syntax tree of the source in the canonical layout:
+///
+/// - one global per line, separated by blank lines, only consecutive uses stay together
+/// - non-empty structs and lists, that contain structs or comments, span several lines
+///   and their content is indented by four spaces
+/// - a single space around `=`, `|` and the operators, and after `:`
+///
+/// Comments stay attached to the global, field or list element, they precede,
+/// a comment at the end of its line stays there.
+/// Comments inside of an item, e.g. after its `=`, are moved before the item.
+/// The source should not contain syntax errors, nodes the parser skipped are copied verbatim.
+pub fn format(ast: &Ast, src: &str) -> String {
+    let mut formatter = Formatter::new(src);
+    formatter.ast(ast);
+    formatter.out
+}
+
+/// Format the globals, that overlap or touch the text range, see [`format`].
+///
+/// Returns the text range from the start of the first of them to the end of the last one,
+/// including a comment at the end of its line, together with its formatted text.
+/// Comments before the first global are left as they are.
+/// Returns `None`, if no global is in the range.
+pub fn format_range(ast: &Ast, src: &str, range: &TextRange) -> Option<(TextRange, String)> {
+    let mut formatter = Formatter::new(src);
+    let globals: Vec<_> = ast
+        .globals
+        .iter()
+        .filter(|global| {
+            let text = formatter.text_range(&global.info().range);
+            text.start <= range.end && range.start <= text.end
+        })
+        .collect();
+    let first = formatter.text_range(&globals.first()?.info().range);
+    let last = &globals.last()?.info().range;
+    formatter.take_comments(globals[0].info().range.start);
+    formatter.globals(&globals);
+    let mut end = formatter.text_range(last).end;
+    if let Some(comment) = formatter.printed.checked_sub(1).and_then(|printed| {
+        let index = formatter.comments[printed];
+        (index >= last.end).then(|| &formatter.tokens[index])
+    }) {
+        // the trailing comment of the last global is part of its line
+        if let TokenType::Comment(text) = &comment.token_type {
+            end = comment.range.start + text.trim_end().len();
+        }
+    }
+    let mut formatted = formatter.out;
+    // the line break after the last global stays in the source
+    formatted.pop();
+    Some((first.start..end, formatted))
+}
+
+struct Formatter<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    /// Token indices of all comments
+    comments: Vec<usize>,
+    /// Number of comments, that are printed or skipped
+    printed: usize,
+    out: String,
+    /// Indentation level of the current line
+    depth: usize,
+    /// Start and indentation level of the items, that are being printed, innermost last
+    items: Vec<(usize, usize)>,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(src: &'a str) -> Self {
+        // the tokens locate the comments, because not all of them are kept in the syntax tree
+        let tokens = lexer::lex(src).tokens;
+        let comments = tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| matches!(token.token_type, TokenType::Comment(_)))
+            .map(|(index, _)| index)
+            .collect();
+        Self {
+            src,
+            tokens,
+            comments,
+            printed: 0,
+            out: String::new(),
+            depth: 0,
+            items: Vec::new(),
+        }
+    }
+
+    fn ast(&mut self, ast: &Ast) {
+        self.globals(&ast.globals.iter().collect::<Vec<_>>());
+        let trailing = self.take_comments(self.tokens.len());
+        if !trailing.is_empty() && !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.comment_lines(&trailing);
+    }
+
+    fn globals(&mut self, globals: &[&Global]) {
+        let mut previous: Option<&Global> = None;
+        for global in globals {
+            let range = &global.info().range;
+            match previous {
+                None => self.header(range.start),
+                Some(&Global::Use(_)) if matches!(global, Global::Use(_)) => {}
+                Some(_) => self.out.push('\n'),
+            }
+            self.item(range, |f| f.global(global));
+            previous = Some(global);
+        }
+    }
+
+    fn global(&mut self, global: &Global) {
+        match global {
+            Global::Use(u) => self.use_statement(u),
+            Global::Def(def) => {
+                self.out.push_str("def");
+                self.binding(def.name.as_ref(), def.type_expr.as_ref(), def.expr.as_ref());
+                self.alias(def.alias.as_ref());
+            }
+            Global::Let(l) => {
+                self.out.push_str("let");
+                self.binding(l.name.as_ref(), l.type_expr.as_ref(), l.expr.as_ref());
+                self.alias(l.alias.as_ref());
+            }
+            Global::Init(init) => {
+                self.expr(&init.expr);
+                self.alias(init.alias.as_ref());
+            }
+            Global::Test(test) => {
+                self.out.push_str("test");
+                if let Some(name) = &test.name {
+                    self.out.push(' ');
+                    self.out.push_str(&name.name);
+                }
+                self.out.push(' ');
+                let open = test
+                    .lcurly
+                    .as_ref()
+                    .map_or(test.info.range.end, |lcurly| lcurly.range.start);
+                let close = test
+                    .rcurly
+                    .as_ref()
+                    .map_or(test.info.range.end, |rcurly| rcurly.range.start);
+                match &test.expr {
+                    Some(expr) => self.block(open, close, ["{", "}"], |f| {
+                        f.item(&expr.info().range, |f| f.expr(expr));
+                    }),
+                    None => self.out.push_str("{}"),
+                }
+            }
+            Global::Error(info) => self.verbatim(info),
+        }
+    }
+
+    fn use_statement(&mut self, u: &Use) {
+        self.out.push_str("use");
+        if let Some(name) = &u.name {
+            self.out.push(' ');
+            self.out.push_str(&name.name);
+        }
+        if let Some(body) = &u.body {
+            self.use_body(body);
+        }
+        self.alias(u.alias.as_ref());
+    }
+
+    fn use_body(&mut self, body: &UseBody) {
+        self.out.push_str("::");
+        match &body.kind {
+            Some(UseKind::All(_)) => self.out.push('*'),
+            Some(UseKind::Single(item)) => self.use_item(item),
+            Some(UseKind::Multiple(items)) => {
+                self.out.push('{');
+                for (index, item) in items.items.iter().enumerate() {
+                    if index > 0 {
+                        self.out.push(' ');
+                    }
+                    match item {
+                        Ok(item) => self.use_item(item),
+                        Err(error) => self.verbatim(&error.info),
+                    }
+                }
+                self.out.push('}');
+            }
+            Some(UseKind::Error(info)) => self.verbatim(info),
+            None => {}
+        }
+    }
+
+    fn use_item(&mut self, item: &UseItem) {
+        self.out.push_str(&item.name.name);
+        if let Some(body) = &item.body {
+            self.use_body(body);
+        }
+        self.alias(item.alias.as_ref());
+    }
+
+    /// Name, type annotation and value of a def or let.
+    fn binding(&mut self, name: Option<&Ident>, type_expr: Option<&Expr>, expr: Option<&Expr>) {
+        if let Some(name) = name {
+            self.out.push(' ');
+            self.out.push_str(&name.name);
+        }
+        self.typed(type_expr, expr);
+    }
+
+    /// Type annotation and value, e.g. `: Number = 80`.
+    fn typed(&mut self, type_expr: Option<&Expr>, expr: Option<&Expr>) {
+        if let Some(type_expr) = type_expr {
+            self.out.push_str(": ");
+            self.expr(type_expr);
+        }
+        if let Some(expr) = expr {
+            self.out.push_str(" = ");
+            self.expr(expr);
+        }
+    }
+
+    fn alias(&mut self, alias: Option<&Alias>) {
+        let Some(alias) = alias else {
+            return;
+        };
+        self.out.push_str(" as");
+        match &alias.name {
+            Some(AliasName::Ident(ident)) => {
+                self.out.push(' ');
+                self.out.push_str(&ident.name);
+            }
+            Some(AliasName::String(value)) => {
+                self.out.push(' ');
+                self.verbatim(&value.info);
+            }
+            None => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Union(union) => {
+                self.single(&union.single);
+                for alternative in &union.alternatives {
+                    self.out.push_str(" |");
+                    if let Some(single) = &alternative.single {
+                        self.out.push(' ');
+                        self.single(single);
+                    }
+                }
+            }
+            Expr::Single(single) => self.single(single),
+            Expr::Error(info) => self.verbatim(info),
+        }
+    }
+
+    fn single(&mut self, single: &Single) {
+        match single {
+            Single::Struct(s) => self.structure(s),
+            Single::List(list) => self.list(list),
+            Single::Named(named) => {
+                self.out.push_str(&named.name.name);
+                for inner_name in &named.inner_names {
+                    self.out.push_str("::");
+                    if let Some(name) = &inner_name.name {
+                        self.out.push_str(&name.name);
+                    }
+                }
+                if let Some(length) = &named.length {
+                    self.out.push_str(&length.to_string());
+                }
+                match &named.expr {
+                    Some(StructOrList::Struct(s)) => {
+                        self.out.push(' ');
+                        self.structure(s);
+                    }
+                    Some(StructOrList::List(list)) => {
+                        self.out.push(' ');
+                        self.list(list);
+                    }
+                    None => {}
+                }
+            }
+            // literals are copied, so strings, chars and numbers keep their spelling
+            Single::Primitive(primitive) => self.verbatim(primitive.info()),
+            Single::Not(not) => {
+                self.out.push('!');
+                if let Some(single) = &not.single {
+                    self.single(single);
+                }
+            }
+            Single::Neg(neg) => {
+                self.out.push('-');
+                if let Some(single) = &neg.single {
+                    self.single(single);
+                }
+            }
+            Single::BoolOp(op) => {
+                self.single(&op.lhs);
+                self.operator(op.operator.as_str(), op.rhs.as_deref());
+            }
+            Single::ArithOp(op) => {
+                self.single(&op.lhs);
+                self.operator(op.operator.as_str(), op.rhs.as_deref());
+            }
+        }
+    }
+
+    fn operator(&mut self, operator: &str, rhs: Option<&Single>) {
+        self.out.push(' ');
+        self.out.push_str(operator);
+        if let Some(rhs) = rhs {
+            self.out.push(' ');
+            self.single(rhs);
+        }
+    }
+
+    fn structure(&mut self, s: &Struct) {
+        if s.fields.is_empty() && s.spreads.is_empty() && !self.has_comments(&s.info.range) {
+            self.out.push_str("{}");
+            return;
+        }
+        let close = s
+            .rcurly
+            .as_ref()
+            .map_or(s.info.range.end, |rcurly| rcurly.range.start);
+        // fields and spreads are printed in the order of the source
+        let mut items: Vec<_> = s
+            .fields
+            .iter()
+            .map(Ok)
+            .chain(s.spreads.iter().map(Err))
+            .collect();
+        items.sort_by_key(|item| match item {
+            Ok(Ok(field)) => field.info.range.start,
+            Ok(Err(error)) => error.info.range.start,
+            Err(spread) => spread.info.range.start,
+        });
+        self.block(s.lcurly.range.start, close, ["{", "}"], |f| {
+            for item in items {
+                match item {
+                    Ok(Ok(field)) => f.item(&field.info.range, |f| {
+                        f.out.push_str(&field.name.name);
+                        f.typed(field.type_expr.as_ref(), field.expr.as_ref());
+                        f.alias(field.alias.as_ref());
+                    }),
+                    Ok(Err(error)) => f.item(&error.info.range, |f| f.verbatim(&error.info)),
+                    Err(spread) => f.item(&spread.info.range, |f| {
+                        f.out.push_str(token::SPREAD);
+                        if let Some(single) = &spread.single {
+                            f.single(single);
+                        }
+                    }),
+                }
+            }
+        });
+    }
+
+    fn list(&mut self, list: &List) {
+        if !self.has_comments(&list.info.range) && !list.exprs.iter().any(has_block) {
+            self.out.push('[');
+            for (index, expr) in list.exprs.iter().enumerate() {
+                if index > 0 {
+                    self.out.push(' ');
+                }
+                self.expr(expr);
+            }
+            self.out.push(']');
+            return;
+        }
+        let close = list
+            .rbracket
+            .as_ref()
+            .map_or(list.info.range.end, |rbracket| rbracket.range.start);
+        self.block(list.lbracket.range.start, close, ["[", "]"], |f| {
+            for expr in &list.exprs {
+                f.item(&expr.info().range, |f| f.expr(expr));
+            }
+        });
+    }
+
+    /// Print the items between the delimiters on their own lines, indented by one level.
+    ///
+    /// Comments before the opening token belong to the enclosing item,
+    /// comments before the closing token are kept above it.
+    fn block(
+        &mut self,
+        open: usize,
+        close: usize,
+        [opening, closing]: [&str; 2],
+        items: impl FnOnce(&mut Self),
+    ) {
+        self.hoist(open);
+        self.out.push_str(opening);
+        self.out.push('\n');
+        self.depth += 1;
+        items(self);
+        let comments = self.take_comments(close);
+        self.comment_lines(&comments);
+        self.depth -= 1;
+        self.indent();
+        self.out.push_str(closing);
+    }
+
+    /// Print the item on its own line after its comments.
+    fn item(&mut self, range: &TokenRange, print: impl FnOnce(&mut Self)) {
+        let comments = self.take_comments(range.start);
+        self.comment_lines(&comments);
+        self.items.push((self.out.len(), self.depth));
+        self.indent();
+        print(self);
+        self.hoist(range.end);
+        self.items.pop();
+        if let Some(comment) = self.trailing_comment(range.end) {
+            self.out.push(' ');
+            self.out.push_str(&comment);
+        }
+        self.out.push('\n');
+    }
+
+    /// Move the comments before the token index above the current item.
+    fn hoist(&mut self, end: usize) {
+        let comments = self.take_comments(end);
+        let Some(&(start, depth)) = self.items.last() else {
+            return;
+        };
+        let lines: String = comments
+            .iter()
+            .map(|comment| format!("{}{}\n", INDENT.repeat(depth), comment))
+            .collect();
+        self.out.insert_str(start, &lines);
+    }
+
+    /// Comment after the token index on the same line, which is printed after the item.
+    fn trailing_comment(&mut self, index: usize) -> Option<String> {
+        let mut index = index;
+        if let Some(Token {
+            token_type: TokenType::Whitespace(whitespace),
+            ..
+        }) = self.tokens.get(index)
+        {
+            if whitespace.contains('\n') {
+                return None;
+            }
+            index += 1;
+        }
+        if self.comments.get(self.printed) != Some(&index) {
+            return None;
+        }
+        self.take_comments(index + 1).pop()
+    }
+
+    /// All comments, that are not yet printed, before the token index.
+    fn take_comments(&mut self, end: usize) -> Vec<String> {
+        let mut comments = Vec::new();
+        while let Some(&index) = self.comments.get(self.printed) {
+            if index >= end {
+                break;
+            }
+            if let TokenType::Comment(comment) = &self.tokens[index].token_type {
+                comments.push(comment.trim_end().to_string());
+            }
+            self.printed += 1;
+        }
+        comments
+    }
+
+    fn has_comments(&self, range: &TokenRange) -> bool {
+        self.comments.iter().any(|index| range.contains(index))
+    }
+
+    /// Print the comments before the first global up to the last empty line between them,
+    /// together with their empty lines, so a file header stays apart from the first global.
+    fn header(&mut self, start: usize) {
+        let leading: Vec<_> = self.comments[self.printed..]
+            .iter()
+            .copied()
+            .take_while(|index| *index < start)
+            .collect();
+        let Some(last) = leading
+            .iter()
+            .rposition(|index| self.blank_line_after(*index))
+        else {
+            return;
+        };
+        for index in &leading[..=last] {
+            let comments = self.take_comments(index + 1);
+            self.comment_lines(&comments);
+            if self.blank_line_after(*index) {
+                self.out.push('\n');
+            }
+        }
+    }
+
+    /// Whether an empty line follows the comment at the token index.
+    fn blank_line_after(&self, index: usize) -> bool {
+        // comments end with their newline, so any further newline leaves an empty line
+        matches!(
+            self.tokens.get(index + 1),
+            Some(Token {
+                token_type: TokenType::Whitespace(whitespace),
+                ..
+            }) if whitespace.contains('\n')
+        )
+    }
+
+    fn comment_lines(&mut self, comments: &[String]) {
+        for comment in comments {
+            self.indent();
+            self.out.push_str(comment);
+            self.out.push('\n');
+        }
+    }
+
+    /// Copy the source of the node, its comments are part of it.
+    fn verbatim(&mut self, info: &AstInfo) {
+        self.hoist(info.range.start);
+        let range = self.text_range(&info.range);
+        self.out.push_str(self.src[range].trim());
+        self.take_comments(info.range.end);
+    }
+
+    fn text_range(&self, range: &TokenRange) -> TextRange {
+        diagnostics::to_text_range(&self.tokens, range)
+    }
+
+    fn indent(&mut self) {
+        self.out.push_str(&INDENT.repeat(self.depth));
+    }
+}
+
+/// Whether the expression contains a non-empty struct, so a list of it spans several lines.
+fn has_block(expr: &Expr) -> bool {
+    match expr {
+        Expr::Union(union) => {
+            single_has_block(&union.single)
+                || union
+                    .alternatives
+                    .iter()
+                    .filter_map(|alternative| alternative.single.as_ref())
+                    .any(single_has_block)
+        }
+        Expr::Single(single) => single_has_block(single),
+        Expr::Error(_) => false,
+    }
+}
+
+fn single_has_block(single: &Single) -> bool {
+    match single {
+        Single::Struct(s) => !s.fields.is_empty() || !s.spreads.is_empty(),
+        Single::List(list) => list.exprs.iter().any(has_block),
+        Single::Named(named) => match &named.expr {
+            Some(StructOrList::Struct(s)) => !s.fields.is_empty() || !s.spreads.is_empty(),
+            Some(StructOrList::List(list)) => list.exprs.iter().any(has_block),
+            None => false,
+        },
+        Single::Primitive(_) => false,
+        Single::Not(Not { single, .. }) | Single::Neg(Neg { single, .. }) => {
+            single.as_deref().is_some_and(single_has_block)
+        }
+        Single::BoolOp(BoolOp { lhs, rhs, .. }) | Single::ArithOp(ArithOp { lhs, rhs, .. }) => {
+            single_has_block(lhs) || rhs.as_deref().is_some_and(single_has_block)
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use super::*;
+use crate::{
+    lexer::{lex, LexerResult},
+    node::{Node, ToNode},
+    parser::{parse, ParserResult},
+};
+use pretty_assertions::assert_eq;
+
+fn parse_src(src: &str) -> Ast {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    ast
+}
+
+/// Format the source and check, that the result is stable and means the same.
+fn format_src(src: &str) -> String {
+    let formatted = format(&parse_src(src), src);
+    assert_eq!(
+        formatted,
+        format(&parse_src(&formatted), &formatted),
+        "formatting is not idempotent"
+    );
+    assert_eq!(
+        shape(&parse_src(src).to_node()),
+        shape(&parse_src(&formatted).to_node()),
+        "the formatted source has a different syntax tree"
+    );
+    assert_eq!(comments(src), comments(&formatted), "comments are lost");
+    formatted
+}
+
+/// Node tree without token ranges
+fn shape(node: &Node) -> String {
+    let children: Vec<_> = node.children.iter().map(shape).collect();
+    format!("{:?}{:?}({})", node.kind, node.value, children.join(" "))
+}
+
+fn comments(src: &str) -> Vec<String> {
+    lex(src)
+        .tokens
+        .into_iter()
+        .filter_map(|token| match token.token_type {
+            TokenType::Comment(comment) => Some(comment.trim_end().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn globals() {
+    let src = "use shared::Server use shared::{port Host as H}
+def Port=Number let   port : Port=80
+test local {
+Server { port = port }
+}
+Server{port=port}as \"server\"";
+    let expected = "use shared::Server
+use shared::{port Host as H}
+
+def Port = Number
+
+let port: Port = 80
+
+test local {
+    Server {
+        port = port
+    }
+}
+
+Server {
+    port = port
+} as \"server\"
+";
+    assert_eq!(expected, format_src(src));
+}
+
+#[test]
+fn def_and_let_aliases() {
+    let src = "def Config={ a:Number }as \"config\" let db=1   as \"database\"";
+    let expected = "def Config = {
+    a: Number
+} as \"config\"
+
+let db = 1 as \"database\"
+";
+    assert_eq!(expected, format_src(src));
+}
+
+#[test]
+fn struct_spreads() {
+    let src = "let base={a=1} { x=1 ... base ...{ b = 2 } }";
+    let expected = "let base = {
+    a = 1
+}
+
+{
+    x = 1
+    ...base
+    ...{
+        b = 2
+    }
+}
+";
+    assert_eq!(expected, format_src(src));
+}
+
+#[test]
+fn structs_and_lists() {
+    let src = "def Config = { name : String(1..10) tls: { enabled: Bool } = {enabled=false}
+  ports: Number[] = [80   443] hosts = [{ a = 1 } \"b\"] empty = {} none = []
+}";
+    let expected = "def Config = {
+    name: String(1..10)
+    tls: {
+        enabled: Bool
+    } = {
+        enabled = false
+    }
+    ports: Number [] = [80 443]
+    hosts = [
+        {
+            a = 1
+        }
+        \"b\"
+    ]
+    empty = {}
+    none = []
+}
+";
+    assert_eq!(expected, format_src(src));
+}
+
+#[test]
+fn expressions() {
+    let src = "let a = !b&&c ||d
+let x = 1+2*-three /4
+def Scheme = \"http\"|\"https\" |Custom::inner::0";
+    let expected = "let a = !b && c || d
+
+let x = 1 + 2 * -three / 4
+
+def Scheme = \"http\" | \"https\" | Custom::inner::0
+";
+    assert_eq!(expected, format_src(src));
+}
+
+#[test]
+fn comments_stay_attached() {
+    let src = "// nabla: target = json
+
+// the port
+let port = 80 // default
+def Config = {
+  /// Name of the deployment
+  name: String
+  hosts: String = // inside
+    \"a\"
+  // before the curly
+}
+// trailing
+";
+    let expected = "// nabla: target = json
+
+// the port
+let port = 80 // default
+
+def Config = {
+    /// Name of the deployment
+    name: String
+    // inside
+    hosts: String = \"a\"
+    // before the curly
+}
+
+// trailing
+";
+    assert_eq!(expected, format_src(src));
+}
+
+#[test]
+fn lists_with_comments() {
+    let src = "[1 // one
+2]";
+    let expected = "[
+    1 // one
+    2
+]
+";
+    assert_eq!(expected, format_src(src));
+}
+
+#[test]
+fn formatted_sources() {
+    for src in [
+        "",
+        "// only a comment\n",
+        "def Server = {\n    host: String\n    port: Number = 80 as _\n}\n",
+    ] {
+        assert_eq!(src, format_src(src));
+    }
+}
+
+/// Apply the formatting of the globals in the text range
+fn format_range_src(src: &str, range: TextRange) -> String {
+    let (replaced, formatted) =
+        format_range(&parse_src(src), src, &range).expect("globals are in the range");
+    let mut result = src.to_string();
+    result.replace_range(replaced, &formatted);
+    result
+}
+
+#[test]
+fn range() {
+    let src = "let a=1\n// b\nlet b=2 // two\nlet c=3\n";
+    let start = src.find("let b").unwrap();
+    assert_eq!(
+        "let a=1\n// b\nlet b = 2 // two\nlet c=3\n",
+        format_range_src(src, start..start + 3)
+    );
+    // every global in the range, the cursor touches a global
+    assert_eq!(
+        "let a = 1\n\n// b\nlet b = 2 // two\nlet c=3\n",
+        format_range_src(src, 2..start)
+    );
+    assert_eq!(
+        "let a=1\n// b\nlet b=2 // two\nlet c = 3\n",
+        format_range_src(src, src.len() - 1..src.len() - 1)
+    );
+}
+
+#[test]
+fn range_without_globals() {
+    let src = "let a = 1\n\n\n// comment\n";
+    assert_eq!(None, format_range(&parse_src(src), src, &(11..11)));
+}
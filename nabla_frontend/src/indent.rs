@@ -0,0 +1,85 @@
+//! Indentation of nabla files, computed from the tokens,
+//! so it stays correct while curlies or brackets are unbalanced during an edit.
+
+use crate::token::{TextRange, Token, TokenType};
+
+#[cfg(test)]
+mod tests;
+
+/// Number of curlies and brackets, that are opened before the offset and not yet closed.
+///
+/// A closing token closes its nearest open counterpart and every token opened after it.
+/// Closing tokens without an open counterpart are ignored.
+pub fn depth(tokens: &[Token], offset: usize) -> usize {
+    let mut open = Vec::new();
+    for token in tokens.iter().take_while(|token| token.range.end <= offset) {
+        visit(&mut open, token);
+    }
+    open.len()
+}
+
+/// Expected indentation level of the line, that starts at the offset.
+///
+/// A line, which starts with a closing curly or bracket,
+/// is dedented to the level of the line of the token it closes.
+pub fn line_depth(tokens: &[Token], line_start: usize) -> usize {
+    match leading_token(tokens, line_start) {
+        Some(token) if matches!(token.token_type, TokenType::RCurly | TokenType::RBracket) => {
+            depth(tokens, token.range.end)
+        }
+        _ => depth(tokens, line_start),
+    }
+}
+
+/// Text ranges of the matching curlies and brackets, sorted by the opening token.
+/// Unmatched tokens are left out.
+pub fn bracket_pairs(tokens: &[Token]) -> Vec<(TextRange, TextRange)> {
+    let mut open = Vec::new();
+    let mut pairs = Vec::new();
+    for token in tokens {
+        if let Some(opening) = visit(&mut open, token) {
+            pairs.push((opening.range.clone(), token.range.clone()));
+        }
+    }
+    pairs.sort_by_key(|(opening, _)| opening.start);
+    pairs
+}
+
+/// Track the open curlies and brackets.
+/// Returns the opening token, which is closed by the token.
+fn visit<'a>(open: &mut Vec<&'a Token>, token: &'a Token) -> Option<&'a Token> {
+    let opening_type = match token.token_type {
+        TokenType::LCurly | TokenType::LBracket => {
+            open.push(token);
+            return None;
+        }
+        TokenType::RCurly => TokenType::LCurly,
+        TokenType::RBracket => TokenType::LBracket,
+        _ => return None,
+    };
+    let index = open
+        .iter()
+        .rposition(|opening| opening.token_type == opening_type)?;
+    open.drain(index..).next()
+}
+
+/// First token of the line, that starts at the offset, if the line is not blank.
+fn leading_token(tokens: &[Token], line_start: usize) -> Option<&Token> {
+    for token in tokens.iter().filter(|token| token.range.end > line_start) {
+        match &token.token_type {
+            TokenType::Whitespace(whitespace) => {
+                let skipped = line_start.saturating_sub(token.range.start);
+                if whitespace
+                    .get(skipped..)
+                    .is_some_and(|rest| rest.contains('\n'))
+                {
+                    return None;
+                }
+            }
+            TokenType::Bom => {}
+            TokenType::Eof => return None,
+            _ => return Some(token),
+        }
+    }
+    None
+}
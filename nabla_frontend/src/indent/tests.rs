@@ -0,0 +1,83 @@
+use super::*;
+use crate::lexer::{lex, LexerResult};
+use pretty_assertions::assert_eq;
+
+/// Expected level of every line of the source
+fn line_depths(src: &str) -> Vec<usize> {
+    let LexerResult { tokens, .. } = lex(src);
+    std::iter::once(0)
+        .chain(src.match_indices('\n').map(|(index, _)| index + 1))
+        .map(|line_start| line_depth(&tokens, line_start))
+        .collect()
+}
+
+#[test]
+fn nested_structures() {
+    let src = "def Config = {
+server: {
+hosts: [
+\"a\"
+]
+}
+}
+";
+    assert_eq!(vec![0, 1, 2, 3, 2, 1, 0, 0], line_depths(src));
+}
+
+#[test]
+fn depth_at_offset() {
+    let src = "a = { b = [1] c = {";
+    let LexerResult { tokens, .. } = lex(src);
+    assert_eq!(0, depth(&tokens, 4));
+    assert_eq!(1, depth(&tokens, 5));
+    assert_eq!(2, depth(&tokens, 11));
+    assert_eq!(1, depth(&tokens, 13));
+    assert_eq!(2, depth(&tokens, src.len()));
+}
+
+#[test]
+fn unclosed_while_editing() {
+    let src = "def Config = {
+port: Number = 80
+hosts: [
+";
+    assert_eq!(vec![0, 1, 1, 2], line_depths(src));
+}
+
+#[test]
+fn unmatched_closing() {
+    let src = "}
+a = [
+{
+]
+b = 1
+";
+    // `]` also closes the unclosed `{` inside of its list
+    assert_eq!(vec![0, 0, 1, 0, 0, 0], line_depths(src));
+}
+
+#[test]
+fn comments_and_blank_lines() {
+    let src = "def A = {
+  // comment
+
+  } ";
+    assert_eq!(vec![0, 1, 1, 0], line_depths(src));
+}
+
+#[test]
+fn pairs() {
+    let src = "{ a = [1 {}] ] }";
+    let LexerResult { tokens, .. } = lex(src);
+    assert_eq!(
+        vec![(0..1, 15..16), (6..7, 11..12), (9..10, 10..11)],
+        bracket_pairs(&tokens)
+    );
+}
+
+#[test]
+fn unclosed_pairs_are_left_out() {
+    let src = "{ a = [";
+    let LexerResult { tokens, .. } = lex(src);
+    assert_eq!(Vec::<(TextRange, TextRange)>::new(), bracket_pairs(&tokens));
+}
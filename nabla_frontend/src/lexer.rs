@@ -1,15 +1,18 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
 
-use crate::token::{Error, ErrorMessage, TextRange, ToTextRange, Token, TokenType};
+use crate::{
+    limits,
+    token::{Error, ErrorMessage, TextRange, ToTextRange, Token, TokenType, RESERVED},
+};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till},
-    character::complete::{anychar, digit1, multispace1},
-    combinator::{eof, map, opt, peek, recognize, verify},
-    multi::many0,
-    sequence::{delimited, pair, preceded, terminated, tuple},
+    bytes::complete::{tag, take_till, take_till1, take_while_m_n},
+    character::complete::{anychar, multispace1, one_of},
+    combinator::{eof, map, not, opt, peek, recognize, verify},
+    multi::{many0, many_m_n},
+    sequence::{pair, preceded, terminated, tuple},
 };
-use utility::{alpha_numeric1, expect, is_alpha_numeric};
+use utility::{alpha_numeric1, digits, expect, is_alpha_numeric};
 
 #[cfg(test)]
 mod tests;
@@ -38,14 +41,43 @@ pub struct LexerResult {
 
 /// Tokenizes the given source code.
 ///
+/// A leading byte order mark is lexed as `TokenType::Bom` without an error.
+/// At most `limits::MAX_TOKENS` tokens are lexed, see `lex_with_limit`.
+///
 /// # Panics
 ///
 /// Panics if lexing fails.
 pub fn lex(src: &str) -> LexerResult {
+    lex_with_limit(src, limits::MAX_TOKENS)
+}
+
+/// Tokenizes the given source code into at most `max_tokens` tokens.
+///
+/// If the source has more tokens, the lexer aborts early and reports the rest of the source
+/// as `ErrorMessage::TooManyTokens`. The partial tokens are still terminated by `TokenType::Eof`.
+///
+/// # Panics
+///
+/// Panics if lexing fails.
+pub fn lex_with_limit(src: &str, max_tokens: usize) -> LexerResult {
     let input = Span::new_extra(src, Rc::default());
-    let (input, (mut tokens, eof_token)) =
-        pair(many0(Token::lex), Eof::lex)(input).expect("Lexing must not fail.");
+    let (input, (bom_token, mut tokens)) =
+        pair(opt(Bom::lex), many_m_n(0, max_tokens, Token::lex))(input)
+            .expect("Lexing must not fail.");
+    let (input, eof_token) = if input.fragment().is_empty() {
+        Eof::lex(input).expect("Input must be at its end")
+    } else {
+        let range = input.to_text_range();
+        input.extra.borrow_mut().push(Error::new(
+            ErrorMessage::TooManyTokens(max_tokens),
+            range.clone(),
+        ));
+        (input, Token::new(TokenType::Eof, range.end..range.end))
+    };
     tokens.push(eof_token);
+    if let Some(bom_token) = bom_token {
+        tokens.insert(0, bom_token);
+    }
     let errors = Rc::try_unwrap(input.extra)
         .expect("There must only be one owner")
         .into_inner();
@@ -92,28 +124,47 @@ macro_rules! lex_keyword {
 impl Lexer for Token {
     fn lex(input: Span) -> IResult {
         alt((
-            lex_symbol!(TokenType::LBracket),
-            lex_symbol!(TokenType::RBracket),
-            lex_symbol!(TokenType::LCurly),
-            lex_symbol!(TokenType::RCurly),
-            lex_symbol!(TokenType::DoubleColon),
-            lex_symbol!(TokenType::Star),
-            lex_symbol!(TokenType::Pipe),
-            lex_symbol!(TokenType::Eq),
-            lex_symbol!(TokenType::Colon),
-            lex_keyword!(TokenType::Use),
-            lex_keyword!(TokenType::Def),
-            lex_keyword!(TokenType::Let),
-            lex_keyword!(TokenType::As),
-            lex_keyword!(TokenType::True),
-            lex_keyword!(TokenType::False),
-            lex_keyword!(TokenType::Null),
+            // `//` must be lexed before `/`
+            Comment::lex,
+            // lookalikes must be lexed before `=` and `:`
+            EqLookalike::lex,
+            alt((
+                lex_symbol!(TokenType::LBracket),
+                lex_symbol!(TokenType::RBracket),
+                lex_symbol!(TokenType::LCurly),
+                lex_symbol!(TokenType::RCurly),
+                lex_symbol!(TokenType::LParen),
+                lex_symbol!(TokenType::RParen),
+                // `...` must be lexed before `..`
+                lex_symbol!(TokenType::Spread),
+                lex_symbol!(TokenType::DotDot),
+                lex_symbol!(TokenType::DoubleColon),
+                lex_symbol!(TokenType::Star),
+                // `||` must be lexed before `|`
+                lex_symbol!(TokenType::Or),
+                lex_symbol!(TokenType::Pipe),
+                lex_symbol!(TokenType::And),
+                lex_symbol!(TokenType::Not),
+                lex_symbol!(TokenType::Plus),
+                lex_symbol!(TokenType::Minus),
+                lex_symbol!(TokenType::Slash),
+                lex_symbol!(TokenType::Eq),
+                lex_symbol!(TokenType::Colon),
+            )),
+            alt((
+                lex_keyword!(TokenType::Use),
+                lex_keyword!(TokenType::Def),
+                lex_keyword!(TokenType::Let),
+                lex_keyword!(TokenType::As),
+                lex_keyword!(TokenType::True),
+                lex_keyword!(TokenType::False),
+                lex_keyword!(TokenType::Null),
+            )),
             alt((
                 String::lex,
                 Char::lex,
                 Number::lex,
                 Ident::lex,
-                Comment::lex,
                 Whitespace::lex,
                 Unknown::lex,
             )),
@@ -124,12 +175,22 @@ impl Lexer for Token {
 impl Lexer for String {
     fn lex(input: Span) -> IResult {
         let start = input.location_offset();
-        let (input, s) = delimited(tag("\""), take_till(|c| c == '\"'), tag("\""))(input)?;
+        let (input, (_, raw, closing_quote)) = tuple((
+            tag("\""),
+            // an escaped quote does not close the string and a newline ends an unclosed one
+            recognize(many0(alt((
+                recognize(pair(tag("\\"), opt(verify(anychar, |c| *c != '\n')))),
+                take_till1(|c| c == '\"' || c == '\\' || c == '\n'),
+            )))),
+            expect(tag("\""), ErrorMessage::MissingClosingDoubleQuote),
+        ))(input)?;
         let end = input.location_offset();
-        Ok((
-            input,
-            Token::new(TokenType::String(s.to_string()), start..end),
-        ))
+        let value = unescape(&raw, &mut input.extra.borrow_mut());
+        let token = Token::new(TokenType::String(Arc::from(value)), start..end);
+        if let Err(quote_err) = closing_quote {
+            input.extra.borrow_mut().push(quote_err);
+        }
+        Ok((input, token))
     }
 }
 
@@ -137,19 +198,23 @@ struct Char;
 impl Lexer for Char {
     fn lex(input: Span) -> IResult {
         let start = input.location_offset();
-        let (input, (_, char, closing_quote)) = tuple((
+        let (input, (_, raw, closing_quote)) = tuple((
             tag("'"),
             alt((
-                map(preceded(tag("\\"), anychar), |c| {
-                    "\\".to_string() + &c.to_string()
-                }),
-                map(peek(tag("'")), |_| "".to_string()),
-                map(anychar, |c| c.to_string()),
+                recognize(tuple((
+                    tag("\\u{"),
+                    take_while_m_n(1, 6, |c: char| c.is_ascii_hexdigit()),
+                    tag("}"),
+                ))),
+                recognize(preceded(tag("\\"), anychar)),
+                recognize(peek(tag("'"))),
+                recognize(anychar),
             )),
             expect(tag("'"), ErrorMessage::MissingClosingSingleQuote),
         ))(input)?;
         let end = input.location_offset();
-        let token = Token::new(TokenType::Char(char), start..end);
+        let value = unescape(&raw, &mut input.extra.borrow_mut());
+        let token = Token::new(TokenType::Char(value.into()), start..end);
         if let Err(quote_err) = closing_quote {
             input.extra.borrow_mut().push(quote_err);
         }
@@ -157,30 +222,119 @@ impl Lexer for Char {
     }
 }
 
+/// Decode the escape sequences of the content of a string or char literal.
+///
+/// Supported are `\"`, `\'`, `\\`, `\n`, `\t`, `\r` and `\u{...}` with one to six hex digits.
+/// Unknown sequences are reported and kept as they are written.
+fn unescape(raw: &Span, errors: &mut Vec<Error>) -> String {
+    let mut value = String::with_capacity(raw.len());
+    let mut rest = *raw.fragment();
+    while let Some(index) = rest.find('\\') {
+        value.push_str(&rest[..index]);
+        let escape = &rest[index..];
+        let len = escape_len(escape);
+        match decode_escape(&escape[..len]) {
+            Some(c) => value.push(c),
+            None => {
+                let start = raw.location_offset() + raw.len() - escape.len();
+                errors.push(Error::new(
+                    ErrorMessage::InvalidEscape(escape[..len].to_string()),
+                    start..start + len,
+                ));
+                value.push_str(&escape[..len]);
+            }
+        }
+        rest = &escape[len..];
+    }
+    value.push_str(rest);
+    value
+}
+
+/// Byte length of the escape sequence at the start of the text.
+/// A `\u{` reaches up to its closing brace, if it is close enough for six digits.
+fn escape_len(escape: &str) -> usize {
+    match escape[1..].chars().next() {
+        None => 1,
+        Some('u') if escape[2..].starts_with('{') => escape
+            .char_indices()
+            .take(10)
+            .find(|(_, c)| *c == '}')
+            .map_or(2, |(end, _)| end + 1),
+        Some(c) => 1 + c.len_utf8(),
+    }
+}
+
+fn decode_escape(escape: &str) -> Option<char> {
+    match escape {
+        "\\\"" => Some('"'),
+        "\\'" => Some('\''),
+        "\\\\" => Some('\\'),
+        "\\n" => Some('\n'),
+        "\\t" => Some('\t'),
+        "\\r" => Some('\r'),
+        _ => {
+            let hex = escape.strip_prefix("\\u{")?.strip_suffix('}')?;
+            if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        }
+    }
+}
+
+/// Write the text as the content of a string literal, the reverse of the lexer's decoding.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 struct Number;
 impl Lexer for Number {
     fn lex(input: Span) -> IResult {
         let start = input.location_offset();
-        let (input, (pre_decimals, decimals)) = pair(
-            digit1,
+        let (input, (pre_decimals, decimals, exponent)) = tuple((
+            digits,
+            // `..` after a number starts a range, e.g. `1..10`
             opt(preceded(
-                tag("."),
-                expect(digit1, ErrorMessage::MissingDecimals),
+                terminated(tag("."), not(tag("."))),
+                expect(digits, ErrorMessage::MissingDecimals),
             )),
-        )(input)?;
+            opt(pair(
+                recognize(pair(one_of("eE"), opt(one_of("+-")))),
+                expect(digits, ErrorMessage::MissingExponent),
+            )),
+        ))(input)?;
         let end = input.location_offset();
-        let range = start..end;
-        let token = match decimals {
-            Some(Ok(decimals)) => Token::new(
-                TokenType::Number(pre_decimals.to_string() + "." + decimals.fragment()),
-                range,
-            ),
-            Some(Err(decimal_err)) => {
-                input.extra.borrow_mut().push(decimal_err);
-                Token::new(TokenType::Number(pre_decimals.to_string() + "."), range)
+        let mut number = pre_decimals.to_string();
+        let mut errors = Vec::new();
+        if let Some(decimals) = decimals {
+            number.push('.');
+            match decimals {
+                Ok(decimals) => number.push_str(decimals.fragment()),
+                Err(decimal_err) => errors.push(decimal_err),
+            }
+        }
+        if let Some((e, exponent)) = exponent {
+            number.push_str(e.fragment());
+            match exponent {
+                Ok(exponent) => number.push_str(exponent.fragment()),
+                Err(exponent_err) => errors.push(exponent_err),
             }
-            None => Token::new(TokenType::Number(pre_decimals.to_string()), range),
-        };
+        }
+        input.extra.borrow_mut().extend(errors);
+        // underscores only separate digits for the reader, e.g. `1_000_000`
+        let token = Token::new(TokenType::Number(number.replace('_', "")), start..end);
         Ok((input, token))
     }
 }
@@ -191,6 +345,12 @@ impl Lexer for Ident {
         let start = input.location_offset();
         let (input, ident) = alpha_numeric1(input)?;
         let end = input.location_offset();
+        if RESERVED.contains(ident.fragment()) {
+            input.extra.borrow_mut().push(Error::new(
+                ErrorMessage::Reserved(ident.to_string()),
+                start..end,
+            ));
+        }
         Ok((
             input,
             Token::new(TokenType::Ident(ident.to_string()), start..end),
@@ -216,8 +376,13 @@ struct Comment;
 impl Lexer for Comment {
     fn lex(input: Span) -> IResult {
         let start = input.location_offset();
-        let (input, text) = delimited(tag("//"), take_till(|c| c == '\n'), tag("\n"))(input)?;
-        let comment = "//".to_string() + text.fragment() + "\n";
+        // the last line of the file may end without a line break
+        let (input, comment) = recognize(tuple((
+            tag("//"),
+            take_till(|c| c == '\n'),
+            alt((tag("\n"), eof)),
+        )))(input)?;
+        let comment = comment.fragment().to_string();
         let end = input.location_offset();
         Ok((input, Token::new(TokenType::Comment(comment), start..end)))
     }
@@ -238,6 +403,32 @@ impl Lexer for Unknown {
     }
 }
 
+/// Operator of another language instead of `=`, e.g. `==`.
+struct EqLookalike;
+impl Lexer for EqLookalike {
+    fn lex(input: Span) -> IResult {
+        let (input, (lookalike, message)) = alt((
+            map(tag("=="), |span| (span, ErrorMessage::DoubleEq)),
+            map(tag(":="), |span| (span, ErrorMessage::ColonEq)),
+            map(tag("=>"), |span| (span, ErrorMessage::FatArrow)),
+        ))(input)?;
+        let range = lookalike.to_text_range();
+        input
+            .extra
+            .borrow_mut()
+            .push(Error::new(message, range.clone()));
+        let token = Token::new(TokenType::Unknown(lookalike.to_string()), range);
+        Ok((input, token))
+    }
+}
+
+struct Bom;
+impl Lexer for Bom {
+    fn lex(input: Span) -> IResult {
+        lex_symbol!(TokenType::Bom)(input)
+    }
+}
+
 struct Eof;
 impl Lexer for Eof {
     fn lex(input: Span) -> IResult {
@@ -1,4 +1,5 @@
 use super::*;
+use crate::diagnostics::Severity;
 use pretty_assertions::assert_eq;
 
 #[test]
@@ -11,7 +12,7 @@ fn empty() {
 
 #[test]
 fn all_in_one() {
-    let src = "@//abc\n \t\r\ntest 0123.456789'\\n'\"xyz\"true false use def let as :=|*::[]{}";
+    let src = "@//abc\n \t\r\ntest 0123.456789'\\n'\"xyz\"true false use def let as =:|*::[]{}";
     let LexerResult {tokens, errors} = lex(src);
     assert_eq!(vec![Error::new(ErrorMessage::Unknown, 0..1)], errors);
     assert_eq!(
@@ -22,8 +23,8 @@ fn all_in_one() {
             Token::new(TokenType::Ident("test".to_string()), 11..15),
             Token::new(TokenType::Whitespace(" ".to_string()), 15..16),
             Token::new(TokenType::Number("0123.456789".to_string()), 16..27),
-            Token::new(TokenType::Char("\\n".to_string()), 27..31),
-            Token::new(TokenType::String("xyz".to_string()), 31..36),
+            Token::new(TokenType::Char("\n".into()), 27..31),
+            Token::new(TokenType::String("xyz".into()), 31..36),
             Token::new(TokenType::True, 36..40),
             Token::new(TokenType::Whitespace(" ".to_string()), 40..41),
             Token::new(TokenType::False, 41..46),
@@ -36,8 +37,8 @@ fn all_in_one() {
             Token::new(TokenType::Whitespace(" ".to_string()), 58..59),
             Token::new(TokenType::As, 59..61),
             Token::new(TokenType::Whitespace(" ".to_string()), 61..62),
-            Token::new(TokenType::Colon, 62..63),
-            Token::new(TokenType::Eq, 63..64),
+            Token::new(TokenType::Eq, 62..63),
+            Token::new(TokenType::Colon, 63..64),
             Token::new(TokenType::Pipe, 64..65),
             Token::new(TokenType::Star, 65..66),
             Token::new(TokenType::DoubleColon, 66..68),
@@ -78,7 +79,7 @@ fn char_missing_single_quote() {
     );
     assert_eq!(
         vec![
-            Token::new(TokenType::Char("a".to_string()), 0..2),
+            Token::new(TokenType::Char("a".into()), 0..2),
             Token::new(TokenType::Eof, 2..2),
         ],
         tokens
@@ -92,9 +93,397 @@ fn char_escape() {
     assert_empty!(errors);
     assert_eq!(
         vec![
-            Token::new(TokenType::Char("\\'".to_string()), 0..4),
+            Token::new(TokenType::Char("'".into()), 0..4),
             Token::new(TokenType::Eof, 4..4),
         ],
         tokens
     );
 }
+
+#[test]
+fn bom() {
+    let src = "\u{feff}let";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Bom, 0..3),
+            Token::new(TokenType::Let, 3..6),
+            Token::new(TokenType::Eof, 6..6),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn bom_not_leading() {
+    let src = "a\u{feff}";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_eq!(vec![Error::new(ErrorMessage::Unknown, 1..4)], errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Ident("a".to_string()), 0..1),
+            Token::new(TokenType::Unknown("\u{feff}".to_string()), 1..4),
+            Token::new(TokenType::Eof, 4..4),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn crlf() {
+    let src = "// a\r\nlet\r\n";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Comment("// a\r\n".to_string()), 0..6),
+            Token::new(TokenType::Let, 6..9),
+            Token::new(TokenType::Whitespace("\r\n".to_string()), 9..11),
+            Token::new(TokenType::Eof, 11..11),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn bool_operators() {
+    let src = "!a||b&&c|d";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Not, 0..1),
+            Token::new(TokenType::Ident("a".to_string()), 1..2),
+            Token::new(TokenType::Or, 2..4),
+            Token::new(TokenType::Ident("b".to_string()), 4..5),
+            Token::new(TokenType::And, 5..7),
+            Token::new(TokenType::Ident("c".to_string()), 7..8),
+            Token::new(TokenType::Pipe, 8..9),
+            Token::new(TokenType::Ident("d".to_string()), 9..10),
+            Token::new(TokenType::Eof, 10..10),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn arith_operators() {
+    let src = "-a+b*2/c// comment\n";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Minus, 0..1),
+            Token::new(TokenType::Ident("a".to_string()), 1..2),
+            Token::new(TokenType::Plus, 2..3),
+            Token::new(TokenType::Ident("b".to_string()), 3..4),
+            Token::new(TokenType::Star, 4..5),
+            Token::new(TokenType::Number("2".to_string()), 5..6),
+            Token::new(TokenType::Slash, 6..7),
+            Token::new(TokenType::Ident("c".to_string()), 7..8),
+            Token::new(TokenType::Comment("// comment\n".to_string()), 8..19),
+            Token::new(TokenType::Eof, 19..19),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn comment_at_eof() {
+    let src = "a // comment";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Ident("a".to_string()), 0..1),
+            Token::new(TokenType::Whitespace(" ".to_string()), 1..2),
+            Token::new(TokenType::Comment("// comment".to_string()), 2..12),
+            Token::new(TokenType::Eof, 12..12),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn length_range() {
+    let src = "String(1..10)";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Ident("String".to_string()), 0..6),
+            Token::new(TokenType::LParen, 6..7),
+            Token::new(TokenType::Number("1".to_string()), 7..8),
+            Token::new(TokenType::DotDot, 8..10),
+            Token::new(TokenType::Number("10".to_string()), 10..12),
+            Token::new(TokenType::RParen, 12..13),
+            Token::new(TokenType::Eof, 13..13),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn spread() {
+    let src = "...base";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Spread, 0..3),
+            Token::new(TokenType::Ident("base".to_string()), 3..7),
+            Token::new(TokenType::Eof, 7..7),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn reserved() {
+    let src = "if match in priv inner";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::Reserved("if".to_string()), 0..2),
+            Error::new(ErrorMessage::Reserved("match".to_string()), 3..8),
+            Error::new(ErrorMessage::Reserved("in".to_string()), 9..11),
+            Error::new(ErrorMessage::Reserved("priv".to_string()), 12..16),
+        ],
+        errors
+    );
+    assert!(errors.iter().all(|error| error.message.severity() == Severity::Warning));
+    assert_eq!(
+        Token::new(TokenType::Ident("inner".to_string()), 17..22),
+        tokens[8]
+    );
+}
+
+#[test]
+fn token_limit() {
+    let src = "[]".repeat(1000);
+    let LexerResult { tokens, errors } = lex_with_limit(&src, 10);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::TooManyTokens(10), 10..2000)],
+        errors
+    );
+    assert_eq!(11, tokens.len());
+    assert_eq!(Token::new(TokenType::Eof, 2000..2000), tokens[10]);
+}
+
+#[test]
+fn token_limit_reached_exactly() {
+    let src = "[]".repeat(5);
+    let LexerResult { tokens, errors } = lex_with_limit(&src, 10);
+    assert_empty!(errors);
+    assert_eq!(lex(&src).tokens, tokens);
+}
+
+#[test]
+fn test_is_ident() {
+    let src = "test tests";
+    let LexerResult {tokens, errors} = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Ident("test".to_string()), 0..4),
+            Token::new(TokenType::Whitespace(" ".to_string()), 4..5),
+            Token::new(TokenType::Ident("tests".to_string()), 5..10),
+            Token::new(TokenType::Eof, 10..10),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn eq_lookalikes() {
+    let src = "== := => = :";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::DoubleEq, 0..2),
+            Error::new(ErrorMessage::ColonEq, 3..5),
+            Error::new(ErrorMessage::FatArrow, 6..8),
+        ],
+        errors
+    );
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Unknown("==".to_string()), 0..2),
+            Token::new(TokenType::Unknown(":=".to_string()), 3..5),
+            Token::new(TokenType::Unknown("=>".to_string()), 6..8),
+            Token::new(TokenType::Eq, 9..10),
+            Token::new(TokenType::Colon, 11..12),
+        ],
+        tokens
+            .into_iter()
+            .filter(|token| !token.token_type.is_trivia() && token.token_type != TokenType::Eof)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn string_escapes() {
+    let src = r#""a\"b\\c\nd\te\rf\u{1F600}" '\u{e9}' '\"'"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::String("a\"b\\c\nd\te\rf\u{1F600}".into()), 0..27),
+            Token::new(TokenType::Char("\u{e9}".into()), 28..36),
+            Token::new(TokenType::Char("\"".into()), 37..41),
+        ],
+        tokens
+            .into_iter()
+            .filter(|token| !token.token_type.is_trivia() && token.token_type != TokenType::Eof)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn invalid_escapes() {
+    let src = r#""a\qb\u{110000}\u{1234567}c" '\x'"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::InvalidEscape("\\q".to_string()), 2..4),
+            Error::new(ErrorMessage::InvalidEscape("\\u{110000}".to_string()), 5..15),
+            Error::new(ErrorMessage::InvalidEscape("\\u".to_string()), 15..17),
+            Error::new(ErrorMessage::InvalidEscape("\\x".to_string()), 30..32),
+        ],
+        errors
+    );
+    assert_eq!(
+        vec![
+            Token::new(
+                TokenType::String("a\\qb\\u{110000}\\u{1234567}c".into()),
+                0..28
+            ),
+            Token::new(TokenType::Char("\\x".into()), 29..33),
+        ],
+        tokens
+            .into_iter()
+            .filter(|token| !token.token_type.is_trivia() && token.token_type != TokenType::Eof)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn escape_round_trip() {
+    let text = "a\"b\\c\nd\te\u{1}";
+    assert_eq!("a\\\"b\\\\c\\nd\\te\\u{1}", escape(text));
+    let src = format!("\"{}\"", escape(text));
+    let LexerResult { tokens, errors } = lex(&src);
+    assert_empty!(errors);
+    assert_eq!(TokenType::String(text.into()), tokens[0].token_type);
+}
+
+#[test]
+fn unterminated_string() {
+    let src = "let a = \"abc\nlet b = 1";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::MissingClosingDoubleQuote, 12..12)],
+        errors
+    );
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Let, 0..3),
+            Token::new(TokenType::Ident("a".to_string()), 4..5),
+            Token::new(TokenType::Eq, 6..7),
+            Token::new(TokenType::String("abc".into()), 8..12),
+            Token::new(TokenType::Let, 13..16),
+            Token::new(TokenType::Ident("b".to_string()), 17..18),
+            Token::new(TokenType::Eq, 19..20),
+            Token::new(TokenType::Number("1".to_string()), 21..22),
+        ],
+        tokens
+            .into_iter()
+            .filter(|token| !token.token_type.is_trivia() && token.token_type != TokenType::Eof)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn unterminated_string_at_eof() {
+    let src = "\"ab\\";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::InvalidEscape("\\".to_string()), 3..4),
+            Error::new(ErrorMessage::MissingClosingDoubleQuote, 4..4),
+        ],
+        errors
+    );
+    assert_eq!(
+        vec![
+            Token::new(TokenType::String("ab\\".into()), 0..4),
+            Token::new(TokenType::Eof, 4..4),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn number_exponent_and_underscores() {
+    let src = "1e9 2.5e-3 1E+2 1_000_000 0.000_1";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Number("1e9".to_string()), 0..3),
+            Token::new(TokenType::Number("2.5e-3".to_string()), 4..10),
+            Token::new(TokenType::Number("1E+2".to_string()), 11..15),
+            Token::new(TokenType::Number("1000000".to_string()), 16..25),
+            Token::new(TokenType::Number("0.0001".to_string()), 26..33),
+        ],
+        tokens
+            .into_iter()
+            .filter(|token| !token.token_type.is_trivia() && token.token_type != TokenType::Eof)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn number_misplaced_underscores() {
+    let src = "1_ 1__0 1_e3 2.5_";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::MisplacedSeparator, 1..2),
+            Error::new(ErrorMessage::MisplacedSeparator, 4..6),
+            Error::new(ErrorMessage::MisplacedSeparator, 9..10),
+            Error::new(ErrorMessage::MisplacedSeparator, 16..17),
+        ],
+        errors
+    );
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Number("1".to_string()), 0..2),
+            Token::new(TokenType::Number("10".to_string()), 3..7),
+            Token::new(TokenType::Number("1e3".to_string()), 8..12),
+            Token::new(TokenType::Number("2.5".to_string()), 13..17),
+        ],
+        tokens
+            .into_iter()
+            .filter(|token| !token.token_type.is_trivia() && token.token_type != TokenType::Eof)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn number_missing_exponent() {
+    let src = "1e-";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::MissingExponent, 3..3)],
+        errors
+    );
+    assert_eq!(
+        vec![
+            Token::new(TokenType::Number("1e-".to_string()), 0..3),
+            Token::new(TokenType::Eof, 3..3),
+        ],
+        tokens
+    );
+}
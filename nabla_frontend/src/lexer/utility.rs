@@ -1,6 +1,11 @@
 use super::Span;
 use crate::token::{Error, ErrorMessage};
-use nom::{bytes::complete::take_while1, character::is_alphanumeric};
+use nom::{
+    bytes::complete::{take_while, take_while1},
+    character::{complete::digit1, is_alphanumeric},
+    combinator::recognize,
+    sequence::pair,
+};
 
 type IResult<'a, O> = nom::IResult<Span<'a>, O>;
 
@@ -10,6 +15,32 @@ pub(super) fn alpha_numeric1(input: Span) -> IResult<Span> {
     take_while1(is_alpha_numeric)(input)
 }
 
+/// Parser for digits, that may be separated by underscores, e.g. `1_000`.
+/// The first character must be a digit.
+/// Underscores, that are not a single one between two digits, are reported, e.g. in `1__0` or `1_`.
+pub(super) fn digits(input: Span) -> IResult<Span> {
+    let (input, digits) = recognize(pair(
+        digit1,
+        take_while(|c: char| c.is_ascii_digit() || c == '_'),
+    ))(input)?;
+    let mut offset = digits.location_offset();
+    let mut rest = *digits.fragment();
+    while let Some(index) = rest.find('_') {
+        let after = rest[index..].trim_start_matches('_');
+        let len = rest.len() - index - after.len();
+        if len > 1 || after.is_empty() {
+            let start = offset + index;
+            input.extra.borrow_mut().push(Error::new(
+                ErrorMessage::MisplacedSeparator,
+                start..start + len,
+            ));
+        }
+        offset += rest.len() - after.len();
+        rest = after;
+    }
+    Ok((input, digits))
+}
+
 /// Checks if provided char is alphanumeric or an underscore.
 pub(super) fn is_alpha_numeric(c: char) -> bool {
     is_alphanumeric(c as u8) || c == '_'
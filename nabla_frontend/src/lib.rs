@@ -1,3 +1,36 @@
+//! Lexer, parser and semantic analysis of nabla files.
+//!
+//! The types needed to run all stages are re-exported by the [`prelude`]:
+//!
+//! ```
+//! use nabla_frontend::{lexer, parser, prelude::*, semantics};
+//!
+//! /// Number of errors of all stages
+//! fn count(lex: &[LexError], parse: &[ParseError], semantic: &[SemanticError]) -> usize {
+//!     lex.len() + parse.len() + semantic.len()
+//! }
+//!
+//! let src = "def Config = { port: Number = 80 }\nConfig {}";
+//! let LexerResult { tokens, errors: lex_errors } = lexer::lex(src);
+//! let tokens: Vec<Token> = tokens;
+//! let ParserResult { ast, errors: parse_errors } = parser::parse(&tokens);
+//! let ast: Ast = ast;
+//! let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+//! let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+//! assert_eq!(0, count(&lex_errors, &parse_errors, &errors));
+//! assert_eq!(Some(&Value::from([("port", 80)])), inits.first());
+//! ```
+//!
+//! [`compile`] runs all stages at once and locates the errors of every stage in the source:
+//!
+//! ```
+//! use nabla_frontend::prelude::*;
+//!
+//! let result: CompileResult = nabla_frontend::compile("def Config = { port: Number = 80 }\nConfig {}");
+//! assert!(result.errors.is_empty());
+//! assert_eq!(Some(&Value::from([("port", 80)])), result.init());
+//! ```
+
 #![warn(clippy::nursery)]
 
 use ast::Ast;
@@ -15,20 +48,32 @@ macro_rules! assert_empty {
 }
 
 pub mod ast;
+pub mod compile;
+pub mod diagnostics;
 pub mod eval;
+pub mod format;
+pub mod indent;
 pub mod lexer;
+pub mod limits;
+pub mod line_index;
+pub mod node;
 pub mod parser;
+pub mod prelude;
+pub mod resolver;
 pub mod semantics;
 pub mod token;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub use compile::compile;
+pub use format::format;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct GlobalIdent {
     pub root: String,
     pub path: Vec<String>,
 }
 
 impl GlobalIdent {
-    pub fn new(name: String) -> Self {
+    pub const fn new(name: String) -> Self {
         Self {
             root: name,
             path: Vec::new(),
@@ -0,0 +1,11 @@
+//! Limits, that keep the analysis of huge inputs bounded,
+//! e.g. of a large file, that was selected by accident.
+
+/// Number of bytes in a megabyte
+pub const MEGABYTE: usize = 1024 * 1024;
+
+/// Size in bytes of the largest source, that is read by the CLI and analyzed by the language server
+pub const MAX_FILE_SIZE: usize = 32 * MEGABYTE;
+
+/// Maximum number of tokens, that are lexed before the lexer aborts
+pub const MAX_TOKENS: usize = 4_000_000;
@@ -0,0 +1,137 @@
+use crate::token::{TextRange, BOM};
+
+#[cfg(test)]
+mod tests;
+
+/// Zero-based line and column of a position in the source code.
+/// The column is given in bytes or, for a `position`, in UTF-16 code units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Maps byte offsets of the source code to lines and columns.
+///
+/// Both `\n` and `\r\n` terminate a line.
+/// Terminators and a leading byte order mark are not counted into the column,
+/// so offsets inside of them are mapped to the end or start of their line.
+///
+/// Lines are found by binary search, so mapping a position is O(log n)
+/// plus the number of non-ASCII characters in front of it on its line.
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    /// Content of each line, without its terminator
+    lines: Vec<TextRange>,
+    /// Offset and UTF-8 length of every non-ASCII character
+    wide_chars: Vec<(usize, usize)>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut start = if text.starts_with(BOM) { BOM.len() } else { 0 };
+        for (index, _) in text.match_indices('\n') {
+            let end = if text[..index].ends_with('\r') {
+                index - 1
+            } else {
+                index
+            };
+            lines.push(start.min(end)..end);
+            start = index + 1;
+        }
+        lines.push(start.min(text.len())..text.len());
+        let wide_chars = text
+            .char_indices()
+            .filter(|(_, c)| !c.is_ascii())
+            .map(|(offset, c)| (offset, c.len_utf8()))
+            .collect();
+        Self { lines, wide_chars }
+    }
+
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        // the first line always starts at or before the first byte
+        let line = self
+            .lines
+            .partition_point(|line| line.start <= offset)
+            .saturating_sub(1);
+        let range = &self.lines[line];
+        LineCol {
+            line,
+            col: offset.clamp(range.start, range.end) - range.start,
+        }
+    }
+
+    /// Byte offset of the line and column, the inverse of `line_col`.
+    ///
+    /// Positions after the end of their line or after the last line are clamped to that end.
+    pub fn offset(&self, line_col: LineCol) -> usize {
+        self.lines.get(line_col.line).map_or_else(
+            || self.lines.last().map_or(0, |line| line.end),
+            |range| (range.start + line_col.col).min(range.end),
+        )
+    }
+
+    pub fn line_col_range(&self, range: &TextRange) -> (LineCol, LineCol) {
+        (self.line_col(range.start), self.line_col(range.end))
+    }
+
+    /// Line and column of the offset, with the column in UTF-16 code units,
+    /// as the language server protocol and most editors count them.
+    pub fn position(&self, offset: usize) -> LineCol {
+        let LineCol { line, col } = self.line_col(offset);
+        let start = self.lines[line].start;
+        let surplus: usize = self
+            .wide_chars_in(&(start..start + col))
+            .iter()
+            .map(|(_, len)| utf16_surplus(*len))
+            .sum();
+        LineCol {
+            line,
+            col: col.saturating_sub(surplus),
+        }
+    }
+
+    /// Byte offset of the position, the inverse of `position`.
+    ///
+    /// Positions inside of a character are moved behind it,
+    /// positions after the end of their line or after the last line are clamped to that end.
+    pub fn position_offset(&self, position: LineCol) -> usize {
+        let Some(range) = self.lines.get(position.line) else {
+            return self.lines.last().map_or(0, |line| line.end);
+        };
+        let mut offset = range.start + position.col;
+        for (start, len) in self.wide_chars_in(range) {
+            if *start >= offset {
+                break;
+            }
+            offset = (offset + utf16_surplus(*len)).max(start + len);
+        }
+        offset.min(range.end)
+    }
+
+    /// Start and end of the range as `position`s.
+    pub fn range(&self, range: &TextRange) -> (LineCol, LineCol) {
+        (self.position(range.start), self.position(range.end))
+    }
+
+    /// Non-ASCII characters, that start inside of the range.
+    fn wide_chars_in(&self, range: &TextRange) -> &[(usize, usize)] {
+        let first = self
+            .wide_chars
+            .partition_point(|(offset, _)| *offset < range.start);
+        let last = self
+            .wide_chars
+            .partition_point(|(offset, _)| *offset < range.end);
+        &self.wide_chars[first..last]
+    }
+}
+
+/// Number of bytes, that a character of the UTF-8 length takes more than UTF-16 code units.
+const fn utf16_surplus(len_utf8: usize) -> usize {
+    if len_utf8 == 4 {
+        2
+    } else {
+        len_utf8 - 1
+    }
+}
@@ -0,0 +1,87 @@
+use super::*;
+use pretty_assertions::assert_eq;
+
+const fn pos(line: usize, col: usize) -> LineCol {
+    LineCol { line, col }
+}
+
+#[test]
+fn lf() {
+    let index = LineIndex::new("ab\ncd\n");
+    assert_eq!(pos(0, 0), index.line_col(0));
+    assert_eq!(pos(0, 2), index.line_col(2));
+    assert_eq!(pos(1, 1), index.line_col(4));
+    assert_eq!(pos(2, 0), index.line_col(6));
+}
+
+#[test]
+fn crlf() {
+    let index = LineIndex::new("ab\r\ncd\r\n");
+    assert_eq!(pos(0, 2), index.line_col(2));
+    // `\r` belongs to the line terminator
+    assert_eq!(pos(0, 2), index.line_col(3));
+    assert_eq!(pos(1, 0), index.line_col(4));
+    assert_eq!(pos(1, 2), index.line_col(7));
+    assert_eq!(pos(2, 0), index.line_col(8));
+}
+
+#[test]
+fn lone_cr() {
+    let index = LineIndex::new("a\rb");
+    assert_eq!(pos(0, 2), index.line_col(2));
+}
+
+#[test]
+fn bom() {
+    let src = "\u{feff}ab\r\ncd";
+    let index = LineIndex::new(src);
+    assert_eq!(pos(0, 0), index.line_col(0));
+    assert_eq!(pos(0, 0), index.line_col(3));
+    assert_eq!(pos(0, 2), index.line_col(5));
+    assert_eq!(pos(1, 1), index.line_col(8));
+}
+
+#[test]
+fn multi_line_range() {
+    let index = LineIndex::new("a\r\nbc\r\nd");
+    assert_eq!((pos(0, 1), pos(1, 2)), index.line_col_range(&(1..6)));
+}
+
+#[test]
+fn offset() {
+    let src = "\u{feff}ab\r\ncd";
+    let index = LineIndex::new(src);
+    for offset in [3, 4, 7, 8, 9] {
+        assert_eq!(offset, index.offset(index.line_col(offset)));
+    }
+    // clamped to the end of the line and of the text
+    assert_eq!(5, index.offset(pos(0, 10)));
+    assert_eq!(9, index.offset(pos(5, 0)));
+}
+
+#[test]
+fn emoji_position() {
+    // `é` is 2 bytes and 1 code unit, `🦀` is 4 bytes and 2 code units
+    let index = LineIndex::new("é🦀a\r\n🦀b");
+    assert_eq!(pos(0, 7), index.line_col(7));
+    assert_eq!(pos(0, 3), index.position(6));
+    assert_eq!(pos(0, 4), index.position(7));
+    assert_eq!(pos(0, 4), index.position(8));
+    assert_eq!(pos(1, 0), index.position(9));
+    assert_eq!(pos(1, 3), index.position(14));
+    assert_eq!((pos(0, 1), pos(1, 2)), index.range(&(2..13)));
+}
+
+#[test]
+fn position_offset() {
+    let src = "\u{feff}é🦀a\r\n🦀b";
+    let index = LineIndex::new(src);
+    for offset in [3, 5, 9, 10, 12, 16, 17] {
+        assert_eq!(offset, index.position_offset(index.position(offset)));
+    }
+    // inside of the surrogate pair of `🦀`
+    assert_eq!(9, index.position_offset(pos(0, 2)));
+    // clamped to the end of the line and of the text
+    assert_eq!(10, index.position_offset(pos(0, 10)));
+    assert_eq!(17, index.position_offset(pos(5, 0)));
+}
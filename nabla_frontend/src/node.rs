@@ -0,0 +1,388 @@
+use crate::{ast::*, token::TokenRange};
+
+#[cfg(test)]
+mod tests;
+
+/// Uniform tree representation of the AST.
+///
+/// Keywords and punctuation are left out,
+/// so every node is either a named construct, a value or an error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Node {
+    pub kind: NodeKind,
+    pub range: TokenRange,
+    /// Name or literal value of leaf nodes
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub value: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub children: Vec<Self>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum NodeKind {
+    Ast,
+    Use,
+    UseBody,
+    UseAll,
+    UseItems,
+    UseItem,
+    Alias,
+    Def,
+    Let,
+    Init,
+    Test,
+    Union,
+    UnionAlternative,
+    Struct,
+    StructField,
+    StructSpread,
+    List,
+    Named,
+    InnerName,
+    Length,
+    Not,
+    BoolOp,
+    Neg,
+    ArithOp,
+    Ident,
+    String,
+    Char,
+    Number,
+    Bool,
+    Null,
+    /// Tokens that were skipped during parser error recovery
+    Error,
+}
+
+impl Node {
+    fn new(kind: NodeKind, info: &AstInfo) -> Self {
+        Self {
+            kind,
+            range: info.range.clone(),
+            value: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn with_value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    fn with_child<T: ToNode>(mut self, child: Option<&T>) -> Self {
+        self.children.extend(child.map(ToNode::to_node));
+        self
+    }
+
+    fn with_children<'a, T: ToNode + 'a>(mut self, children: impl Iterator<Item = &'a T>) -> Self {
+        self.children.extend(children.map(ToNode::to_node));
+        self
+    }
+
+    fn error(info: &AstInfo) -> Self {
+        Self::new(NodeKind::Error, info)
+    }
+}
+
+/// Conversion of AST types into their node representation.
+pub trait ToNode {
+    fn to_node(&self) -> Node;
+}
+
+impl<T: ToNode> ToNode for Box<T> {
+    fn to_node(&self) -> Node {
+        self.as_ref().to_node()
+    }
+}
+
+impl<T: ToNode, E: ToNode> ToNode for Result<T, E> {
+    fn to_node(&self) -> Node {
+        match self {
+            Ok(t) => t.to_node(),
+            Err(e) => e.to_node(),
+        }
+    }
+}
+
+impl ToNode for Ast {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Ast, &self.info).with_children(self.globals.iter())
+    }
+}
+
+impl ToNode for Global {
+    fn to_node(&self) -> Node {
+        match self {
+            Self::Use(u) => u.to_node(),
+            Self::Def(def) => def.to_node(),
+            Self::Let(l) => l.to_node(),
+            Self::Init(init) => init.to_node(),
+            Self::Test(test) => test.to_node(),
+            Self::Error(info) => Node::error(info),
+        }
+    }
+}
+
+impl ToNode for Use {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Use, &self.info)
+            .with_child(self.name.as_ref())
+            .with_child(self.body.as_ref())
+            .with_child(self.alias.as_ref())
+    }
+}
+
+impl ToNode for UseBody {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::UseBody, &self.info).with_child(self.kind.as_ref())
+    }
+}
+
+impl ToNode for UseKind {
+    fn to_node(&self) -> Node {
+        match self {
+            Self::All(info) => Node::new(NodeKind::UseAll, info),
+            Self::Single(item) => item.to_node(),
+            Self::Multiple(items) => items.to_node(),
+            Self::Error(info) => Node::error(info),
+        }
+    }
+}
+
+impl ToNode for UseItems {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::UseItems, &self.info).with_children(self.items.iter())
+    }
+}
+
+impl ToNode for UseItem {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::UseItem, &self.info)
+            .with_child(Some(&self.name))
+            .with_child(self.body.as_ref())
+            .with_child(self.alias.as_ref())
+    }
+}
+
+impl ToNode for UseItemError {
+    fn to_node(&self) -> Node {
+        Node::error(&self.info)
+    }
+}
+
+impl ToNode for Alias {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Alias, &self.info).with_child(self.name.as_ref())
+    }
+}
+
+impl ToNode for AliasName {
+    fn to_node(&self) -> Node {
+        match self {
+            Self::String(value) => {
+                Node::new(NodeKind::String, &value.info).with_value(&value.value)
+            }
+            Self::Ident(ident) => ident.to_node(),
+        }
+    }
+}
+
+impl ToNode for Def {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Def, &self.info)
+            .with_child(self.name.as_ref())
+            .with_child(self.type_expr.as_ref())
+            .with_child(self.expr.as_ref())
+            .with_child(self.alias.as_ref())
+    }
+}
+
+impl ToNode for Let {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Let, &self.info)
+            .with_child(self.name.as_ref())
+            .with_child(self.type_expr.as_ref())
+            .with_child(self.expr.as_ref())
+            .with_child(self.alias.as_ref())
+    }
+}
+
+impl ToNode for Init {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Init, &self.info)
+            .with_child(Some(&self.expr))
+            .with_child(self.alias.as_ref())
+    }
+}
+
+impl ToNode for Test {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Test, &self.info)
+            .with_child(self.name.as_ref())
+            .with_child(self.expr.as_ref())
+    }
+}
+
+impl ToNode for Expr {
+    fn to_node(&self) -> Node {
+        match self {
+            Self::Union(union) => union.to_node(),
+            Self::Single(single) => single.to_node(),
+            Self::Error(info) => Node::error(info),
+        }
+    }
+}
+
+impl ToNode for Union {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Union, &self.info)
+            .with_child(Some(&self.single))
+            .with_children(self.alternatives.iter())
+    }
+}
+
+impl ToNode for UnionAlternative {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::UnionAlternative, &self.info).with_child(self.single.as_ref())
+    }
+}
+
+impl ToNode for Single {
+    fn to_node(&self) -> Node {
+        match self {
+            Self::Struct(s) => s.to_node(),
+            Self::List(list) => list.to_node(),
+            Self::Named(named) => named.to_node(),
+            Self::Primitive(primitive) => primitive.to_node(),
+            Self::Not(not) => not.to_node(),
+            Self::BoolOp(bool_op) => bool_op.to_node(),
+            Self::Neg(neg) => neg.to_node(),
+            Self::ArithOp(arith_op) => arith_op.to_node(),
+        }
+    }
+}
+
+impl ToNode for Not {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Not, &self.info).with_child(self.single.as_ref())
+    }
+}
+
+impl ToNode for BoolOp {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::BoolOp, &self.info)
+            .with_value(self.operator.as_str())
+            .with_child(Some(&self.lhs))
+            .with_child(self.rhs.as_ref())
+    }
+}
+
+impl ToNode for Neg {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Neg, &self.info).with_child(self.single.as_ref())
+    }
+}
+
+impl ToNode for ArithOp {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::ArithOp, &self.info)
+            .with_value(self.operator.as_str())
+            .with_child(Some(&self.lhs))
+            .with_child(self.rhs.as_ref())
+    }
+}
+
+impl ToNode for StructOrList {
+    fn to_node(&self) -> Node {
+        match self {
+            Self::Struct(s) => s.to_node(),
+            Self::List(list) => list.to_node(),
+        }
+    }
+}
+
+impl ToNode for Struct {
+    fn to_node(&self) -> Node {
+        let mut node = Node::new(NodeKind::Struct, &self.info)
+            .with_children(self.fields.iter())
+            .with_children(self.spreads.iter());
+        // fields and spreads are stored apart, but their nodes are in the order of the source
+        node.children.sort_by_key(|child| child.range.start);
+        node
+    }
+}
+
+impl ToNode for StructField {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::StructField, &self.info)
+            .with_child(Some(&self.name))
+            .with_child(self.type_expr.as_ref())
+            .with_child(self.expr.as_ref())
+            .with_child(self.alias.as_ref())
+    }
+}
+
+impl ToNode for StructSpread {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::StructSpread, &self.info).with_child(self.single.as_ref())
+    }
+}
+
+impl ToNode for StructFieldError {
+    fn to_node(&self) -> Node {
+        Node::error(&self.info)
+    }
+}
+
+impl ToNode for List {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::List, &self.info).with_children(self.exprs.iter())
+    }
+}
+
+impl ToNode for Named {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Named, &self.info)
+            .with_child(Some(&self.name))
+            .with_children(self.inner_names.iter())
+            .with_child(self.length.as_ref())
+            .with_child(self.expr.as_ref())
+    }
+}
+
+impl ToNode for Length {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Length, &self.info).with_value(&self.to_string())
+    }
+}
+
+impl ToNode for InnerName {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::InnerName, &self.info).with_child(self.name.as_ref())
+    }
+}
+
+impl ToNode for Ident {
+    fn to_node(&self) -> Node {
+        Node::new(NodeKind::Ident, &self.info).with_value(&self.name)
+    }
+}
+
+impl ToNode for Primitive {
+    fn to_node(&self) -> Node {
+        let kind = match self {
+            Self::String(_) => NodeKind::String,
+            Self::Char(_) => NodeKind::Char,
+            Self::Number(_) => NodeKind::Number,
+            Self::Bool(_) => NodeKind::Bool,
+            Self::Null(_) => NodeKind::Null,
+        };
+        Node::new(kind, self.info()).with_value(self.as_str())
+    }
+}
@@ -0,0 +1,120 @@
+use super::*;
+use crate::{
+    lexer::{lex, LexerResult},
+    parser::{parse, ParserResult},
+    token::ToTokenRange,
+};
+use pretty_assertions::assert_eq;
+
+fn to_node(src: &str) -> Node {
+    let LexerResult { tokens, .. } = lex(src);
+    let ParserResult { ast, .. } = parse(&tokens);
+    ast.to_node()
+}
+
+fn leaf(kind: NodeKind, range: TokenRange, value: &str) -> Node {
+    Node {
+        kind,
+        range,
+        value: Some(value.to_string()),
+        children: Vec::new(),
+    }
+}
+
+fn inner(kind: NodeKind, range: TokenRange, children: Vec<Node>) -> Node {
+    Node {
+        kind,
+        range,
+        value: None,
+        children,
+    }
+}
+
+#[test]
+fn def_with_union() {
+    assert_eq!(
+        inner(
+            NodeKind::Ast,
+            0..10,
+            vec![inner(
+                NodeKind::Def,
+                0..9,
+                vec![
+                    leaf(NodeKind::Ident, 2..3, "x"),
+                    inner(
+                        NodeKind::Union,
+                        6..9,
+                        vec![
+                            leaf(NodeKind::Number, 6..7, "1"),
+                            inner(
+                                NodeKind::UnionAlternative,
+                                7..9,
+                                vec![leaf(NodeKind::String, 8..9, "a")]
+                            ),
+                        ]
+                    ),
+                ]
+            )]
+        ),
+        to_node(r#"def x = 1|"a""#)
+    );
+}
+
+#[test]
+fn error_recovery() {
+    let node = to_node("def x = {\n    a: String,\n}");
+    let fields: Vec<_> = node.children[0].children[1]
+        .children
+        .iter()
+        .map(|field| field.kind)
+        .collect();
+    assert_eq!(vec![NodeKind::StructField, NodeKind::Error], fields);
+    let node = to_node("def x = {}=");
+    assert_eq!(NodeKind::Error, node.children[1].kind);
+}
+
+/// Assert, that the children are within the node and do not overlap.
+fn assert_nested(node: &Node) {
+    let mut start = node.range.start;
+    for child in &node.children {
+        assert!(
+            start <= child.range.start && child.range.end <= node.range.end,
+            "{:?} {:?} is not within {:?} {:?} after {}",
+            child.kind,
+            child.range,
+            node.kind,
+            node.range,
+            start
+        );
+        start = child.range.end;
+        assert_nested(child);
+    }
+}
+
+#[test]
+fn nested_ranges() {
+    let src = r#"
+use std::net::{ Host as H Port }
+use lib::*
+def Config = {
+    name: String(1..10) = "app" as "app-name"
+    mode: "a" | "b" | 'c' = "a"
+    hosts: [H] = []
+    debug: Bool = !false && true || null
+    _secret = -1 * 2 + 3 / 4 as _
+}
+let base: Config = Config { name = "x" }
+base::hosts
+Config::name { } as "root"
+def broken = { a: , }
+=
+"#;
+    let LexerResult { tokens, .. } = lex(src);
+    let ParserResult { ast, .. } = parse(&tokens);
+    let node = ast.to_node();
+    assert_eq!(ast.to_token_range(), node.range);
+    assert_nested(&node);
+    for (global, child) in ast.globals.iter().zip(&node.children) {
+        assert_eq!(global.to_token_range(), child.range);
+    }
+}
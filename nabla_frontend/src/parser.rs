@@ -5,9 +5,10 @@ use crate::{
 pub use error::*;
 use nom::{
     branch::alt,
-    combinator::{map, opt},
+    bytes::complete::take,
+    combinator::{map, not, opt, peek, success},
     multi::many0,
-    sequence::tuple,
+    sequence::{pair, preceded, tuple},
 };
 
 use self::utility::{expect, ignore_until, info};
@@ -60,7 +61,8 @@ impl Parser for Global {
             map(Use::parse, Self::Use),
             map(Def::parse, Self::Def),
             map(Let::parse, Self::Let),
-            map(Expr::parse, Self::Init),
+            map(Test::parse, Self::Test),
+            map(Init::parse, Self::Init),
             map(info(ignore_until(lookahead::global)), |(_, info)| {
                 Self::Error(info)
             }),
@@ -75,7 +77,7 @@ impl Parser for Use {
                 token::r#use,
                 expect(Ident::parse, ErrorMessage::ExpectedIdent),
                 opt(UseBody::parse),
-                opt(Alias::parse),
+                parse_use_alias,
             ))),
             |((use_kw, name, body, alias), info)| Self {
                 use_kw,
@@ -144,11 +146,7 @@ impl Parser for UseItems {
 impl Parser for UseItem {
     fn parse(input: TokenStream) -> IResult<Self> {
         map(
-            info(tuple((
-                Ident::parse,
-                opt(UseBody::parse),
-                opt(Alias::parse),
-            ))),
+            info(tuple((Ident::parse, opt(UseBody::parse), parse_use_alias))),
             |((name, body, alias), info)| Self {
                 name,
                 body: body.map(Box::new),
@@ -159,6 +157,23 @@ impl Parser for UseItem {
     }
 }
 
+/// Parses the optional alias of a use or use item, which must come last.
+/// A use body after the alias is consumed and reported.
+fn parse_use_alias(input: TokenStream) -> IResult<Option<Alias>> {
+    let (input, alias) = opt(Alias::parse)(input)?;
+    if alias.is_none() {
+        return Ok((input, alias));
+    }
+    match UseBody::parse(input.clone()) {
+        Ok((mut input, body)) => {
+            input.append_error(Error::new(ErrorMessage::AliasNotLast, body.info.range));
+            Ok((input, alias))
+        }
+        Err(nom::Err::Error(_)) => Ok((input, alias)),
+        Err(err) => Err(err),
+    }
+}
+
 impl Parser for UseItemError {
     fn parse(input: TokenStream) -> IResult<Self> {
         map(info(ignore_until(lookahead::use_item)), |(_, info)| Self {
@@ -172,18 +187,19 @@ impl Parser for Def {
         map(
             info(tuple((
                 token::def,
-                expect(Ident::parse, ErrorMessage::ExpectedIdent),
+                expect(parse_name, ErrorMessage::ExpectedIdent),
                 parse_type_annotation,
-                expect(token::eq, ErrorMessage::ExpectedEQ),
-                expect(Expr::parse, ErrorMessage::ExpectedExpr),
+                parse_def_body,
+                opt(Alias::parse),
             ))),
-            |((def_kw, name, (colon, type_expr), eq, expr), info)| Self {
+            |((def_kw, name, (colon, type_expr), (eq, expr), alias), info)| Self {
                 def_kw,
                 name,
                 colon,
                 type_expr,
                 eq,
                 expr,
+                alias,
                 info,
             },
         )(input)
@@ -195,18 +211,55 @@ impl Parser for Let {
         map(
             info(tuple((
                 token::r#let,
-                expect(Ident::parse, ErrorMessage::ExpectedIdent),
+                expect(parse_name, ErrorMessage::ExpectedIdent),
                 parse_type_annotation,
                 expect(token::eq, ErrorMessage::ExpectedEQ),
                 expect(Expr::parse, ErrorMessage::ExpectedExpr),
+                opt(Alias::parse),
             ))),
-            |((let_kw, name, (colon, type_expr), eq, expr), info)| Self {
+            |((let_kw, name, (colon, type_expr), eq, expr, alias), info)| Self {
                 let_kw,
                 name,
                 colon,
                 type_expr,
                 eq,
                 expr,
+                alias,
+                info,
+            },
+        )(input)
+    }
+}
+
+impl Parser for Init {
+    fn parse(input: TokenStream) -> IResult<Self> {
+        map(
+            info(tuple((Expr::parse, opt(Alias::parse)))),
+            |((expr, alias), info)| Self { expr, alias, info },
+        )(input)
+    }
+}
+
+impl Parser for Test {
+    fn parse(input: TokenStream) -> IResult<Self> {
+        // `test` is only a keyword, if a name and `{` follow, e.g. `{ test = 1 }` is a field
+        map(
+            preceded(
+                peek(tuple((token::test, token::ident, token::lcurly))),
+                info(tuple((
+                    token::test,
+                    Ident::parse,
+                    token::lcurly,
+                    expect(Expr::parse, ErrorMessage::ExpectedExpr),
+                    expect(token::rcurly, ErrorMessage::MissingClosingCurly),
+                ))),
+            ),
+            |((test_kw, name, lcurly, expr, rcurly), info)| Self {
+                test_kw,
+                name: Some(name),
+                lcurly: Some(lcurly),
+                expr,
+                rcurly,
                 info,
             },
         )(input)
@@ -217,13 +270,12 @@ impl Parser for Expr {
     fn parse(input: TokenStream) -> IResult<Self> {
         match info(Single::parse)(input) {
             Ok((input, (single, single_info))) => {
-                let (input, (alternatives, alt_info)) =
-                    info(many0(UnionAlternative::parse))(input)?;
-                if alternatives.is_empty() {
-                    Ok((input, Self::Single(single)))
-                } else {
+                // the alternatives are not wrapped in `info`, because its prelude
+                // would consume the trivia after a single, e.g. the comments of the next field
+                let (input, alternatives) = many0(UnionAlternative::parse)(input)?;
+                if let Some(last) = alternatives.last() {
                     let start = single_info.range.start;
-                    let end = alt_info.range.end;
+                    let end = last.info.range.end;
                     Ok((
                         input,
                         Self::Union(Union {
@@ -232,6 +284,8 @@ impl Parser for Expr {
                             info: AstInfo::new(single_info.prelude, start..end),
                         }),
                     ))
+                } else {
+                    Ok((input, Self::Single(single)))
                 }
             }
             Err(nom::Err::Error(err)) => map(info(ignore_until(lookahead::expr)), |(_, info)| {
@@ -256,12 +310,168 @@ impl Parser for UnionAlternative {
 
 impl Parser for Single {
     fn parse(input: TokenStream) -> IResult<Self> {
+        parse_or(input)
+    }
+}
+
+/// Parses `||` chains of `&&` chains, so `&&` binds stronger than `||`.
+fn parse_or(input: TokenStream) -> IResult<Single> {
+    parse_bool_op(input, parse_and, token::or, BoolOperator::Or)
+}
+
+fn parse_and(input: TokenStream) -> IResult<Single> {
+    parse_bool_op(input, parse_sum, token::and, BoolOperator::And)
+}
+
+/// Parses a left associative chain of the same boolean operator.
+fn parse_bool_op<'a>(
+    input: TokenStream<'a>,
+    operand: fn(TokenStream<'a>) -> IResult<'a, Single>,
+    operator_token: fn(TokenStream<'a>) -> IResult<'a, AstInfo>,
+    operator: BoolOperator,
+) -> IResult<'a, Single> {
+    let (input, lhs) = operand(input)?;
+    let (input, rhs_list) = many0(info(tuple((
+        operator_token,
+        expect(operand, ErrorMessage::ExpectedSingle),
+    ))))(input)?;
+    let single = rhs_list
+        .into_iter()
+        .fold(lhs, |lhs, ((operator_info, rhs), rhs_info)| {
+            let info = AstInfo::new(
+                lhs.info().prelude.clone(),
+                lhs.info().range.start..rhs_info.range.end,
+            );
+            Single::BoolOp(BoolOp {
+                lhs: Box::new(lhs),
+                operator,
+                operator_info,
+                rhs: rhs.map(Box::new),
+                info,
+            })
+        });
+    Ok((input, single))
+}
+
+/// Parses `+`/`-` chains of `*`/`/` chains, so `*` and `/` bind stronger than `+` and `-`.
+fn parse_sum(input: TokenStream) -> IResult<Single> {
+    parse_arith_op(
+        input,
+        parse_product,
         alt((
-            map(Struct::parse, Self::Struct),
-            map(List::parse, Self::List),
-            map(Named::parse, Self::Named),
-            map(Primitive::parse, Self::Primitive),
-        ))(input)
+            map(token::plus, |info| (ArithOperator::Add, info)),
+            map(token::minus, |info| (ArithOperator::Sub, info)),
+        )),
+    )
+}
+
+/// Parses a `-` after trivia, that is directly followed by its operand, e.g. ` -2`.
+///
+/// Such a `-` negates the next single instead of subtracting it,
+/// so `[1 -2]` is a list of two numbers, while `1 - 2` and `1-2` subtract.
+fn negation_after_trivia(input: TokenStream) -> IResult<AstInfo> {
+    let original_input = input.clone();
+    let after_trivia = input
+        .first_token()
+        .is_some_and(|token| token.token_type.is_trivia());
+    let (input, info) = token::minus(input)?;
+    let before_operand = input
+        .first_token()
+        .is_some_and(|token| !token.token_type.is_trivia());
+    if after_trivia && before_operand {
+        Ok((input, info))
+    } else {
+        Err(nom::Err::Error(ParserError {
+            kind: ParserErrorKind::Token,
+            input: original_input,
+        }))
+    }
+}
+
+fn parse_product(input: TokenStream) -> IResult<Single> {
+    parse_arith_op(
+        input,
+        parse_unary,
+        alt((
+            map(token::star, |info| (ArithOperator::Mul, info)),
+            map(token::slash, |info| (ArithOperator::Div, info)),
+        )),
+    )
+}
+
+/// Parses a left associative chain of arithmetic operators of the same precedence.
+fn parse_arith_op<'a>(
+    input: TokenStream<'a>,
+    operand: fn(TokenStream<'a>) -> IResult<'a, Single>,
+    operator: impl FnMut(TokenStream<'a>) -> IResult<'a, (ArithOperator, AstInfo)>,
+) -> IResult<'a, Single> {
+    let (input, lhs) = operand(input)?;
+    let (input, rhs_list) = many0(preceded(
+        not(negation_after_trivia),
+        info(tuple((
+            operator,
+            expect(operand, ErrorMessage::ExpectedSingle),
+        ))),
+    ))(input)?;
+    let single =
+        rhs_list
+            .into_iter()
+            .fold(lhs, |lhs, (((operator, operator_info), rhs), rhs_info)| {
+                let info = AstInfo::new(
+                    lhs.info().prelude.clone(),
+                    lhs.info().range.start..rhs_info.range.end,
+                );
+                Single::ArithOp(ArithOp {
+                    lhs: Box::new(lhs),
+                    operator,
+                    operator_info,
+                    rhs: rhs.map(Box::new),
+                    info,
+                })
+            });
+    Ok((input, single))
+}
+
+fn parse_unary(input: TokenStream) -> IResult<Single> {
+    alt((
+        map(Not::parse, Single::Not),
+        map(Neg::parse, Single::Neg),
+        map(Struct::parse, Single::Struct),
+        map(List::parse, Single::List),
+        map(Named::parse, Single::Named),
+        map(Primitive::parse, Single::Primitive),
+    ))(input)
+}
+
+impl Parser for Not {
+    fn parse(input: TokenStream) -> IResult<Self> {
+        map(
+            info(tuple((
+                token::not,
+                expect(parse_unary, ErrorMessage::ExpectedSingle),
+            ))),
+            |((not, single), info)| Self {
+                not,
+                single: single.map(Box::new),
+                info,
+            },
+        )(input)
+    }
+}
+
+impl Parser for Neg {
+    fn parse(input: TokenStream) -> IResult<Self> {
+        map(
+            info(tuple((
+                token::minus,
+                expect(parse_unary, ErrorMessage::ExpectedSingle),
+            ))),
+            |((minus, single), info)| Self {
+                minus,
+                single: single.map(Box::new),
+                info,
+            },
+        )(input)
     }
 }
 
@@ -271,15 +481,44 @@ impl Parser for Struct {
             info(tuple((
                 token::lcurly,
                 many0(alt((
-                    map(StructField::parse, Ok),
-                    map(StructFieldError::parse, Err),
+                    map(StructSpread::parse, |spread| Err(Box::new(spread))),
+                    map(StructField::parse, |field| Ok(Ok(field))),
+                    map(StructFieldError::parse, |error| Ok(Err(error))),
                 ))),
                 expect(token::rcurly, ErrorMessage::MissingClosingCurly),
             ))),
-            |((lcurly, fields, rcurly), info)| Self {
-                lcurly,
-                fields,
-                rcurly,
+            |((lcurly, items, rcurly), info)| {
+                // spreads are kept apart from the fields, which most analyses only look at
+                let mut fields = Vec::new();
+                let mut spreads = Vec::new();
+                for item in items {
+                    match item {
+                        Ok(field) => fields.push(field),
+                        Err(spread) => spreads.push(*spread),
+                    }
+                }
+                Self {
+                    lcurly,
+                    fields,
+                    spreads,
+                    rcurly,
+                    info,
+                }
+            },
+        )(input)
+    }
+}
+
+impl Parser for StructSpread {
+    fn parse(input: TokenStream) -> IResult<Self> {
+        map(
+            info(tuple((
+                token::spread,
+                expect(Single::parse, ErrorMessage::ExpectedSingle),
+            ))),
+            |((spread, single), info)| Self {
+                spread,
+                single,
                 info,
             },
         )(input)
@@ -290,7 +529,7 @@ impl Parser for StructField {
     fn parse(input: TokenStream) -> IResult<Self> {
         map(
             info(tuple((
-                Ident::parse,
+                parse_name,
                 parse_type_annotation,
                 map(
                     opt(tuple((
@@ -310,6 +549,7 @@ impl Parser for StructField {
                 type_expr,
                 eq,
                 expr,
+                hidden: alias.as_ref().is_some_and(Alias::is_hidden),
                 alias,
                 info,
             },
@@ -349,14 +589,16 @@ impl Parser for Named {
             info(tuple((
                 Ident::parse,
                 many0(InnerName::parse),
+                opt(map(Length::parse, Box::new)),
                 opt(alt((
                     map(Struct::parse, StructOrList::Struct),
                     map(List::parse, StructOrList::List),
                 ))),
             ))),
-            |((name, inner_names, expr), info)| Self {
+            |((name, inner_names, length, expr), info)| Self {
                 name,
                 inner_names,
+                length,
                 expr,
                 info,
             },
@@ -364,12 +606,45 @@ impl Parser for Named {
     }
 }
 
+impl Parser for Length {
+    fn parse(input: TokenStream) -> IResult<Self> {
+        map(
+            info(tuple((
+                token::lparen,
+                opt(map(token::number, PrimitiveValue::new)),
+                opt(pair(
+                    token::dot_dot,
+                    opt(map(token::number, PrimitiveValue::new)),
+                )),
+                expect(token::rparen, ErrorMessage::MissingClosingParen),
+            ))),
+            |((lparen, min, range, rparen), info)| {
+                let (dot_dot, max) = match range {
+                    Some((dot_dot, max)) => (Some(dot_dot), max),
+                    None => (None, None),
+                };
+                Self {
+                    lparen,
+                    min,
+                    dot_dot,
+                    max,
+                    rparen,
+                    info,
+                }
+            },
+        )(input)
+    }
+}
+
 impl Parser for InnerName {
     fn parse(input: TokenStream) -> IResult<Self> {
         map(
             info(tuple((
                 token::double_colon,
-                expect(Ident::parse, ErrorMessage::ExpectedIdent),
+                expect(
+                    alt((Ident::parse, parse_index)),
+                    ErrorMessage::ExpectedIdent,
+                ),
             ))),
             |((double_colon, name), info)| Self {
                 double_colon,
@@ -430,10 +705,12 @@ impl Parser for Bool {
 
 impl Parser for Prelude {
     fn parse(input: TokenStream) -> IResult<Self> {
+        // the end of the trivia is precomputed, because the prelude is parsed before every token
         let start = input.location_offset();
-        let (input, tokens) = many0(alt((token::whitespace, token::comment)))(input)?;
-        let end = input.location_offset();
-        let comments = tokens
+        let end = input.trivia_end();
+        let (input, trivia) = take(end - start)(input)?;
+        let comments = trivia
+            .tokens()
             .iter()
             .filter_map(|token| {
                 if let TokenType::Comment(comment) = &token.token_type {
@@ -466,47 +743,82 @@ fn parse_type_annotation(input: TokenStream) -> IResult<(Option<AstInfo>, Option
     )(input)
 }
 
+/// Parses the name of a def, let or struct field.
+///
+/// A keyword is reported, but still accepted as name, so the analysis can continue.
+fn parse_name(input: TokenStream) -> IResult<Ident> {
+    alt((Ident::parse, |input| {
+        let (mut input, (name, info)) = token::keyword(input)?;
+        input.append_error(Error::new(
+            ErrorMessage::KeywordAsIdent(name.clone()),
+            info.range.clone(),
+        ));
+        Ok((input, Ident { name, info }))
+    }))(input)
+}
+
+/// Parses a list index as identifier, e.g. the `0` in `hosts::0`.
+fn parse_index(input: TokenStream) -> IResult<Ident> {
+    let original_input = input.clone();
+    let (input, (number, info)) = token::number(input)?;
+    if number.chars().all(|c| c.is_ascii_digit()) {
+        Ok((input, Ident { name: number, info }))
+    } else {
+        Err(nom::Err::Error(ParserError {
+            kind: ParserErrorKind::Token,
+            input: original_input,
+        }))
+    }
+}
+
+/// Parses the optional body of a def.
+///
+/// A def without `=` and expression declares an opaque type.
+/// An expression without `=` on the same line is still reported,
+/// an expression on a later line is the next global, e.g. an init.
+fn parse_def_body(input: TokenStream) -> IResult<(Option<AstInfo>, Option<Expr>)> {
+    alt((
+        map(
+            tuple((token::eq, expect(Expr::parse, ErrorMessage::ExpectedExpr))),
+            |(eq, expr)| (Some(eq), expr),
+        ),
+        map(
+            tuple((
+                on_same_line,
+                expect(token::eq, ErrorMessage::ExpectedEQ),
+                Expr::parse,
+            )),
+            |(_, eq, expr)| (eq, Some(expr)),
+        ),
+        success((None, None)),
+    ))(input)
+}
+
+/// Succeeds without consuming input, if the leading trivia has no line break.
+fn on_same_line(input: TokenStream) -> IResult<()> {
+    let trivia = &input.tokens()[..input.trivia_end() - input.location_offset()];
+    let has_line_break = trivia.iter().any(|token| match &token.token_type {
+        TokenType::Whitespace(s) | TokenType::Comment(s) => s.contains('\n'),
+        _ => false,
+    });
+    if has_line_break {
+        Err(nom::Err::Error(ParserError {
+            kind: ParserErrorKind::Token,
+            input,
+        }))
+    } else {
+        Ok((input, ()))
+    }
+}
+
 mod token {
     use crate::{
         ast::AstInfo,
         parser::{utility::info, IResult, ParserError, ParserErrorKind},
-        token::{Token, TokenStream, TokenType},
+        token::{TokenStream, TokenType, EQ_LOOKALIKES, TEST},
     };
     use nom::bytes::complete::take;
-
-    pub fn comment(input: TokenStream) -> IResult<Token> {
-        let original_input = input.clone();
-        let (input, token_stream) = take(1usize)(input)?;
-        let token = token_stream
-            .first_token()
-            .cloned()
-            .expect("TokenStream must no be empty");
-        if matches!(token.token_type, TokenType::Comment(_)) {
-            Ok((input, token))
-        } else {
-            Err(nom::Err::Error(ParserError {
-                kind: ParserErrorKind::Token,
-                input: original_input,
-            }))
-        }
-    }
-
-    pub fn whitespace(input: TokenStream) -> IResult<Token> {
-        let original_input = input.clone();
-        let (input, token_stream) = take(1usize)(input)?;
-        let token = token_stream
-            .first_token()
-            .cloned()
-            .expect("TokenStream must no be empty");
-        if matches!(token.token_type, TokenType::Whitespace(_)) {
-            Ok((input, token))
-        } else {
-            Err(nom::Err::Error(ParserError {
-                kind: ParserErrorKind::Token,
-                input: original_input,
-            }))
-        }
-    }
+    use std::sync::Arc;
 
     macro_rules! simple_token_parser {
         ($name:ident, $token_type:pat) => {
@@ -515,7 +827,6 @@ mod token {
                 let (input, (token_stream, info)) = info(take(1usize))(input)?;
                 let token = token_stream
                     .first_token()
-                    .cloned()
                     .expect("TokenStream must no be empty");
                 if matches!(token.token_type, $token_type) {
                     Ok((input, info))
@@ -530,16 +841,15 @@ mod token {
     }
 
     macro_rules! complex_token_parser {
-        ($name:ident, $token_type:path) => {
-            pub fn $name(input: TokenStream) -> IResult<(String, AstInfo)> {
+        ($name:ident, $token_type:path, $output:ty) => {
+            pub fn $name(input: TokenStream) -> IResult<($output, AstInfo)> {
                 let original_input = input.clone();
                 let (input, (token_stream, info)) = info(take(1usize))(input)?;
                 let token = token_stream
                     .first_token()
-                    .cloned()
                     .expect("TokenStream must no be empty");
-                match token.token_type {
-                    $token_type(s) => Ok((input, (s, info))),
+                match &token.token_type {
+                    $token_type(s) => Ok((input, (s.clone(), info))),
                     _ => Err(nom::Err::Error(ParserError {
                         kind: ParserErrorKind::Token,
                         input: original_input,
@@ -554,10 +864,19 @@ mod token {
     simple_token_parser!(rbracket, TokenType::RBracket);
     simple_token_parser!(lcurly, TokenType::LCurly);
     simple_token_parser!(rcurly, TokenType::RCurly);
+    simple_token_parser!(lparen, TokenType::LParen);
+    simple_token_parser!(rparen, TokenType::RParen);
+    simple_token_parser!(dot_dot, TokenType::DotDot);
+    simple_token_parser!(spread, TokenType::Spread);
     simple_token_parser!(double_colon, TokenType::DoubleColon);
     simple_token_parser!(star, TokenType::Star);
     simple_token_parser!(pipe, TokenType::Pipe);
-    simple_token_parser!(eq, TokenType::Eq);
+    simple_token_parser!(or, TokenType::Or);
+    simple_token_parser!(and, TokenType::And);
+    simple_token_parser!(not, TokenType::Not);
+    simple_token_parser!(plus, TokenType::Plus);
+    simple_token_parser!(minus, TokenType::Minus);
+    simple_token_parser!(slash, TokenType::Slash);
     simple_token_parser!(colon, TokenType::Colon);
     simple_token_parser!(r#use, TokenType::Use);
     simple_token_parser!(def, TokenType::Def);
@@ -569,10 +888,58 @@ mod token {
     simple_token_parser!(eof, TokenType::Eof);
 
     // Tokens with string inside.
-    complex_token_parser!(string, TokenType::String);
-    complex_token_parser!(char, TokenType::Char);
-    complex_token_parser!(number, TokenType::Number);
-    complex_token_parser!(ident, TokenType::Ident);
+    complex_token_parser!(string, TokenType::String, Arc<str>);
+    complex_token_parser!(char, TokenType::Char, Arc<str>);
+    complex_token_parser!(number, TokenType::Number, String);
+    complex_token_parser!(ident, TokenType::Ident, String);
+
+    /// `=` or one of its lookalikes, e.g. `==`, which the lexer already reported.
+    pub fn eq(input: TokenStream) -> IResult<AstInfo> {
+        let original_input = input.clone();
+        let (input, (token_stream, info)) = info(take(1usize))(input)?;
+        let token = token_stream
+            .first_token()
+            .expect("TokenStream must no be empty");
+        match &token.token_type {
+            TokenType::Eq => Ok((input, info)),
+            TokenType::Unknown(s) if EQ_LOOKALIKES.contains(&&**s) => Ok((input, info)),
+            _ => Err(nom::Err::Error(ParserError {
+                kind: ParserErrorKind::Token,
+                input: original_input,
+            })),
+        }
+    }
+
+    /// Any keyword, e.g. `def`, by its name.
+    /// Parses `test`, which is lexed as an identifier, because it is only a keyword before a test block.
+    pub fn test(input: TokenStream) -> IResult<AstInfo> {
+        let original_input = input.clone();
+        let (input, (name, info)) = ident(input)?;
+        if name == TEST {
+            Ok((input, info))
+        } else {
+            Err(nom::Err::Error(ParserError {
+                kind: ParserErrorKind::Token,
+                input: original_input,
+            }))
+        }
+    }
+
+    pub fn keyword(input: TokenStream) -> IResult<(String, AstInfo)> {
+        let original_input = input.clone();
+        let (input, (token_stream, info)) = info(take(1usize))(input)?;
+        let token = token_stream
+            .first_token()
+            .expect("TokenStream must no be empty");
+        if token.token_type.is_keyword() {
+            Ok((input, (token.token_type.to_string(), info)))
+        } else {
+            Err(nom::Err::Error(ParserError {
+                kind: ParserErrorKind::Token,
+                input: original_input,
+            }))
+        }
+    }
 }
 
 mod lookahead {
@@ -613,6 +980,7 @@ mod lookahead {
         token::rcurly,
         token::rbracket,
         token::r#as,
+        token::spread,
         global,
     );
     lookahead_parser!(struct_field, token::pipe, expr,);
@@ -19,13 +19,16 @@ impl Error {
 /// Syntax error message
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ErrorMessage {
+    AliasNotLast,
     ExpectedIdent,
     ExpectedUseKind,
     ExpectedEQ,
     ExpectedExpr,
     ExpectedSingle,
+    KeywordAsIdent(String),
     MissingClosingCurly,
     MissingClosingBracket,
+    MissingClosingParen,
     TokensAfterEof,
     UnexpectedTokens,
 }
@@ -33,15 +36,20 @@ pub enum ErrorMessage {
 impl std::fmt::Display for ErrorMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let message = match self {
-            Self::ExpectedIdent => "expected ident",
-            Self::ExpectedUseKind => "expected use expression",
-            Self::ExpectedEQ => "expected `=`",
-            Self::ExpectedExpr => "expected expression",
-            Self::ExpectedSingle => "expected only a single expression",
-            Self::MissingClosingCurly => "missing closing `}`",
-            Self::MissingClosingBracket => "missing closing `]`",
-            Self::TokensAfterEof => "EOF was not the last provided token",
-            Self::UnexpectedTokens => "unexpected tokens",
+            Self::AliasNotLast => "alias must come last in a use item".to_string(),
+            Self::ExpectedIdent => "expected ident".to_string(),
+            Self::ExpectedUseKind => "expected use expression".to_string(),
+            Self::ExpectedEQ => "expected `=`".to_string(),
+            Self::ExpectedExpr => "expected expression".to_string(),
+            Self::ExpectedSingle => "expected only a single expression".to_string(),
+            Self::KeywordAsIdent(keyword) => {
+                format!("keyword `{}` cannot be used as name", keyword)
+            }
+            Self::MissingClosingCurly => "missing closing `}`".to_string(),
+            Self::MissingClosingBracket => "missing closing `]`".to_string(),
+            Self::MissingClosingParen => "missing closing `)`".to_string(),
+            Self::TokensAfterEof => "EOF was not the last provided token".to_string(),
+            Self::UnexpectedTokens => "unexpected tokens".to_string(),
         };
         write!(f, "{}", message)
     }
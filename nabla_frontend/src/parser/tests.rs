@@ -227,9 +227,11 @@ fn def_ident() {
                 expr: Some(Expr::Single(Single::Named(Named {
                     name: ident("y", 6..6, 6..7),
                     inner_names: Vec::new(),
+                    length: None,
                     expr: None,
                     info: info(6..6, 6..7),
                 }))),
+                alias: None,
                 info: info(0..0, 0..7),
             })],
             info: info(0..0, 0..8),
@@ -255,7 +257,7 @@ fn def_union() {
                 eq: Some(info(3..4, 4..5)),
                 expr: Some(Expr::Union(Union {
                     single: Single::Primitive(Primitive::String(PrimitiveValue {
-                        value: "yes".to_string(),
+                        value: "yes".into(),
                         info: info(6..6, 6..7),
                     })),
                     alternatives: vec![UnionAlternative {
@@ -264,10 +266,11 @@ fn def_union() {
                             9..10,
                             10..11
                         ))))),
-                        info: info(8..8, 8..11),
+                        info: info(7..8, 8..11),
                     }],
                     info: info(5..6, 6..11),
                 })),
+                alias: None,
                 info: info(0..0, 0..11),
             })],
             info: info(0..0, 0..12),
@@ -304,13 +307,15 @@ def Person = {
                             type_expr: Some(Expr::Single(Single::Named(Named {
                                 name: ident("string", 12..12, 12..13),
                                 inner_names: Vec::new(),
+                                length: None,
                                 expr: None,
                                 info: info(12..12, 12..13),
                             }))),
                             eq: None,
                             expr: None,
                             alias: None,
-                            info: info(8..9, 9..14),
+                            hidden: false,
+                            info: info(8..9, 9..13),
                         }),
                         Ok(StructField {
                             name: ident("age", 14..14, 14..15),
@@ -318,23 +323,27 @@ def Person = {
                             type_expr: Some(Expr::Single(Single::Named(Named {
                                 name: ident("number", 17..17, 17..18),
                                 inner_names: Vec::new(),
+                                length: None,
                                 expr: None,
                                 info: info(17..17, 17..18),
                             }))),
-                            eq: Some(info(19..19, 19..20)),
+                            eq: Some(info(18..19, 19..20)),
                             expr: Some(Expr::Single(Single::Primitive(Primitive::Number(
                                 PrimitiveValue {
-                                    value: "0".to_string(),
+                                    value: "0".into(),
                                     info: info(21..21, 21..22),
                                 }
                             )))),
                             alias: None,
-                            info: info(14..14, 14..23),
+                            hidden: false,
+                            info: info(13..14, 14..22),
                         }),
                     ],
-                    rcurly: Some(info(23..23, 23..24)),
+                    spreads: Vec::new(),
+                    rcurly: Some(info(22..23, 23..24)),
                     info: info(7..7, 7..24),
                 }))),
+                alias: None,
                 info: info(1..1, 1..24),
             })],
             info: info(0..1, 1..25),
@@ -363,12 +372,14 @@ fn def_list() {
                     exprs: vec![Expr::Single(Single::Named(Named {
                         name: ident("string", 8..8, 8..9),
                         inner_names: Vec::new(),
+                        length: None,
                         expr: None,
                         info: info(8..8, 8..9),
                     }))],
-                    rbracket: Some(info(10..10, 10..11)),
+                    rbracket: Some(info(9..10, 10..11)),
                     info: info(6..6, 6..11),
                 }))),
+                alias: None,
                 info: info(0..0, 0..11),
             })],
             info: info(0..0, 0..12),
@@ -400,6 +411,58 @@ def x = {
     insta::assert_debug_snapshot!(ast);
 }
 
+#[test]
+fn def_opaque() {
+    let src = "def x";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    assert_eq!(
+        Ast {
+            globals: vec![Global::Def(Def {
+                def_kw: info(0..0, 0..1),
+                name: Some(ident("x", 1..2, 2..3)),
+                colon: None,
+                type_expr: None,
+                eq: None,
+                expr: None,
+                alias: None,
+                info: info(0..0, 0..3),
+            })],
+            info: info(0..0, 0..4),
+        },
+        ast
+    );
+}
+
+#[test]
+fn def_missing_eq() {
+    let src = "def x y";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_eq!(vec![Error::new(ErrorMessage::ExpectedEQ, 2..2)], errors);
+    let Global::Def(def) = &ast.globals[0] else {
+        panic!("Expected def");
+    };
+    assert_eq!(None, def.eq);
+    assert!(def.expr.is_some());
+}
+
+#[test]
+fn def_opaque_before_init() {
+    let src = "def Opaque\n{ a = 1 }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let [Global::Def(def), Global::Init(_)] = ast.globals.as_slice() else {
+        panic!("Expected def and init");
+    };
+    assert_eq!(None, def.expr);
+}
+
 #[test]
 fn ignore_expr_error() {
     let src = "def x = @";
@@ -442,6 +505,44 @@ fn ignore_use_kind_error() {
     insta::assert_debug_snapshot!(ast);
 }
 
+#[test]
+fn use_alias_after_body() {
+    let src = "use a::b::{c} as x";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let Some(Global::Use(Use {
+        alias: None,
+        body: Some(UseBody {
+            kind: Some(UseKind::Single(item)),
+            ..
+        }),
+        ..
+    })) = ast.globals.first()
+    else {
+        panic!("Expected use with a single item, got {:?}", ast);
+    };
+    assert_eq!("b", item.name.name);
+    assert!(matches!(
+        &item.alias,
+        Some(Alias {
+            name: Some(AliasName::Ident(Ident { name, .. })),
+            ..
+        }) if name == "x"
+    ));
+}
+
+#[test]
+fn use_body_after_alias() {
+    let src = "use a::b as x::c";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_eq!(vec![Error::new(ErrorMessage::AliasNotLast, 9..11)], errors);
+    insta::assert_debug_snapshot!(ast);
+}
+
 #[test]
 fn ignore_use_item_error() {
     let src = "use x::{=}";
@@ -474,3 +575,378 @@ def x = {
     );
     insta::assert_debug_snapshot!(ast);
 }
+
+#[test]
+fn index_inner_name() {
+    let src = "let x = hosts::0::name";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let Global::Let(Let {
+        expr: Some(Expr::Single(Single::Named(named))),
+        ..
+    }) = &ast.globals[0]
+    else {
+        panic!("Expected let with named");
+    };
+    assert_eq!(vec!["hosts", "0", "name"], named.names());
+    assert_eq!(Some(0), named.inner_names[0].name.as_ref().unwrap().as_index());
+}
+
+#[test]
+fn decimal_inner_name() {
+    let src = "let x = hosts::0.5";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { errors, .. } = parse(&tokens);
+    assert_eq!(vec![Error::new(ErrorMessage::ExpectedIdent, 7..7)], errors);
+}
+
+#[test]
+fn init_alias() {
+    let src = r#"{ a = 1 } as "config""#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn bom_and_crlf() {
+    let src = "\u{feff}// a\r\nlet x = y\r\n";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    assert_eq!(
+        Ast {
+            globals: vec![Global::Let(Let {
+                let_kw: info(2..2, 2..3),
+                name: Some(ident("x", 3..4, 4..5)),
+                colon: None,
+                type_expr: None,
+                eq: Some(info(5..6, 6..7)),
+                expr: Some(Expr::Single(Single::Named(Named {
+                    name: ident("y", 8..8, 8..9),
+                    inner_names: Vec::new(),
+                    length: None,
+                    expr: None,
+                    info: info(8..8, 8..9),
+                }))),
+                alias: None,
+                info: info(2..2, 2..9),
+            })],
+            info: AstInfo::new(
+                Prelude {
+                    comments: vec!["// a\r\n".to_string()],
+                    range: 0..2,
+                },
+                2..11
+            ),
+        },
+        ast
+    );
+}
+
+#[test]
+fn bool_operator_precedence() {
+    let src = "let x = !a || b && c | d";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn bool_operator_missing_operand() {
+    let src = "let x = a &&";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_eq!(vec![Error::new(ErrorMessage::ExpectedSingle, 8..8)], errors);
+    let Global::Let(Let {
+        expr: Some(Expr::Single(Single::BoolOp(bool_op))),
+        ..
+    }) = &ast.globals[0]
+    else {
+        panic!("Expected bool operation");
+    };
+    assert_eq!(BoolOperator::And, bool_op.operator);
+    assert_eq!(None, bool_op.rhs);
+}
+
+#[test]
+fn arith_operator_precedence() {
+    let src = "let x = -a + b * 2 - c / d && e";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn string_length() {
+    let src = "def Code = String(2)";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn string_length_range() {
+    let src = "def Name = String(1..10)\ndef Short = String(..3)";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let lengths: Vec<_> = ast
+        .globals
+        .iter()
+        .map(|global| match global {
+            Global::Def(Def {
+                expr: Some(Expr::Single(Single::Named(Named {
+                    length: Some(length),
+                    ..
+                }))),
+                ..
+            }) => length.to_string(),
+            _ => panic!("Expected def with length"),
+        })
+        .collect();
+    assert_eq!(vec!["(1..10)", "(..3)"], lengths);
+}
+
+#[test]
+fn string_length_missing_paren() {
+    let src = "def Code = String(2";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { errors, .. } = parse(&tokens);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::MissingClosingParen, 8..8)],
+        errors
+    );
+}
+
+#[test]
+fn keyword_as_name() {
+    for keyword in ["use", "def", "let", "as", "true", "false", "null"] {
+        for src in [
+            format!("def {} = 1", keyword),
+            format!("let {} = 1", keyword),
+            format!("{{ {} = 1 }}", keyword),
+        ] {
+            let LexerResult { tokens, errors } = lex(&src);
+            assert_empty!(errors);
+            let ParserResult { ast, errors } = parse(&tokens);
+            assert_eq!(
+                vec![Error::new(
+                    ErrorMessage::KeywordAsIdent(keyword.to_string()),
+                    2..3
+                )],
+                errors,
+                "{}",
+                src
+            );
+            let name = match &ast.globals[0] {
+                Global::Def(def) => def.name.clone(),
+                Global::Let(r#let) => r#let.name.clone(),
+                Global::Init(Init {
+                    expr: Expr::Single(Single::Struct(r#struct)),
+                    ..
+                }) => r#struct.fields[0].as_ref().ok().map(|field| field.name.clone()),
+                global => panic!("unexpected global: {:?}", global),
+            };
+            assert_eq!(Some(keyword), name.as_ref().map(|name| name.name.as_str()));
+        }
+    }
+}
+
+#[test]
+fn unknown_between_comments() {
+    let src = "// first\n@\n// second\ndef Port = Number";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_eq!(
+        vec![token::Error::new(token::ErrorMessage::Unknown, 9..10)],
+        errors
+    );
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::UnexpectedTokens, 1..2)],
+        errors
+    );
+    assert!(matches!(
+        ast.globals[0],
+        Global::Init(Init {
+            expr: Expr::Error(_),
+            ..
+        })
+    ));
+    let Global::Def(def) = &ast.globals[1] else {
+        panic!("expected def: {:?}", ast.globals[1]);
+    };
+    assert_eq!(vec!["// second\n".to_string()], def.info.prelude.comments);
+}
+
+#[test]
+fn eq_lookalikes_are_parsed_as_eq() {
+    for lookalike in ["==", ":=", "=>"] {
+        let src = format!(
+            "def Config = {{ port: Number {} 80 }}\nlet a {} 1",
+            lookalike, lookalike
+        );
+        let LexerResult { tokens, errors } = lex(&src);
+        // only the lexer reports the lookalikes, one error each
+        assert_eq!(2, errors.len(), "{}", lookalike);
+        let ParserResult { ast, errors } = parse(&tokens);
+        assert_empty!(errors);
+        let LexerResult { tokens, .. } = lex(&src.replace(lookalike, "="));
+        assert_eq!(format!("{:?}", parse(&tokens).ast), format!("{:?}", ast));
+    }
+}
+
+#[test]
+fn named_test_block() {
+    let src = "test local { Config { port = 80 } }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn test_block_missing_curly() {
+    let src = "test local { 1";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::MissingClosingCurly, 6..6)],
+        errors
+    );
+    let Global::Test(test) = &ast.globals[0] else {
+        panic!("Expected test");
+    };
+    assert_eq!(
+        Some("local"),
+        test.name.as_ref().map(|name| name.name.as_str())
+    );
+    assert!(test.expr.is_some());
+}
+
+#[test]
+fn test_as_name() {
+    let src = "let test = 1\n{ test = test }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let [Global::Let(Let { name: Some(name), .. }), Global::Init(_)] = ast.globals.as_slice()
+    else {
+        panic!("Expected let and init");
+    };
+    assert_eq!("test", name.name);
+}
+
+#[test]
+fn truncated_def() {
+    let src = "def x = {";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::MissingClosingCurly, 6..6)],
+        errors
+    );
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn lone_double_colon() {
+    let src = "::";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::UnexpectedTokens, 0..1)],
+        errors
+    );
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn def_and_let_alias() {
+    let src = r#"
+def Config = { a: Number } as "config"
+let db_config = Config { a = 1 } as "database-config"
+def Opaque as "opaque"
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn struct_spread() {
+    let src = "{ a = 1 ...base ...{ b = 2 } }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    insta::assert_debug_snapshot!(ast);
+}
+
+#[test]
+fn spread_without_single() {
+    let src = "{ ... }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { errors, .. } = parse(&tokens);
+    assert_eq!(vec![Error::new(ErrorMessage::ExpectedSingle, 2..2)], errors);
+}
+
+/// Kinds of the elements of the list `x` in `let x = [...]`.
+fn list_element_kinds(src: &str) -> Vec<&'static str> {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let Some(Global::Let(Let {
+        expr: Some(Expr::Single(Single::List(list))),
+        ..
+    })) = ast.globals.first()
+    else {
+        panic!("Expected list let");
+    };
+    list.exprs
+        .iter()
+        .map(|expr| match expr {
+            Expr::Single(Single::Neg(_)) => "neg",
+            Expr::Single(Single::ArithOp(_)) => "arith",
+            Expr::Single(Single::Primitive(_)) => "primitive",
+            _ => "other",
+        })
+        .collect()
+}
+
+#[test]
+fn negative_list_elements() {
+    assert_eq!(
+        vec!["primitive", "neg", "primitive"],
+        list_element_kinds("let x = [1 -2 3]")
+    );
+    assert_eq!(vec!["neg", "neg"], list_element_kinds("let x = [-1 -2]"));
+    assert_eq!(
+        vec!["arith", "arith", "arith"],
+        list_element_kinds("let x = [1 - 2 1-2 1- 2]")
+    );
+}
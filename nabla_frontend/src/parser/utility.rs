@@ -1,12 +1,15 @@
 use crate::{
     ast::{AstInfo, Prelude},
     parser::{self, IResult, ParserError, ParserErrorKind},
-    token::TokenStream,
+    token::{TokenStream, TokenType},
 };
 use nom::bytes::complete::take;
 
 use super::Parser;
 
+#[cfg(test)]
+mod tests;
+
 pub(super) fn expect<'a, O, F>(
     mut expected: F,
     error_message: parser::ErrorMessage,
@@ -28,6 +31,12 @@ where
     }
 }
 
+/// Skip tokens until the pattern matches, which is not consumed,
+/// and report the skipped tokens as `UnexpectedTokens`.
+///
+/// The EOF token is never skipped, even if the pattern does not match it,
+/// so the enclosing parsers still find the end of the file.
+/// Fails, if the pattern matches immediately or only the EOF token is left.
 pub(super) fn ignore_until<'a, O, F>(mut pattern: F) -> impl FnMut(TokenStream<'a>) -> IResult<()>
 where
     F: FnMut(TokenStream<'a>) -> IResult<'a, O>,
@@ -40,14 +49,11 @@ where
         Err(nom::Err::Error(err)) => {
             let mut input = err.input;
             let start = input.location_offset();
-            loop {
+            while !at_eof(&input) {
                 match pattern(input) {
-                    Ok((mut input, _)) => {
-                        let end = input.location_offset();
-                        let error =
-                            parser::Error::new(parser::ErrorMessage::UnexpectedTokens, start..end);
-                        input.append_error(error);
-                        return Ok((input, ()));
+                    Ok((i, _)) => {
+                        input = i;
+                        break;
                     }
                     Err(nom::Err::Error(err)) => {
                         match take::<usize, TokenStream<'a>, ParserError<'a>>(1)(err.input) {
@@ -59,11 +65,29 @@ where
                     Err(_) => panic!("ignore_until: unexpected error"),
                 }
             }
+            let end = input.location_offset();
+            if start == end {
+                return Err(nom::Err::Error(ParserError {
+                    input,
+                    kind: ParserErrorKind::IgnoreUntil,
+                }));
+            }
+            let error = parser::Error::new(parser::ErrorMessage::UnexpectedTokens, start..end);
+            input.append_error(error);
+            Ok((input, ()))
         }
         Err(_) => panic!("ignore_until: unexpected error"),
     }
 }
 
+/// Whether only trivia and the EOF token are left.
+fn at_eof(input: &TokenStream) -> bool {
+    input
+        .tokens()
+        .get(input.trivia_end() - input.location_offset())
+        .is_none_or(|token| token.token_type == TokenType::Eof)
+}
+
 pub(super) fn info<'a, O, F>(
     mut parser: F,
 ) -> impl FnMut(TokenStream<'a>) -> IResult<'a, (O, AstInfo)>
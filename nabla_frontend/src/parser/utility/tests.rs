@@ -0,0 +1,107 @@
+use super::{expect, ignore_until, info};
+use crate::{
+    parser::{lookahead, token, Error, ErrorMessage},
+    token::{Token, TokenStream, TokenType},
+};
+use nom::sequence::tuple;
+use pretty_assertions::assert_eq;
+
+fn tokens(token_types: Vec<TokenType>) -> Vec<Token> {
+    token_types
+        .into_iter()
+        .enumerate()
+        .map(|(index, token_type)| Token::new(token_type, index..index + 1))
+        .collect()
+}
+
+fn space() -> TokenType {
+    TokenType::Whitespace(" ".to_string())
+}
+
+#[test]
+fn expect_present() {
+    let tokens = tokens(vec![TokenType::Eq, TokenType::Eof]);
+    let (input, eq) = expect(token::eq, ErrorMessage::ExpectedEQ)(tokens.as_slice().into())
+        .expect("expect must not fail");
+    assert!(eq.is_some());
+    assert_eq!(1, input.location_offset());
+    assert_empty!(input.error_buffer);
+}
+
+#[test]
+fn expect_missing() {
+    let tokens = tokens(vec![TokenType::Ident("x".to_string()), TokenType::Eof]);
+    let (input, eq) = expect(token::eq, ErrorMessage::ExpectedEQ)(tokens.as_slice().into())
+        .expect("expect must not fail");
+    assert_eq!(None, eq);
+    assert_eq!(0, input.location_offset());
+    assert_eq!(
+        vec![Error::new(ErrorMessage::ExpectedEQ, 0..0)],
+        input.error_buffer
+    );
+}
+
+#[test]
+fn ignore_until_pattern() {
+    let tokens = tokens(vec![
+        TokenType::DoubleColon,
+        TokenType::Pipe,
+        TokenType::Def,
+        TokenType::Eof,
+    ]);
+    let (input, ()) = ignore_until(lookahead::global)(tokens.as_slice().into())
+        .expect("ignore_until must skip the tokens");
+    assert_eq!(2, input.location_offset());
+    assert_eq!(
+        vec![Error::new(ErrorMessage::UnexpectedTokens, 0..2)],
+        input.error_buffer
+    );
+}
+
+#[test]
+fn ignore_until_immediate_match() {
+    let tokens = tokens(vec![TokenType::Def, TokenType::Eof]);
+    assert!(ignore_until(lookahead::global)(tokens.as_slice().into()).is_err());
+}
+
+#[test]
+fn ignore_until_stops_before_eof() {
+    let tokens = tokens(vec![TokenType::DoubleColon, space(), TokenType::Eof]);
+    let (input, ()) = ignore_until(token::def)(tokens.as_slice().into())
+        .expect("ignore_until must skip the tokens");
+    assert_eq!(1, input.location_offset());
+    assert_eq!(2, input.tokens().len());
+    assert_eq!(
+        vec![Error::new(ErrorMessage::UnexpectedTokens, 0..1)],
+        input.error_buffer
+    );
+}
+
+#[test]
+fn ignore_until_only_eof() {
+    let tokens = tokens(vec![space(), TokenType::Eof]);
+    assert!(ignore_until(token::def)(tokens.as_slice().into()).is_err());
+}
+
+#[test]
+fn info_range_after_prelude() {
+    let tokens = tokens(vec![space(), TokenType::Eq, TokenType::Eof]);
+    let (input, (_, info)) =
+        info(token::eq)(tokens.as_slice().into()).expect("info must parse the token");
+    assert_eq!(0..1, info.prelude.range);
+    assert_eq!(1..2, info.range);
+    assert_eq!(2, input.location_offset());
+}
+
+#[test]
+fn info_drops_errors_on_failure() {
+    let tokens = tokens(vec![TokenType::Ident("x".to_string()), TokenType::Eof]);
+    let input: TokenStream = tokens.as_slice().into();
+    let Err(nom::Err::Error(err)) = info(tuple((
+        expect(token::eq, ErrorMessage::ExpectedEQ),
+        token::def,
+    )))(input) else {
+        panic!("info must fail");
+    };
+    assert_empty!(err.input.error_buffer);
+}
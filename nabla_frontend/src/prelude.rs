@@ -0,0 +1,13 @@
+//! Types, that embedders need to run all stages of the analysis,
+//! with a stable import path: `use nabla_frontend::prelude::*;`.
+
+pub use crate::{
+    ast::Ast,
+    compile::{CompileError, CompileResult},
+    eval::Value,
+    lexer::LexerResult,
+    parser::{Error as ParseError, ParserResult},
+    semantics::{error::Error as SemanticError, SemanticsResult},
+    token::{Error as LexError, Token},
+    GlobalIdent, ModuleAst,
+};
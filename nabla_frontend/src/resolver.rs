@@ -0,0 +1,140 @@
+use crate::GlobalIdent;
+use std::collections::HashMap;
+#[cfg(feature = "full")]
+use std::path::Path;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Source code of a resolved module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModuleSource {
+    pub name: GlobalIdent,
+    /// Human readable origin of the source, e.g. the file path
+    pub origin: String,
+    pub src: String,
+}
+
+/// Error that occurred while resolving a module.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ResolveError {
+    #[error("module `{0}` not found")]
+    NotFound(GlobalIdent),
+    #[error("module name `{0}` is invalid")]
+    InvalidName(GlobalIdent),
+    #[error("module `{0}` is ambiguous, candidates: {}", .1.join(", "))]
+    Ambiguous(GlobalIdent, Vec<String>),
+    #[error("module `{0}` could not be read: {1}")]
+    Io(GlobalIdent, String),
+}
+
+/// Maps module identifiers to their source code.
+///
+/// Implementations decide where modules come from (file system, memory, ...).
+/// The root of the identifier names the module tree,
+/// the path segments name the module within the tree.
+/// So `root::a::b` is the module `a::b` inside of the tree `root`.
+pub trait ModuleResolver {
+    fn resolve(&self, ident: &GlobalIdent) -> Result<ModuleSource, ResolveError>;
+
+    /// All modules, that can be resolved, sorted by their identifier.
+    fn modules(&self) -> Vec<GlobalIdent>;
+}
+
+/// Checks that all path segments of the identifier can be used as path components.
+///
+/// Identifiers created by the parser always satisfy this.
+/// Empty segments, `.`/`..` and segments containing separators are rejected,
+/// so a module identifier can never escape its module tree.
+pub fn validate_module_name(ident: &GlobalIdent) -> Result<(), ResolveError> {
+    let is_valid = |segment: &String| {
+        !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    if !ident.path.is_empty() && ident.path.iter().all(is_valid) {
+        Ok(())
+    } else {
+        Err(ResolveError::InvalidName(ident.clone()))
+    }
+}
+
+/// All files with the extension below the directory as modules of the tree `root`,
+/// e.g. `<dir>/a/b.nabla` as `root::a::b`, sorted by their identifier.
+///
+/// Unreadable directories and names, that are not valid module names, are skipped.
+/// Symbolic links to directories are not followed, so the walk always terminates.
+#[cfg(feature = "full")]
+pub fn module_files(dir: &Path, extension: &str) -> Vec<GlobalIdent> {
+    let mut modules = Vec::new();
+    collect_module_files(dir, extension, &mut Vec::new(), &mut modules);
+    modules.sort();
+    modules
+}
+
+#[cfg(feature = "full")]
+fn collect_module_files(
+    dir: &Path,
+    extension: &str,
+    path: &mut Vec<String>,
+    modules: &mut Vec<GlobalIdent>,
+) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Some(file_name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            path.push(file_name);
+            collect_module_files(&entry.path(), extension, path, modules);
+            path.pop();
+        } else if let Some(name) = file_name
+            .strip_suffix(extension)
+            .and_then(|name| name.strip_suffix('.'))
+        {
+            let ident = GlobalIdent::default()
+                .extend_multiple(path.clone())
+                .extend(name.to_string());
+            if validate_module_name(&ident).is_ok() {
+                modules.push(ident);
+            }
+        }
+    }
+}
+
+/// Resolver for modules kept in memory.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryResolver {
+    modules: HashMap<GlobalIdent, String>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, ident: GlobalIdent, src: String) {
+        self.modules.insert(ident, src);
+    }
+}
+
+impl ModuleResolver for MemoryResolver {
+    fn resolve(&self, ident: &GlobalIdent) -> Result<ModuleSource, ResolveError> {
+        validate_module_name(ident)?;
+        self.modules
+            .get(ident)
+            .map(|src| ModuleSource {
+                name: ident.clone(),
+                origin: ident.to_string(),
+                src: src.clone(),
+            })
+            .ok_or_else(|| ResolveError::NotFound(ident.clone()))
+    }
+
+    fn modules(&self) -> Vec<GlobalIdent> {
+        let mut modules: Vec<_> = self.modules.keys().cloned().collect();
+        modules.sort();
+        modules
+    }
+}
@@ -0,0 +1,46 @@
+use super::*;
+use pretty_assertions::assert_eq;
+
+fn module(path: &[&str]) -> GlobalIdent {
+    GlobalIdent::default().extend_multiple(path.iter().map(|s| s.to_string()).collect())
+}
+
+#[test]
+fn memory_resolver() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert(module(&["a", "b"]), "def X = 1".to_string());
+    assert_eq!(
+        Ok(ModuleSource {
+            name: module(&["a", "b"]),
+            origin: "root::a::b".to_string(),
+            src: "def X = 1".to_string(),
+        }),
+        resolver.resolve(&module(&["a", "b"]))
+    );
+    assert_eq!(
+        Err(ResolveError::NotFound(module(&["a", "B"]))),
+        resolver.resolve(&module(&["a", "B"]))
+    );
+}
+
+#[test]
+fn invalid_module_names() {
+    for path in [&[][..], &[""], &[".."], &["a/b"], &["a\\b"], &["a", "."]] {
+        assert_eq!(
+            Err(ResolveError::InvalidName(module(path))),
+            validate_module_name(&module(path))
+        );
+    }
+    assert_eq!(Ok(()), validate_module_name(&module(&["dir", "file_1"])));
+}
+
+#[test]
+fn memory_resolver_modules() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert(module(&["b"]), String::new());
+    resolver.insert(module(&["a", "c"]), String::new());
+    assert_eq!(
+        vec![module(&["a", "c"]), module(&["b"])],
+        resolver.modules()
+    );
+}
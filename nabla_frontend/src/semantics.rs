@@ -1,17 +1,34 @@
 use crate::{
+    ast::Global,
     eval::Value,
+    resolver::ModuleResolver,
     semantics::{
         error::{Error, ErrorMessage},
         namespace::Binding,
     },
+    token::TokenRange,
     GlobalIdent, ModuleAst,
 };
-use std::collections::HashMap;
+use std::{borrow::Cow, cell::OnceCell, collections::HashMap};
 
-use self::{namespace::NamespaceResult, values::ValuesResult};
+use self::{
+    directives::FileDirectives,
+    docs::Docs,
+    modules::{Imports, Loader},
+    namespace::NamespaceResult,
+    types::TypesResult,
+    values::{EmitPaths, ValuesResult},
+};
 
-mod error;
+pub mod aliases;
+pub mod compat;
+pub mod directives;
+pub mod docs;
+pub mod error;
+pub mod graph;
+mod modules;
 pub mod namespace;
+pub mod structure;
 #[cfg(test)]
 mod tests;
 pub mod types;
@@ -21,47 +38,427 @@ pub mod values;
 pub type SymbolTable = HashMap<GlobalIdent, Value>;
 /// Valid identifiers and their global names
 type Namespace = HashMap<String, GlobalIdent>;
+/// Valid identifiers and the token range of their first declaration
+type Declarations = HashMap<String, TokenRange>;
 /// Global identifiers and their binding type
-type BindingMap = HashMap<GlobalIdent, Binding>;
+pub type BindingMap = HashMap<GlobalIdent, Binding>;
 type Errors = Vec<Error>;
 
+/// Options of the semantic analysis
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Report references to globals, that are declared later in the module.
+    /// By default, globals can be referenced regardless of their order.
+    pub strict_order: bool,
+    /// Infer the built-in type of struct fields in defs, that only have a primitive default.
+    /// E.g. `x = 0` is typed as `x: Number = 0` instead of only accepting `0`.
+    pub widen_defaults: bool,
+    /// Accept any number of inits, e.g. to emit them as a stream of documents.
+    /// By default, every init after the first is reported.
+    pub multiple_inits: bool,
+    /// Collect plain comments (`//`) of fields as their docs, not only doc comments (`///`).
+    pub plain_comments: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            strict_order: false,
+            widen_defaults: true,
+            multiple_inits: false,
+            plain_comments: false,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SemanticsResult {
+    /// Values of all inits in order, only the first is emitted
     pub inits: Vec<Value>,
+    /// Emit name of the first init, if it has a valid alias
+    pub init_name: Option<String>,
+    /// Values of the globals of the module and of all imported modules
     pub symbol_table: SymbolTable,
+    /// Default values of the defs without their hidden fields, as an empty init would emit them
+    pub defaults: SymbolTable,
+    /// Paths of the hidden and aliased fields inside of the value of every global in the symbol table
+    pub emit_paths: EmitPaths,
+    /// Whether each global of the module is a def or a let
+    pub bindings: BindingMap,
+    /// Result of the type analysis.
+    /// Its errors are moved to `errors`.
+    pub types: TypesResult,
+    /// Output preferences declared by the first comment
+    pub directives: FileDirectives,
+    /// Doc comments of the fields of the first init
+    pub docs: Docs,
     pub errors: Errors,
 }
 
 /// Analyze the semantics of the module.
 ///
-/// The analysis is split into four parts:
+/// The analysis is split into seven parts:
 ///
-/// 1. Use analysis
-/// 2. Namespace analysis
-/// 3. Type analysis
-/// 4. Value analysis
+/// 1. Structure analysis
+/// 2. Use analysis
+/// 3. Directive analysis
+/// 4. Alias analysis
+/// 5. Namespace analysis
+/// 6. Type analysis
+/// 7. Value analysis
 ///
 /// The analyses are executed in order and their errors accumulated.
 pub fn analyze(module_ast: &ModuleAst) -> SemanticsResult {
-    let (uses, mut errors) = uses::analyze(module_ast);
-    let NamespaceResult {
-        namespace,
-        bindings,
-        errors: namespace_errors,
-    } = namespace::analyze(&uses, module_ast);
-    errors.extend(namespace_errors);
-    let types_result = types::analyze(module_ast, &namespace, &bindings);
-    errors.extend(types_result.errors);
-    let ValuesResult {
-        inits,
-        symbol_table,
-        errors: value_errors,
-    } = values::analyze(module_ast);
-    errors.extend(value_errors);
-
-    SemanticsResult {
-        inits,
-        symbol_table,
-        errors,
+    analyze_with_options(module_ast, &Options::default())
+}
+
+/// Analyze the semantics of the module with the given options.
+pub fn analyze_with_options(module_ast: &ModuleAst, options: &Options) -> SemanticsResult {
+    SemanticModel::with_options(module_ast, options.clone()).into_result()
+}
+
+/// Analyze the module together with the modules of its uses, which are loaded from the resolver.
+///
+/// Every used module is lexed, parsed and analyzed once, before the modules importing it.
+/// Its defs and lets are then resolved like the globals of the importing module,
+/// so they are type checked and evaluated across modules.
+/// A use of a module, that cannot be resolved, is reported as a warning and its items are unknown.
+/// A use of a module with errors is reported as an error with their count.
+/// Modules, that import each other, are reported at the use closing the cycle.
+pub fn analyze_modules(entry: &ModuleAst, resolver: &dyn ModuleResolver) -> SemanticsResult {
+    analyze_modules_with_options(entry, resolver, &Options::default())
+}
+
+/// Analyze the module and the modules of its uses with the given options.
+pub fn analyze_modules_with_options(
+    entry: &ModuleAst,
+    resolver: &dyn ModuleResolver,
+    options: &Options,
+) -> SemanticsResult {
+    Loader::new(resolver, options).analyze(entry)
+}
+
+/// Semantic analysis of a module, whose parts are computed on demand.
+///
+/// Every part is computed at most once, together with the parts it depends on.
+/// E.g. `types` computes the uses and the namespace, but not the values,
+/// which is enough for tooling, that does not need the inits.
+///
+/// After a global is replaced by `update_global`, only the parts depending on it are recomputed.
+pub struct SemanticModel<'a> {
+    module_ast: Cow<'a, ModuleAst>,
+    options: Options,
+    /// Modules, the uses are checked against
+    resolver: Option<&'a dyn ModuleResolver>,
+    /// Analyzed modules, whose globals are resolved like the globals of the module
+    imports: Option<&'a Imports>,
+    structure: OnceCell<Errors>,
+    uses: OnceCell<(Namespace, Declarations, Errors)>,
+    directives: OnceCell<(FileDirectives, Errors)>,
+    docs: OnceCell<Docs>,
+    aliases: OnceCell<Errors>,
+    namespace: OnceCell<NamespaceResult>,
+    /// Type rules of every global
+    type_segments: OnceCell<Vec<types::Segment>>,
+    types: OnceCell<TypesResult>,
+    /// Value rules of every global
+    value_segments: OnceCell<Vec<values::Segment>>,
+    values: OnceCell<ValuesResult>,
+}
+
+impl<'a> SemanticModel<'a> {
+    pub fn new(module_ast: &'a ModuleAst) -> Self {
+        Self::with_options(module_ast, Options::default())
+    }
+
+    pub const fn with_options(module_ast: &'a ModuleAst, options: Options) -> Self {
+        Self {
+            module_ast: Cow::Borrowed(module_ast),
+            options,
+            resolver: None,
+            imports: None,
+            structure: OnceCell::new(),
+            uses: OnceCell::new(),
+            directives: OnceCell::new(),
+            docs: OnceCell::new(),
+            aliases: OnceCell::new(),
+            namespace: OnceCell::new(),
+            type_segments: OnceCell::new(),
+            types: OnceCell::new(),
+            value_segments: OnceCell::new(),
+            values: OnceCell::new(),
+        }
+    }
+
+    /// Check the uses against the modules of the resolver.
+    /// Without a resolver, every use is accepted.
+    pub const fn with_resolver(mut self, resolver: &'a dyn ModuleResolver) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Resolve the imported globals in the analyzed modules.
+    const fn with_imports(mut self, imports: &'a Imports) -> Self {
+        self.imports = Some(imports);
+        self
+    }
+
+    /// The analyzed module, including all updated globals.
+    pub fn module_ast(&self) -> &ModuleAst {
+        &self.module_ast
+    }
+
+    fn structure_errors(&self) -> &Errors {
+        self.structure.get_or_init(|| {
+            let mut errors = structure::analyze(&self.module_ast.ast);
+            if self.options.multiple_inits {
+                errors.retain(|error| error.message != ErrorMessage::MultipleInits);
+            }
+            errors
+        })
+    }
+
+    /// Imported identifiers and the token range of their declaration.
+    pub fn uses(&self) -> (&Namespace, &Declarations) {
+        let (uses, declarations, _) = self
+            .uses
+            .get_or_init(|| uses::analyze(&self.module_ast, self.resolver));
+        (uses, declarations)
+    }
+
+    pub fn directives(&self) -> &FileDirectives {
+        &self
+            .directives
+            .get_or_init(|| directives::analyze(&self.module_ast.ast))
+            .0
+    }
+
+    /// Doc comments of the fields of the first init by their path in the emitted value.
+    /// With the option `plain_comments`, their plain comments are included.
+    pub fn docs(&self) -> &Docs {
+        self.docs.get_or_init(|| {
+            docs::analyze_with_comments(&self.module_ast.ast, self.options.plain_comments)
+        })
+    }
+
+    fn alias_errors(&self) -> &Errors {
+        self.aliases
+            .get_or_init(|| aliases::analyze(&self.module_ast))
+    }
+
+    pub fn namespace(&self) -> &NamespaceResult {
+        self.namespace.get_or_init(|| {
+            let (uses, declarations) = self.uses();
+            namespace::analyze(uses, declarations, &self.module_ast, &self.options)
+        })
+    }
+
+    /// Bindings of the globals of the module and of the imported modules.
+    fn type_bindings(&self) -> Cow<'_, BindingMap> {
+        let bindings = &self.namespace().bindings;
+        self.imports.map_or(Cow::Borrowed(bindings), |imports| {
+            let mut imported = imports.bindings();
+            imported.extend(bindings.clone());
+            Cow::Owned(imported)
+        })
+    }
+
+    fn type_segments(&self) -> &[types::Segment] {
+        self.type_segments.get_or_init(|| {
+            let namespace = &self.namespace().namespace;
+            let bindings = self.type_bindings();
+            self.module_ast
+                .ast
+                .globals
+                .iter()
+                .map(|global| {
+                    types::analyze_global(
+                        global,
+                        &self.module_ast.name,
+                        (namespace, &bindings),
+                        self.options.widen_defaults,
+                    )
+                })
+                .collect()
+        })
+    }
+
+    pub fn types(&self) -> &TypesResult {
+        self.types.get_or_init(|| {
+            let imports = self.imports.map(Imports::types).unwrap_or_default();
+            types::link(
+                &self.module_ast,
+                self.type_segments(),
+                self.options.widen_defaults,
+                &imports,
+            )
+        })
+    }
+
+    fn value_segments(&self) -> &[values::Segment] {
+        self.value_segments.get_or_init(|| {
+            self.module_ast
+                .ast
+                .globals
+                .iter()
+                .map(|global| values::analyze_global(global, &self.module_ast.name))
+                .collect()
+        })
+    }
+
+    pub fn values(&self) -> &ValuesResult {
+        self.values.get_or_init(|| {
+            let imported = self
+                .imports
+                .map(|imports| imports.values(self.uses().0))
+                .unwrap_or_default();
+            values::link(&self.module_ast, self.value_segments(), &imported)
+        })
+    }
+
+    /// Replace the global at the index and invalidate the parts, that depend on it.
+    ///
+    /// The type and value rules of the other globals are reused,
+    /// because they only refer to other globals by name.
+    /// Only if the declared names change, the type rules of all globals are recomputed.
+    /// Resolving the names and checking and evaluating the rules is always repeated,
+    /// so the results are the same as of a new analysis of the changed module.
+    ///
+    /// The leading comments of the file belong to the module and not to its first global,
+    /// so changing them, e.g. the directives, needs a new model.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn update_global(&mut self, index: usize, global: Global) {
+        let globals = &mut self.module_ast.to_mut().ast.globals;
+        let old_global = std::mem::replace(&mut globals[index], global);
+        if matches!(old_global, Global::Use(_)) || matches!(globals[index], Global::Use(_)) {
+            self.uses.take();
+        }
+        self.structure.take();
+        self.docs.take();
+        self.aliases.take();
+        // the directives are kept, because the leading comments of the file are in no global
+        let old_namespace = self.namespace.take();
+        self.types.take();
+        self.values.take();
+        if let Some(segments) = self.value_segments.get_mut() {
+            segments[index] =
+                values::analyze_global(&self.module_ast.ast.globals[index], &self.module_ast.name);
+        }
+        if self.type_segments.get().is_some() {
+            let NamespaceResult {
+                namespace,
+                bindings,
+                ..
+            } = self.namespace();
+            let names_changed = old_namespace.is_none_or(|old_namespace| {
+                old_namespace.namespace != *namespace || old_namespace.bindings != *bindings
+            });
+            if names_changed {
+                self.type_segments.take();
+            } else {
+                let segment = types::analyze_global(
+                    &self.module_ast.ast.globals[index],
+                    &self.module_ast.name,
+                    (namespace, &self.type_bindings()),
+                    self.options.widen_defaults,
+                );
+                if let Some(segments) = self.type_segments.get_mut() {
+                    segments[index] = segment;
+                }
+            }
+        }
+    }
+
+    /// Errors of all parts, that are computed so far, in the order of the parts.
+    pub fn errors(&self) -> Errors {
+        let mut errors = Errors::new();
+        if let Some(structure_errors) = self.structure.get() {
+            errors.extend_from_slice(structure_errors);
+        }
+        if let Some((_, _, use_errors)) = self.uses.get() {
+            errors.extend_from_slice(use_errors);
+        }
+        if let Some(imports) = self.imports {
+            errors.extend_from_slice(&imports.errors);
+        }
+        if let Some((_, directive_errors)) = self.directives.get() {
+            errors.extend_from_slice(directive_errors);
+        }
+        if let Some(alias_errors) = self.aliases.get() {
+            errors.extend_from_slice(alias_errors);
+        }
+        if let Some(namespace_result) = self.namespace.get() {
+            errors.extend_from_slice(&namespace_result.errors);
+        }
+        if let Some(types_result) = self.types.get() {
+            errors.extend_from_slice(&types_result.errors);
+        }
+        if let Some(values_result) = self.values.get() {
+            errors.extend_from_slice(&values_result.errors);
+        }
+        errors
+    }
+
+    /// Compute all parts and accumulate their errors.
+    pub fn into_result(self) -> SemanticsResult {
+        self.structure_errors();
+        self.directives();
+        self.docs();
+        self.alias_errors();
+        self.types();
+        self.values();
+        let computed = "All parts must be computed";
+        let mut errors = self.structure.into_inner().expect(computed);
+        let (_, _, use_errors) = self.uses.into_inner().expect(computed);
+        errors.extend(use_errors);
+        if let Some(imports) = self.imports {
+            errors.extend_from_slice(&imports.errors);
+        }
+        let (directives, directive_errors) = self.directives.into_inner().expect(computed);
+        errors.extend(directive_errors);
+        let docs = self.docs.into_inner().expect(computed);
+        errors.extend(self.aliases.into_inner().expect(computed));
+        let NamespaceResult {
+            bindings,
+            errors: namespace_errors,
+            ..
+        } = self.namespace.into_inner().expect(computed);
+        errors.extend(namespace_errors);
+        let mut types = self.types.into_inner().expect(computed);
+        errors.append(&mut types.errors);
+        let ValuesResult {
+            inits,
+            init_name,
+            mut symbol_table,
+            defaults,
+            mut emit_paths,
+            errors: value_errors,
+        } = self.values.into_inner().expect(computed);
+        errors.extend(value_errors);
+        if let Some(imports) = self.imports {
+            let imported = imports.values(&Namespace::new());
+            for (ident, value) in imported.symbol_table {
+                symbol_table.entry(ident).or_insert(value);
+            }
+            for (ident, paths) in imported.emit_paths {
+                emit_paths.entry(ident).or_insert(paths);
+            }
+        }
+
+        SemanticsResult {
+            inits,
+            init_name,
+            symbol_table,
+            defaults,
+            emit_paths,
+            bindings,
+            types,
+            directives,
+            docs,
+            errors,
+        }
     }
 }
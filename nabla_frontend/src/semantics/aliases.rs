@@ -0,0 +1,172 @@
+use crate::{
+    ast::{
+        Alias, AliasName, Expr, Global, List, Single, Struct, StructOrList, TypedExpr, Use,
+        UseBody, UseItem, UseKind,
+    },
+    semantics::{
+        error::{Error, ErrorMessage},
+        Errors,
+    },
+    token::ToTokenRange,
+    ModuleAst,
+};
+
+/// Analyze the aliases of a module.
+///
+/// Uses and use items are referred to by their alias, so it must be an identifier.
+/// Struct fields, inits and the defs and lets, that inits refer to,
+/// are emitted by their alias, so it must be a string.
+/// The only exception is the reserved alias `as _` of struct fields, which are not emitted.
+/// Aliases of the wrong kind are otherwise ignored by the other analyses.
+pub fn analyze(module_ast: &ModuleAst) -> Errors {
+    let mut errors = Errors::new();
+    for global in &module_ast.ast.globals {
+        match global {
+            Global::Use(u) => analyze_use(u, &mut errors),
+            Global::Def(d) => {
+                check_string(d.alias.as_ref(), &mut errors);
+                analyze_typed_expr(d, &mut errors);
+            }
+            Global::Let(l) => {
+                check_string(l.alias.as_ref(), &mut errors);
+                analyze_typed_expr(l, &mut errors);
+            }
+            Global::Init(init) => {
+                check_string(init.alias.as_ref(), &mut errors);
+                analyze_expr(&init.expr, &mut errors);
+            }
+            Global::Test(test) => {
+                if let Some(expr) = &test.expr {
+                    analyze_expr(expr, &mut errors);
+                }
+            }
+            Global::Error(_) => {}
+        }
+    }
+    errors
+}
+
+fn analyze_use(u: &Use, errors: &mut Errors) {
+    check_ident(u.alias.as_ref(), errors);
+    if let Some(body) = &u.body {
+        analyze_use_body(body, errors);
+    }
+}
+
+fn analyze_use_body(body: &UseBody, errors: &mut Errors) {
+    match &body.kind {
+        Some(UseKind::Single(item)) => analyze_use_item(item, errors),
+        Some(UseKind::Multiple(items)) => items
+            .items
+            .iter()
+            .flatten()
+            .for_each(|item| analyze_use_item(item, errors)),
+        _ => {}
+    }
+}
+
+fn analyze_use_item(item: &UseItem, errors: &mut Errors) {
+    check_ident(item.alias.as_ref(), errors);
+    if let Some(body) = &item.body {
+        analyze_use_body(body, errors);
+    }
+}
+
+fn analyze_typed_expr(typed_expr: &impl TypedExpr, errors: &mut Errors) {
+    typed_expr
+        .type_expr()
+        .into_iter()
+        .chain(typed_expr.expr())
+        .for_each(|expr| analyze_expr(expr, errors));
+}
+
+fn analyze_expr(expr: &Expr, errors: &mut Errors) {
+    match expr {
+        Expr::Union(union) => {
+            analyze_single(&union.single, errors);
+            union
+                .alternatives
+                .iter()
+                .flat_map(|alternative| alternative.single.as_ref())
+                .for_each(|single| analyze_single(single, errors));
+        }
+        Expr::Single(single) => analyze_single(single, errors),
+        Expr::Error(_) => {}
+    }
+}
+
+fn analyze_single(single: &Single, errors: &mut Errors) {
+    match single {
+        Single::Struct(s) => analyze_struct(s, errors),
+        Single::List(l) => analyze_list(l, errors),
+        Single::Named(named) => match &named.expr {
+            Some(StructOrList::Struct(s)) => analyze_struct(s, errors),
+            Some(StructOrList::List(l)) => analyze_list(l, errors),
+            None => {}
+        },
+        Single::Primitive(_) => {}
+        Single::Not(not) => not
+            .single
+            .iter()
+            .for_each(|single| analyze_single(single, errors)),
+        Single::Neg(neg) => neg
+            .single
+            .iter()
+            .for_each(|single| analyze_single(single, errors)),
+        Single::BoolOp(bool_op) => std::iter::once(&bool_op.lhs)
+            .chain(bool_op.rhs.iter())
+            .for_each(|single| analyze_single(single, errors)),
+        Single::ArithOp(arith_op) => std::iter::once(&arith_op.lhs)
+            .chain(arith_op.rhs.iter())
+            .for_each(|single| analyze_single(single, errors)),
+    }
+}
+
+fn analyze_struct(s: &Struct, errors: &mut Errors) {
+    for field in s.fields.iter().flatten() {
+        if !field.hidden {
+            check_string(field.alias.as_ref(), errors);
+        }
+        analyze_typed_expr(field, errors);
+    }
+    s.spreads
+        .iter()
+        .filter_map(|spread| spread.single.as_ref())
+        .for_each(|single| analyze_single(single, errors));
+}
+
+fn analyze_list(l: &List, errors: &mut Errors) {
+    l.exprs.iter().for_each(|expr| analyze_expr(expr, errors));
+}
+
+/// Report an error, if the alias is not an identifier.
+fn check_ident(alias: Option<&Alias>, errors: &mut Errors) {
+    if let Some(
+        alias @ Alias {
+            name: Some(AliasName::String(_)),
+            ..
+        },
+    ) = alias
+    {
+        errors.push(Error::new(
+            ErrorMessage::AliasMustBeIdent,
+            alias.to_token_range(),
+        ));
+    }
+}
+
+/// Report an error, if the alias is not a string.
+fn check_string(alias: Option<&Alias>, errors: &mut Errors) {
+    if let Some(
+        alias @ Alias {
+            name: Some(AliasName::Ident(_)),
+            ..
+        },
+    ) = alias
+    {
+        errors.push(Error::new(
+            ErrorMessage::AliasMustBeString,
+            alias.to_token_range(),
+        ));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    ast::{Expr, Global, Single, StructOrList},
+    eval::Value,
+    semantics::{
+        error::{Error, ErrorMessage},
+        Errors,
+    },
+    token::{ToTokenRange, TokenRange},
+    ModuleAst,
+};
+
+/// Output format, the init is converted to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Target {
+    Json,
+    Yaml,
+    Toml,
+    Xml,
+}
+
+impl Target {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+            Self::Xml => "XML",
+        }
+    }
+}
+
+impl std::str::FromStr for Target {
+    type Err = String;
+
+    /// Parses the lowercase name of a target, e.g. `toml`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            "xml" => Ok(Self::Xml),
+            _ => Err(format!("unknown target `{}`", s)),
+        }
+    }
+}
+
+/// Name of the field, that is the text of its element in XML
+const XML_TEXT: &str = "#text";
+
+/// Where a value is placed in the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Position {
+    Root,
+    Field,
+    ListElement,
+}
+
+/// Check that the init can be represented in the target.
+///
+/// Constructs the target cannot express are reported as warnings,
+/// because the conversion would fail or silently drop them.
+/// Values are located by the expression they are written in.
+/// Values that stem from a reference are located by the reference.
+///
+/// If `is_wrapped` is set, the init is not the root of the document,
+/// but wrapped in a struct (e.g. with `--wrap-root`).
+pub fn check(module_ast: &ModuleAst, init: &Value, target: Target, is_wrapped: bool) -> Errors {
+    let options = Options {
+        is_wrapped,
+        ..Options::default()
+    };
+    check_with_options(module_ast, init, target, &options)
+}
+
+/// How the init is placed in the output of the target
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    /// The init is not the root of the document, but wrapped in a struct
+    pub is_wrapped: bool,
+    /// XML lists are wrapped in an element of their own, so they may be nested or top-level
+    pub xml_wrapped_lists: bool,
+}
+
+/// Check that the init can be represented in the target with the options, see [`check`].
+pub fn check_with_options(
+    module_ast: &ModuleAst,
+    init: &Value,
+    target: Target,
+    options: &Options,
+) -> Errors {
+    let Some(init_expr) = module_ast
+        .ast
+        .globals
+        .iter()
+        .find_map(|global| match global {
+            Global::Init(init) => Some(&init.expr),
+            _ => None,
+        })
+    else {
+        return Errors::new();
+    };
+    let mut errors = Errors::new();
+    let position = if options.is_wrapped {
+        Position::Field
+    } else {
+        Position::Root
+    };
+    check_value(
+        init,
+        Some(init_expr),
+        &init_expr.to_token_range(),
+        position,
+        target,
+        options,
+        &mut errors,
+    );
+    errors
+}
+
+fn check_value(
+    value: &Value,
+    expr: Option<&Expr>,
+    range: &TokenRange,
+    position: Position,
+    target: Target,
+    options: &Options,
+    errors: &mut Errors,
+) {
+    let unrepresentable = match (target, value, position) {
+        (Target::Toml, Value::Null, _) => Some("`null`".to_string()),
+        (Target::Toml, Value::Struct(_), Position::Root) => None,
+        (Target::Toml, _, Position::Root) => {
+            Some("a top-level value, that is no struct".to_string())
+        }
+        (Target::Xml, Value::List(_), _) if options.xml_wrapped_lists => None,
+        (Target::Xml, Value::List(_), Position::Root) => Some("a top-level list".to_string()),
+        (Target::Xml, Value::List(_), Position::ListElement) => {
+            Some("a list inside of a list".to_string())
+        }
+        _ => None,
+    };
+    if let Some(construct) = unrepresentable {
+        errors.push(Error::new(
+            ErrorMessage::Unrepresentable(construct, target.as_str().to_string()),
+            range.clone(),
+        ));
+    }
+    match value {
+        Value::Struct(fields) => {
+            for (name, field_value) in fields {
+                let (field_expr, field_range) =
+                    field_expr(expr, name).unwrap_or_else(|| (None, range.clone()));
+                let construct = match (target, field_value) {
+                    (Target::Xml, _) if !is_xml_field_name(name) => {
+                        Some(format!("the field name `{}`", name))
+                    }
+                    (Target::Xml, Value::Struct(_) | Value::List(_))
+                        if name.starts_with('@') || name == XML_TEXT =>
+                    {
+                        Some(format!("a struct or list as `{}`", name))
+                    }
+                    _ => None,
+                };
+                if let Some(construct) = construct {
+                    errors.push(Error::new(
+                        ErrorMessage::Unrepresentable(construct, target.as_str().to_string()),
+                        field_range.clone(),
+                    ));
+                }
+                check_value(
+                    field_value,
+                    field_expr,
+                    &field_range,
+                    Position::Field,
+                    target,
+                    options,
+                    errors,
+                );
+            }
+        }
+        Value::List(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                let element_expr = list_exprs(expr).and_then(|exprs| exprs.get(index));
+                let element_range =
+                    element_expr.map_or_else(|| range.clone(), |expr| expr.to_token_range());
+                check_value(
+                    element,
+                    element_expr,
+                    &element_range,
+                    Position::ListElement,
+                    target,
+                    options,
+                    errors,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Find the expression and range of the field in a struct expression.
+fn field_expr<'a>(expr: Option<&'a Expr>, name: &str) -> Option<(Option<&'a Expr>, TokenRange)> {
+    let fields = match expr {
+        Some(Expr::Single(Single::Struct(s))) => &s.fields,
+        Some(Expr::Single(Single::Named(named))) => match &named.expr {
+            Some(StructOrList::Struct(s)) => &s.fields,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    fields
+        .iter()
+        .flatten()
+        .find(|field| field.emit_name() == name)
+        .map(|field| {
+            let range = field
+                .expr
+                .as_ref()
+                .map_or_else(|| field.to_token_range(), Expr::to_token_range);
+            (field.expr.as_ref(), range)
+        })
+}
+
+fn list_exprs(expr: Option<&Expr>) -> Option<&[Expr]> {
+    match expr {
+        Some(Expr::Single(Single::List(list))) => Some(&list.exprs),
+        Some(Expr::Single(Single::Named(named))) => match &named.expr {
+            Some(StructOrList::List(list)) => Some(&list.exprs),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Checks whether the field name can be emitted in XML:
+/// as element, as attribute with an `@` prefix (e.g. `@host`) or as text named `#text`.
+fn is_xml_field_name(name: &str) -> bool {
+    name == XML_TEXT || is_xml_name(name.strip_prefix('@').unwrap_or(name))
+}
+
+/// Checks whether the name can be used as XML element name.
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
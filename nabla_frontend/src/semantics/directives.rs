@@ -0,0 +1,85 @@
+use crate::{
+    ast::Ast,
+    semantics::{
+        compat::Target,
+        error::{Error, ErrorMessage},
+        Errors,
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Marks the first comment of a file as directives, e.g. `// nabla: target=yaml`.
+pub const DIRECTIVE_PREFIX: &str = "nabla:";
+
+/// Output preferences, that a file declares in its first comment,
+/// e.g. `// nabla: target=yaml root=config wrap-root`.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileDirectives {
+    /// `target=<json|yaml|toml|xml>`
+    pub target: Option<Target>,
+    /// `root=<name>`, the name of the root element
+    pub root: Option<String>,
+    /// `wrap-root`
+    pub wrap_root: bool,
+    /// `sort-keys`, emit the keys sorted instead of in the order of their declaration
+    pub sort_keys: bool,
+}
+
+/// Parse the directives of the first comment of the file.
+///
+/// Unknown keys and invalid values are reported as warnings
+/// at the leading comments of the file and otherwise ignored.
+pub fn analyze(ast: &Ast) -> (FileDirectives, Errors) {
+    let mut directives = FileDirectives::default();
+    let mut errors = Vec::new();
+    let prelude = &ast.info.prelude;
+    let Some(items) = prelude.comments.first().and_then(|comment| {
+        comment
+            .trim_start_matches('/')
+            .trim()
+            .strip_prefix(DIRECTIVE_PREFIX)
+    }) else {
+        return (directives, errors);
+    };
+    for item in items.split_whitespace() {
+        let (key, value) = item
+            .split_once('=')
+            .map_or((item, None), |(key, value)| (key, Some(value)));
+        let valid = match (key, value) {
+            ("target", Some(value)) => value
+                .parse()
+                .map(|target| directives.target = Some(target))
+                .is_ok(),
+            ("root", Some(value)) if !value.is_empty() => {
+                directives.root = Some(value.to_string());
+                true
+            }
+            ("wrap-root", None) => {
+                directives.wrap_root = true;
+                true
+            }
+            ("sort-keys", None) => {
+                directives.sort_keys = true;
+                true
+            }
+            ("target" | "root" | "wrap-root" | "sort-keys", _) => false,
+            _ => {
+                errors.push(Error::new(
+                    ErrorMessage::UnknownDirective(key.to_string()),
+                    prelude.range.clone(),
+                ));
+                continue;
+            }
+        };
+        if !valid {
+            errors.push(Error::new(
+                ErrorMessage::InvalidDirective(item.to_string()),
+                prelude.range.clone(),
+            ));
+        }
+    }
+    (directives, errors)
+}
@@ -0,0 +1,70 @@
+use crate::{
+    lexer::{lex, LexerResult},
+    parser::{parse, ParserResult},
+    semantics::{
+        compat::Target,
+        directives::{self, FileDirectives},
+        error::{Error, ErrorMessage},
+        Errors,
+    },
+};
+use pretty_assertions::assert_eq;
+
+fn analyze(src: &str) -> (FileDirectives, Errors) {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    directives::analyze(&ast)
+}
+
+#[test]
+fn all_directives() {
+    let (directives, errors) = analyze("// nabla: target=yaml root=config wrap-root sort-keys\n{}");
+    assert_empty!(errors);
+    assert_eq!(
+        FileDirectives {
+            target: Some(Target::Yaml),
+            root: Some("config".to_string()),
+            wrap_root: true,
+            sort_keys: true,
+        },
+        directives
+    );
+}
+
+#[test]
+fn no_directives() {
+    let (directives, errors) = analyze("// just a comment\n{}");
+    assert_empty!(errors);
+    assert_eq!(FileDirectives::default(), directives);
+}
+
+#[test]
+fn only_first_comment() {
+    let (directives, errors) = analyze("// header\n// nabla: target=toml\n{}");
+    assert_empty!(errors);
+    assert_eq!(FileDirectives::default(), directives);
+}
+
+#[test]
+fn unknown_and_invalid_directives() {
+    let (directives, errors) = analyze("// nabla: color=red target=csv root wrap-root=yes xml\n{}");
+    assert_eq!(FileDirectives::default(), directives);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::UnknownDirective("color".to_string()), 0..1),
+            Error::new(
+                ErrorMessage::InvalidDirective("target=csv".to_string()),
+                0..1
+            ),
+            Error::new(ErrorMessage::InvalidDirective("root".to_string()), 0..1),
+            Error::new(
+                ErrorMessage::InvalidDirective("wrap-root=yes".to_string()),
+                0..1
+            ),
+            Error::new(ErrorMessage::UnknownDirective("xml".to_string()), 0..1),
+        ],
+        errors
+    );
+}
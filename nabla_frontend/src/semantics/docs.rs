@@ -0,0 +1,243 @@
+use crate::{
+    ast::{Ast, AstInfo, Expr, Global, Named, Single, Struct, StructOrList},
+    eval::ValuePath,
+};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests;
+
+/// Marks a comment as doc comment of the following node, e.g. `/// port of the server`.
+pub const DOC_PREFIX: &str = "///";
+
+/// Starts every line comment, including doc comments
+pub const COMMENT_PREFIX: &str = "//";
+
+/// Doc comments of emitted fields by their path in the emitted value
+pub type Docs = HashMap<ValuePath, String>;
+
+/// Defs and lets by their name, that the emitted value may refer to
+struct Globals<'a> {
+    defs: HashMap<&'a str, &'a Struct>,
+    lets: HashMap<&'a str, &'a Expr>,
+    /// Whether plain comments document fields like doc comments
+    plain_comments: bool,
+}
+
+/// Collect the doc comments of the fields of the first init.
+///
+/// The doc comment of a field in the init takes precedence over the one of the def.
+/// Fields of defs are documented, whether they are set in the init or by a default.
+/// Elements of lists in the init are documented by their index,
+/// the element types of lists in defs are not documented.
+pub fn analyze(ast: &Ast) -> Docs {
+    analyze_with_comments(ast, false)
+}
+
+/// Collect the docs of the fields of the first init, see [`analyze`].
+///
+/// With `plain_comments`, the plain comments (`//`) of a field document it as well,
+/// together with its doc comments in the order of the source.
+pub fn analyze_with_comments(ast: &Ast, plain_comments: bool) -> Docs {
+    let mut globals = Globals {
+        defs: HashMap::new(),
+        lets: HashMap::new(),
+        plain_comments,
+    };
+    for global in &ast.globals {
+        match global {
+            Global::Def(def) => {
+                let s = [&def.expr, &def.type_expr]
+                    .into_iter()
+                    .flatten()
+                    .find_map(as_struct);
+                if let (Some(name), Some(s)) = (&def.name, s) {
+                    globals.defs.insert(&name.name, s);
+                }
+            }
+            Global::Let(l) => {
+                if let (Some(name), Some(expr)) = (&l.name, &l.expr) {
+                    globals.lets.insert(&name.name, expr);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut docs = Docs::new();
+    let init = ast.globals.iter().find_map(|global| match global {
+        Global::Init(init) => Some(init),
+        _ => None,
+    });
+    if let Some(init) = init {
+        globals.document_value(&init.expr, &ValuePath::root(), &mut Vec::new(), &mut docs);
+    }
+    docs
+}
+
+/// Text of the leading doc comments of the node without their prefix.
+/// Multiple doc comments are joined by line breaks.
+pub fn doc_comment(info: &AstInfo) -> Option<String> {
+    comment_text(info, |comment| comment.strip_prefix(DOC_PREFIX))
+}
+
+/// Text of all leading comments of the node, plain and doc comments, without their prefix.
+pub fn all_comments(info: &AstInfo) -> Option<String> {
+    comment_text(info, |comment| {
+        comment
+            .strip_prefix(DOC_PREFIX)
+            .or_else(|| comment.strip_prefix(COMMENT_PREFIX))
+    })
+}
+
+fn comment_text(info: &AstInfo, strip_prefix: fn(&str) -> Option<&str>) -> Option<String> {
+    let lines: Vec<_> = info
+        .prelude
+        .comments
+        .iter()
+        .filter_map(|comment| strip_prefix(comment))
+        .map(|line| {
+            let line = line.trim_end();
+            line.strip_prefix(' ').unwrap_or(line)
+        })
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Prepend a key to the paths of all docs, e.g. when the emitted value is wrapped.
+pub fn nest(docs: Docs, key: &str) -> Docs {
+    docs.into_iter()
+        .map(|(path, doc)| {
+            let mut nested = ValuePath::root().join_key(key);
+            nested.segments.extend(path.segments);
+            (nested, doc)
+        })
+        .collect()
+}
+
+const fn as_struct(expr: &Expr) -> Option<&Struct> {
+    match expr {
+        Expr::Single(Single::Struct(s)) => Some(s),
+        _ => None,
+    }
+}
+
+/// Name of the def or let, which is referred to without a subsection.
+fn reference(named: &Named) -> Option<&str> {
+    named.inner_names.is_empty().then_some(&*named.name.name)
+}
+
+impl Globals<'_> {
+    fn comment(&self, info: &AstInfo) -> Option<String> {
+        if self.plain_comments {
+            all_comments(info)
+        } else {
+            doc_comment(info)
+        }
+    }
+
+    /// Document a value of the init.
+    ///
+    /// The visited names guard against recursive defs and lets.
+    fn document_value<'b>(
+        &'b self,
+        expr: &'b Expr,
+        path: &ValuePath,
+        visited: &mut Vec<&'b str>,
+        docs: &mut Docs,
+    ) {
+        let Expr::Single(single) = expr else {
+            return;
+        };
+        match single {
+            Single::Struct(s) => self.document_struct(s, path, visited, docs),
+            Single::List(list) => self.document_list(&list.exprs, path, visited, docs),
+            Single::Named(named) => {
+                if let Some(name) = reference(named).filter(|name| !visited.contains(name)) {
+                    visited.push(name);
+                    if let Some(s) = self.defs.get(name) {
+                        self.document_def(s, path, visited, docs);
+                    } else if let Some(expr) = self.lets.get(name) {
+                        self.document_value(expr, path, visited, docs);
+                    }
+                    visited.pop();
+                }
+                match &named.expr {
+                    Some(StructOrList::Struct(s)) => self.document_struct(s, path, visited, docs),
+                    Some(StructOrList::List(list)) => {
+                        self.document_list(&list.exprs, path, visited, docs)
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn document_struct<'b>(
+        &'b self,
+        s: &'b Struct,
+        path: &ValuePath,
+        visited: &mut Vec<&'b str>,
+        docs: &mut Docs,
+    ) {
+        for field in s.fields.iter().flatten().filter(|field| !field.hidden) {
+            let path = path.join_key(field.emit_name());
+            if let Some(expr) = &field.expr {
+                self.document_value(expr, &path, visited, docs);
+            }
+            if let Some(doc) = self.comment(&field.info) {
+                docs.insert(path, doc);
+            }
+        }
+    }
+
+    fn document_list<'b>(
+        &'b self,
+        exprs: &'b [Expr],
+        path: &ValuePath,
+        visited: &mut Vec<&'b str>,
+        docs: &mut Docs,
+    ) {
+        for (index, expr) in exprs.iter().enumerate() {
+            self.document_value(expr, &path.join_index(index), visited, docs);
+        }
+    }
+
+    /// Document the fields of a struct in a def, including the fields of the defs they refer to.
+    fn document_def<'b>(
+        &'b self,
+        s: &'b Struct,
+        path: &ValuePath,
+        visited: &mut Vec<&'b str>,
+        docs: &mut Docs,
+    ) {
+        for field in s.fields.iter().flatten().filter(|field| !field.hidden) {
+            let path = path.join_key(field.emit_name());
+            for expr in [&field.type_expr, &field.expr].into_iter().flatten() {
+                let Expr::Single(single) = expr else {
+                    continue;
+                };
+                match single {
+                    Single::Struct(s) => self.document_def(s, &path, visited, docs),
+                    Single::Named(named) => {
+                        if let Some(name) = reference(named).filter(|name| !visited.contains(name))
+                        {
+                            if let Some(s) = self.defs.get(name) {
+                                visited.push(name);
+                                self.document_def(s, &path, visited, docs);
+                                visited.pop();
+                            }
+                        }
+                        if let Some(StructOrList::Struct(s)) = &named.expr {
+                            self.document_def(s, &path, visited, docs);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(doc) = self.comment(&field.info) {
+                docs.insert(path, doc);
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    eval::ValuePath,
+    lexer::{lex, LexerResult},
+    parser::{parse, ParserResult},
+    semantics::docs::{self, Docs},
+};
+use pretty_assertions::assert_eq;
+
+fn analyze(src: &str) -> Docs {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    docs::analyze(&ast)
+}
+
+fn path(keys: &[&str]) -> ValuePath {
+    keys.iter()
+        .fold(ValuePath::root(), |path, key| path.join_key(key))
+}
+
+/// Docs sorted by their path
+fn sorted(docs: Docs) -> Vec<(String, String)> {
+    let mut docs: Vec<_> = docs
+        .into_iter()
+        .map(|(path, doc)| (path.to_string(), doc))
+        .collect();
+    docs.sort();
+    docs
+}
+
+#[test]
+fn def_fields() {
+    let docs = analyze(
+        "
+def Server = {
+    /// port to listen on
+    /// defaults to 80
+    port: Number = 80
+    // not a doc comment
+    host: String
+}
+def Config = {
+    /// the server
+    server: Server
+}
+Config { server = { host = \"localhost\" } }
+",
+    );
+    assert_eq!(
+        vec![
+            ("/server".to_string(), "the server".to_string()),
+            (
+                "/server/port".to_string(),
+                "port to listen on\ndefaults to 80".to_string()
+            ),
+        ],
+        sorted(docs)
+    );
+}
+
+#[test]
+fn init_doc_takes_precedence() {
+    let docs = analyze(
+        "
+def Config = {
+    /// from the def
+    name: String
+}
+Config {
+    /// from the init
+    name = \"x\"
+}
+",
+    );
+    assert_eq!(
+        Some(&"from the init".to_string()),
+        docs.get(&path(&["name"]))
+    );
+}
+
+#[test]
+fn aliases_lists_and_hidden_fields() {
+    let docs = analyze(
+        "
+{
+    /// renamed
+    name = \"x\" as \"full-name\"
+    /// not emitted
+    secret = 1 as _
+    servers = [{
+        /// first port
+        port = 1
+    }]
+}
+",
+    );
+    assert_eq!(
+        vec![
+            ("/full-name".to_string(), "renamed".to_string()),
+            ("/servers/0/port".to_string(), "first port".to_string()),
+        ],
+        sorted(docs)
+    );
+}
+
+#[test]
+fn recursive_def() {
+    let docs = analyze(
+        "
+def Node = {
+    /// next node
+    next: Node | null = null
+}
+Node { }
+",
+    );
+    assert_eq!(
+        vec![("/next".to_string(), "next node".to_string())],
+        sorted(docs)
+    );
+}
+
+#[test]
+fn nest() {
+    let docs = docs::nest(Docs::from([(path(&["a"]), "doc".to_string())]), "root");
+    assert_eq!(
+        Docs::from([(path(&["root", "a"]), "doc".to_string())]),
+        docs
+    );
+}
+
+#[test]
+fn plain_comments() {
+    let src = "
+def Server = {
+    // plain
+    /// doc
+    port: Number = 80
+    // only plain
+    host: String
+}
+Server { host = \"localhost\" }
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![("/port".to_string(), "doc".to_string())],
+        sorted(docs::analyze(&ast))
+    );
+    assert_eq!(
+        vec![
+            ("/host".to_string(), "only plain".to_string()),
+            ("/port".to_string(), "plain\ndoc".to_string()),
+        ],
+        sorted(docs::analyze_with_comments(&ast, true))
+    );
+}
@@ -1,4 +1,4 @@
-use crate::token::TokenRange;
+use crate::{diagnostics::Severity, token::TokenRange};
 use thiserror::Error;
 
 /// Semantic error
@@ -8,30 +8,90 @@ use thiserror::Error;
 pub struct Error {
     pub message: ErrorMessage,
     pub range: TokenRange,
+    /// Other locations, that are involved in the error
+    pub secondary: Vec<SecondarySpan>,
 }
 
 impl Error {
     pub const fn new(message: ErrorMessage, range: TokenRange) -> Self {
-        Self { message, range }
+        Self {
+            message,
+            range,
+            secondary: Vec::new(),
+        }
     }
+
+    pub fn with_secondary(mut self, message: &str, range: TokenRange) -> Self {
+        self.secondary.push(SecondarySpan {
+            message: message.to_string(),
+            range,
+        });
+        self
+    }
+}
+
+/// Location, that is involved in an error,
+/// e.g. the first declaration of a redeclared identifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecondarySpan {
+    pub message: String,
+    pub range: TokenRange,
 }
 
+/// Message of the secondary span at the first declaration of an identifier
+pub const FIRST_DECLARED: &str = "first declared here";
+
+/// Message of the secondary span at the first init of a file with several inits
+pub const FIRST_INIT: &str = "first init here";
+
+/// Message of the secondary span at the local def or let, that has the name of an import
+pub const DECLARED_LOCALLY: &str = "declared locally here";
+
+/// Message of the secondary span at the field of a def, that a misspelled field likely means
+pub const FIELD_DEFINED: &str = "field defined here";
+
 /// Semantic error message
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ErrorMessage {
     AliasMustBeString,
     AliasMustBeIdent,
-    AliasingNonSingle,
+    AliasingNonSingle(String),
+    DivisionByZero,
     DuplicateField(String),
     DuplicateUse(String),
+    FieldOfNonStruct,
+    ForwardReference(String),
     ImmutableLet(String),
+    /// At the use, that closes a cycle of imports, with the modules of the cycle
+    ImportCycle(Vec<String>),
+    /// At the name of a use, that a def or let of the module also declares
+    ImportShadowedByLocal(String),
+    IndexOutOfBounds(usize, usize),
+    InvalidDirective(String),
+    InvalidLength(String),
+    LengthOfNonString,
     MissingField(String),
+    /// MisspelledField(actual, expected), at an unexpected field,
+    /// whose name differs only by case or underscores from a missing one
+    MisspelledField(String, String),
+    /// ModuleErrors(module, count), at the first use of a module, whose analysis has errors
+    ModuleErrors(String, usize),
+    /// At every element of a list in type position except the first
     MultipleListTypes,
+    /// At every init of a file except the first
     MultipleInits,
+    NotIndexable,
+    OpaqueUsage(String),
+    /// At the reference, that closes a cycle of values
     RecursiveInit,
     Redeclaration(String),
     SelfReference(String),
+    /// At a spread in a struct, whose value is no struct
+    SpreadOfNonStruct,
+    /// At the name of a def or let, that is also the name of a built-in type
+    ShadowsBuiltIn(String),
     TypeMismatch,
+    UndefinedField(String),
     UndefinedIdent(String),
     UnexpecedField(String),
     UnexpecedListElement,
@@ -40,30 +100,91 @@ pub enum ErrorMessage {
     UntypedField,
     UninitializedLet,
     UninitializedInit,
+    UnknownDirective(String),
     UnknownType,
+    /// At the first use of a module, that could not be resolved, with the reason
+    UnresolvedModule(String),
+    /// Unrepresentable(construct, target)
+    Unrepresentable(String, String),
     Unsupported(String),
     ValueMismatch(String, String),
 }
 
+impl ErrorMessage {
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::ForwardReference(_)
+            | Self::InvalidDirective(_)
+            | Self::OpaqueUsage(_)
+            | Self::ShadowsBuiltIn(_)
+            | Self::UnknownDirective(_)
+            | Self::UnresolvedModule(_)
+            | Self::Unrepresentable(_, _) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
 impl std::fmt::Display for ErrorMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let message = match self {
             Self::AliasMustBeString => "alias must be a string".to_string(),
             Self::AliasMustBeIdent => "alias must be an identifier".to_string(),
-            Self::AliasingNonSingle => "only single use items can be aliased".to_string(),
+            Self::AliasingNonSingle(item_name) => format!(
+                "`{}` cannot be aliased, only single use items can be",
+                item_name
+            ),
+            Self::DivisionByZero => "division by zero".to_string(),
             Self::DuplicateField(field_name) => format!("duplicate field: `{}`", field_name),
             Self::DuplicateUse(use_name) => format!("duplicate use: `{}`", use_name),
+            Self::FieldOfNonStruct => "only structs have fields".to_string(),
+            Self::ForwardReference(ident) => {
+                format!("`{}` is referenced before its declaration", ident)
+            }
             Self::ImmutableLet(let_name) => format!(
                 "`{}` is bound my a let-statement and therefore immutable",
                 let_name
             ),
+            Self::ImportCycle(modules) => format!(
+                "modules import each other in a cycle: {}",
+                modules.join(" -> ")
+            ),
+            Self::ImportShadowedByLocal(ident) => format!(
+                "`{}` is imported and also declared in this module; rename the import with `as`",
+                ident
+            ),
+            Self::IndexOutOfBounds(index, len) => format!(
+                "index {} is out of bounds for list of length {}",
+                index, len
+            ),
+            Self::InvalidDirective(directive) => {
+                format!("invalid directive `{}`, it is ignored", directive)
+            }
+            Self::InvalidLength(length) => format!("`{}` is not a valid length", length),
+            Self::LengthOfNonString => "only strings can have a length".to_string(),
             Self::MissingField(field_name) => format!("missing field: `{}`", field_name),
+            Self::MisspelledField(actual, expected) => format!(
+                "field `{}` does not exist; did you mean `{}`?",
+                actual, expected
+            ),
+            Self::ModuleErrors(module, count) => {
+                format!("module `{}` has {} error(s)", module, count)
+            }
             Self::MultipleListTypes => "more than one type in list".to_string(),
             Self::MultipleInits => "more than one initialization".to_string(),
-            Self::RecursiveInit => "cannot be initialize value recursively".to_string(),
+            Self::NotIndexable => "only lists can be indexed".to_string(),
+            Self::OpaqueUsage(ident) => {
+                format!("`{}` is opaque, values of it are not checked", ident)
+            }
+            Self::RecursiveInit => "value cannot be initialized recursively".to_string(),
             Self::Redeclaration(ident) => format!("`{}` was alreay declared", ident),
             Self::SelfReference(ident) => format!("`{}` references itself", ident),
+            Self::SpreadOfNonStruct => "only structs can be spread into a struct".to_string(),
+            Self::ShadowsBuiltIn(ident) => {
+                format!("`{}` shadows the built-in type of the same name", ident)
+            }
             Self::TypeMismatch => "types do not match".to_string(),
+            Self::UndefinedField(field_name) => format!("field `{}` is not defined", field_name),
             Self::UndefinedIdent(ident) => format!("`{}` is not defined", ident),
             Self::UnexpecedField(field_name) => format!("unexpected field: `{}`", field_name),
             Self::UnexpecedListElement => "unexpected element in list".to_string(),
@@ -72,7 +193,12 @@ impl std::fmt::Display for ErrorMessage {
             Self::UninitializedLet => "let statement must be fully initialized".to_string(),
             Self::UninitializedInit => "initialization must be fully initialized".to_string(),
             Self::UntypedField => "this field must be assigned a type".to_string(),
+            Self::UnknownDirective(key) => format!("unknown directive `{}`, it is ignored", key),
             Self::UnknownType => "unknown type".to_string(),
+            Self::UnresolvedModule(reason) => format!("{}, its items are unknown", reason),
+            Self::Unrepresentable(construct, target) => {
+                format!("{} cannot be represented in {}", construct, target)
+            }
             Self::Unsupported(name) => format!("{} is currently unsupported", name),
             Self::ValueMismatch(r#type, value) => {
                 format!("`{}` does not match type {}", value, r#type)
@@ -0,0 +1,98 @@
+use crate::{
+    ast::Global,
+    eval::Value,
+    semantics::{namespace, structure::INIT_CONTEXT, SemanticsResult},
+    ModuleAst,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Kind of the global of a node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphNodeKind {
+    Def,
+    Let,
+    Init,
+}
+
+/// Global of the module, e.g. `def Config`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphNode {
+    /// Name of the def or let, `<init>` for inits
+    pub name: String,
+    pub kind: GraphNodeKind,
+    /// Whether the value of the global is fully known
+    pub is_known: bool,
+}
+
+/// Which globals of a module reference which.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Graph {
+    /// Nodes in the order of their globals
+    pub nodes: Vec<GraphNode>,
+    /// Edges from the referencing to the referenced node by their index, sorted and distinct
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Build the dependency graph of the defs, lets and inits of the module.
+///
+/// References to uses and built-in types and undefined references have no edge.
+/// A name, that is declared multiple times, refers to its first declaration.
+pub fn dependency_graph(module: &ModuleAst, semantics: &SemanticsResult) -> Graph {
+    let mut graph = Graph::default();
+    let mut referencing = Vec::new();
+    let mut init_index = 0;
+    for global in &module.ast.globals {
+        let (name, kind, value) = match global {
+            Global::Def(def) => {
+                let Some(name) = &def.name else { continue };
+                (
+                    name.name.clone(),
+                    GraphNodeKind::Def,
+                    symbol(module, semantics, &name.name),
+                )
+            }
+            Global::Let(l) => {
+                let Some(name) = &l.name else { continue };
+                (
+                    name.name.clone(),
+                    GraphNodeKind::Let,
+                    symbol(module, semantics, &name.name),
+                )
+            }
+            Global::Init(_) => {
+                let value = semantics.inits.get(init_index);
+                init_index += 1;
+                (INIT_CONTEXT.to_string(), GraphNodeKind::Init, value)
+            }
+            Global::Use(_) | Global::Test(_) | Global::Error(_) => continue,
+        };
+        referencing.push((graph.nodes.len(), global));
+        graph.nodes.push(GraphNode {
+            name,
+            kind,
+            is_known: value.is_some_and(Value::is_known),
+        });
+    }
+    for (from, global) in referencing {
+        for named in namespace::references(global) {
+            let to = graph
+                .nodes
+                .iter()
+                .position(|node| node.kind != GraphNodeKind::Init && node.name == named.name.name);
+            if let Some(to) = to {
+                graph.edges.push((from, to));
+            }
+        }
+    }
+    graph.edges.sort_unstable();
+    graph.edges.dedup();
+    graph
+}
+
+fn symbol<'a>(module: &ModuleAst, semantics: &'a SemanticsResult, name: &str) -> Option<&'a Value> {
+    semantics
+        .symbol_table
+        .get(&module.name.clone().extend(name.to_string()))
+}
@@ -0,0 +1,60 @@
+use crate::{
+    lexer::{lex, LexerResult},
+    parser::{parse, ParserResult},
+    semantics::{
+        self,
+        graph::{dependency_graph, Graph, GraphNode, GraphNodeKind},
+    },
+    GlobalIdent, ModuleAst,
+};
+use pretty_assertions::assert_eq;
+
+fn graph(src: &str) -> Graph {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let semantics_result = semantics::analyze(&module_ast);
+    dependency_graph(&module_ast, &semantics_result)
+}
+
+fn node(name: &str, kind: GraphNodeKind, is_known: bool) -> GraphNode {
+    GraphNode {
+        name: name.to_string(),
+        kind,
+        is_known,
+    }
+}
+
+#[test]
+fn chain() {
+    let src =
+        "let port = 80\ndef Server = { port: Number = port host = \"a\" }\nServer { port = port }";
+    assert_eq!(
+        Graph {
+            nodes: vec![
+                node("port", GraphNodeKind::Let, true),
+                node("Server", GraphNodeKind::Def, true),
+                node("<init>", GraphNodeKind::Init, true),
+            ],
+            edges: vec![(1, 0), (2, 0), (2, 1)],
+        },
+        graph(src)
+    );
+}
+
+#[test]
+fn uses_and_built_ins_have_no_edges() {
+    let src = "use other::x\nlet a = x\ndef B = String";
+    assert_eq!(
+        Graph {
+            nodes: vec![
+                node("a", GraphNodeKind::Let, false),
+                node("B", GraphNodeKind::Def, false),
+            ],
+            edges: Vec::new(),
+        },
+        graph(src)
+    );
+}
@@ -0,0 +1,226 @@
+use crate::{
+    diagnostics::Severity,
+    lexer::{self, LexerResult},
+    parser::{self, ParserResult},
+    resolver::{validate_module_name, ModuleResolver, ResolveError},
+    semantics::{
+        error::{Error, ErrorMessage},
+        types::TypesResult,
+        uses, values, BindingMap, Errors, Namespace, Options, SemanticModel, SemanticsResult,
+    },
+    token::TokenRange,
+    GlobalIdent, ModuleAst,
+};
+use std::{collections::HashMap, rc::Rc};
+
+/// Analyzed modules, whose globals a module imports.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Imports {
+    /// Imported modules with the range of the first use of each
+    modules: Vec<(Rc<SemanticsResult>, TokenRange)>,
+    /// Errors of loading the modules, at the uses of the importing module
+    pub(super) errors: Errors,
+}
+
+impl Imports {
+    /// Type rules of the imported modules and the ranges, their diagnostics point to.
+    pub(super) fn types(&self) -> Vec<(&TypesResult, TokenRange)> {
+        self.modules
+            .iter()
+            .map(|(result, range)| (&result.types, range.clone()))
+            .collect()
+    }
+
+    /// Bindings of all imported globals
+    pub(super) fn bindings(&self) -> BindingMap {
+        self.modules
+            .iter()
+            .flat_map(|(result, _)| result.bindings.clone())
+            .collect()
+    }
+
+    /// Values of all imported globals, that the uses of the module name.
+    pub(super) fn values(&self, uses: &Namespace) -> values::Imported {
+        let mut imported = values::Imported {
+            namespace: uses.clone(),
+            ..Default::default()
+        };
+        for (result, _) in &self.modules {
+            imported.symbol_table.extend(result.symbol_table.clone());
+            imported.emit_paths.extend(result.emit_paths.clone());
+        }
+        imported
+    }
+}
+
+/// Loads the modules of the uses from the resolver and analyzes them before their importers.
+///
+/// Every module is analyzed at most once.
+/// A module, that imports a module, whose analysis is still in progress, closes a cycle,
+/// which is reported instead of being followed.
+pub(super) struct Loader<'a> {
+    resolver: &'a dyn ModuleResolver,
+    options: &'a Options,
+    /// Analyzed modules and the number of their errors, or the reason they could not be loaded
+    loaded: HashMap<GlobalIdent, Result<(Rc<SemanticsResult>, usize), ResolveError>>,
+    /// Modules, whose analysis is in progress, in the order they were imported
+    stack: Vec<GlobalIdent>,
+}
+
+impl<'a> Loader<'a> {
+    pub(super) fn new(resolver: &'a dyn ModuleResolver, options: &'a Options) -> Self {
+        Self {
+            resolver,
+            options,
+            loaded: HashMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Analyze the module together with all modules, that it imports transitively.
+    pub(super) fn analyze(&mut self, module_ast: &ModuleAst) -> SemanticsResult {
+        self.stack.push(module_ast.name.clone());
+        let imports = self.imports(module_ast);
+        let result = SemanticModel::with_options(module_ast, self.options.clone())
+            .with_imports(&imports)
+            .into_result();
+        self.stack.pop();
+        result
+    }
+
+    /// Load the modules of all uses in the order of their declaration.
+    fn imports(&mut self, module_ast: &ModuleAst) -> Imports {
+        let (namespace, declarations, _) = uses::analyze(module_ast, None);
+        let mut used: Vec<_> = namespace
+            .iter()
+            .filter_map(|(name, ident)| Some((declarations.get(name)?.clone(), ident)))
+            .collect();
+        used.sort_by_key(|(range, _)| (range.start, range.end));
+        let mut imports = Imports::default();
+        let mut seen = Vec::new();
+        for (range, ident) in used {
+            let (name, result, error_count) = match self.load(ident) {
+                Ok(module) => module,
+                Err(message) => {
+                    // every module is reported only at its first use
+                    if !imports.errors.iter().any(|error| error.message == message) {
+                        imports.errors.push(Error::new(message, range));
+                    }
+                    continue;
+                }
+            };
+            if seen.contains(&name) {
+                continue;
+            }
+            if error_count > 0 {
+                imports.errors.push(Error::new(
+                    ErrorMessage::ModuleErrors(name.to_string(), error_count),
+                    range.clone(),
+                ));
+            }
+            // cycles are reported along all their imports, so they are visible at the entry
+            for error in &result.errors {
+                if matches!(error.message, ErrorMessage::ImportCycle(_))
+                    && !imports
+                        .errors
+                        .iter()
+                        .any(|known| known.message == error.message)
+                {
+                    imports
+                        .errors
+                        .push(Error::new(error.message.clone(), range.clone()));
+                }
+            }
+            seen.push(name);
+            imports.modules.push((result, range));
+        }
+        imports
+    }
+
+    /// Load the module, that the used identifier names or that contains it.
+    ///
+    /// E.g. `root::a::Server` is the item `Server` of the module `root::a`,
+    /// or the module `root::a::Server` itself, if there is no module `root::a`.
+    fn load(
+        &mut self,
+        ident: &GlobalIdent,
+    ) -> Result<(GlobalIdent, Rc<SemanticsResult>, usize), ErrorMessage> {
+        let containing = ident.path.split_last().map(|(_, path)| GlobalIdent {
+            root: ident.root.clone(),
+            path: path.to_vec(),
+        });
+        let mut first_error = None;
+        for module in containing.into_iter().chain(std::iter::once(ident.clone())) {
+            let Ok(request) = request_name(&module) else {
+                continue;
+            };
+            if let Some(position) = self.stack.iter().position(|name| *name == module) {
+                let mut cycle: Vec<_> = self.stack[position..]
+                    .iter()
+                    .map(GlobalIdent::to_string)
+                    .collect();
+                cycle.push(module.to_string());
+                return Err(ErrorMessage::ImportCycle(cycle));
+            }
+            if !self.loaded.contains_key(&module) {
+                let loaded = self.analyze_source(&module, &request);
+                self.loaded.insert(module.clone(), loaded);
+            }
+            match &self.loaded[&module] {
+                Ok((result, error_count)) => {
+                    return Ok((module, Rc::clone(result), *error_count));
+                }
+                Err(error) => {
+                    // the containing module is reported, if neither can be loaded
+                    first_error.get_or_insert_with(|| error.to_string());
+                }
+            }
+        }
+        Err(ErrorMessage::UnresolvedModule(first_error.unwrap_or_else(
+            || ResolveError::InvalidName(ident.clone()).to_string(),
+        )))
+    }
+
+    /// Resolve, lex, parse and analyze the module.
+    /// Returns the result and the number of errors of all stages.
+    fn analyze_source(
+        &mut self,
+        module: &GlobalIdent,
+        request: &GlobalIdent,
+    ) -> Result<(Rc<SemanticsResult>, usize), ResolveError> {
+        let source = self.resolver.resolve(request)?;
+        let LexerResult { tokens, errors } = lexer::lex(&source.src);
+        let lexer_errors = errors
+            .iter()
+            .filter(|error| error.message.severity() == Severity::Error)
+            .count();
+        let ParserResult { ast, errors } = parser::parse(&tokens);
+        let parser_errors = errors.len();
+        let result = self.analyze(&ModuleAst::new(module.clone(), ast));
+        let semantic_errors = result
+            .errors
+            .iter()
+            .filter(|error| error.message.severity() == Severity::Error)
+            .count();
+        Ok((
+            Rc::new(result),
+            lexer_errors + parser_errors + semantic_errors,
+        ))
+    }
+}
+
+/// Name of the module in the resolver.
+///
+/// A use, that does not start with the tree `root`, names a module directly inside of it,
+/// so `use shared::port` imports `port` from `root::shared`, like `use root::shared::port`.
+fn request_name(module: &GlobalIdent) -> Result<GlobalIdent, ResolveError> {
+    let request = if module.root == GlobalIdent::default().root {
+        module.clone()
+    } else {
+        GlobalIdent::default()
+            .extend(module.root.clone())
+            .extend_multiple(module.path.clone())
+    };
+    validate_module_name(&request)?;
+    Ok(request)
+}
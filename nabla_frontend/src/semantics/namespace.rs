@@ -1,14 +1,14 @@
 use crate::{
-    ast::{Expr, Global, Named, Single, StructOrList, TypedExpr},
+    ast::{Expr, Global, Named, Single, Struct, StructOrList, TypedExpr},
     semantics::{
-        error::{Error, ErrorMessage},
-        types, BindingMap, Errors, Namespace,
+        error::{Error, ErrorMessage, DECLARED_LOCALLY, FIRST_DECLARED},
+        types, BindingMap, Declarations, Errors, Namespace, Options,
     },
     token::ToTokenRange,
     ModuleAst,
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Binding {
     Def,
     Let,
@@ -20,10 +20,27 @@ pub struct NamespaceResult {
     pub errors: Errors,
 }
 
-pub fn analyze(uses: &Namespace, module_ast: &ModuleAst) -> NamespaceResult {
+/// Analyze the namespace of a module.
+///
+/// Globals can be referenced before their declaration,
+/// because the values are evaluated independent of their order.
+/// With `Options::strict_order` such forward references are reported as warnings.
+///
+/// The declarations of the uses are needed to point redeclarations to the first declaration.
+/// A def or let with the name of a use is reported at the use,
+/// because usually the import is the one to rename.
+pub fn analyze(
+    uses: &Namespace,
+    use_declarations: &Declarations,
+    module_ast: &ModuleAst,
+    options: &Options,
+) -> NamespaceResult {
     let module_name = module_ast.name.clone();
     let mut namespace = uses.clone();
     let mut bindings = BindingMap::new();
+    let mut declarations = use_declarations.clone();
+    // names declared by defs and lets, the other names of the namespace are imported
+    let mut locals = Declarations::new();
     let mut errors = Errors::new();
     for (ident, global_ident, binding) in
         module_ast
@@ -51,42 +68,68 @@ pub fn analyze(uses: &Namespace, module_ast: &ModuleAst) -> NamespaceResult {
         use std::collections::hash_map::Entry;
         match namespace.entry(ident.name.clone()) {
             Entry::Vacant(entry) => {
+                if types::BuiltInType::from_name(&ident.name).is_some() {
+                    errors.push(Error::new(
+                        ErrorMessage::ShadowsBuiltIn(ident.name.clone()),
+                        ident.to_token_range(),
+                    ));
+                }
                 entry.insert(global_ident.clone());
                 bindings.insert(global_ident, binding);
+                declarations.insert(ident.name.clone(), ident.to_token_range());
+                locals.insert(ident.name.clone(), ident.to_token_range());
+            }
+            Entry::Occupied(_) if !locals.contains_key(&ident.name) => {
+                // references still resolve to the import
+                locals.insert(ident.name.clone(), ident.to_token_range());
+                if let Some(use_range) = use_declarations.get(&ident.name) {
+                    errors.push(
+                        Error::new(
+                            ErrorMessage::ImportShadowedByLocal(ident.name.clone()),
+                            use_range.clone(),
+                        )
+                        .with_secondary(DECLARED_LOCALLY, ident.to_token_range()),
+                    );
+                }
             }
             Entry::Occupied(_) => {
-                errors.push(Error::new(
+                let mut error = Error::new(
                     ErrorMessage::Redeclaration(ident.name.clone()),
-                    ident.info.to_token_range(),
-                ));
+                    ident.to_token_range(),
+                );
+                if let Some(first_range) = locals.get(&ident.name) {
+                    error = error.with_secondary(FIRST_DECLARED, first_range.clone());
+                }
+                errors.push(error);
             }
         }
     }
-    for named in module_ast
-        .ast
-        .globals
-        .iter()
-        .flat_map(|global| match global {
-            Global::Def(def) => get_named_typed_expr(def),
-            Global::Let(l) => get_named_typed_expr(l),
-            Global::Init(init) => get_named(init),
-            Global::Use(_) | Global::Error(_) => Vec::new(),
-        })
-    {
+    for named in module_ast.ast.globals.iter().flat_map(references) {
         match namespace.get(&named.name.name) {
             None => {
                 if !(named.inner_names.is_empty()
-                    && types::BuiltInType::into_iter()
-                        .map(|built_in| built_in.as_str())
-                        .any(|built_in| named.name.name == built_in))
+                    && types::BuiltInType::from_name(&named.name.name).is_some())
                 {
                     errors.push(Error::new(
                         ErrorMessage::UndefinedIdent(named.name.name.clone()),
-                        named.name.info.to_token_range(),
+                        named.name.to_token_range(),
+                    ));
+                }
+            }
+            Some(global_ident) => {
+                // inner names are checked, when the projection is resolved
+                if options.strict_order
+                    && bindings.contains_key(global_ident)
+                    && declarations
+                        .get(&named.name.name)
+                        .is_some_and(|declaration| declaration.start > named.name.info.range.start)
+                {
+                    errors.push(Error::new(
+                        ErrorMessage::ForwardReference(named.name.name.clone()),
+                        named.name.to_token_range(),
                     ));
                 }
             }
-            Some(_global_ident) => { /* TODO: check if inner names actually exist */ }
         }
     }
 
@@ -97,6 +140,18 @@ pub fn analyze(uses: &Namespace, module_ast: &ModuleAst) -> NamespaceResult {
     }
 }
 
+/// Named expressions of the global in the order of the source,
+/// e.g. the references to other globals and to built-in types.
+pub fn references(global: &Global) -> Vec<&Named> {
+    match global {
+        Global::Def(def) => get_named_typed_expr(def),
+        Global::Let(l) => get_named_typed_expr(l),
+        Global::Init(init) => get_named(&init.expr),
+        Global::Test(test) => test.expr.as_ref().map_or_else(Vec::new, get_named),
+        Global::Use(_) | Global::Error(_) => Vec::new(),
+    }
+}
+
 fn get_named(expr: &Expr) -> Vec<&Named> {
     match expr {
         Expr::Union(union) => [
@@ -124,6 +179,20 @@ fn get_named_typed_expr(typed_expr: &impl TypedExpr) -> Vec<&Named> {
     .concat()
 }
 
+fn get_named_struct(s: &Struct) -> Vec<&Named> {
+    s.fields
+        .iter()
+        .flatten()
+        .flat_map(get_named_typed_expr)
+        .chain(
+            s.spreads
+                .iter()
+                .filter_map(|spread| spread.single.as_ref())
+                .flat_map(get_named_single),
+        )
+        .collect()
+}
+
 fn get_named_single(single: &Single) -> Vec<&Named> {
     match single {
         Single::Named(named) => [
@@ -133,23 +202,41 @@ fn get_named_single(single: &Single) -> Vec<&Named> {
                 .as_ref()
                 .map(|struct_or_list| match struct_or_list {
                     StructOrList::List(l) => l.exprs.iter().flat_map(get_named).collect(),
-                    StructOrList::Struct(s) => s
-                        .fields
-                        .iter()
-                        .flatten()
-                        .flat_map(get_named_typed_expr)
-                        .collect(),
+                    StructOrList::Struct(s) => get_named_struct(s),
                 })
                 .unwrap_or_default(),
         ]
         .concat(),
         Single::List(l) => l.exprs.iter().flat_map(get_named).collect(),
-        Single::Struct(s) => s
-            .fields
-            .iter()
-            .flatten()
-            .flat_map(get_named_typed_expr)
-            .collect(),
+        Single::Struct(s) => get_named_struct(s),
         Single::Primitive(_) => Vec::new(),
+        Single::Not(not) => not
+            .single
+            .as_deref()
+            .map(get_named_single)
+            .unwrap_or_default(),
+        Single::BoolOp(bool_op) => [
+            get_named_single(&bool_op.lhs),
+            bool_op
+                .rhs
+                .as_deref()
+                .map(get_named_single)
+                .unwrap_or_default(),
+        ]
+        .concat(),
+        Single::Neg(neg) => neg
+            .single
+            .as_deref()
+            .map(get_named_single)
+            .unwrap_or_default(),
+        Single::ArithOp(arith_op) => [
+            get_named_single(&arith_op.lhs),
+            arith_op
+                .rhs
+                .as_deref()
+                .map(get_named_single)
+                .unwrap_or_default(),
+        ]
+        .concat(),
     }
 }
@@ -0,0 +1,80 @@
+use crate::{
+    ast::{Ast, Global, HasInfo},
+    semantics::{
+        error::{Error, ErrorMessage, FIRST_INIT},
+        Errors,
+    },
+    token::{ToTokenRange, TokenRange},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Check the invariants of a file, that only depend on the order and kind of its globals.
+///
+/// A file has at most one init, every further init is reported at its expression
+/// with a secondary span at the first init.
+/// The later analyses still analyze all inits, but only the first one is emitted.
+/// Tests are no inits, so a file may contain any number of them.
+///
+/// Globals, that could not be parsed, are already reported by the parser.
+pub fn analyze(ast: &Ast) -> Errors {
+    let mut inits = ast.globals.iter().filter_map(|global| match global {
+        Global::Init(init) => Some(init),
+        _ => None,
+    });
+    let Some(first_init) = inits.next() else {
+        return Errors::new();
+    };
+    let first_range = first_init.expr.to_token_range();
+    inits
+        .map(|init| {
+            Error::new(ErrorMessage::MultipleInits, init.expr.to_token_range())
+                .with_secondary(FIRST_INIT, first_range.clone())
+        })
+        .collect()
+}
+
+/// Context of an init, which has no name
+pub const INIT_CONTEXT: &str = "<init>";
+
+/// Token ranges of the named globals and inits of a file, e.g. to tell,
+/// in which definition a diagnostic is located.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GlobalRanges {
+    /// Ranges sorted by their start and their context, e.g. `def Config` or `<init>`
+    ranges: Vec<(TokenRange, String)>,
+}
+
+impl GlobalRanges {
+    /// Uses and globals, that could not be parsed, have no context.
+    pub fn new(ast: &Ast) -> Self {
+        let ranges = ast
+            .globals
+            .iter()
+            .filter_map(|global| {
+                let context = match global {
+                    Global::Def(d) => format!("def {}", d.name.as_ref()?.name),
+                    Global::Let(l) => format!("let {}", l.name.as_ref()?.name),
+                    Global::Init(_) => INIT_CONTEXT.to_string(),
+                    Global::Test(t) => format!("test {}", t.name.as_ref()?.name),
+                    Global::Use(_) | Global::Error(_) => return None,
+                };
+                Some((global.info().range.clone(), context))
+            })
+            .collect();
+        Self { ranges }
+    }
+
+    /// Context of the global, that contains the start of the range.
+    /// An empty range at the end of a global, e.g. of a missing token, belongs to it.
+    pub fn enclosing(&self, range: &TokenRange) -> Option<&str> {
+        let index = self
+            .ranges
+            .partition_point(|(global_range, _)| global_range.start <= range.start);
+        let (global_range, context) = self.ranges.get(index.checked_sub(1)?)?;
+        let contains = global_range.contains(&range.start)
+            || (range.is_empty() && range.start == global_range.end);
+        contains.then_some(context.as_str())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    eval::Value,
+    lexer::{lex, LexerResult},
+    parser::{parse, ParserResult},
+    semantics::{
+        self,
+        error::{Error, ErrorMessage, FIRST_INIT},
+        structure::{GlobalRanges, INIT_CONTEXT},
+        SemanticsResult,
+    },
+    GlobalIdent, ModuleAst,
+};
+use pretty_assertions::assert_eq;
+
+fn analyze(src: &str) -> SemanticsResult {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    semantics::analyze(&module_ast)
+}
+
+#[test]
+fn single_init() {
+    let SemanticsResult { errors, inits, .. } = analyze("let a = 1 { a = a }");
+    assert_empty!(errors);
+    assert_eq!(vec![Value::from([("a", 1)])], inits);
+}
+
+#[test]
+fn no_init() {
+    let SemanticsResult { errors, inits, .. } = analyze("def A = { a: Number }");
+    assert_empty!(errors);
+    assert_empty!(inits);
+}
+
+#[test]
+fn three_inits() {
+    let SemanticsResult { errors, inits, .. } = analyze("{ a = 1 }\n{ b = 2 }\n{ c = 3 }");
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::MultipleInits, 10..19).with_secondary(FIRST_INIT, 0..9),
+            Error::new(ErrorMessage::MultipleInits, 20..29).with_secondary(FIRST_INIT, 0..9),
+        ],
+        errors
+    );
+    assert_eq!(
+        vec![
+            Value::from([("a", 1)]),
+            Value::from([("b", 2)]),
+            Value::from([("c", 3)]),
+        ],
+        inits
+    );
+}
+
+#[test]
+fn errors_inside_extra_inits() {
+    let SemanticsResult { errors, .. } =
+        analyze("def A = { a: Number }\nA { a = 1 }\nA { a = true }");
+    let messages: Vec<_> = errors.into_iter().map(|error| error.message).collect();
+    assert_eq!(
+        vec![
+            ErrorMessage::MultipleInits,
+            ErrorMessage::ValueMismatch("Number".to_string(), "true".to_string()),
+        ],
+        messages
+    );
+}
+
+#[test]
+fn enclosing_globals() {
+    let src = "use a::b\ndef A = { a: Number }\nlet c = 1\n{ a = 1 }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let globals = GlobalRanges::new(&ast);
+    let context = |token| globals.enclosing(&(token..token + 1));
+    // `use`, `def`, `A`, the newline after the def, `c`, `1` and the closing curly of the init
+    assert_eq!(None, context(0));
+    assert_eq!(Some("def A"), context(6));
+    assert_eq!(Some("def A"), context(8));
+    assert_eq!(None, context(20));
+    assert_eq!(Some("let c"), context(23));
+    assert_eq!(Some("let c"), context(27));
+    assert_eq!(Some(INIT_CONTEXT), context(37));
+    assert_eq!(None, context(100));
+}
+
+#[test]
+fn errors_in_two_defs() {
+    let src = "def A = { a: Number = true }\ndef B = { b: String = 1 }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let globals = GlobalRanges::new(&ast);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    let contexts: Vec<_> = errors
+        .iter()
+        .map(|error| globals.enclosing(&error.range))
+        .collect();
+    assert_eq!(vec![Some("def A"), Some("def B")], contexts);
+}
+
+#[test]
+fn tests_are_no_inits() {
+    let SemanticsResult { errors, inits, .. } = analyze(
+        "def A = { a: Number }\ntest one { A { a = 1 } }\ntest two { A { a = 2 } }\n{ b = 3 }",
+    );
+    assert_empty!(errors);
+    assert_eq!(vec![Value::from([("b", 3)])], inits);
+}
+
+#[test]
+fn errors_in_tests() {
+    let src = "def A = { a: Number }\ntest ok { A { a = 1 } }\ntest mismatch { A { a = true } }\ntest uninitialized { A }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let globals = GlobalRanges::new(&ast);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, inits, .. } = semantics::analyze(&module_ast);
+    assert_empty!(inits);
+    let errors: Vec<_> = errors
+        .iter()
+        .map(|error| (globals.enclosing(&error.range), error.message.clone()))
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                Some("test mismatch"),
+                ErrorMessage::ValueMismatch("Number".to_string(), "true".to_string())
+            ),
+            (Some("test uninitialized"), ErrorMessage::UninitializedInit),
+        ],
+        errors
+    );
+}
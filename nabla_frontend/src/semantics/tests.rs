@@ -1,14 +1,18 @@
 use crate::{
-    ast::Global,
-    eval::{eval, Value},
+    ast::{Global, HasInfo},
+    diagnostics::{self, Severity},
+    eval::{eval_literal, Value},
     lexer::{lex, LexerResult},
     parser::{parse, ParserResult},
+    resolver::MemoryResolver,
     semantics::{
         self,
-        error::{Error, ErrorMessage},
+        compat::{self, Target},
+        error::{Error, ErrorMessage, DECLARED_LOCALLY, FIELD_DEFINED, FIRST_DECLARED},
+        namespace::Binding,
         uses,
         values::{self, ValuesResult},
-        SemanticsResult,
+        Options, SemanticModel, SemanticsResult,
     },
     GlobalIdent, ModuleAst,
 };
@@ -39,7 +43,7 @@ use f::g as h
     let ParserResult { ast, errors } = parse(&tokens);
     assert_empty!(errors);
     let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
-    let (_, errors) = uses::analyze(&module_ast);
+    let (_, _, errors) = uses::analyze(&module_ast, None);
     assert_empty!(errors);
 }
 
@@ -54,12 +58,12 @@ use c::b
     let ParserResult { ast, errors } = parse(&tokens);
     assert_empty!(errors);
     let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
-    let (_, errors) = uses::analyze(&module_ast);
+    let (_, _, errors) = uses::analyze(&module_ast, None);
     assert_eq!(
-        vec![Error::new(
-            ErrorMessage::DuplicateUse("b".to_string()),
-            7..12
-        )],
+        vec![
+            Error::new(ErrorMessage::DuplicateUse("b".to_string()), 7..12)
+                .with_secondary(FIRST_DECLARED, 5..6)
+        ],
         errors
     );
 }
@@ -75,8 +79,79 @@ use c::b as d
     let ParserResult { ast, errors } = parse(&tokens);
     assert_empty!(errors);
     let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
-    let (_, errors) = uses::analyze(&module_ast);
+    let (_, _, errors) = uses::analyze(&module_ast, None);
+    assert_empty!(errors);
+}
+
+fn test_resolver() -> MemoryResolver {
+    let mut resolver = MemoryResolver::new();
+    for path in [&["config"][..], &["dir", "server"]] {
+        resolver.insert(
+            GlobalIdent::default().extend_multiple(path.iter().map(|s| s.to_string()).collect()),
+            String::new(),
+        );
+    }
+    resolver
+}
+
+#[test]
+fn use_valid_root() {
+    let src = "
+use root
+use root::config::Port
+use root::dir::{server server::Host}
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let (_, _, errors) = uses::analyze(&module_ast, Some(&test_resolver()));
+    assert_empty!(errors);
+}
+
+#[test]
+fn use_invalid_root() {
+    let src = "
+use lib::config::Port
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let resolver = test_resolver();
+    let SemanticsResult { errors, .. } = SemanticModel::new(&module_ast)
+        .with_resolver(&resolver)
+        .into_result();
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::UndefinedIdent("lib".to_string()),
+            3..4
+        )],
+        errors
+    );
+}
+
+#[test]
+fn use_invalid_nested_item() {
+    let src = "
+use root::{config::Port dir::client::Host}
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
     assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let (namespace, _, errors) = uses::analyze(&module_ast, Some(&test_resolver()));
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::UndefinedIdent("root::dir::client::Host".to_string()),
+            14..15
+        )],
+        errors
+    );
+    assert!(namespace.contains_key("Host"));
 }
 
 #[test]
@@ -152,11 +227,11 @@ fn evaluate_struct() {
         .globals
         .iter()
         .find_map(|global| match global {
-            Global::Init(init) => Some(init),
+            Global::Init(init) => Some(&init.expr),
             _ => None,
         })
         .unwrap();
-    let value = eval(init);
+    let value = eval_literal(init);
     assert_eq!(
         Value::from([
             ("name", Value::from("Test")),
@@ -182,11 +257,11 @@ fn evaluate_list() {
         .globals
         .iter()
         .find_map(|global| match global {
-            Global::Init(init) => Some(init),
+            Global::Init(init) => Some(&init.expr),
             _ => None,
         })
         .unwrap();
-    let value = eval(init);
+    let value = eval_literal(init);
     assert_eq!(Value::from(["a", "b", "c"]), value);
 }
 
@@ -224,11 +299,11 @@ fn evaluate_complex_struct() {
         .globals
         .iter()
         .find_map(|global| match global {
-            Global::Init(init) => Some(init),
+            Global::Init(init) => Some(&init.expr),
             _ => None,
         })
         .unwrap();
-    let value = eval(init);
+    let value = eval_literal(init);
     assert_eq!(
         Value::from([
             ("random_number", Value::from(42)),
@@ -244,6 +319,43 @@ fn evaluate_complex_struct() {
         ]),
         value
     );
+    let Value::Struct(fields) = &value else {
+        panic!("Expected struct");
+    };
+    assert_eq!(
+        vec!["random_number", "primes", "map"],
+        fields.keys().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn inits_keep_field_order() {
+    let src = r#"
+def Server = {
+    host: String
+    port: Number = 80
+    tls: Bool = false
+}
+Server {
+    port = 8080
+    host = "alpha"
+}
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    let Some(Value::Struct(fields)) = inits.first() else {
+        panic!("Expected struct init");
+    };
+    // the fields of the init come first, the defaults of the def are appended
+    assert_eq!(
+        vec!["port", "host", "tls"],
+        fields.keys().collect::<Vec<_>>()
+    );
 }
 
 #[test]
@@ -265,6 +377,54 @@ Config {
     assert_empty!(errors);
 }
 
+#[test]
+fn any_accepts_every_value() {
+    let src = r#"
+def Config = {
+    a: Any
+    b: Any
+    c: Any
+    d: [Any]
+}
+Config {
+    a = { host = "localhost" }
+    b = [1 2]
+    c = null
+    d = [{ x = 1 } { x = 2 }]
+}
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+}
+
+#[test]
+fn any_in_union() {
+    let src = r#"
+def Config = {
+    a: Any | null
+    b: Any | null
+    c: Any | null
+}
+Config {
+    a = { host = "localhost" }
+    b = [true]
+    c = null
+}
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+}
+
 #[test]
 fn self_reference_expr() {
     let src = "
@@ -305,6 +465,211 @@ def Type: Type = {}
     );
 }
 
+#[test]
+fn malformed_field_does_not_poison_siblings() {
+    let src = r#"
+def Server = {
+    host: String
+    port: )
+    tls: Bool
+}
+Server {
+    host = "localhost"
+    port = 80
+    tls = true
+}
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_eq!(1, errors.len());
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+}
+
+#[test]
+fn shadows_built_in() {
+    let src = "
+def String = { a = 1 }
+let x: String = { a = 2 }
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::ShadowsBuiltIn("String".to_string()),
+            3..4
+        )],
+        errors
+    );
+}
+
+#[test]
+fn shadowing_def_references_built_in() {
+    let src = "
+def Number = Number | null
+let x: Number = 3
+let y: Number = null
+let z: Number = \"a\"
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::ShadowsBuiltIn("Number".to_string()), 3..4),
+            Error::new(ErrorMessage::TypeMismatch, 44..45),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn multiple_list_types_in_def() {
+    let src = "
+def List = [Number String Bool]
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::MultipleListTypes, 10..11),
+            Error::new(ErrorMessage::MultipleListTypes, 12..13),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn multiple_list_types_in_annotation() {
+    let src = "
+let x: [Number String] = [1 2]
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::MultipleListTypes, 9..10)],
+        errors
+    );
+}
+
+#[test]
+fn unexpected_list_elements_at_the_element() {
+    let src = "let x: [] = [1 \"a\"]";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::UnexpecedListElement, 11..12),
+            Error::new(ErrorMessage::UnexpecedListElement, 13..14),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn missing_field_at_the_opening_brace() {
+    let src = "def Server = { tls: { enabled: Bool } }
+let server: Server = {
+    tls = {}
+}";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    let ranges: Vec<_> = errors
+        .iter()
+        .map(|error| {
+            let range = diagnostics::to_text_range(&tokens, &error.range);
+            (error.message.clone(), &src[range])
+        })
+        .collect();
+    assert_eq!(
+        vec![(ErrorMessage::MissingField("enabled".to_string()), "{")],
+        ranges
+    );
+}
+
+#[test]
+fn string_length_in_init() {
+    let src = r#"
+def Address = {
+    country: String(2)
+    name: String(1..)
+    code: String(..4) = "abc"
+}
+Address {
+    country = "DEU"
+    name = ""
+}
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { mut errors, .. } = semantics::analyze(&module_ast);
+    errors.sort_by_key(|error| error.range.start);
+    assert_eq!(
+        vec![
+            Error::new(
+                ErrorMessage::ValueMismatch("String(2)".to_string(), "DEU (length 3)".to_string()),
+                49..50
+            ),
+            Error::new(
+                ErrorMessage::ValueMismatch("String(1..)".to_string(), " (length 0)".to_string()),
+                55..56
+            ),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn invalid_string_length() {
+    let src = "
+def A = String(2.5)
+def B = String(3..2)
+def C = Number(2)
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::InvalidLength("(2.5)".to_string()), 8..11),
+            Error::new(ErrorMessage::InvalidLength("(3..2)".to_string()), 19..24),
+            Error::new(ErrorMessage::LengthOfNonString, 32..35),
+        ],
+        errors
+    );
+}
+
 #[test]
 fn legal_self_reference() {
     let src = r#"
@@ -438,6 +803,7 @@ let a = "x"
         inits,
         symbol_table,
         errors,
+        ..
     } = semantics::analyze(&module_ast);
     assert_empty!(errors);
     assert_eq!(
@@ -451,11 +817,11 @@ let a = "x"
 }
 
 #[test]
-fn default_init() {
+fn bindings() {
     let src = r#"
-def Config = {
-    x = 0
-}
+def Config = {}
+let a = "x"
+use x::y
 Config {}
     "#;
     let LexerResult { tokens, errors } = lex(src);
@@ -464,19 +830,49 @@ Config {}
     assert_empty!(errors);
     let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
     let SemanticsResult {
-        inits,
-        symbol_table,
-        errors,
+        bindings, errors, ..
     } = semantics::analyze(&module_ast);
     assert_empty!(errors);
     assert_eq!(
-        HashMap::from([(
-            GlobalIdent::default().extend("Config".to_string()),
-            Value::from([("x", 0)]),
-        )]),
-        symbol_table
+        HashMap::from([
+            (
+                GlobalIdent::default().extend("Config".to_string()),
+                Binding::Def
+            ),
+            (GlobalIdent::default().extend("a".to_string()), Binding::Let),
+        ]),
+        bindings
     );
-    assert_eq!(vec![Value::from([("x", 0)])], inits);
+}
+
+#[test]
+fn default_init() {
+    let src = r#"
+def Config = {
+    x = 0
+}
+Config {}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        inits,
+        symbol_table,
+        errors,
+        ..
+    } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        HashMap::from([(
+            GlobalIdent::default().extend("Config".to_string()),
+            Value::from([("x", 0)]),
+        )]),
+        symbol_table
+    );
+    assert_eq!(vec![Value::from([("x", 0)])], inits);
 }
 
 #[test]
@@ -498,6 +894,7 @@ Config {
         inits,
         symbol_table,
         errors,
+        ..
     } = semantics::analyze(&module_ast);
     assert_empty!(errors);
     assert_eq!(
@@ -510,6 +907,156 @@ Config {
     assert_eq!(vec![Value::from([("x", 1)])], inits);
 }
 
+/// Def with a default, that has no type annotation
+const UNTYPED_DEFAULT: &str = r#"
+def Config = {
+    x = 0
+    y = { z = "a" }
+}
+Config {
+    x = 1
+    y = { z = "b" }
+}
+    "#;
+
+#[test]
+fn untyped_default_overwrite() {
+    let LexerResult { tokens, errors } = lex(UNTYPED_DEFAULT);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![Value::from([
+            ("x", Value::from(1)),
+            ("y", Value::from([("z", "b")]))
+        ])],
+        inits
+    );
+}
+
+#[test]
+fn untyped_default_without_widening() {
+    let LexerResult { tokens, errors } = lex(UNTYPED_DEFAULT);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let options = Options {
+        widen_defaults: false,
+        ..Default::default()
+    };
+    let SemanticsResult { mut errors, .. } = semantics::analyze_with_options(&module_ast, &options);
+    // fields are checked in arbitrary order
+    errors.sort_by_key(|error| error.range.start);
+    assert_eq!(
+        vec![
+            Error::new(
+                ErrorMessage::ValueMismatch("0".to_string(), "1".to_string()),
+                39..40
+            ),
+            Error::new(
+                ErrorMessage::ValueMismatch("a".to_string(), "b".to_string()),
+                51..52
+            ),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn unexpected_field_at_init() {
+    let src = r#"
+def Config = {
+    b: Number = 0
+    inner: {
+        a: Number
+    }
+}
+Config {
+    inner = {
+        a = 1
+        b = 2
+    }
+}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::UnexpecedField("b".to_string()),
+            48..49
+        )],
+        errors
+    );
+    // the name in the init is underlined, not the field `b` of the def
+    let text_range = diagnostics::to_text_range(&tokens, &errors[0].range);
+    assert_eq!("b", &src[text_range.clone()]);
+    assert!(text_range.start > src.find("Config {").unwrap());
+}
+
+#[test]
+fn misspelled_field() {
+    let src = "def Pool = { maxConnections: Number }
+Pool { max_connections = 5 }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(
+                ErrorMessage::MisspelledField(
+                    "max_connections".to_string(),
+                    "maxConnections".to_string()
+                ),
+                19..20
+            )
+            .with_secondary(FIELD_DEFINED, 8..9),
+            // the value of `maxConnections` is still missing
+            Error::new(ErrorMessage::UninitializedInit, 15..26),
+        ],
+        errors
+    );
+    assert_eq!(
+        "field `max_connections` does not exist; did you mean `maxConnections`?",
+        errors[0].message.to_string()
+    );
+}
+
+#[test]
+fn misspelled_field_needs_unique_match() {
+    let src = "def Pool = { maxConnections: Number max_connections: Number timeout: Number }
+Pool { MaxConnections = 5 timeout_ms = 1 }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    let mut messages: Vec<_> = errors.into_iter().map(|error| error.message).collect();
+    messages.sort_by_key(ToString::to_string);
+    assert_eq!(
+        vec![
+            ErrorMessage::UninitializedInit,
+            ErrorMessage::MissingField("maxConnections".to_string()),
+            ErrorMessage::MissingField("max_connections".to_string()),
+            ErrorMessage::MissingField("timeout".to_string()),
+            ErrorMessage::UnexpecedField("MaxConnections".to_string()),
+            ErrorMessage::UnexpecedField("timeout_ms".to_string()),
+        ],
+        messages
+    );
+}
+
 #[test]
 fn nested_default() {
     let src = r#"
@@ -534,6 +1081,7 @@ Config {
         inits,
         symbol_table,
         errors,
+        ..
     } = semantics::analyze(&module_ast);
     assert_empty!(errors);
     assert_eq!(
@@ -550,13 +1098,148 @@ Config {
 }
 
 #[test]
-fn let_default() {
+fn layered_default() {
+    let src = r#"
+def Inner = {
+    a: Number = 1
+    b: Number = 2
+    c: Number = 3
+}
+def Config = {
+    x: Inner = {
+        b = 20
+        c = 30
+    }
+}
+Config {
+    x = {
+        c = 300
+    }
+}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        inits,
+        symbol_table,
+        errors,
+        ..
+    } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        Some(&Value::from([("x", [("a", 1), ("b", 20), ("c", 30)])])),
+        symbol_table.get(&GlobalIdent::default().extend("Config".to_string()))
+    );
+    assert_eq!(
+        vec![Value::from([("x", [("a", 1), ("b", 20), ("c", 300)])])],
+        inits
+    );
+}
+
+#[test]
+fn union_with_a_single_known_alternative() {
+    let src = r#"
+def Port = Number | null
+def Config = {
+    level: String | null
+    port: Port
+}
+let explicit: String | null = null
+Config {
+    level = null
+    port = null
+}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        inits,
+        symbol_table,
+        errors,
+        ..
+    } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    let root = GlobalIdent::default();
+    assert_eq!(
+        Some(&Value::Null),
+        symbol_table.get(&root.clone().extend("Port".to_string()))
+    );
+    assert_eq!(
+        Some(&Value::from([
+            ("level", Value::Null),
+            ("port", Value::Null)
+        ])),
+        symbol_table.get(&root.clone().extend("Config".to_string()))
+    );
+    assert_eq!(
+        Some(&Value::Null),
+        symbol_table.get(&root.extend("explicit".to_string()))
+    );
+    assert_eq!(
+        vec![Value::from([("level", Value::Null), ("port", Value::Null)])],
+        inits
+    );
+}
+
+#[test]
+fn union_with_a_default() {
+    let src = r#"
+def Level = "info"
+def Config = {
+    level: "debug" | Level = Level
+}
+Config {}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(vec![Value::from([("level", "info")])], inits);
+}
+
+#[test]
+fn union_with_multiple_known_alternatives() {
     let src = r#"
+def Level = "debug" | "info"
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        symbol_table,
+        errors,
+        ..
+    } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        Some(&Value::Unknown),
+        symbol_table.get(&GlobalIdent::default().extend("Level".to_string()))
+    );
+}
+
+/// Def with a default, that references a let declared after it
+const LET_DEFAULT: &str = r#"
 def Config = {
     x: Number = pi
 }
 let pi = 3.14
     "#;
+
+#[test]
+#[allow(clippy::approx_constant)]
+fn let_default() {
+    let src = LET_DEFAULT;
     let LexerResult { tokens, errors } = lex(src);
     assert_empty!(errors);
     let ParserResult { ast, errors } = parse(&tokens);
@@ -583,6 +1266,66 @@ let pi = 3.14
     );
 }
 
+#[test]
+fn forward_reference_allowed() {
+    let LexerResult { tokens, errors } = lex(LET_DEFAULT);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+}
+
+#[test]
+fn forward_reference_strict_order() {
+    let LexerResult { tokens, errors } = lex(LET_DEFAULT);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let options = Options {
+        strict_order: true,
+        ..Default::default()
+    };
+    let SemanticsResult { errors, .. } = semantics::analyze_with_options(&module_ast, &options);
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::ForwardReference("pi".to_string()),
+            16..17
+        )],
+        errors
+    );
+    assert_eq!(Severity::Warning, errors[0].message.severity());
+}
+
+#[test]
+fn multiple_inits_option() {
+    let src = "{ a = 1 }\n{ b = 2 }\n";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(1, errors.len());
+    assert_eq!(ErrorMessage::MultipleInits, errors[0].message);
+    let options = Options {
+        multiple_inits: true,
+        ..Default::default()
+    };
+    let SemanticsResult { errors, inits, .. } =
+        semantics::analyze_with_options(&module_ast, &options);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![
+            Value::from([("a", Value::from(1))]),
+            Value::from([("b", Value::from(2))])
+        ],
+        inits
+    );
+}
+
 #[test]
 fn uninitialized_default() {
     let src = r#"
@@ -619,10 +1362,7 @@ let rec = Rec {}
     assert_empty!(errors);
     let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
     assert_eq!(
-        vec![
-            Error::new(ErrorMessage::UninitializedDefault, 13..14),
-            Error::new(ErrorMessage::UninitializedLet, 17..28),
-        ],
+        vec![Error::new(ErrorMessage::RecursiveInit, 13..14)],
         errors
     );
 }
@@ -641,44 +1381,57 @@ let rec = {
     let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
     let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
     assert_eq!(
-        vec![
-            Error::new(ErrorMessage::UninitializedDefault, 13..14),
-            Error::new(ErrorMessage::UninitializedLet, 1..17),
-        ],
+        vec![Error::new(ErrorMessage::RecursiveInit, 13..14)],
         errors
     );
 }
 
-// #[test]
-// fn recursive_value_type() {
-//     let src = r#"
-// let x = {
-//      x: x = x
-// }
-//     "#;
-//     let LexerResult {tokens, errors} = lex(src);
-//     assert_empty!(errors);
-//     let ParserResult {ast, errors} = parse(&tokens);
-//     assert_empty!(errors);
-//     let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
-//     let TypeInfo { errors, .. } = types::analyze(&module_ast);
-//     assert_empty!(errors);
-//     let SemanticsResult { errors, ..} = values::analyze(&module_ast, &HashMap::new(), &HashMap::new());
-//     assert_eq!(
-//         vec![Error::new(ErrorMessage::RecursiveInit, 13..14)],
-//         errors
-//     );
-// }
-
 #[test]
-fn immutable_let() {
-    let src = r#"
-let x = {
-    a = "a"
-}
-
-x {
-    a = "b"
+fn recursive_let_chain() {
+    let src = "
+let a = b
+let b = a
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(vec![Error::new(ErrorMessage::RecursiveInit, 7..8)], errors);
+}
+
+#[test]
+fn recursive_value_type() {
+    let src = r#"
+let x = {
+     x: x = x
+}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::RecursiveInit, 12..13),
+            Error::new(ErrorMessage::RecursiveInit, 16..17),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn immutable_let() {
+    let src = r#"
+let x = {
+    a = "a"
+}
+
+x {
+    a = "b"
 }
     "#;
     let LexerResult { tokens, errors } = lex(src);
@@ -695,3 +1448,1521 @@ x {
         errors
     );
 }
+
+#[test]
+fn bare_let_init() {
+    let src = r#"
+let config = {
+    a = 1
+    b = [true]
+}
+config
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, inits, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    let expected = Value::from([("a", Value::from(1)), ("b", Value::from([true]))]);
+    assert_eq!(vec![expected], inits);
+    // without the symbol table, the reference is not resolved
+    let init = module_ast
+        .ast
+        .globals
+        .iter()
+        .find_map(|global| match global {
+            Global::Init(init) => Some(&init.expr),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(Value::Unknown, eval_literal(init));
+}
+
+#[test]
+fn bare_typed_let_init() {
+    let src = "def Config = { a: Number } let config: Config = { a = 1 } config";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, inits, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(vec![Value::from([("a", 1)])], inits);
+}
+
+#[test]
+fn opaque_annotation() {
+    let src = r#"
+def Opaque
+def Config = {
+    external: Opaque
+}
+Config {
+    external = [1 "a" { b = true }]
+}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::OpaqueUsage("Opaque".to_string()),
+            16..17
+        )],
+        errors
+    );
+    assert!(errors
+        .iter()
+        .all(|error| error.message.severity() == Severity::Warning));
+}
+
+#[test]
+fn use_then_local() {
+    let src = "
+use a::b
+def b = {}
+    ";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::ImportShadowedByLocal("b".to_string()), 5..6)
+                .with_secondary(DECLARED_LOCALLY, 9..10)
+        ],
+        errors
+    );
+}
+
+#[test]
+fn local_then_use() {
+    let src = "
+let b = 1
+use a::b
+    ";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::ImportShadowedByLocal("b".to_string()), 13..14)
+                .with_secondary(DECLARED_LOCALLY, 3..4)
+        ],
+        errors
+    );
+}
+
+#[test]
+fn redeclaration_of_shadowing_local() {
+    let src = "
+use a::b
+def b = {}
+let b = 1
+    ";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::ImportShadowedByLocal("b".to_string()), 5..6)
+                .with_secondary(DECLARED_LOCALLY, 9..10),
+            Error::new(ErrorMessage::Redeclaration("b".to_string()), 18..19)
+                .with_secondary(FIRST_DECLARED, 9..10),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn opaque_redeclaration() {
+    let src = "
+def Opaque
+def Opaque
+    ";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::Redeclaration("Opaque".to_string()), 7..8)
+                .with_secondary(FIRST_DECLARED, 3..4)
+        ],
+        errors
+    );
+}
+
+#[test]
+fn list_index() {
+    let src = r#"
+let hosts = ["a" "b"]
+{
+    primary = hosts::1
+}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(vec![Value::from([("primary", "b")])], inits);
+}
+
+#[test]
+fn list_index_out_of_bounds() {
+    let src = r#"
+let hosts = ["a" "b"]
+let primary = hosts::2
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::IndexOutOfBounds(2, 2), 21..22),
+            Error::new(ErrorMessage::UninitializedLet, 13..22),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn index_non_list() {
+    let src = r#"
+let host = "a"
+let primary = host::0
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::NotIndexable, 17..18),
+            Error::new(ErrorMessage::UninitializedLet, 9..18),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn list_index_type() {
+    let src = r#"
+let hosts = ["a" "b"]
+let port: Number = hosts::0
+let ports: [Number] = [80]
+let port2: String = ports::3
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(
+                ErrorMessage::ValueMismatch("Number".to_string(), "a".to_string()),
+                8..9
+            ),
+            Error::new(ErrorMessage::TypeMismatch, 50..53),
+            Error::new(ErrorMessage::IndexOutOfBounds(3, 1), 52..53),
+            Error::new(ErrorMessage::UninitializedLet, 41..53),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn field_access() {
+    let src = r#"
+let server = {
+    host = "x"
+    port = 80
+    tls = {
+        enabled = true
+    }
+}
+{
+    url_host = server::host
+    tls = server::tls::enabled
+}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![Value::from([
+            ("url_host", Value::from("x")),
+            ("tls", Value::from(true))
+        ])],
+        inits
+    );
+}
+
+#[test]
+fn field_access_missing_field() {
+    let src = r#"
+let server = {
+    host = "x"
+}
+let user = server::user
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::UndefinedField("user".to_string()), 25..26),
+            Error::new(ErrorMessage::UninitializedLet, 17..26),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn field_access_on_primitive() {
+    let src = r#"
+let host = "x"
+let name = host::name
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::FieldOfNonStruct, 17..18),
+            Error::new(ErrorMessage::UninitializedLet, 9..18),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn field_access_type() {
+    let src = r#"
+let server = {
+    host = "x"
+}
+let host: Number = server::host
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::ValueMismatch("Number".to_string(), "x".to_string()),
+            13..14
+        )],
+        errors
+    );
+}
+
+#[test]
+fn init_alias() {
+    let src = r#"{ a = 1 } as "config""#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        init_name, errors, ..
+    } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(Some("config".to_string()), init_name);
+}
+
+#[test]
+fn init_alias_must_be_string() {
+    let src = "{ a = 1 } as config";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        init_name, errors, ..
+    } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::AliasMustBeString, 10..13)],
+        errors
+    );
+    assert_eq!(None, init_name);
+}
+
+#[test]
+fn let_alias_names_init() {
+    let src = r#"
+db_config
+let db_config = { host = "localhost" } as "database-config"
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        inits,
+        init_name,
+        errors,
+        ..
+    } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(Some("database-config".to_string()), init_name);
+    assert_eq!(vec![Value::from([("host", "localhost")])], inits);
+}
+
+#[test]
+fn init_alias_overrides_def_alias() {
+    let analyze = |init: &str| {
+        let src = format!("def Config = {{ a: Number }} as \"config\"\n{}", init);
+        let LexerResult { tokens, errors } = lex(&src);
+        assert_empty!(errors);
+        let ParserResult { ast, errors } = parse(&tokens);
+        assert_empty!(errors);
+        let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+        let SemanticsResult {
+            init_name, errors, ..
+        } = semantics::analyze(&module_ast);
+        assert_empty!(errors);
+        init_name
+    };
+    assert_eq!(Some("config".to_string()), analyze("Config { a = 1 }"));
+    assert_eq!(
+        Some("main".to_string()),
+        analyze(r#"Config { a = 1 } as "main""#)
+    );
+    // only a direct reference is emitted by the alias of the def
+    assert_eq!(None, analyze("{ c = Config { a = 1 } }"));
+}
+
+#[test]
+fn aliased_spread_is_field() {
+    let src = r#"
+let db_config = { host = "db" } as "database-config"
+def Cache = { ttl: Number = 60 } as "cache"
+{ ...db_config ...Cache name = "app" }
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![Value::from([
+            ("database-config", Value::from([("host", "db")])),
+            ("cache", Value::from([("ttl", 60)])),
+            ("name", Value::from("app")),
+        ])],
+        inits
+    );
+}
+
+#[test]
+fn aliased_spread_in_def_is_field() {
+    let src = r#"
+def Db = { host: String } as "db"
+def App = { ...Db name: String }
+App { name = "app" db = { } }
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    // `db` is a field of `App` of the type `Db`, whose `host` is missing
+    assert_eq!(
+        vec![
+            ErrorMessage::MissingField("host".to_string()),
+            ErrorMessage::UninitializedInit,
+        ],
+        errors
+            .into_iter()
+            .map(|error| error.message)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn let_alias_must_be_string() {
+    let src = "let a = 1 as b";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::AliasMustBeString, 8..11)],
+        errors
+    );
+}
+
+#[test]
+fn field_alias_must_be_string() {
+    let src = "{ a = { b = 1 as c } as d }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::AliasMustBeString, 20..23),
+            Error::new(ErrorMessage::AliasMustBeString, 14..17),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn use_alias_must_be_ident() {
+    let src = r#"
+use a as "b"
+use c::{d as "e" f::g as "h"}
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::AliasMustBeIdent, 5..8),
+            Error::new(ErrorMessage::AliasMustBeIdent, 16..19),
+            Error::new(ErrorMessage::AliasMustBeIdent, 24..27),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn aliasing_non_single() {
+    let src = "use a::b::{c d} as x";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::AliasingNonSingle("b".to_string()),
+            12..15
+        )],
+        errors
+    );
+}
+
+#[test]
+fn valid_aliases() {
+    let src = r#"
+use a as b
+use c::{d as e f::g as h}
+{ a = { b = 1 as "c" } as "d" } as "root"
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+}
+
+#[test]
+fn bool_operators_through_lets() {
+    let src = "
+let debug = true
+let quiet = false
+{
+    verbose = !debug
+    enabled = debug && !quiet || false
+    both = debug && quiet
+}
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![Value::from([
+            ("verbose", false),
+            ("enabled", true),
+            ("both", false)
+        ])],
+        inits
+    );
+}
+
+#[test]
+fn bool_operator_precedence_over_union() {
+    let src = "def Flag = true || false | null";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { types, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    let flag = GlobalIdent::default().extend("Flag".to_string());
+    assert_empty!(types.validate_ident(&flag, &Value::Bool(false)).unwrap());
+    assert_empty!(types.validate_ident(&flag, &Value::Null).unwrap());
+}
+
+#[test]
+fn bool_operator_type_mismatch() {
+    let src = r#"
+let n = 1
+{
+    a = !n
+    b = true && "x"
+}
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    let mismatches: Vec<_> = errors
+        .into_iter()
+        .filter(|error| matches!(error.message, ErrorMessage::ValueMismatch(_, _)))
+        .collect();
+    assert_eq!(
+        vec![
+            Error::new(
+                ErrorMessage::ValueMismatch("Bool".to_string(), "1".to_string()),
+                7..8
+            ),
+            Error::new(
+                ErrorMessage::ValueMismatch("Bool".to_string(), "x".to_string()),
+                26..27
+            ),
+        ],
+        mismatches
+    );
+}
+
+#[test]
+fn arith_operators_through_lets() {
+    let src = "
+let total_memory = 4096
+{
+    heap = total_memory * 0.75
+    stack = total_memory - heap_base * 2 + -8
+    ratio = 1 / 4
+    neg_zero = 0 * -1
+}
+let heap_base = 1000
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![Value::from([
+            ("heap", Value::Number("3072".to_string())),
+            ("stack", Value::Number("2088".to_string())),
+            ("ratio", Value::Number("0.25".to_string())),
+            ("neg_zero", Value::Number("0".to_string())),
+        ])],
+        inits
+    );
+}
+
+#[test]
+fn negative_list_elements() {
+    let src = "{ a = [1 -2 3] b = [-1 -2] c = [1 - 2] }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    let number = |n: &str| Value::Number(n.to_string());
+    assert_eq!(
+        vec![Value::from([
+            ("a", Value::List(vec![number("1"), number("-2"), number("3")])),
+            ("b", Value::List(vec![number("-1"), number("-2")])),
+            ("c", Value::List(vec![number("-1")])),
+        ])],
+        inits
+    );
+}
+
+#[test]
+fn arith_operator_type_mismatch() {
+    let src = r#"let x = true + "a""#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    let mismatches: Vec<_> = errors
+        .into_iter()
+        .filter(|error| matches!(error.message, ErrorMessage::ValueMismatch(_, _)))
+        .collect();
+    assert_eq!(
+        vec![
+            Error::new(
+                ErrorMessage::ValueMismatch("Number".to_string(), "true".to_string()),
+                6..7
+            ),
+            Error::new(
+                ErrorMessage::ValueMismatch("Number".to_string(), "a".to_string()),
+                10..11
+            ),
+        ],
+        mismatches
+    );
+}
+
+#[test]
+fn division_by_zero() {
+    let src = "let zero = 0\nlet x = 1 + 2 / zero";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::DivisionByZero, 18..23),
+            Error::new(ErrorMessage::UninitializedLet, 8..23),
+        ],
+        errors
+    );
+}
+
+fn compat_errors(src: &str, target: Target) -> Vec<Error> {
+    compat_errors_with_options(src, target, &compat::Options::default())
+}
+
+fn compat_errors_with_options(src: &str, target: Target, options: &compat::Options) -> Vec<Error> {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    compat::check_with_options(&module_ast, &inits[0], target, options)
+}
+
+#[test]
+fn compat_toml_null() {
+    let src = "let nothing = null\n{ a = null b = [1 nothing] c = 1 }";
+    let unrepresentable =
+        |construct: &str| ErrorMessage::Unrepresentable(construct.to_string(), "TOML".to_string());
+    assert_eq!(
+        vec![
+            Error::new(unrepresentable("`null`"), 14..15),
+            Error::new(unrepresentable("`null`"), 23..24),
+        ],
+        compat_errors(src, Target::Toml)
+    );
+    assert_empty!(compat_errors(src, Target::Json));
+}
+
+#[test]
+fn compat_toml_root() {
+    let src = "[1 2]";
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::Unrepresentable(
+                "a top-level value, that is no struct".to_string(),
+                "TOML".to_string()
+            ),
+            0..5
+        )],
+        compat_errors(src, Target::Toml)
+    );
+    assert_empty!(compat_errors(src, Target::Yaml));
+}
+
+#[test]
+fn compat_xml() {
+    let src = "{ a = [[1] 2] b = 1 }";
+    let unrepresentable =
+        |construct: &str| ErrorMessage::Unrepresentable(construct.to_string(), "XML".to_string());
+    assert_eq!(
+        vec![Error::new(
+            unrepresentable("a list inside of a list"),
+            7..10
+        )],
+        compat_errors(src, Target::Xml)
+    );
+    assert_eq!(
+        vec![Error::new(unrepresentable("a top-level list"), 0..4)],
+        compat_errors("[{}]", Target::Xml)
+    );
+}
+
+#[test]
+fn compat_xml_wrapped_lists() {
+    let options = compat::Options {
+        xml_wrapped_lists: true,
+        ..Default::default()
+    };
+    assert_empty!(compat_errors_with_options(
+        "{ a = [[1] 2] b = 1 }",
+        Target::Xml,
+        &options
+    ));
+    assert_empty!(compat_errors_with_options("[{}]", Target::Xml, &options));
+}
+
+#[test]
+fn compat_xml_attributes() {
+    let src = r##"{
+    host = "x" as "@host"
+    text = "hello" as "#text"
+    tags = ["a"] as "@tags"
+    server = {} as "#other"
+}"##;
+    let unrepresentable =
+        |construct: &str| ErrorMessage::Unrepresentable(construct.to_string(), "XML".to_string());
+    assert_eq!(
+        vec![
+            Error::new(unrepresentable("a struct or list as `@tags`"), 26..29),
+            Error::new(unrepresentable("the field name `#other`"), 38..40),
+        ],
+        compat_errors(src, Target::Xml)
+    );
+}
+
+#[test]
+fn compat_severity() {
+    let message = ErrorMessage::Unrepresentable("`null`".to_string(), "TOML".to_string());
+    assert_eq!(Severity::Warning, message.severity());
+    assert_eq!("`null` cannot be represented in TOML", message.to_string());
+}
+
+#[test]
+fn hidden_field() {
+    let src = "
+let base = {
+    port = 8080
+    internal = true as _
+}
+base
+    ";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        inits,
+        symbol_table,
+        errors,
+        ..
+    } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        HashMap::from([(
+            GlobalIdent::default().extend("base".to_string()),
+            Value::from([("internal", Value::Bool(true)), ("port", Value::from(8080))]),
+        )]),
+        symbol_table
+    );
+    assert_eq!(vec![Value::from([("port", 8080)])], inits);
+}
+
+/// Defs with a hidden field, that is overwritten by the init
+const HIDDEN_DEF_FIELD: &str = "
+def Base = {
+    helper: Number = 1 as _
+    name: String
+}
+def App = {
+    base: Base
+    debug: Bool = false as _
+}
+App {
+    base = {
+        helper = 2
+        name = \"app\"
+    }
+    debug = true
+}
+";
+
+#[test]
+fn hidden_field_stays_hidden() {
+    let LexerResult { tokens, errors } = lex(HIDDEN_DEF_FIELD);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![Value::from([("base", Value::from([("name", "app")]))])],
+        inits
+    );
+}
+
+#[test]
+fn hidden_field_is_type_checked() {
+    let src = HIDDEN_DEF_FIELD.replace("helper = 2", "helper = true");
+    let LexerResult { tokens, errors } = lex(&src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![ErrorMessage::ValueMismatch(
+            "Number".to_string(),
+            "true".to_string()
+        )],
+        errors
+            .into_iter()
+            .map(|error| error.message)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn aliased_fields_are_emitted_by_their_alias() {
+    let src = "
+def Server = {
+    max_size: Number = 1 as \"max-size\"
+    options: {
+        content_type: String = \"json\" as \"content-type\"
+    }
+}
+let server = Server { max_size = 2 options = {} }
+{
+    servers = [server]
+    first = server::max_size
+}
+";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    let server = Value::from([
+        ("max-size", Value::from(2)),
+        ("options", Value::from([("content-type", "json")])),
+    ]);
+    assert_eq!(
+        vec![Value::from([
+            ("servers", Value::List(vec![server])),
+            ("first", Value::from(2)),
+        ])],
+        inits
+    );
+}
+
+#[test]
+fn subsection_init() {
+    let src = r#"
+def Config = {
+    database: {
+        host: String
+        port: Number = 5432
+    }
+    name: String
+}
+Config::database { host = "x" }
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        vec![Value::from([
+            ("host", Value::from("x")),
+            ("port", Value::from(5432))
+        ])],
+        inits
+    );
+}
+
+#[test]
+fn subsection_missing_field() {
+    let src = "def Config = { database: { host: String port: Number = 5432 } }
+Config::database { port = 1 }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::MissingField("host".to_string()), 35..36),
+            Error::new(ErrorMessage::UninitializedInit, 31..44),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn subsection_invalid_path() {
+    let src = "def Config = { database: { host: String } }
+Config::db::host { }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::UndefinedField("db".to_string()),
+            24..25
+        )],
+        errors
+    );
+}
+
+#[test]
+fn semantic_model_computes_parts_on_demand() {
+    let src = "def Port = Number
+let x: Port = \"80\"
+let y = 1 / 0";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let model = SemanticModel::new(&module_ast);
+    assert_empty!(model.errors());
+    let types = model.types();
+    assert!(std::ptr::eq(types, model.types()));
+    let type_mismatch = Error::new(
+        ErrorMessage::ValueMismatch("Number".to_string(), "80".to_string()),
+        17..18,
+    );
+    assert_eq!(vec![type_mismatch.clone()], model.errors());
+    model.values();
+    let errors = model.errors();
+    assert_eq!(type_mismatch, errors[0]);
+    assert!(errors.contains(&Error::new(ErrorMessage::DivisionByZero, 25..30)));
+    assert_eq!(errors, model.into_result().errors);
+    assert_eq!(errors, semantics::analyze(&module_ast).errors);
+}
+
+/// Compute every part of the model, so that `errors` contains all of them.
+fn compute_all(model: &SemanticModel) {
+    model.structure_errors();
+    model.directives();
+    model.alias_errors();
+    model.types();
+    model.values();
+}
+
+fn assert_same_analysis(model: &SemanticModel, module_ast: &ModuleAst, context: &str) {
+    let fresh = SemanticModel::new(module_ast);
+    compute_all(model);
+    compute_all(&fresh);
+    assert_eq!(fresh.errors(), model.errors(), "{}", context);
+    let (types, fresh_types) = (model.types(), fresh.types());
+    assert_eq!(fresh_types.to_dot(), types.to_dot(), "{}", context);
+    assert_eq!(fresh_types.idents, types.idents, "{}", context);
+    assert_eq!(fresh_types.defs, types.defs, "{}", context);
+    let (values, fresh_values) = (model.values(), fresh.values());
+    assert_eq!(fresh_values.inits, values.inits, "{}", context);
+    assert_eq!(fresh_values.init_name, values.init_name, "{}", context);
+    assert_eq!(
+        fresh_values.symbol_table, values.symbol_table,
+        "{}",
+        context
+    );
+    assert_eq!(fresh_values.defaults, values.defaults, "{}", context);
+    assert_eq!(fresh.directives(), model.directives(), "{}", context);
+}
+
+#[test]
+fn update_global() {
+    let src = "def Port = Number
+let x: Port = 80
+let y = x";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let mut model = SemanticModel::new(&module_ast);
+    compute_all(&model);
+    let ParserResult { ast, .. } = parse(&lex("let x: Port = \"80\"").tokens);
+    let global = ast.globals[0].clone();
+    model.update_global(1, global.clone());
+    assert_empty!(model.errors());
+    compute_all(&model);
+    assert_eq!(
+        ErrorMessage::ValueMismatch("Number".to_string(), "80".to_string()),
+        model.errors()[0].message
+    );
+    let mut expected = module_ast.clone();
+    expected.ast.globals[1] = global;
+    assert_eq!(&expected, model.module_ast());
+    assert_same_analysis(&model, &expected, "x is a string");
+}
+
+#[test]
+fn update_first_global_keeps_directives() {
+    let src = "// nabla: target=yaml
+let x = 80";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let mut model = SemanticModel::new(&module_ast);
+    compute_all(&model);
+    assert_eq!(Some(Target::Yaml), model.directives().target);
+    // the leading comments are parsed as part of the module, not of its first global
+    let ParserResult { ast, .. } = parse(&lex("// nabla: target=toml\nlet x = 81").tokens);
+    let global = ast.globals[0].clone();
+    assert!(global.info().prelude.comments.is_empty());
+    model.update_global(0, global.clone());
+    assert_eq!(Some(Target::Yaml), model.directives().target);
+    let mut expected = module_ast.clone();
+    expected.ast.globals[0] = global;
+    assert_same_analysis(&model, &expected, "x is 81");
+}
+
+/// Generator of pseudo random numbers (xorshift), so that failures are reproducible.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+/// Replace random globals of the corpus files by globals of other corpus files.
+/// The updated model must be the same as a new analysis of the changed module.
+#[test]
+fn update_global_matches_new_analysis() {
+    let corpus_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut sources: Vec<_> = std::fs::read_dir(corpus_dir)
+        .expect("Corpus directory must exist")
+        .map(|entry| entry.expect("Reading corpus entry failed").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "nabla"))
+        .collect();
+    sources.sort();
+    let modules: Vec<_> = sources
+        .iter()
+        .map(|source| {
+            let src = std::fs::read_to_string(source).expect("Reading corpus file failed");
+            let ParserResult { ast, .. } = parse(&lex(&src).tokens);
+            (source, ModuleAst::new(GlobalIdent::default(), ast))
+        })
+        .collect();
+    let pool: Vec<&Global> = modules
+        .iter()
+        .flat_map(|(_, module_ast)| &module_ast.ast.globals)
+        .collect();
+    let mut random = Random(0x5eed);
+    for (source, module_ast) in &modules {
+        if module_ast.ast.globals.is_empty() {
+            continue;
+        }
+        let mut expected = module_ast.clone();
+        let mut model = SemanticModel::new(module_ast);
+        for step in 0..8 {
+            // only some parts are computed before the update
+            match random.next(4) {
+                0 => {}
+                1 => {
+                    model.types();
+                }
+                2 => {
+                    model.values();
+                }
+                _ => compute_all(&model),
+            }
+            let index = random.next(expected.ast.globals.len());
+            let global = pool[random.next(pool.len())].clone();
+            expected.ast.globals[index] = global.clone();
+            model.update_global(index, global);
+            let context = format!("{} at step {}", source.display(), step);
+            assert_same_analysis(&model, &expected, &context);
+        }
+    }
+}
+
+#[test]
+fn defaults_of_unreferenced_def() {
+    let src = r#"
+def Config = {
+    host = "localhost"
+    secret = "x" as _
+}
+    "#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult {
+        inits,
+        symbol_table,
+        defaults,
+        errors,
+        ..
+    } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_empty!(inits);
+    let ident = GlobalIdent::default().extend("Config".to_string());
+    assert_eq!(
+        Some(&Value::from([("host", "localhost"), ("secret", "x")])),
+        symbol_table.get(&ident)
+    );
+    assert_eq!(
+        HashMap::from([(ident, Value::from([("host", "localhost")]))]),
+        defaults
+    );
+}
+
+/// Evaluate the init of `expr_src` against the symbol table of `src`.
+fn eval_in_module(src: &str, expr_src: &str) -> (Value, Vec<Error>) {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { symbol_table, .. } = semantics::analyze(&module_ast);
+    let LexerResult { tokens, errors } = lex(expr_src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let Some(Global::Init(init)) = ast.globals.first() else {
+        panic!("Expected init");
+    };
+    values::eval_expr(&init.expr, &GlobalIdent::default(), &symbol_table)
+}
+
+#[test]
+fn eval_expr_with_let() {
+    let (value, errors) = eval_in_module("let port = 8080", "{ server = { port = port } }");
+    assert_empty!(errors);
+    assert_eq!(
+        Value::from([("server", Value::from([("port", 8080)]))]),
+        value
+    );
+}
+
+#[test]
+fn eval_expr_missing_name() {
+    let (value, errors) = eval_in_module("let port = 8080", "{ host = host }");
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::UndefinedIdent("host".to_string()),
+            6..7
+        )],
+        errors
+    );
+    assert!(!value.is_known());
+}
+
+#[test]
+fn eval_expr_composed_with_def() {
+    let (value, errors) = eval_in_module(
+        "def Config = { host = \"localhost\" port: Number = 80 }",
+        "Config { port = 8080 }",
+    );
+    assert_empty!(errors);
+    assert_eq!(
+        Value::from([
+            ("host", Value::from("localhost")),
+            ("port", Value::from(8080))
+        ]),
+        value
+    );
+}
+
+/// Analyze the module `name` with the modules of the tree `root`, that it imports.
+fn analyze_in_tree(name: GlobalIdent, src: &str, modules: &[(&str, &str)]) -> SemanticsResult {
+    let mut resolver = MemoryResolver::new();
+    for (module, module_src) in modules {
+        resolver.insert(
+            GlobalIdent::default().extend(module.to_string()),
+            module_src.to_string(),
+        );
+    }
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(name, ast);
+    semantics::analyze_modules(&module_ast, &resolver)
+}
+
+#[test]
+fn imported_let() {
+    let SemanticsResult { inits, errors, .. } = analyze_in_tree(
+        GlobalIdent::default(),
+        "use root::shared::port\n{ port = port }",
+        &[("shared", "let port = 8080")],
+    );
+    assert_empty!(errors);
+    assert_eq!(vec![Value::from([("port", 8080)])], inits);
+}
+
+#[test]
+fn imported_module_reference() {
+    let SemanticsResult {
+        inits,
+        errors,
+        symbol_table,
+        ..
+    } = analyze_in_tree(
+        GlobalIdent::default(),
+        "use shared\n{ port = shared::ports::1 }",
+        &[("shared", "let ports = [80 8080]")],
+    );
+    assert_empty!(errors);
+    assert_eq!(vec![Value::from([("port", 8080)])], inits);
+    assert_eq!(
+        Some(&Value::List(vec![Value::from(80), Value::from(8080)])),
+        symbol_table.get(&GlobalIdent::new("shared".to_string()).extend("ports".to_string()))
+    );
+}
+
+#[test]
+fn imported_def_is_type_checked() {
+    let SemanticsResult { errors, .. } = analyze_in_tree(
+        GlobalIdent::default(),
+        "use root::shared::Server\nServer { port = \"80\" }",
+        &[("shared", "def Server = { port: Number }")],
+    );
+    assert_eq!(
+        vec![ErrorMessage::ValueMismatch(
+            "Number".to_string(),
+            "80".to_string()
+        )],
+        errors
+            .into_iter()
+            .map(|error| error.message)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn imported_def_keeps_hidden_fields() {
+    let SemanticsResult { inits, errors, .. } = analyze_in_tree(
+        GlobalIdent::default(),
+        "use root::shared::Server\nServer { port = 8080 }",
+        &[(
+            "shared",
+            "def Server = { port: Number scheme: String = \"https\" as _ }",
+        )],
+    );
+    assert_empty!(errors);
+    assert_eq!(vec![Value::from([("port", 8080)])], inits);
+}
+
+#[test]
+fn unresolved_module() {
+    let SemanticsResult { errors, .. } = analyze_in_tree(
+        GlobalIdent::default(),
+        "use root::missing::port\n{ port = port }",
+        &[],
+    );
+    assert_eq!(
+        Some(&Error::new(
+            ErrorMessage::UnresolvedModule("module `root::missing` not found".to_string()),
+            6..7
+        )),
+        errors.first()
+    );
+}
+
+#[test]
+fn module_imports_itself() {
+    let SemanticsResult { errors, .. } = analyze_in_tree(
+        GlobalIdent::default().extend("a".to_string()),
+        "use root::a::port\nlet port = 80",
+        &[("a", "use root::a::port\nlet port = 80")],
+    );
+    assert_eq!(
+        Some(&Error::new(
+            ErrorMessage::ImportCycle(vec!["root::a".to_string(), "root::a".to_string()]),
+            6..7
+        )),
+        errors.first()
+    );
+}
+
+#[test]
+fn import_cycle_terminates() {
+    let SemanticsResult { inits, errors, .. } = analyze_in_tree(
+        GlobalIdent::default(),
+        "use root::a::x\n{ x = x }",
+        &[
+            ("a", "use root::b::y\nlet x = 1"),
+            ("b", "use root::a::x\nlet y = x"),
+        ],
+    );
+    // `b` reports the cycle and its unknown `y`, `a` the errors of `b` and the cycle again
+    let cycle = ["root::a", "root::b", "root::a"]
+        .map(str::to_string)
+        .to_vec();
+    assert_eq!(
+        vec![
+            Error::new(ErrorMessage::ModuleErrors("root::a".to_string(), 2), 6..7),
+            Error::new(ErrorMessage::ImportCycle(cycle), 6..7)
+        ],
+        errors
+    );
+    assert_eq!(vec![Value::from([("x", 1)])], inits);
+}
+
+#[test]
+fn duplicate_field_last_wins() {
+    let src = "{ a = 1 b = 2 a = 3 }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(
+            ErrorMessage::DuplicateField("a".to_string()),
+            14..19
+        )],
+        errors
+    );
+    let Some(Value::Struct(fields)) = inits.first() else {
+        panic!("Expected struct init");
+    };
+    // the value of the last field at the position of the first
+    assert_eq!(
+        vec![
+            ("a", &Value::Number("3".to_string())),
+            ("b", &Value::Number("2".to_string())),
+        ],
+        fields
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn spread_fields_are_overridden_in_order() {
+    let src = "let base = { host = \"localhost\" tls = true }
+let extra = { tls = false }
+{ ...base ...extra port = 8080 tls = true }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { inits, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    let Some(Value::Struct(fields)) = inits.first() else {
+        panic!("Expected struct init");
+    };
+    assert_eq!(
+        vec![
+            ("host", &Value::String("localhost".into())),
+            ("tls", &Value::Bool(true)),
+            ("port", &Value::Number("8080".to_string())),
+        ],
+        fields
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn spread_def_composes_type() {
+    let src = "def Base = { host: String }
+def Server = { ...Base port: Number }
+Server { port = 8080 }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert!(errors
+        .iter()
+        .any(|error| error.message == ErrorMessage::MissingField("host".to_string())));
+}
+
+#[test]
+fn spread_of_non_struct() {
+    let src = "let port = 8080\n{ ...port }";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_eq!(
+        vec![Error::new(ErrorMessage::SpreadOfNonStruct, 10..12)],
+        errors
+    );
+}
@@ -1,16 +1,34 @@
 use crate::{
     ast::*,
-    semantics::{error::Error, types::analysis::TypeAnalyzer, BindingMap, Namespace},
+    eval::Value,
+    semantics::{
+        error::{Error, ErrorMessage},
+        types::analysis::TypeAnalyzer,
+        BindingMap, Namespace,
+    },
+    token::{ToTokenRange, TokenRange},
     GlobalIdent, ModuleAst,
 };
-use std::{array::IntoIter, collections::HashMap};
+use std::{
+    array::IntoIter,
+    collections::{HashMap, HashSet},
+};
+
+pub use resolved::ResolvedType;
+pub use validation::{validate, ValidationError};
 
 mod analysis;
 mod assertions;
+mod dot;
+mod resolved;
+#[cfg(test)]
+mod tests;
+mod validation;
 
 pub const STRING: &str = "String";
 pub const NUMBER: &str = "Number";
 pub const BOOL: &str = "Bool";
+pub const ANY: &str = "Any";
 
 /// Index into rule list
 type RuleIndex = usize;
@@ -19,18 +37,30 @@ type RuleIndex = usize;
 pub struct Rule {
     pub type_description: TypeDescription,
     pub info: AstInfo,
+    /// Whether the rule stands for a node, that the parser could not parse.
+    /// Its error was already reported, so assertions involving it are not checked.
+    pub is_error_recovery: bool,
 }
 
 #[derive(Clone, Debug)]
 pub enum TypeDescription {
     Union(Vec<RuleIndex>),
-    Struct(HashMap<Ident, (RuleIndex, bool)>),
+    /// Fields by their name, with their rule, whether they have a default and their name's range
+    Struct(HashMap<String, (RuleIndex, bool, TokenRange)>),
     List(Vec<RuleIndex>),
     Ident(GlobalIdent),
     ValidIdent(RuleIndex),
     Primitive(Primitive),
     Rule(RuleIndex),
     BuiltIn(BuiltInType),
+    /// String with a length in chars between both bounds, e.g. `String(1..10)`.
+    /// `String(2)` has equal bounds.
+    StringLength {
+        min: usize,
+        max: Option<usize>,
+    },
+    /// Type of a def without body, which accepts any value.
+    Opaque,
     Unknown,
 }
 
@@ -39,6 +69,8 @@ pub enum BuiltInType {
     String,
     Number,
     Bool,
+    /// Accepts every value, including structs, lists and null
+    Any,
 }
 
 impl BuiltInType {
@@ -47,25 +79,60 @@ impl BuiltInType {
             Self::String => STRING,
             Self::Number => NUMBER,
             Self::Bool => BOOL,
+            Self::Any => ANY,
         }
     }
 
-    pub fn into_iter() -> IntoIter<Self, 3> {
-        static BUILT_INS: [BuiltInType; 3] =
-            [BuiltInType::String, BuiltInType::Number, BuiltInType::Bool];
+    /// All built-in types, `Any` last, so the first matching type of a value is the narrowest.
+    pub fn into_iter() -> IntoIter<Self, 4> {
+        static BUILT_INS: [BuiltInType; 4] = [
+            BuiltInType::String,
+            BuiltInType::Number,
+            BuiltInType::Bool,
+            BuiltInType::Any,
+        ];
         BUILT_INS.into_iter()
     }
 
+    /// Built-in type with the name, e.g. `Number`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::into_iter().find(|built_in| built_in.as_str() == name)
+    }
+
     pub const fn matches(&self, value: &Primitive) -> bool {
         matches!(
             (self, value),
             (Self::String, Primitive::String(_))
                 | (Self::Number, Primitive::Number(_))
                 | (Self::Bool, Primitive::Bool(_))
+                | (Self::Any, _)
         )
     }
 }
 
+/// Name of a string type with the given length bounds, e.g. `String(1..10)`.
+fn string_length_name(min: usize, max: Option<usize>) -> String {
+    match max {
+        Some(max) if max == min => format!("{}({})", STRING, min),
+        Some(max) => format!("{}({}..{})", STRING, min, max),
+        None => format!("{}({}..)", STRING, min),
+    }
+}
+
+/// Check the length of the string in chars against the bounds.
+/// The mismatch names the actual length.
+fn check_string_length(min: usize, max: Option<usize>, value: &str) -> Option<ErrorMessage> {
+    let length = value.chars().count();
+    if min <= length && max.is_none_or(|max| length <= max) {
+        None
+    } else {
+        Some(ErrorMessage::ValueMismatch(
+            string_length_name(min, max),
+            format!("{} (length {})", value, length),
+        ))
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 enum Context {
     Expr,
@@ -75,66 +142,453 @@ enum Context {
 #[derive(Clone, Debug, Default)]
 pub struct TypesResult {
     pub rules: Vec<Rule>,
+    /// Rule indices of all global bindings
+    pub idents: HashMap<GlobalIdent, RuleIndex>,
+    /// Global names of all defs
+    pub defs: HashSet<GlobalIdent>,
     pub assertions: Vec<(RuleIndex, RuleIndex)>,
+    /// Spreads (struct rule, spread rule, range of the spread) in their order,
+    /// whose fields are added to the struct rules, when the idents are resolved
+    pub spreads: Vec<(RuleIndex, RuleIndex, TokenRange)>,
     pub errors: Vec<Error>,
+    /// Whether struct fields with only a primitive default are typed by its built-in type
+    widen_defaults: bool,
 }
 
-pub fn analyze(module_ast: &ModuleAst, namespace: &Namespace, bindings: &BindingMap) -> TypesResult {
-    let mut types_result = TypesResult::default();
-    let ident_rules: HashMap<GlobalIdent, RuleIndex> = module_ast
+pub fn analyze(
+    module_ast: &ModuleAst,
+    namespace: &Namespace,
+    bindings: &BindingMap,
+    widen_defaults: bool,
+) -> TypesResult {
+    let segments: Vec<_> = module_ast
         .ast
         .globals
         .iter()
-        .flat_map(|global| {
-            match global {
-                Global::Def(def) => {
-                    analysis::analyze_def(def, &mut types_result, (namespace, bindings)).and_then(
-                        |rule_index| {
-                            def.name
-                                .as_ref()
-                                .map(|ident| ident.name.clone())
-                                .map(|name| module_ast.name.clone().extend(name))
-                                .map(|global_ident| (global_ident, rule_index))
-                        },
-                    )
-                }
-                Global::Let(l) => analysis::analyze_let(l, &mut types_result, (namespace, bindings))
-                    .and_then(|rule_index| {
-                        l.name
-                            .as_ref()
-                            .map(|ident| ident.name.clone())
-                            .map(|name| module_ast.name.clone().extend(name))
-                            .map(|global_ident| (global_ident, rule_index))
-                    }),
-                Global::Init(init) => {
-                    init.analyze(&mut types_result, Context::Expr, (namespace, bindings));
-                    None
-                }
-                Global::Use(_) | Global::Error(_) => {
-                    // no types to check
-                    None
-                }
-            }
+        .map(|global| {
+            analyze_global(
+                global,
+                &module_ast.name,
+                (namespace, bindings),
+                widen_defaults,
+            )
         })
         .collect();
-    // TODO: add import rules to ident_rules
-    validate_idents(&mut types_result, &ident_rules);
+    link(module_ast, &segments, widen_defaults, &[])
+}
+
+/// Rules of a single global, before its idents are resolved.
+///
+/// The rule indices start at zero for every segment,
+/// so a segment stays valid, if other globals of the module change.
+#[derive(Clone, Debug)]
+pub(super) struct Segment {
+    rules: Vec<Rule>,
+    assertions: Vec<(RuleIndex, RuleIndex)>,
+    spreads: Vec<(RuleIndex, RuleIndex, TokenRange)>,
+    errors: Vec<Error>,
+    /// Global name of the binding and its rule
+    ident: Option<(GlobalIdent, RuleIndex)>,
+}
+
+/// Analyze the rules of a global.
+pub(super) fn analyze_global(
+    global: &Global,
+    module_name: &GlobalIdent,
+    space_info: (&Namespace, &BindingMap),
+    widen_defaults: bool,
+) -> Segment {
+    let mut types_result = TypesResult {
+        widen_defaults,
+        ..Default::default()
+    };
+    let global_ident = |ident: &Option<Ident>| {
+        ident
+            .as_ref()
+            .map(|ident| module_name.clone().extend(ident.name.clone()))
+    };
+    let ident = match global {
+        Global::Def(def) => analysis::analyze_def(def, &mut types_result, space_info)
+            .and_then(|rule_index| global_ident(&def.name).map(|ident| (ident, rule_index))),
+        Global::Let(l) => analysis::analyze_let(l, &mut types_result, space_info)
+            .and_then(|rule_index| global_ident(&l.name).map(|ident| (ident, rule_index))),
+        Global::Init(init) => {
+            init.expr
+                .analyze(&mut types_result, Context::Expr, space_info);
+            None
+        }
+        Global::Test(test) => {
+            if let Some(expr) = &test.expr {
+                expr.analyze(&mut types_result, Context::Expr, space_info);
+            }
+            None
+        }
+        Global::Use(_) | Global::Error(_) => {
+            // no types to check
+            None
+        }
+    };
+    Segment {
+        rules: types_result.rules,
+        assertions: types_result.assertions,
+        spreads: types_result.spreads,
+        errors: types_result.errors,
+        ident,
+    }
+}
+
+/// Combine the segments of all globals, resolve their idents and check their assertions.
+///
+/// The rules of the imported modules are appended, so their globals resolve like local ones.
+/// The imported rules point to the range of the use, that imports them,
+/// because their own ranges belong to the tokens of another module.
+pub(super) fn link(
+    module_ast: &ModuleAst,
+    segments: &[Segment],
+    widen_defaults: bool,
+    imports: &[(&TypesResult, TokenRange)],
+) -> TypesResult {
+    let mut types_result = TypesResult {
+        widen_defaults,
+        ..Default::default()
+    };
+    let mut idents = HashMap::new();
+    for segment in segments {
+        let offset = types_result.rules.len();
+        types_result
+            .rules
+            .extend(segment.rules.iter().map(|rule| rule.relocated(offset)));
+        types_result.assertions.extend(
+            segment
+                .assertions
+                .iter()
+                .map(|(expected, actual)| (expected + offset, actual + offset)),
+        );
+        types_result.spreads.extend(segment.spreads.iter().map(
+            |(struct_index, spread_index, range)| {
+                (struct_index + offset, spread_index + offset, range.clone())
+            },
+        ));
+        types_result.errors.extend_from_slice(&segment.errors);
+        if let Some((ident, rule_index)) = &segment.ident {
+            idents.insert(ident.clone(), rule_index + offset);
+        }
+    }
+    let mut imported_defs = HashSet::new();
+    for (imported, range) in imports {
+        let offset = types_result.rules.len();
+        types_result.rules.extend(
+            imported
+                .rules
+                .iter()
+                .map(|rule| rule.relocated(offset).moved_to(range)),
+        );
+        for (ident, rule_index) in &imported.idents {
+            idents.entry(ident.clone()).or_insert(rule_index + offset);
+        }
+        imported_defs.extend(imported.defs.iter().cloned());
+    }
+    wrap_aliased_spreads(&mut types_result, module_ast);
+    validate_idents(&mut types_result, &idents);
+    expand_spreads(&mut types_result);
     assertions::check(&mut types_result);
+    types_result.idents = idents;
+    types_result.defs = module_ast
+        .ast
+        .globals
+        .iter()
+        .filter_map(|global| match global {
+            Global::Def(Def {
+                name: Some(name), ..
+            }) => Some(module_ast.name.clone().extend(name.name.clone())),
+            _ => None,
+        })
+        .chain(imported_defs)
+        .collect();
     types_result
 }
 
+impl Rule {
+    /// The rule with all its rule indices moved by the offset.
+    fn relocated(&self, offset: usize) -> Self {
+        let type_description = match &self.type_description {
+            TypeDescription::Union(indices) => {
+                TypeDescription::Union(indices.iter().map(|index| index + offset).collect())
+            }
+            TypeDescription::Struct(fields) => TypeDescription::Struct(
+                fields
+                    .iter()
+                    .map(|(name, (index, has_default, range))| {
+                        (name.clone(), (index + offset, *has_default, range.clone()))
+                    })
+                    .collect(),
+            ),
+            TypeDescription::List(indices) => {
+                TypeDescription::List(indices.iter().map(|index| index + offset).collect())
+            }
+            TypeDescription::ValidIdent(index) => TypeDescription::ValidIdent(index + offset),
+            TypeDescription::Rule(index) => TypeDescription::Rule(index + offset),
+            type_description => type_description.clone(),
+        };
+        Self {
+            type_description,
+            info: self.info.clone(),
+            is_error_recovery: self.is_error_recovery,
+        }
+    }
+
+    /// The rule with its range and the ranges of its fields replaced by the range.
+    fn moved_to(mut self, range: &TokenRange) -> Self {
+        if let TypeDescription::Struct(fields) = &mut self.type_description {
+            for (_, _, field_range) in fields.values_mut() {
+                *field_range = range.clone();
+            }
+        }
+        self.info.range = range.clone();
+        self
+    }
+}
+
+impl TypesResult {
+    /// Validate a value against the type of the given global binding.
+    ///
+    /// Returns `None`, if the binding does not exist.
+    pub fn validate_ident(
+        &self,
+        ident: &GlobalIdent,
+        value: &Value,
+    ) -> Option<Vec<ValidationError>> {
+        self.idents
+            .get(ident)
+            .map(|rule_index| validate(self, *rule_index, value))
+    }
+}
+
 /// Validate all `Ident` rules.
 ///
 /// If the ident is defined, its rule is replaced by a `ValidIdent`-rule,
 /// containing the original rule index.
-/// Otherwise the rule type is `Unknown`.
+/// If the ident projects into a defined binding (e.g. `hosts::0` or `server::host`),
+/// it is replaced by a `Rule`-rule of the projected inner rule.
+/// Otherwise the rule type is `Unknown`
+/// and a struct, that overrides an invalid projection, is not checked.
+/// Usages of opaque defs are reported as warnings, because they are not checked.
 fn validate_idents(type_info: &mut TypesResult, ident_rules: &HashMap<GlobalIdent, RuleIndex>) {
-    for rule in type_info.rules.iter_mut() {
+    let opaque_indices: Vec<RuleIndex> = ident_rules
+        .values()
+        .copied()
+        .filter(|rule_index| {
+            matches!(
+                type_info.rules[*rule_index].type_description,
+                TypeDescription::Opaque
+            )
+        })
+        .collect();
+    let mut projections = Vec::new();
+    for (index, rule) in type_info.rules.iter_mut().enumerate() {
         if let TypeDescription::Ident(ident) = &rule.type_description {
             let rule_index = ident_rules.get(ident).copied();
+            if rule_index.is_some_and(|rule_index| opaque_indices.contains(&rule_index)) {
+                type_info.errors.push(Error::new(
+                    ErrorMessage::OpaqueUsage(ident.end().to_string()),
+                    rule.info.to_token_range(),
+                ));
+            }
+            if rule_index.is_none() {
+                projections.push((index, ident.clone()));
+            }
             rule.type_description = rule_index
                 .map(TypeDescription::ValidIdent)
                 .unwrap_or(TypeDescription::Unknown);
         };
     }
+    // Projections are resolved after all direct idents,
+    // because the projected rules may contain other idents.
+    let mut invalid_projections = Vec::new();
+    for (index, ident) in projections {
+        let Some((root_len, root_index)) = projection_root(ident_rules, &ident) else {
+            continue;
+        };
+        match resolve_projection(&type_info.rules, root_index, &ident.path[root_len..]) {
+            Some(rule_index) => {
+                type_info.rules[index].type_description = TypeDescription::Rule(rule_index)
+            }
+            None => invalid_projections.push(index),
+        }
+    }
+    // The invalid segment is reported by the value analysis,
+    // so the unknown type is not asserted again.
+    type_info
+        .assertions
+        .retain(|(expected, _)| !invalid_projections.contains(expected));
+}
+
+/// Spread the defs and lets of the module with an alias as a single field named by the alias,
+/// like the value analysis does, e.g. `...db` of `let db = { port = 80 } as "database"`.
+fn wrap_aliased_spreads(types_result: &mut TypesResult, module_ast: &ModuleAst) {
+    let emit_names: HashMap<_, _> = module_ast
+        .ast
+        .globals
+        .iter()
+        .filter_map(|global| match global {
+            Global::Def(d) => Some((d.name.as_ref()?, d.emit_name()?)),
+            Global::Let(l) => Some((l.name.as_ref()?, l.emit_name()?)),
+            _ => None,
+        })
+        .map(|(name, emit_name)| {
+            let ident = module_ast.name.clone().extend(name.name.clone());
+            (ident, emit_name.to_string())
+        })
+        .collect();
+    for (_, spread_index, range) in &mut types_result.spreads {
+        let rules = &mut types_result.rules;
+        let TypeDescription::Ident(ident) = &rules[*spread_index].type_description else {
+            continue;
+        };
+        let Some(emit_name) = emit_names.get(ident) else {
+            continue;
+        };
+        let field = (emit_name.clone(), (*spread_index, true, range.clone()));
+        rules.push(Rule {
+            type_description: TypeDescription::Struct(HashMap::from([field])),
+            info: rules[*spread_index].info.clone(),
+            is_error_recovery: false,
+        });
+        *spread_index = rules.len() - 1;
+    }
+}
+
+/// Add the fields of the spread structs to the structs, they are spread into.
+///
+/// The own fields of a struct are kept and later spreads override earlier ones.
+/// A spread struct, that has spreads itself, is expanded first.
+/// Spreads, that are no struct, are reported, unknown ones are ignored.
+fn expand_spreads(types_result: &mut TypesResult) {
+    let mut spreads: HashMap<RuleIndex, Vec<(RuleIndex, TokenRange)>> = HashMap::new();
+    for (struct_index, spread_index, range) in std::mem::take(&mut types_result.spreads) {
+        spreads
+            .entry(struct_index)
+            .or_default()
+            .push((spread_index, range));
+    }
+    let mut expanded = HashSet::new();
+    let mut struct_indices: Vec<_> = spreads.keys().copied().collect();
+    struct_indices.sort_unstable();
+    for struct_index in struct_indices {
+        expand_struct(types_result, &spreads, struct_index, &mut expanded);
+    }
+}
+
+fn expand_struct(
+    types_result: &mut TypesResult,
+    spreads: &HashMap<RuleIndex, Vec<(RuleIndex, TokenRange)>>,
+    struct_index: RuleIndex,
+    expanded: &mut HashSet<RuleIndex>,
+) {
+    // a struct, that is spread into itself, is a recursive value and reported as such
+    if !expanded.insert(struct_index) {
+        return;
+    }
+    let Some(struct_spreads) = spreads.get(&struct_index) else {
+        return;
+    };
+    for (spread_index, range) in struct_spreads.iter().rev() {
+        let Some(described_index) = described_rule(&types_result.rules, *spread_index) else {
+            continue;
+        };
+        expand_struct(types_result, spreads, described_index, expanded);
+        let spread_fields = match &types_result.rules[described_index].type_description {
+            TypeDescription::Struct(fields) => fields.clone(),
+            TypeDescription::Primitive(_)
+            | TypeDescription::List(_)
+            | TypeDescription::StringLength { .. }
+            | TypeDescription::BuiltIn(
+                BuiltInType::String | BuiltInType::Number | BuiltInType::Bool,
+            ) => {
+                types_result
+                    .errors
+                    .push(Error::new(ErrorMessage::SpreadOfNonStruct, range.clone()));
+                continue;
+            }
+            _ => continue,
+        };
+        let TypeDescription::Struct(fields) =
+            &mut types_result.rules[struct_index].type_description
+        else {
+            continue;
+        };
+        for (name, (field_index, has_default, _)) in spread_fields {
+            // the spread fields are located at the spread, not at the struct they come from
+            fields
+                .entry(name)
+                .or_insert_with(|| (field_index, has_default, range.clone()));
+        }
+    }
+}
+
+/// Follow `ValidIdent` and `Rule` rules to the index of the describing rule.
+/// Returns `None` for references in a cycle, e.g. `let a = b let b = a`.
+fn described_rule(rules: &[Rule], mut rule_index: RuleIndex) -> Option<RuleIndex> {
+    for _ in 0..=rules.len() {
+        match rules.get(rule_index)?.type_description {
+            TypeDescription::ValidIdent(inner_index) | TypeDescription::Rule(inner_index) => {
+                rule_index = inner_index;
+            }
+            _ => return Some(rule_index),
+        }
+    }
+    None
+}
+
+/// Longest defined prefix of the ident and its rule.
+fn projection_root(
+    ident_rules: &HashMap<GlobalIdent, RuleIndex>,
+    ident: &GlobalIdent,
+) -> Option<(usize, RuleIndex)> {
+    (1..ident.path.len()).rev().find_map(|len| {
+        let prefix =
+            GlobalIdent::new(ident.root.clone()).extend_multiple(ident.path[..len].to_vec());
+        ident_rules
+            .get(&prefix)
+            .map(|rule_index| (len, *rule_index))
+    })
+}
+
+/// Find the rule of a projection (e.g. `hosts::0`), starting at the rule of its root.
+///
+/// The segments select list elements or struct fields,
+/// e.g. `Config::database` is the rule of the field `database` in the def `Config`.
+fn resolve_projection(
+    rules: &[Rule],
+    root_index: RuleIndex,
+    segments: &[String],
+) -> Option<RuleIndex> {
+    segments.iter().try_fold(root_index, |rule_index, segment| {
+        match &extract_rule(rules, rule_index)?.type_description {
+            TypeDescription::List(indices) => {
+                let index: usize = segment.parse().ok()?;
+                // lists with a single element are also list types
+                indices
+                    .get(index)
+                    .or_else(|| indices.first().filter(|_| indices.len() == 1))
+                    .copied()
+            }
+            TypeDescription::Struct(fields) => fields
+                .iter()
+                .find(|(field, _)| *field == segment)
+                .map(|(_, (rule_index, _, _))| *rule_index),
+            _ => None,
+        }
+    })
+}
+
+/// Follow `ValidIdent` and `Rule` rules to the describing rule.
+fn extract_rule(rules: &[Rule], rule_index: RuleIndex) -> Option<&Rule> {
+    let rule = rules.get(rule_index)?;
+    match rule.type_description {
+        TypeDescription::ValidIdent(rule_index) | TypeDescription::Rule(rule_index) => {
+            extract_rule(rules, rule_index)
+        }
+        _ => Some(rule),
+    }
 }
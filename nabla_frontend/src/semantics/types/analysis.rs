@@ -16,6 +16,15 @@ pub(super) fn analyze_def(
     types_result: &mut TypesResult,
     space_info: (&Namespace, &BindingMap),
 ) -> Option<RuleIndex> {
+    if let (Some(_), None, None) = (&def.name, &def.type_expr, &def.expr) {
+        let rules = &mut types_result.rules;
+        rules.push(Rule {
+            type_description: TypeDescription::Opaque,
+            info: def.info.clone(),
+            is_error_recovery: false,
+        });
+        return Some(rule_index(rules));
+    }
     analyze_binding(
         def.name.as_ref(),
         def.type_expr.as_ref(),
@@ -53,11 +62,25 @@ fn analyze_binding<'a>(
         matches!(expr, Some(Expr::Single(Single::Named(Named { name, .. }))) if ident == name)
     }
 
+    // inside a binding, that shadows a built-in type, its own name refers to the built-in type
+    let shadowed;
+    let space_info = match name {
+        Some(name) if BuiltInType::from_name(&name.name).is_some() => {
+            let (namespace, bindings) = space_info;
+            let mut namespace = namespace.clone();
+            namespace.remove(&name.name);
+            shadowed = namespace;
+            (&shadowed, bindings)
+        }
+        _ => space_info,
+    };
     if let Some(name) = name {
-        if check_self_reference(name, type_expr) || check_self_reference(name, expr) {
+        if space_info.0.contains_key(&name.name)
+            && (check_self_reference(name, type_expr) || check_self_reference(name, expr))
+        {
             types_result.errors.push(Error::new(
                 ErrorMessage::SelfReference(name.name.clone()),
-                name.info.to_token_range(),
+                name.to_token_range(),
             ));
             return None;
         }
@@ -109,6 +132,7 @@ impl TypeAnalyzer for Expr {
                 rules.push(Rule {
                     type_description: TypeDescription::Unknown,
                     info: info.clone(),
+                    is_error_recovery: true,
                 });
                 rule_index(rules)
             }
@@ -138,6 +162,7 @@ impl TypeAnalyzer for Union {
         rules.push(Rule {
             type_description: TypeDescription::Union(inner_rule_indices),
             info: self.info.clone(),
+            is_error_recovery: false,
         });
         rule_index(rules)
     }
@@ -155,10 +180,99 @@ impl TypeAnalyzer for Single {
             Self::List(list) => list.analyze(types_result, context, space_info),
             Self::Named(named) => named.analyze(types_result, context, space_info),
             Self::Primitive(primitive) => primitive.analyze(types_result, context, space_info),
+            Self::Not(not) => not.analyze(types_result, context, space_info),
+            Self::BoolOp(bool_op) => bool_op.analyze(types_result, context, space_info),
+            Self::Neg(neg) => neg.analyze(types_result, context, space_info),
+            Self::ArithOp(arith_op) => arith_op.analyze(types_result, context, space_info),
         }
     }
 }
 
+impl TypeAnalyzer for Not {
+    fn analyze(
+        &self,
+        types_result: &mut TypesResult,
+        context: Context,
+        space_info: (&Namespace, &BindingMap),
+    ) -> RuleIndex {
+        let operands = self
+            .single
+            .iter()
+            .map(|single| single.analyze(types_result, context, space_info))
+            .collect();
+        analyze_operation(types_result, BuiltInType::Bool, operands, &self.info)
+    }
+}
+
+impl TypeAnalyzer for BoolOp {
+    fn analyze(
+        &self,
+        types_result: &mut TypesResult,
+        context: Context,
+        space_info: (&Namespace, &BindingMap),
+    ) -> RuleIndex {
+        let operands = std::iter::once(&self.lhs)
+            .chain(self.rhs.iter())
+            .map(|single| single.analyze(types_result, context, space_info))
+            .collect();
+        analyze_operation(types_result, BuiltInType::Bool, operands, &self.info)
+    }
+}
+
+impl TypeAnalyzer for Neg {
+    fn analyze(
+        &self,
+        types_result: &mut TypesResult,
+        context: Context,
+        space_info: (&Namespace, &BindingMap),
+    ) -> RuleIndex {
+        let operands = self
+            .single
+            .iter()
+            .map(|single| single.analyze(types_result, context, space_info))
+            .collect();
+        analyze_operation(types_result, BuiltInType::Number, operands, &self.info)
+    }
+}
+
+impl TypeAnalyzer for ArithOp {
+    fn analyze(
+        &self,
+        types_result: &mut TypesResult,
+        context: Context,
+        space_info: (&Namespace, &BindingMap),
+    ) -> RuleIndex {
+        let operands = std::iter::once(&self.lhs)
+            .chain(self.rhs.iter())
+            .map(|single| single.analyze(types_result, context, space_info))
+            .collect();
+        analyze_operation(types_result, BuiltInType::Number, operands, &self.info)
+    }
+}
+
+/// Operations are of the given built in type and all operands must be of that type as well,
+/// e.g. `Bool` for boolean and `Number` for arithmetic operations.
+fn analyze_operation(
+    types_result: &mut TypesResult,
+    built_in: BuiltInType,
+    operands: Vec<RuleIndex>,
+    info: &AstInfo,
+) -> RuleIndex {
+    let rules = &mut types_result.rules;
+    rules.push(Rule {
+        type_description: TypeDescription::BuiltIn(built_in),
+        info: info.clone(),
+        is_error_recovery: false,
+    });
+    let operation_rule_index = rule_index(rules);
+    types_result.assertions.extend(
+        operands
+            .into_iter()
+            .map(|operand| (operation_rule_index, operand)),
+    );
+    operation_rule_index
+}
+
 impl TypeAnalyzer for StructOrList {
     fn analyze(
         &self,
@@ -191,7 +305,7 @@ impl TypeAnalyzer for Struct {
                 if field_names.contains(&field_name) {
                     errors.push(Error::new(
                         ErrorMessage::DuplicateField(field_name.clone()),
-                        field.info.to_token_range(),
+                        field.to_token_range(),
                     ));
                 } else {
                     field_names.push(field_name);
@@ -199,21 +313,38 @@ impl TypeAnalyzer for Struct {
             })
             .map(|field| {
                 (
-                    field.name.clone(),
+                    field.name.name.clone(),
                     (
                         field.analyze(types_result, context, space_info),
                         field.expr.is_some(),
+                        field.name.to_token_range(),
                     ),
                 )
             })
             .collect();
         types_result.errors.extend(errors);
+        let spreads: Vec<_> = self
+            .spreads
+            .iter()
+            .filter_map(|spread| {
+                let single = spread.single.as_ref()?;
+                let spread_index = single.analyze(types_result, context, space_info);
+                Some((spread_index, spread.to_token_range()))
+            })
+            .collect();
         let rules = &mut types_result.rules;
         rules.push(Rule {
             type_description: TypeDescription::Struct(field_rule_indices),
             info: self.info.clone(),
+            is_error_recovery: false,
         });
-        rule_index(rules)
+        let struct_index = rule_index(rules);
+        types_result.spreads.extend(
+            spreads
+                .into_iter()
+                .map(|(spread_index, range)| (struct_index, spread_index, range)),
+        );
+        struct_index
     }
 }
 
@@ -240,6 +371,7 @@ impl TypeAnalyzer for StructField {
                 Rule {
                     type_description: TypeDescription::Rule(type_expr_index),
                     info,
+                    is_error_recovery: false,
                 }
             }
             (Some(type_expr_index), None) => {
@@ -250,12 +382,36 @@ impl TypeAnalyzer for StructField {
                 Rule {
                     type_description: TypeDescription::Rule(type_expr_index),
                     info,
+                    is_error_recovery: false,
+                }
+            }
+            (None, Some(expr_index)) => {
+                let widened = match (context, &self.expr) {
+                    (Context::TypeExpr, Some(Expr::Single(Single::Primitive(primitive))))
+                        if types_result.widen_defaults =>
+                    {
+                        BuiltInType::into_iter().find(|built_in| built_in.matches(primitive))
+                    }
+                    _ => None,
+                };
+                let type_index = match widened {
+                    Some(built_in) => {
+                        let rules = &mut types_result.rules;
+                        rules.push(Rule {
+                            type_description: TypeDescription::BuiltIn(built_in),
+                            info: info.clone(),
+                            is_error_recovery: false,
+                        });
+                        rule_index(rules)
+                    }
+                    None => expr_index,
+                };
+                Rule {
+                    type_description: TypeDescription::Rule(type_index),
+                    info,
+                    is_error_recovery: false,
                 }
             }
-            (None, Some(expr_index)) => Rule {
-                type_description: TypeDescription::Rule(expr_index),
-                info,
-            },
             (None, None) => {
                 let error = match context {
                     Context::Expr => Error::new(ErrorMessage::UnassignedField, info.range.clone()),
@@ -265,6 +421,7 @@ impl TypeAnalyzer for StructField {
                 Rule {
                     type_description: TypeDescription::Unknown,
                     info,
+                    is_error_recovery: false,
                 }
             }
         };
@@ -281,15 +438,27 @@ impl TypeAnalyzer for List {
         context: Context,
         space_info: (&Namespace, &BindingMap),
     ) -> RuleIndex {
-        let inner_rule_indices = self
+        let inner_rule_indices: Vec<_> = self
             .exprs
             .iter()
             .map(|expr| expr.analyze(types_result, context, space_info))
             .collect();
+        // a list type has exactly one element type, so reject the others
+        // where they are declared instead of where the type is used
+        if matches!(context, Context::TypeExpr) {
+            let errors: Vec<_> = inner_rule_indices
+                .iter()
+                .skip(1)
+                .map(|index| types_result.rules.get(*index).expect("Rule must exist"))
+                .map(|rule| Error::new(ErrorMessage::MultipleListTypes, rule.info.range.clone()))
+                .collect();
+            types_result.errors.extend(errors);
+        }
         let rules = &mut types_result.rules;
         rules.push(Rule {
             type_description: TypeDescription::List(inner_rule_indices),
             info: self.info.clone(),
+            is_error_recovery: false,
         });
         rule_index(rules)
     }
@@ -315,6 +484,7 @@ impl TypeAnalyzer for Named {
                 Rule {
                     info: self.info.clone(),
                     type_description: TypeDescription::Ident(ident.clone()),
+                    is_error_recovery: false,
                 },
                 Some(ident),
             )
@@ -322,13 +492,13 @@ impl TypeAnalyzer for Named {
             .first()
             // && names.len() == 1
             .and_then(|name| if names.len() == 1 { Some(name) } else { None })
-            .map(Deref::deref)
-            .and_then(|name| BuiltInType::into_iter().find(|built_in| built_in.as_str() == name))
+            .and_then(|name| BuiltInType::from_name(name))
         {
             (
                 Rule {
                     info: self.info.clone(),
                     type_description: TypeDescription::BuiltIn(built_in),
+                    is_error_recovery: false,
                 },
                 None,
             )
@@ -337,10 +507,27 @@ impl TypeAnalyzer for Named {
                 Rule {
                     info: self.info.clone(),
                     type_description: TypeDescription::Unknown,
+                    is_error_recovery: false,
                 },
                 None,
             )
         };
+        let mut named_rule = named_rule;
+        if let Some(length) = &self.length {
+            if matches!(
+                named_rule.type_description,
+                TypeDescription::BuiltIn(BuiltInType::String)
+            ) {
+                if let Some((min, max)) = analyze_length(length, types_result) {
+                    named_rule.type_description = TypeDescription::StringLength { min, max };
+                }
+            } else {
+                types_result.errors.push(Error::new(
+                    ErrorMessage::LengthOfNonString,
+                    length.to_token_range(),
+                ));
+            }
+        }
         let rules = &mut types_result.rules;
         rules.push(named_rule);
         let named_rule_index = rule_index(rules);
@@ -353,7 +540,7 @@ impl TypeAnalyzer for Named {
                 if matches!(bindings.get(&ident), Some(Binding::Let)) {
                     types_result.errors.push(Error::new(
                         ErrorMessage::ImmutableLet(ident.end().to_string()),
-                        self.name.info.to_token_range(),
+                        self.name.to_token_range(),
                     ));
                 } else {
                     types_result
@@ -377,11 +564,40 @@ impl TypeAnalyzer for Primitive {
         rules.push(Rule {
             type_description: TypeDescription::Primitive(self.clone()),
             info: self.info().clone(),
+            is_error_recovery: false,
         });
         rule_index(rules)
     }
 }
 
+/// Bounds of a length constraint.
+/// A single number is used as both bounds, an omitted lower bound is zero.
+fn analyze_length(
+    length: &Length,
+    types_result: &mut TypesResult,
+) -> Option<(usize, Option<usize>)> {
+    let parse = |bound: &Option<PrimitiveValue>| {
+        bound
+            .as_ref()
+            .map_or(Ok(None), |bound| bound.value.parse::<usize>().map(Some))
+    };
+    let bounds = match (parse(&length.min), parse(&length.max)) {
+        (Ok(Some(min)), Ok(_)) if length.dot_dot.is_none() => Some((min, Some(min))),
+        (Ok(min), Ok(max)) if length.dot_dot.is_some() => {
+            let min = min.unwrap_or(0);
+            Some((min, max)).filter(|_| max.is_none_or(|max| min <= max))
+        }
+        _ => None,
+    };
+    if bounds.is_none() {
+        types_result.errors.push(Error::new(
+            ErrorMessage::InvalidLength(length.to_string()),
+            length.to_token_range(),
+        ));
+    }
+    bounds
+}
+
 fn is_union(types_result: &TypesResult, rule_index: RuleIndex) -> bool {
     let rule = types_result.rules.get(rule_index).expect("Rule must exist");
     matches!(rule.type_description, TypeDescription::Union(_))
@@ -1,8 +1,10 @@
 use crate::{
-    ast::Ident,
     semantics::{
-        error::ErrorMessage,
-        types::{BuiltInType, Primitive, Rule, RuleIndex, TypeDescription, TypesResult},
+        error::{ErrorMessage, FIELD_DEFINED},
+        types::{
+            check_string_length, BuiltInType, Primitive, Rule, RuleIndex, TypeDescription,
+            TypesResult,
+        },
         Error,
     },
     token::{ToTokenRange, TokenRange},
@@ -60,36 +62,91 @@ fn check_in_union(rules: &[Rule], expected: &[RuleIndex], actual_rule: &Rule) ->
     )]
 }
 
+/// Check the fields of a struct against the expected ones.
+///
+/// Missing fields are reported at the opening brace of the actual struct,
+/// so the whole struct is not marked, unexpected fields at their name in the actual struct.
+/// An unexpected field, that is likely a misspelling of a missing one
+/// (see [`misspelled_fields`]), is reported once as misspelled instead.
 fn check_struct(
     rules: &[Rule],
-    expected: &HashMap<Ident, (RuleIndex, bool)>,
+    expected: &HashMap<String, (RuleIndex, bool, TokenRange)>,
     actual_rule: &Rule,
-    actual: &HashMap<Ident, (RuleIndex, bool)>,
+    actual: &HashMap<String, (RuleIndex, bool, TokenRange)>,
 ) -> Vec<Error> {
     let mut errors = Vec::new();
-    for (field, (expected_index, has_default)) in expected {
-        if let Some((actual_index, _)) = actual.get(field) {
+    let absent: Vec<_> = expected
+        .keys()
+        .filter(|field| !actual.contains_key(*field))
+        .collect();
+    let unexpected: Vec<_> = actual
+        .keys()
+        .filter(|field| !expected.contains_key(*field))
+        .collect();
+    let misspelled = misspelled_fields(&absent, &unexpected);
+    let opening_brace = {
+        let range = own_rule(rules, actual_rule).info.to_token_range();
+        range.start..(range.start + 1).min(range.end)
+    };
+    for (field, (expected_index, has_default, _)) in expected {
+        if let Some((actual_index, _, _)) = actual.get(field) {
             let expected_rule = rules.get(*expected_index).expect("Rule must exist");
             let actual_rule = rules.get(*actual_index).expect("Rule must exist");
             errors.extend(check_rules(rules, expected_rule, actual_rule));
-        } else if !has_default {
+        } else if !has_default && !misspelled.values().any(|expected| *expected == field) {
             errors.push(Error::new(
-                ErrorMessage::MissingField(field.name.clone()),
-                actual_rule.info.to_token_range(),
+                ErrorMessage::MissingField(field.clone()),
+                opening_brace.clone(),
             ));
         }
     }
-    for field in actual.keys() {
-        if !expected.contains_key(field) {
-            errors.push(Error::new(
-                ErrorMessage::UnexpecedField(field.name.clone()),
-                field.info.to_token_range(),
-            ));
-        }
+    for field in unexpected {
+        let (_, _, range) = &actual[field];
+        errors.push(misspelled.get(field).map_or_else(
+            || Error::new(ErrorMessage::UnexpecedField(field.clone()), range.clone()),
+            |expected_field| {
+                Error::new(
+                    ErrorMessage::MisspelledField(field.clone(), (*expected_field).clone()),
+                    range.clone(),
+                )
+                .with_secondary(FIELD_DEFINED, expected[*expected_field].2.clone())
+            },
+        ));
     }
     errors
 }
 
+/// Pair unexpected fields with absent expected ones, whose names are equal
+/// ignoring case and underscores, e.g. `max_connections` and `maxConnections`.
+///
+/// Only unique matches are paired: neither field may match any other field.
+/// Returns the expected field of each paired unexpected field.
+fn misspelled_fields<'a>(
+    absent: &[&'a String],
+    unexpected: &[&'a String],
+) -> HashMap<&'a String, &'a String> {
+    fn fold(field: &str) -> String {
+        field.replace('_', "").to_lowercase()
+    }
+    let matches = |field: &String, candidates: &[&'a String]| {
+        let folded = fold(field);
+        let mut matching = candidates
+            .iter()
+            .filter(|candidate| fold(candidate) == folded);
+        match (matching.next(), matching.next()) {
+            (Some(candidate), None) => Some(*candidate),
+            _ => None,
+        }
+    };
+    unexpected
+        .iter()
+        .filter_map(|field| {
+            let expected = matches(field, absent)?;
+            (matches(expected, unexpected) == Some(*field)).then_some((*field, expected))
+        })
+        .collect()
+}
+
 fn check_list(
     rules: &[Rule],
     expected_indices: &[RuleIndex],
@@ -117,16 +174,8 @@ fn check_list(
                 .flat_map(|actual_rule| check_rules(rules, expected_rule, actual_rule))
                 .collect()
         }
-        // TODO: Check outside of assertions
-        // TODO: Should this be legal (value-type-duality)?
-        _ => expected_indices
-            .iter()
-            .skip(1)
-            .map(|index| rules.get(*index).expect("Rule must exist"))
-            .map(|rule| &rule.info.range)
-            .cloned()
-            .map(|range| Error::new(ErrorMessage::MultipleListTypes, range))
-            .collect(),
+        // more than one element type was already rejected by the type analysis
+        _ => Vec::new(),
     }
 }
 
@@ -144,18 +193,27 @@ fn check_primitive(expected: &Primitive, actual: &Primitive) -> Vec<Error> {
     } else {
         vec![Error::new(
             ErrorMessage::ValueMismatch(expected.as_str().to_string(), actual.as_str().to_string()),
-            actual.info().to_token_range(),
+            actual.to_token_range(),
         )]
     }
 }
 
+fn check_length(min: usize, max: Option<usize>, actual: &Primitive) -> Vec<Error> {
+    match actual {
+        Primitive::String(value) => check_string_length(min, max, &value.value)
+            .map(|message| vec![Error::new(message, actual.to_token_range())])
+            .unwrap_or_default(),
+        _ => check_value(&BuiltInType::String, actual),
+    }
+}
+
 fn check_value(expected: &BuiltInType, actual: &Primitive) -> Vec<Error> {
     if expected.matches(actual) {
         Vec::new()
     } else {
         vec![Error::new(
             ErrorMessage::ValueMismatch(expected.as_str().to_string(), actual.as_str().to_string()),
-            actual.info().to_token_range(),
+            actual.to_token_range(),
         )]
     }
 }
@@ -170,6 +228,8 @@ fn extract_type_description<'a>(
         | TypeDescription::List(_)
         | TypeDescription::Primitive(_)
         | TypeDescription::BuiltIn(_)
+        | TypeDescription::StringLength { .. }
+        | TypeDescription::Opaque
         | TypeDescription::Unknown => type_description, // no need to extract
         TypeDescription::ValidIdent(rule_index) | TypeDescription::Rule(rule_index) => {
             let rule = rules.get(*rule_index).expect("Rule must exist");
@@ -179,11 +239,41 @@ fn extract_type_description<'a>(
     }
 }
 
+/// Rule, that the rule is an alias of, e.g. the value of a struct field.
+///
+/// Identifiers are not followed, so the rule stays in the source of the actual value.
+fn own_rule<'a>(rules: &'a [Rule], rule: &'a Rule) -> &'a Rule {
+    match &rule.type_description {
+        TypeDescription::Rule(rule_index) => {
+            own_rule(rules, rules.get(*rule_index).expect("Rule must exist"))
+        }
+        _ => rule,
+    }
+}
+
+/// Whether the rule or the rule it refers to stands for a node, that could not be parsed.
+fn is_error_recovery(rules: &[Rule], rule: &Rule) -> bool {
+    match &rule.type_description {
+        TypeDescription::ValidIdent(rule_index) | TypeDescription::Rule(rule_index) => {
+            is_error_recovery(rules, rules.get(*rule_index).expect("Rule must exist"))
+        }
+        _ => rule.is_error_recovery,
+    }
+}
+
 fn check_rules(rules: &[Rule], expected_rule: &Rule, actual_rule: &Rule) -> Vec<Error> {
-    match (
-        extract_type_description(rules, &expected_rule.type_description),
-        extract_type_description(rules, &actual_rule.type_description),
-    ) {
+    if is_error_recovery(rules, expected_rule) || is_error_recovery(rules, actual_rule) {
+        // The parse error was already reported.
+        return Vec::new();
+    }
+    let expected = extract_type_description(rules, &expected_rule.type_description);
+    let actual = extract_type_description(rules, &actual_rule.type_description);
+    if std::ptr::eq(expected, actual) {
+        // A rule matches itself. Checking its fields would not end for recursive types,
+        // e.g. `def X = { x: X = X }`.
+        return Vec::new();
+    }
+    match (expected, actual) {
         // sort out types that were already replaced by `lookup_imports`, `validate_idents` or extracted
         (TypeDescription::Ident(_), _)
         | (TypeDescription::ValidIdent(_), _)
@@ -191,11 +281,18 @@ fn check_rules(rules: &[Rule], expected_rule: &Rule, actual_rule: &Rule) -> Vec<
         | (_, TypeDescription::Ident(_))
         | (_, TypeDescription::ValidIdent(_))
         | (_, TypeDescription::Rule(_)) => panic!("Unexpected type description"),
+        // any
+        (TypeDescription::BuiltIn(BuiltInType::Any), _) => Vec::new(),
         // union
         (TypeDescription::Union(expected), TypeDescription::Union(actual)) => {
             check_union(rules, expected, actual)
         }
         (TypeDescription::Union(union), _) => check_in_union(rules, union, actual_rule),
+        // opaque
+        (TypeDescription::Opaque, _) | (_, TypeDescription::Opaque) => {
+            // Usage was reported as warning by `validate_idents`.
+            Vec::new()
+        }
         // unknown
         (TypeDescription::Unknown, _) => vec![Error::new(
             ErrorMessage::UnknownType,
@@ -212,6 +309,33 @@ fn check_rules(rules: &[Rule], expected_rule: &Rule, actual_rule: &Rule) -> Vec<
         (TypeDescription::BuiltIn(expected), TypeDescription::BuiltIn(actual)) => {
             check_built_in(expected, actual, actual_rule.info.to_token_range())
         }
+        // string length
+        (TypeDescription::StringLength { min, max }, TypeDescription::Primitive(actual)) => {
+            check_length(*min, *max, actual)
+        }
+        (
+            TypeDescription::StringLength { min, max },
+            TypeDescription::StringLength {
+                min: actual_min,
+                max: actual_max,
+            },
+        ) => {
+            let within = *min <= *actual_min
+                && max.is_none_or(|max| actual_max.is_some_and(|actual| actual <= max));
+            if within {
+                Vec::new()
+            } else {
+                vec![Error::new(
+                    ErrorMessage::TypeMismatch,
+                    actual_rule.info.to_token_range(),
+                )]
+            }
+        }
+        // the length of non-literal strings is not known
+        (TypeDescription::StringLength { .. }, TypeDescription::BuiltIn(BuiltInType::String))
+        | (TypeDescription::BuiltIn(BuiltInType::String), TypeDescription::StringLength { .. }) => {
+            Vec::new()
+        }
         // struct
         (TypeDescription::Struct(expected), TypeDescription::Struct(actual)) => {
             check_struct(rules, expected, actual_rule, actual)
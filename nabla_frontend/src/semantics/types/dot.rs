@@ -0,0 +1,127 @@
+use crate::{
+    ast::Primitive,
+    lexer,
+    semantics::types::{string_length_name, RuleIndex, TypeDescription, TypesResult},
+};
+use std::{collections::HashMap, fmt::Write};
+
+/// Short summary of a type description.
+///
+/// Nested rules are only referred to by their index,
+/// so recursive types never lead to endless output.
+impl std::fmt::Display for TypeDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Union(indices) => write!(f, "{}", join(indices, " | ")),
+            Self::Struct(fields) => {
+                let mut fields: Vec<_> = fields
+                    .iter()
+                    .map(|(name, (rule_index, has_default, _))| {
+                        let default = if *has_default { " (default)" } else { "" };
+                        format!("{}: #{}{}", name, rule_index, default)
+                    })
+                    .collect();
+                fields.sort();
+                write!(f, "{{ {} }}", fields.join(", "))
+            }
+            Self::List(indices) => write!(f, "[{}]", join(indices, ", ")),
+            Self::Ident(ident) => write!(f, "ident {}", ident),
+            Self::ValidIdent(rule_index) => write!(f, "valid ident #{}", rule_index),
+            Self::Primitive(primitive) => match primitive {
+                Primitive::String(value) => write!(f, "\"{}\"", lexer::escape(&value.value)),
+                Primitive::Char(value) => write!(f, "'{}'", lexer::escape(&value.value)),
+                _ => write!(f, "{}", primitive.as_str()),
+            },
+            Self::Rule(rule_index) => write!(f, "rule #{}", rule_index),
+            Self::BuiltIn(built_in) => write!(f, "{}", built_in.as_str()),
+            Self::StringLength { min, max } => write!(f, "{}", string_length_name(*min, *max)),
+            Self::Opaque => write!(f, "opaque"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+fn join(indices: &[RuleIndex], separator: &str) -> String {
+    indices
+        .iter()
+        .map(|rule_index| format!("#{}", rule_index))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+impl TypesResult {
+    /// Render the rules and assertions as Graphviz DOT.
+    ///
+    /// Every rule is a node, labeled with its index, type description and token range.
+    /// Nested rules are connected by solid edges, `ValidIdent` and `Rule` references
+    /// by dashed edges and assertions by bold edges from the expected to the actual rule.
+    pub fn to_dot(&self) -> String {
+        let mut names: HashMap<RuleIndex, Vec<String>> = HashMap::new();
+        for (ident, rule_index) in &self.idents {
+            names
+                .entry(*rule_index)
+                .or_default()
+                .push(ident.to_string());
+        }
+        let mut output = String::from("digraph types {\n    node [shape=box];\n");
+        for (rule_index, rule) in self.rules.iter().enumerate() {
+            let mut label = format!("#{} {}", rule_index, rule.type_description);
+            if let Some(names) = names.get_mut(&rule_index) {
+                names.sort();
+                label = format!("{}\n{}", names.join(", "), label);
+            }
+            label = format!("{}\n{:?}", label, rule.info.range);
+            writeln!(output, "    {} [label=\"{}\"];", rule_index, escape(&label))
+                .expect("Writing to string");
+        }
+        for (rule_index, rule) in self.rules.iter().enumerate() {
+            match &rule.type_description {
+                TypeDescription::Union(indices) | TypeDescription::List(indices) => {
+                    for child in indices {
+                        writeln!(output, "    {} -> {};", rule_index, child)
+                            .expect("Writing to string");
+                    }
+                }
+                TypeDescription::Struct(fields) => {
+                    let mut fields: Vec<_> = fields
+                        .iter()
+                        .map(|(name, (child, _, _))| (name, child))
+                        .collect();
+                    fields.sort();
+                    for (name, child) in fields {
+                        writeln!(
+                            output,
+                            "    {} -> {} [label=\"{}\"];",
+                            rule_index,
+                            child,
+                            escape(name)
+                        )
+                        .expect("Writing to string");
+                    }
+                }
+                TypeDescription::ValidIdent(child) | TypeDescription::Rule(child) => {
+                    writeln!(output, "    {} -> {} [style=dashed];", rule_index, child)
+                        .expect("Writing to string");
+                }
+                _ => {}
+            }
+        }
+        for (expected_index, actual_index) in &self.assertions {
+            writeln!(
+                output,
+                "    {} -> {} [style=bold, color=blue, label=\"asserts\"];",
+                expected_index, actual_index
+            )
+            .expect("Writing to string");
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,153 @@
+use crate::{
+    ast::Primitive,
+    semantics::types::{BuiltInType, RuleIndex, TypeDescription, TypesResult},
+};
+
+/// Type of a rule with all references followed, e.g. to display inferred types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResolvedType {
+    BuiltIn(BuiltInType),
+    Null,
+    /// Reference to a def by its name
+    Def(String),
+    /// Fields sorted by their name
+    Struct(Vec<(String, Self)>),
+    /// Distinct types of the elements
+    List(Vec<Self>),
+    Union(Vec<Self>),
+    Unknown,
+}
+
+impl ResolvedType {
+    /// Combine the distinct types into a union.
+    /// A single type is not wrapped.
+    fn union(types: impl IntoIterator<Item = Self>) -> Self {
+        let mut types = distinct(types);
+        if types.len() == 1 {
+            types.remove(0)
+        } else {
+            Self::Union(types)
+        }
+    }
+
+    /// Returns whether this type does not contain `ResolvedType::Unknown`.
+    pub fn is_known(&self) -> bool {
+        match self {
+            Self::Unknown => false,
+            Self::BuiltIn(_) | Self::Null | Self::Def(_) => true,
+            Self::Struct(fields) => fields.iter().all(|(_, field_type)| field_type.is_known()),
+            Self::List(types) | Self::Union(types) => types.iter().all(Self::is_known),
+        }
+    }
+}
+
+/// Renders the type in the syntax of type expressions, e.g. `{ port: Number hosts: [String] }`.
+impl std::fmt::Display for ResolvedType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BuiltIn(built_in) => write!(f, "{}", built_in.as_str()),
+            Self::Null => write!(f, "null"),
+            Self::Def(name) => write!(f, "{}", name),
+            Self::Struct(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Self::Struct(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(name, field_type)| format!("{}: {}", name, field_type))
+                    .collect();
+                write!(f, "{{ {} }}", fields.join(" "))
+            }
+            Self::List(types) => write!(f, "[{}]", join(types)),
+            Self::Union(types) => write!(f, "{}", join(types)),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+fn join(types: &[ResolvedType]) -> String {
+    types
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn distinct(types: impl IntoIterator<Item = ResolvedType>) -> Vec<ResolvedType> {
+    let mut distinct = Vec::new();
+    for resolved_type in types {
+        if !distinct.contains(&resolved_type) {
+            distinct.push(resolved_type);
+        }
+    }
+    distinct
+}
+
+impl TypesResult {
+    /// Resolve the type of a rule.
+    ///
+    /// References to defs are kept by their name, all other references are followed.
+    /// Primitive values are generalized to their built-in type, e.g. `3` to `Number`.
+    /// Recursive references are unknown.
+    pub fn resolve(&self, rule_index: RuleIndex) -> ResolvedType {
+        self.resolve_rule(rule_index, &mut Vec::new())
+    }
+
+    fn resolve_rule(&self, rule_index: RuleIndex, visited: &mut Vec<RuleIndex>) -> ResolvedType {
+        let Some(rule) = self.rules.get(rule_index) else {
+            return ResolvedType::Unknown;
+        };
+        if visited.contains(&rule_index) {
+            return ResolvedType::Unknown;
+        }
+        visited.push(rule_index);
+        let resolved_type = match &rule.type_description {
+            TypeDescription::Union(indices) => ResolvedType::union(
+                indices
+                    .iter()
+                    .map(|rule_index| self.resolve_rule(*rule_index, visited)),
+            ),
+            TypeDescription::Struct(fields) => {
+                let mut fields: Vec<_> = fields
+                    .iter()
+                    .map(|(name, (rule_index, _, _))| {
+                        (name.clone(), self.resolve_rule(*rule_index, visited))
+                    })
+                    .collect();
+                fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+                ResolvedType::Struct(fields)
+            }
+            TypeDescription::List(indices) => ResolvedType::List(distinct(
+                indices
+                    .iter()
+                    .map(|rule_index| self.resolve_rule(*rule_index, visited)),
+            )),
+            TypeDescription::ValidIdent(rule_index) => self.def_name(*rule_index).map_or_else(
+                || self.resolve_rule(*rule_index, visited),
+                |name| ResolvedType::Def(name.to_string()),
+            ),
+            TypeDescription::Rule(rule_index) => self.resolve_rule(*rule_index, visited),
+            TypeDescription::Primitive(primitive) => match primitive {
+                Primitive::String(_) | Primitive::Char(_) => {
+                    ResolvedType::BuiltIn(BuiltInType::String)
+                }
+                Primitive::Number(_) => ResolvedType::BuiltIn(BuiltInType::Number),
+                Primitive::Bool(_) => ResolvedType::BuiltIn(BuiltInType::Bool),
+                Primitive::Null(_) => ResolvedType::Null,
+            },
+            TypeDescription::BuiltIn(built_in) => ResolvedType::BuiltIn(*built_in),
+            TypeDescription::StringLength { .. } => ResolvedType::BuiltIn(BuiltInType::String),
+            TypeDescription::Ident(_) | TypeDescription::Opaque | TypeDescription::Unknown => {
+                ResolvedType::Unknown
+            }
+        };
+        visited.pop();
+        resolved_type
+    }
+
+    /// Name of the def, which is described by the rule.
+    fn def_name(&self, rule_index: RuleIndex) -> Option<&str> {
+        self.idents
+            .iter()
+            .find(|(ident, index)| **index == rule_index && self.defs.contains(ident))
+            .map(|(ident, _)| ident.end())
+    }
+}
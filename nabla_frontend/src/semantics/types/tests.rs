@@ -0,0 +1,362 @@
+use crate::{
+    eval::{Value, ValuePath},
+    lexer::{lex, LexerResult},
+    parser::{parse, ParserResult},
+    semantics::{self, error::ErrorMessage, types::ValidationError, SemanticsResult},
+    GlobalIdent, ModuleAst,
+};
+use pretty_assertions::assert_eq;
+
+const SCHEMA: &str = r#"
+def Config = {
+    input_file: String
+    output_folder: String = "out/"
+    version: 1 | 2 = 2
+    verbose: Bool | null = null
+    tags: [String] = []
+    server: {
+        host: String
+        port: Number = 80
+    }
+}
+"#;
+
+fn validate(src: &str, name: &str, value: &Value) -> Option<Vec<ValidationError>> {
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { types, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    types.validate_ident(&GlobalIdent::default().extend(name.to_string()), value)
+}
+
+fn path(segments: &[&str]) -> ValuePath {
+    segments.iter().fold(ValuePath::root(), |path, segment| {
+        segment
+            .parse::<usize>()
+            .map_or_else(|_| path.join_key(segment), |index| path.join_index(index))
+    })
+}
+
+#[test]
+fn valid_value() {
+    let value = Value::from([
+        ("input_file", Value::from("in.txt")),
+        ("version", Value::from(1)),
+        ("tags", Value::from(["a", "b"])),
+        ("server", Value::from([("host", "localhost")])),
+    ]);
+    let errors = validate(SCHEMA, "Config", &value).unwrap();
+    assert_empty!(errors);
+}
+
+#[test]
+fn undefined_ident() {
+    assert_eq!(None, validate(SCHEMA, "Other", &Value::Null));
+}
+
+#[test]
+fn missing_required_fields() {
+    let value = Value::from([("server", Value::from([("port", 8080)]))]);
+    let mut errors = validate(SCHEMA, "Config", &value).unwrap();
+    errors.sort_by_key(|error| error.path.to_string());
+    assert_eq!(
+        vec![
+            ValidationError::new(
+                ErrorMessage::MissingField("input_file".to_string()),
+                path(&[])
+            ),
+            ValidationError::new(
+                ErrorMessage::MissingField("host".to_string()),
+                path(&["server"])
+            ),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn unexpected_field() {
+    let value = Value::from([
+        ("input_file", Value::from("in.txt")),
+        (
+            "server",
+            Value::from([("host", "localhost"), ("user", "x")]),
+        ),
+    ]);
+    let errors = validate(SCHEMA, "Config", &value).unwrap();
+    assert_eq!(
+        vec![ValidationError::new(
+            ErrorMessage::UnexpecedField("user".to_string()),
+            path(&["server", "user"])
+        )],
+        errors
+    );
+}
+
+#[test]
+fn built_in_mismatch() {
+    let value = Value::from([
+        ("input_file", Value::from(1)),
+        ("server", Value::from([("host", "localhost")])),
+    ]);
+    let errors = validate(SCHEMA, "Config", &value).unwrap();
+    assert_eq!(
+        vec![ValidationError::new(
+            ErrorMessage::ValueMismatch("String".to_string(), "1".to_string()),
+            path(&["input_file"])
+        )],
+        errors
+    );
+}
+
+#[test]
+fn string_length_mismatch() {
+    let src = "def Country = String(2)";
+    assert_empty!(validate(src, "Country", &Value::from("DE")).unwrap());
+    assert_empty!(validate(src, "Country", &Value::from("ÄÖ")).unwrap());
+    assert_eq!(
+        vec![ValidationError::new(
+            ErrorMessage::ValueMismatch("String(2)".to_string(), "DEU (length 3)".to_string()),
+            ValuePath::root()
+        )],
+        validate(src, "Country", &Value::from("DEU")).unwrap()
+    );
+}
+
+#[test]
+fn literal_union_mismatch() {
+    let value = Value::from([
+        ("input_file", Value::from("in.txt")),
+        ("version", Value::from(3)),
+        ("verbose", Value::from("yes")),
+        ("server", Value::from([("host", "localhost")])),
+    ]);
+    let mut errors = validate(SCHEMA, "Config", &value).unwrap();
+    errors.sort_by_key(|error| error.path.to_string());
+    assert_eq!(
+        vec![
+            ValidationError::new(ErrorMessage::TypeMismatch, path(&["verbose"])),
+            ValidationError::new(ErrorMessage::TypeMismatch, path(&["version"])),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn union_with_null() {
+    let value = Value::from([
+        ("input_file", Value::from("in.txt")),
+        ("verbose", Value::Null),
+        ("server", Value::from([("host", "localhost")])),
+    ]);
+    let errors = validate(SCHEMA, "Config", &value).unwrap();
+    assert_empty!(errors);
+}
+
+#[test]
+fn numbers_compare_numerically() {
+    let value = Value::from([
+        ("input_file", Value::from("in.txt")),
+        ("version", Value::Number("2.0".to_string())),
+        ("server", Value::from([("host", "localhost")])),
+    ]);
+    let errors = validate(SCHEMA, "Config", &value).unwrap();
+    assert_empty!(errors);
+}
+
+#[test]
+fn list_element_mismatch() {
+    let value = Value::from([
+        ("input_file", Value::from("in.txt")),
+        (
+            "tags",
+            Value::List(vec![Value::from("a"), Value::from(true)]),
+        ),
+        ("server", Value::from([("host", "localhost")])),
+    ]);
+    let errors = validate(SCHEMA, "Config", &value).unwrap();
+    assert_eq!(
+        vec![ValidationError::new(
+            ErrorMessage::ValueMismatch("String".to_string(), "true".to_string()),
+            path(&["tags", "1"])
+        )],
+        errors
+    );
+}
+
+#[test]
+fn struct_expected() {
+    let value = Value::from([
+        ("input_file", Value::from("in.txt")),
+        ("server", Value::from(["localhost"])),
+    ]);
+    let errors = validate(SCHEMA, "Config", &value).unwrap();
+    assert_eq!(
+        vec![ValidationError::new(
+            ErrorMessage::TypeMismatch,
+            path(&["server"])
+        )],
+        errors
+    );
+}
+
+#[test]
+fn referenced_def() {
+    let src = r#"
+def Server = {
+    host: String
+}
+def Servers = [Server]
+"#;
+    let value = Value::from([
+        Value::from([("host", "a")]),
+        Value::from([("hostname", "b")]),
+    ]);
+    let errors = validate(src, "Servers", &value).unwrap();
+    assert_eq!(
+        vec![
+            ValidationError::new(ErrorMessage::MissingField("host".to_string()), path(&["1"])),
+            ValidationError::new(
+                ErrorMessage::UnexpecedField("hostname".to_string()),
+                path(&["1", "hostname"])
+            ),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn path_display() {
+    assert_eq!("/", path(&[]).to_string());
+    assert_eq!(
+        "/servers/0/a~1b~0c",
+        path(&["servers", "0"]).join_key("a/b~c").to_string()
+    );
+}
+
+#[test]
+fn opaque_accepts_any_value() {
+    let src = r#"
+def Opaque
+def Config = {
+    external: Opaque
+}
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { types, .. } = semantics::analyze(&module_ast);
+    for external in [
+        Value::Null,
+        Value::from("a"),
+        Value::from([1, 2]),
+        Value::from([("b", true)]),
+    ] {
+        let value = Value::from([("external", external)]);
+        let errors = types
+            .validate_ident(&GlobalIdent::default().extend("Config".to_string()), &value)
+            .unwrap();
+        assert_empty!(errors);
+    }
+}
+
+#[test]
+fn any_validates_every_value() {
+    let src = r#"
+def Config = {
+    a: Any
+    b: Any | null
+    c: [Any]
+}
+"#;
+    for any in [
+        Value::Null,
+        Value::from("a"),
+        Value::from([1, 2]),
+        Value::from([("b", true)]),
+    ] {
+        let value = Value::from([
+            ("a", any.clone()),
+            ("b", any.clone()),
+            ("c", Value::List(vec![any])),
+        ]);
+        let errors = validate(src, "Config", &value).unwrap();
+        assert_empty!(errors);
+    }
+}
+
+#[test]
+fn dot_graph() {
+    let src = r#"
+def Node = {
+    value: Number | "none"
+    next: Node | null = null
+}
+let head: Node = { value = 1 }
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { types, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    insta::assert_snapshot!(types.to_dot());
+}
+
+#[test]
+fn resolved_let_types() {
+    let src = r#"
+def Server = {
+    host: String
+    port: Number = 80
+}
+let port = 8080
+let hosts = ["a" "b"]
+let server = Server { host = "localhost" }
+let fallback = port
+let flag = !true
+let mixed = { values = [1 "b" null] server = server }
+"#;
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { types, errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    let resolve = |name: &str| {
+        let rule_index = types.idents[&GlobalIdent::default().extend(name.to_string())];
+        types.resolve(rule_index).to_string()
+    };
+    assert_eq!("Number", resolve("port"));
+    assert_eq!("[String]", resolve("hosts"));
+    assert_eq!("Server", resolve("server"));
+    assert_eq!("Number", resolve("fallback"));
+    assert_eq!("Bool", resolve("flag"));
+    assert_eq!(
+        "{ server: Server values: [Number | String | null] }",
+        resolve("mixed")
+    );
+}
+
+#[test]
+fn exponents_match_numbers() {
+    let src = "def Limit = 1000 | 2e6\nlet a: Number = 2.5e-3\nlet b: Limit = 1e3\nlet c: Limit = 2_000_000";
+    let LexerResult { tokens, errors } = lex(src);
+    assert_empty!(errors);
+    let ParserResult { ast, errors } = parse(&tokens);
+    assert_empty!(errors);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    assert_empty!(errors);
+    assert_eq!(
+        Some(Vec::new()),
+        validate(src, "Limit", &Value::Number("1E3".to_string()))
+    );
+}
@@ -0,0 +1,204 @@
+use crate::{
+    eval::{Eval, Value, ValuePath},
+    semantics::{
+        error::ErrorMessage,
+        types::{check_string_length, BuiltInType, Rule, RuleIndex, TypeDescription, TypesResult},
+    },
+};
+
+/// Error that occurred while validating a value against a type.
+/// Since external values have no token ranges,
+/// the location is given as path into the value.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{path}: {message}")]
+pub struct ValidationError {
+    pub message: ErrorMessage,
+    pub path: ValuePath,
+}
+
+impl ValidationError {
+    pub const fn new(message: ErrorMessage, path: ValuePath) -> Self {
+        Self { message, path }
+    }
+}
+
+/// Validate a value against the type of the rule with the given index.
+pub fn validate(
+    types_result: &TypesResult,
+    rule_index: RuleIndex,
+    value: &Value,
+) -> Vec<ValidationError> {
+    let rule = types_result.rules.get(rule_index).expect("Rule must exist");
+    check(&types_result.rules, rule, value, &ValuePath::root())
+}
+
+fn get_rule(rules: &[Rule], rule_index: RuleIndex) -> &Rule {
+    rules.get(rule_index).expect("Rule must exist")
+}
+
+fn extract_type_description<'a>(rules: &'a [Rule], rule: &'a Rule) -> &'a TypeDescription {
+    match &rule.type_description {
+        TypeDescription::ValidIdent(rule_index) | TypeDescription::Rule(rule_index) => {
+            extract_type_description(rules, get_rule(rules, *rule_index))
+        }
+        type_description => type_description,
+    }
+}
+
+fn check(
+    rules: &[Rule],
+    expected_rule: &Rule,
+    value: &Value,
+    path: &ValuePath,
+) -> Vec<ValidationError> {
+    let mismatch = || {
+        vec![ValidationError::new(
+            ErrorMessage::TypeMismatch,
+            path.clone(),
+        )]
+    };
+    match (extract_type_description(rules, expected_rule), value) {
+        (TypeDescription::Ident(_), _)
+        | (TypeDescription::ValidIdent(_), _)
+        | (TypeDescription::Rule(_), _) => panic!("Unexpected type description"),
+        (_, Value::Unknown) | (TypeDescription::Opaque, _) => Vec::new(),
+        (TypeDescription::Unknown, _) => {
+            vec![ValidationError::new(
+                ErrorMessage::UnknownType,
+                path.clone(),
+            )]
+        }
+        (TypeDescription::Union(alternatives), value) => {
+            if alternatives
+                .iter()
+                .any(|index| check(rules, get_rule(rules, *index), value, path).is_empty())
+            {
+                Vec::new()
+            } else {
+                mismatch()
+            }
+        }
+        (TypeDescription::BuiltIn(built_in), value) => check_built_in(built_in, value, path),
+        (TypeDescription::StringLength { min, max }, Value::String(actual)) => {
+            check_string_length(*min, *max, actual)
+                .map(|message| vec![ValidationError::new(message, path.clone())])
+                .unwrap_or_default()
+        }
+        (TypeDescription::StringLength { .. }, value) => {
+            check_built_in(&BuiltInType::String, value, path)
+        }
+        (TypeDescription::Primitive(primitive), value) => {
+            let expected = primitive.eval();
+            if primitive_eq(&expected, value) {
+                Vec::new()
+            } else {
+                vec![ValidationError::new(
+                    ErrorMessage::ValueMismatch(primitive.as_str().to_string(), value_str(value)),
+                    path.clone(),
+                )]
+            }
+        }
+        (TypeDescription::Struct(expected), Value::Struct(actual)) => {
+            let mut errors = Vec::new();
+            for (field, (rule_index, has_default, _)) in expected {
+                if let Some(value) = actual.get(field) {
+                    errors.extend(check(
+                        rules,
+                        get_rule(rules, *rule_index),
+                        value,
+                        &path.join_key(field),
+                    ));
+                } else if !has_default {
+                    errors.push(ValidationError::new(
+                        ErrorMessage::MissingField(field.clone()),
+                        path.clone(),
+                    ));
+                }
+            }
+            let mut unexpected: Vec<_> = actual
+                .keys()
+                .filter(|key| !expected.contains_key(*key))
+                .collect();
+            unexpected.sort();
+            errors.extend(unexpected.into_iter().map(|key| {
+                ValidationError::new(
+                    ErrorMessage::UnexpecedField(key.clone()),
+                    path.join_key(key),
+                )
+            }));
+            errors
+        }
+        (TypeDescription::List(expected), Value::List(actual)) => match expected.as_slice() {
+            [] => actual
+                .iter()
+                .enumerate()
+                .map(|(index, _)| {
+                    ValidationError::new(ErrorMessage::UnexpecedListElement, path.join_index(index))
+                })
+                .collect(),
+            [element_index] => {
+                let element_rule = get_rule(rules, *element_index);
+                actual
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(index, value)| {
+                        check(rules, element_rule, value, &path.join_index(index))
+                    })
+                    .collect()
+            }
+            // more than one element type was already rejected by the type analysis
+            _ => Vec::new(),
+        },
+        _ => mismatch(),
+    }
+}
+
+fn check_built_in(built_in: &BuiltInType, value: &Value, path: &ValuePath) -> Vec<ValidationError> {
+    let matches = matches!(
+        (built_in, value),
+        (BuiltInType::String, Value::String(_))
+            | (BuiltInType::Number, Value::Number(_))
+            | (BuiltInType::Bool, Value::Bool(_))
+            | (BuiltInType::Any, _)
+    );
+    if matches {
+        Vec::new()
+    } else if matches!(value, Value::Struct(_) | Value::List(_)) {
+        vec![ValidationError::new(
+            ErrorMessage::TypeMismatch,
+            path.clone(),
+        )]
+    } else {
+        vec![ValidationError::new(
+            ErrorMessage::ValueMismatch(built_in.as_str().to_string(), value_str(value)),
+            path.clone(),
+        )]
+    }
+}
+
+/// Compare primitive values.
+/// Numbers are compared numerically, because external formats might normalize them
+/// (e.g. `1.0` and `1`).
+fn primitive_eq(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Number(expected), Value::Number(actual)) => {
+            match (expected.parse::<f64>(), actual.parse::<f64>()) {
+                (Ok(expected), Ok(actual)) => expected == actual,
+                _ => expected == actual,
+            }
+        }
+        (expected, actual) => expected == actual,
+    }
+}
+
+fn value_str(value: &Value) -> String {
+    match value {
+        Value::Unknown => "unknown".to_string(),
+        Value::Null => crate::token::NULL.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.clone(),
+        Value::String(s) => s.to_string(),
+        Value::List(_) => "list".to_string(),
+        Value::Struct(_) => "struct".to_string(),
+    }
+}
@@ -1,20 +1,35 @@
 use crate::{
     ast::{AstInfo, Global, Use, UseBody, UseItem, UseKind},
+    resolver::ModuleResolver,
     semantics::{
-        error::{Error, ErrorMessage},
-        Errors, Namespace,
+        error::{Error, ErrorMessage, FIRST_DECLARED},
+        Declarations, Errors, Namespace,
     },
-    token::ToTokenRange,
+    token::{ToTokenRange, TokenRange},
     GlobalIdent, ModuleAst,
 };
+use std::collections::HashMap;
+
+/// Used identifiers with their global name and the token range of their declaration
+type Imports = HashMap<String, (GlobalIdent, TokenRange)>;
 
 /// Analyze use statements for a module.
 ///
-/// Returns (_namespace_, _errors_).
+/// Returns (_namespace_, _declarations_, _errors_).
 ///
 /// The namespace is a map from the module-local identifier as a String to the global identifier.
-pub fn analyze(module_ast: &ModuleAst) -> (Namespace, Errors) {
-    module_ast
+/// The declarations map the same identifiers to the token range, where they are used.
+///
+/// With a resolver, the used identifiers are checked against its modules.
+/// An unknown root or an identifier, that names neither a module, a directory of modules
+/// nor an item of a module, is undefined. Undefined identifiers are imported anyway,
+/// so their references are not reported again.
+pub fn analyze(
+    module_ast: &ModuleAst,
+    resolver: Option<&dyn ModuleResolver>,
+) -> (Namespace, Declarations, Errors) {
+    let modules = resolver.map(ModuleResolver::modules);
+    let (imports, errors) = module_ast
         .ast
         .globals
         .iter()
@@ -23,26 +38,38 @@ pub fn analyze(module_ast: &ModuleAst) -> (Namespace, Errors) {
             _ => None,
         })
         .map(|u| {
-            let (idents, errors) = analyze_use(u);
+            let (idents, errors) = analyze_use(u, modules.as_deref());
             (&u.info, idents, errors)
         })
-        .fold((Namespace::new(), Errors::new()), fold_uses)
+        .fold((Imports::new(), Errors::new()), fold_uses);
+    let mut namespace = Namespace::new();
+    let mut declarations = Declarations::new();
+    for (name, (global_ident, range)) in imports {
+        namespace.insert(name.clone(), global_ident);
+        declarations.insert(name, range);
+    }
+    (namespace, declarations, errors)
 }
 
 /// Fold uses into a single map.
-/// If a name is used twice, a duplicate error is reported.
+/// If a name is used twice, a duplicate error is reported,
+/// which points to the first use of the name.
 fn fold_uses(
-    (mut idents, mut errors): (Namespace, Errors),
-    (info, new_idents, new_errors): (&AstInfo, Namespace, Errors),
-) -> (Namespace, Errors) {
+    (mut idents, mut errors): (Imports, Errors),
+    (info, new_idents, new_errors): (&AstInfo, Imports, Errors),
+) -> (Imports, Errors) {
     use std::collections::hash_map::Entry;
     for (key, value) in new_idents {
         match idents.entry(key) {
             Entry::Occupied(entry) => {
-                errors.push(Error::new(
-                    ErrorMessage::DuplicateUse(entry.key().clone()),
-                    info.to_token_range(),
-                ));
+                let (_, first_range) = entry.get();
+                errors.push(
+                    Error::new(
+                        ErrorMessage::DuplicateUse(entry.key().clone()),
+                        info.to_token_range(),
+                    )
+                    .with_secondary(FIRST_DECLARED, first_range.clone()),
+                );
             }
             Entry::Vacant(entry) => {
                 entry.insert(value);
@@ -53,12 +80,33 @@ fn fold_uses(
     (idents, errors)
 }
 
-fn analyze_use(u: &Use) -> (Namespace, Errors) {
-    match (&u.name, &u.body) {
+/// Whether the identifier names a module, a directory of modules or an item of a module.
+fn is_known(ident: &GlobalIdent, modules: &[GlobalIdent]) -> bool {
+    modules.iter().any(|module| {
+        module.root == ident.root
+            && (module.path.starts_with(&ident.path)
+                || (ident.path.len() == module.path.len() + 1
+                    && ident.path.starts_with(&module.path)))
+    })
+}
+
+fn analyze_use(u: &Use, mut modules: Option<&[GlobalIdent]>) -> (Imports, Errors) {
+    let mut errors = Errors::new();
+    if let (Some(root), Some(known)) = (&u.name, modules) {
+        if !is_known(&GlobalIdent::new(root.name.clone()), known) {
+            errors.push(Error::new(
+                ErrorMessage::UndefinedIdent(root.name.clone()),
+                root.to_token_range(),
+            ));
+            // the nested items of an undefined root are not reported again
+            modules = None;
+        }
+    }
+    let (idents, body_errors) = match (&u.name, &u.body) {
         (Some(root), Some(body)) => {
             // the path stack is used to keep track of the module hierarchy.
             let mut path_stack = vec![root.name.clone()];
-            let (idents, errors, _) = analyze_body(body, &mut path_stack);
+            let (idents, errors, _) = analyze_body(body, &mut path_stack, modules);
             (idents, errors)
         }
         (Some(root), None) => {
@@ -66,32 +114,40 @@ fn analyze_use(u: &Use) -> (Namespace, Errors) {
                 root: root.name.clone(),
                 path: Vec::new(),
             };
+            let identifier = u.identifier().expect("name is present");
             (
-                Namespace::from([(u.identifier().expect("name is present").name.clone(), ident)]),
+                Imports::from([(
+                    identifier.name.clone(),
+                    (ident, identifier.to_token_range()),
+                )]),
                 Errors::new(),
             )
         }
-        _ => (Namespace::new(), Errors::new()),
-    }
+        _ => (Imports::new(), Errors::new()),
+    };
+    errors.extend(body_errors);
+    (idents, errors)
 }
 
 /// Analyzes the body and returns whether the `UseKind` was `Single`.
-fn analyze_body(body: &UseBody, path_stack: &mut Vec<String>) -> (Namespace, Errors, bool) {
+fn analyze_body(
+    body: &UseBody,
+    path_stack: &mut Vec<String>,
+    modules: Option<&[GlobalIdent]>,
+) -> (Imports, Errors, bool) {
     body.kind.as_ref().map_or_else(
-        || (Namespace::new(), Errors::new(), false),
+        || (Imports::new(), Errors::new(), false),
         |kind| match kind {
             UseKind::All(info) => (
-                Namespace::new(),
-                vec![
-                    (Error {
-                        message: ErrorMessage::Unsupported("glob import".to_string()),
-                        range: info.to_token_range(),
-                    }),
-                ],
+                Imports::new(),
+                vec![Error::new(
+                    ErrorMessage::Unsupported("glob import".to_string()),
+                    info.to_token_range(),
+                )],
                 false,
             ),
             UseKind::Single(item) => {
-                let (idents, errors) = analyze_item(item, path_stack);
+                let (idents, errors) = analyze_item(item, path_stack, modules);
                 (idents, errors, true)
             }
             UseKind::Multiple(items) => {
@@ -100,26 +156,30 @@ fn analyze_body(body: &UseBody, path_stack: &mut Vec<String>) -> (Namespace, Err
                     .iter()
                     .flatten()
                     .map(|item| {
-                        let (idents, errors) = analyze_item(item, path_stack);
+                        let (idents, errors) = analyze_item(item, path_stack, modules);
                         (&item.info, idents, errors)
                     })
-                    .fold((Namespace::new(), Errors::new()), fold_uses);
+                    .fold((Imports::new(), Errors::new()), fold_uses);
                 (idents, errors, false)
             }
-            UseKind::Error(_) => (Namespace::new(), Errors::new(), false),
+            UseKind::Error(_) => (Imports::new(), Errors::new(), false),
         },
     )
 }
 
-fn analyze_item(item: &UseItem, path_stack: &mut Vec<String>) -> (Namespace, Errors) {
+fn analyze_item(
+    item: &UseItem,
+    path_stack: &mut Vec<String>,
+    modules: Option<&[GlobalIdent]>,
+) -> (Imports, Errors) {
     path_stack.push(item.name.name.clone());
     if let Some(body) = &item.body {
-        let (idents, mut errors, is_single) = analyze_body(body, path_stack);
+        let (idents, mut errors, is_single) = analyze_body(body, path_stack, modules);
         if !is_single {
             if let Some(alias) = &item.alias {
                 errors.push(Error::new(
-                    ErrorMessage::AliasingNonSingle,
-                    alias.info.to_token_range(),
+                    ErrorMessage::AliasingNonSingle(item.name.name.clone()),
+                    alias.to_token_range(),
                 ));
             }
         }
@@ -135,9 +195,20 @@ fn analyze_item(item: &UseItem, path_stack: &mut Vec<String>) -> (Namespace, Err
             path: path_stack[1..].to_vec(),
         };
         path_stack.pop();
+        let mut errors = Errors::new();
+        if modules.is_some_and(|modules| !is_known(&ident, modules)) {
+            errors.push(Error::new(
+                ErrorMessage::UndefinedIdent(ident.to_string()),
+                item.name.to_token_range(),
+            ));
+        }
+        let identifier = item.identifier();
         (
-            Namespace::from([(item.identifier().name.clone(), ident)]),
-            Errors::new(),
+            Imports::from([(
+                identifier.name.clone(),
+                (ident, identifier.to_token_range()),
+            )]),
+            errors,
         )
     }
 }
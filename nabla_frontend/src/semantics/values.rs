@@ -1,17 +1,21 @@
 use crate::{
-    ast::{AstInfo, Global, Ident, Let},
-    eval::Value,
-    semantics::{Error, ErrorMessage, Errors, SymbolTable},
-    token::ToTokenRange,
+    ast::{ArithOperator, AstInfo, BoolOperator, Expr, Global, Ident, Single},
+    eval::{PathSegment, Value, ValuePath},
+    semantics::{types::BuiltInType, Error, ErrorMessage, Errors, Namespace, SymbolTable},
+    token::{ToTokenRange, TokenRange},
     GlobalIdent, ModuleAst,
 };
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
 
 mod analysis;
 
 /// Index into rule list
 type RuleIndex = usize;
 
+/// Value of the globals of imports, whose module was not loaded
+static UNKNOWN: Value = Value::Unknown;
+
 #[derive(Clone, Debug)]
 struct Rule {
     pub value_description: ValueDescription,
@@ -19,71 +23,286 @@ struct Rule {
     pub info: AstInfo,
 }
 
+impl Rule {
+    /// The rule with all its rule indices moved by the offset.
+    fn relocated(&self, offset: usize) -> Self {
+        let value_description = match &self.value_description {
+            ValueDescription::Union(indices) => {
+                ValueDescription::Union(indices.iter().map(|index| index + offset).collect())
+            }
+            ValueDescription::Struct(fields) => ValueDescription::Struct(
+                fields
+                    .iter()
+                    .map(|(name, (index, emit))| (name.clone(), (index + offset, emit.clone())))
+                    .collect(),
+            ),
+            ValueDescription::List(indices) => {
+                ValueDescription::List(indices.iter().map(|index| index + offset).collect())
+            }
+            ValueDescription::Composed(own_index, super_index) => {
+                ValueDescription::Composed(own_index + offset, super_index + offset)
+            }
+            ValueDescription::Spread(own_index, spread_indices) => ValueDescription::Spread(
+                own_index + offset,
+                spread_indices.iter().map(|index| index + offset).collect(),
+            ),
+            ValueDescription::Not(operand) => ValueDescription::Not(operand + offset),
+            ValueDescription::BoolOp(operator, lhs, rhs) => {
+                ValueDescription::BoolOp(*operator, lhs + offset, rhs + offset)
+            }
+            ValueDescription::Neg(operand) => ValueDescription::Neg(operand + offset),
+            ValueDescription::ArithOp(operator, lhs, rhs) => {
+                ValueDescription::ArithOp(*operator, lhs + offset, rhs + offset)
+            }
+            value_description => value_description.clone(),
+        };
+        Self {
+            value_description,
+            is_default: self.is_default,
+            info: self.info.clone(),
+        }
+    }
+}
+
+/// How a struct field is emitted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldEmit {
+    /// By its name
+    Name,
+    /// By its alias, e.g. `as "content-type"`
+    Alias(String),
+    /// Not at all, because of the reserved alias `as _`
+    Hidden,
+}
+
 #[derive(Clone, Debug)]
 enum ValueDescription {
     Union(Vec<RuleIndex>),
-    Struct(HashMap<String, RuleIndex>),
+    /// Fields with their rule and how they are emitted
+    Struct(IndexMap<String, (RuleIndex, FieldEmit)>),
     List(Vec<RuleIndex>),
     Primitive(Value),
     /// Composed(own rule, super rule)
+    ///
+    /// The fields of the own value take precedence over the fields of the super value.
+    /// Nesting gives the precedence init > field default > type default.
     Composed(RuleIndex, RuleIndex),
-    Ref(Ident),
+    /// Spread(own struct rule, spread rules)
+    ///
+    /// Unlike composition, the fields of later spreads replace the ones of earlier spreads
+    /// and the own fields replace all of them, e.g. `{ ...base port = 80 }`.
+    Spread(RuleIndex, Vec<RuleIndex>),
+    /// Ref(root, inner names)
+    Ref(Ident, Vec<Ident>),
+    Not(RuleIndex),
+    /// BoolOp(operator, lhs, rhs)
+    BoolOp(BoolOperator, RuleIndex, RuleIndex),
+    Neg(RuleIndex),
+    /// ArithOp(operator, lhs, rhs)
+    ArithOp(ArithOperator, RuleIndex, RuleIndex),
     Empty,
     Unknown,
 }
 
 #[derive(Clone, Debug)]
 pub struct ValuesResult {
+    /// Values of all inits in order, only the first is emitted
     pub inits: Vec<Value>,
+    /// Emit name of the first init, if it has a valid alias
+    pub init_name: Option<String>,
     pub symbol_table: SymbolTable,
+    /// Default values of the defs without their hidden fields, as an empty init would emit them
+    pub defaults: SymbolTable,
+    /// Paths of the hidden and aliased fields inside of the value of every global
+    pub emit_paths: EmitPaths,
     pub errors: Errors,
 }
 
+/// Global identifiers and the paths of the fields inside of their value,
+/// that are not emitted by their name
+pub type EmitPaths = HashMap<GlobalIdent, Vec<(ValuePath, FieldEmit)>>;
+
+/// Globals of other modules, that the module imports.
+#[derive(Clone, Debug, Default)]
+pub struct Imported {
+    /// Imported identifiers and their global name
+    pub namespace: Namespace,
+    /// Values of the globals of the imported modules, including their hidden fields
+    pub symbol_table: SymbolTable,
+    pub emit_paths: EmitPaths,
+}
+
+impl Imported {
+    /// Imported global named by the reference and the number of inner names,
+    /// that are part of its name.
+    /// E.g. `a::Server::port` names `root::a::Server` with one inner name, if `root::a` is used.
+    fn lookup(&self, ident: &Ident, inner_names: &[Ident]) -> Option<(&GlobalIdent, usize)> {
+        let global_ident = self.namespace.get(&ident.name)?;
+        (0..=inner_names.len()).find_map(|len| {
+            let names = inner_names[..len]
+                .iter()
+                .map(|name| name.name.clone())
+                .collect();
+            self.symbol_table
+                .get_key_value(&global_ident.clone().extend_multiple(names))
+                .map(|(global_ident, _)| (global_ident, len))
+        })
+    }
+}
+
 pub fn analyze(module_ast: &ModuleAst) -> ValuesResult {
+    let segments: Vec<_> = module_ast
+        .ast
+        .globals
+        .iter()
+        .map(|global| analyze_global(global, &module_ast.name))
+        .collect();
+    link(module_ast, &segments, &Imported::default())
+}
+
+/// Rules of a single global, before they are evaluated.
+///
+/// The rule indices start at zero for every segment,
+/// so a segment stays valid, if other globals of the module change.
+#[derive(Clone, Debug)]
+pub(super) struct Segment {
+    rules: Vec<Rule>,
+    kind: SegmentKind,
+}
+
+/// Global, whose value is described by a segment
+#[derive(Clone, Debug)]
+enum SegmentKind {
+    /// Def(global name, emit name)
+    Def(Option<GlobalIdent>, Option<String>),
+    /// Let(global name, range of the let, emit name)
+    Let(Option<GlobalIdent>, TokenRange, Option<String>),
+    /// Init(emit name, global the init refers to, e.g. `Config` of `Config { port = 80 }`)
+    Init(Option<String>, Option<Ident>),
+    /// Test, that is checked like an init, but not emitted
+    Test,
+    /// Globals without value
+    None,
+}
+
+/// Analyze the value rules of a global.
+pub(super) fn analyze_global(global: &Global, module_name: &GlobalIdent) -> Segment {
+    let mut rules = Vec::new();
+    let global_ident = |ident: &Option<Ident>| {
+        ident
+            .as_ref()
+            .map(|ident| module_name.clone().extend(ident.name.clone()))
+    };
+    let kind = match global {
+        Global::Def(d) => d.expr.as_ref().map_or(SegmentKind::None, |expr| {
+            analysis::analyze(expr, &mut rules);
+            SegmentKind::Def(global_ident(&d.name), d.emit_name().map(str::to_string))
+        }),
+        Global::Let(l) => l.expr.as_ref().map_or(SegmentKind::None, |expr| {
+            analysis::analyze(expr, &mut rules);
+            SegmentKind::Let(
+                global_ident(&l.name),
+                l.to_token_range(),
+                l.emit_name().map(str::to_string),
+            )
+        }),
+        Global::Init(init) => {
+            analysis::analyze(&init.expr, &mut rules);
+            let referred = match &init.expr {
+                Expr::Single(Single::Named(named)) if named.inner_names.is_empty() => {
+                    Some(named.name.clone())
+                }
+                _ => None,
+            };
+            SegmentKind::Init(init.emit_name().map(str::to_string), referred)
+        }
+        Global::Test(test) => test.expr.as_ref().map_or(SegmentKind::None, |expr| {
+            analysis::analyze(expr, &mut rules);
+            SegmentKind::Test
+        }),
+        _ => SegmentKind::None,
+    };
+    Segment { rules, kind }
+}
+
+/// Combine the segments of all globals and evaluate them.
+///
+/// References resolve to the imported globals first, like the namespace,
+/// and to the globals of the module otherwise.
+pub(super) fn link(
+    module_ast: &ModuleAst,
+    segments: &[Segment],
+    imported: &Imported,
+) -> ValuesResult {
     let mut rules = Vec::new();
     let mut rule_table: HashMap<GlobalIdent, RuleIndex> = HashMap::new();
+    let mut defs: HashSet<RuleIndex> = HashSet::new();
     let mut inits: Vec<RuleIndex> = Vec::new();
-    let mut lets: Vec<(&Let, RuleIndex)> = Vec::new();
-
-    for global in module_ast.ast.globals.iter() {
-        match global {
-            Global::Def(d) => {
-                if let Some(expr) = &d.expr {
-                    analysis::analyze(expr, &mut rules);
-                    if let Some(ident) = &d.name {
-                        let rule_index = rules.len() - 1;
-                        rule_table.insert(
-                            module_ast.name.clone().extend(ident.name.clone()),
-                            rule_index,
-                        );
-                    }
+    let mut tests: Vec<RuleIndex> = Vec::new();
+    let mut init_names: Vec<(Option<String>, Option<Ident>)> = Vec::new();
+    let mut emit_names: HashMap<GlobalIdent, String> = HashMap::new();
+    let mut errors = Vec::new();
+    let mut lets: Vec<(TokenRange, RuleIndex)> = Vec::new();
+
+    for segment in segments {
+        let offset = rules.len();
+        rules.extend(segment.rules.iter().map(|rule| rule.relocated(offset)));
+        // the value of the global is described by the last rule of its segment
+        let root_index = rules.len().checked_sub(1);
+        match (&segment.kind, root_index) {
+            (SegmentKind::Def(Some(ident), emit_name), Some(rule_index)) => {
+                rule_table.insert(ident.clone(), rule_index);
+                defs.insert(rule_index);
+                if let Some(emit_name) = emit_name {
+                    emit_names.insert(ident.clone(), emit_name.clone());
                 }
             }
-            Global::Let(l) => {
-                if let Some(expr) = &l.expr {
-                    analysis::analyze(expr, &mut rules);
-                    let rule_index = rules.len() - 1;
-                    if let Some(ident) = &l.name {
-                        rule_table.insert(
-                            module_ast.name.clone().extend(ident.name.clone()),
-                            rule_index,
-                        );
+            (SegmentKind::Let(ident, range, emit_name), Some(rule_index)) => {
+                if let Some(ident) = ident {
+                    rule_table.insert(ident.clone(), rule_index);
+                    if let Some(emit_name) = emit_name {
+                        emit_names.insert(ident.clone(), emit_name.clone());
                     }
-                    lets.push((l, rule_index));
                 }
+                lets.push((range.clone(), rule_index));
             }
-            Global::Init(expr) => {
-                analysis::analyze(expr, &mut rules);
-                let rule_index = rules.len() - 1;
+            (SegmentKind::Init(init_name, referred), Some(rule_index)) => {
                 inits.push(rule_index);
+                init_names.push((init_name.clone(), referred.clone()));
             }
+            (SegmentKind::Test, Some(rule_index)) => tests.push(rule_index),
             _ => {}
         }
     }
-    let mut errors = Vec::new();
-    let evaluated = evaluate(module_ast.name.clone(), &rules, &rule_table, &mut errors);
+    wrap_aliased_spreads(&mut rules, |ident| {
+        if imported.lookup(ident, &[]).is_some() {
+            return None;
+        }
+        emit_names
+            .get(&module_ast.name.clone().extend(ident.name.clone()))
+            .cloned()
+    });
+    let resolve = |ident: &Ident, inner_names: &[Ident]| {
+        if let Some((global_ident, len)) = imported.lookup(ident, inner_names) {
+            return imported
+                .symbol_table
+                .get(global_ident)
+                .map(|value| Resolved::Value(value, len));
+        }
+        rule_table
+            .get(&module_ast.name.clone().extend(ident.name.clone()))
+            .map(|rule_index| Resolved::Rule(*rule_index, defs.contains(rule_index)))
+            .or_else(|| {
+                // the module of the import was not loaded, so its globals are unknown
+                imported
+                    .namespace
+                    .contains_key(&ident.name)
+                    .then_some(Resolved::Value(&UNKNOWN, inner_names.len()))
+            })
+    };
+    let (evaluated, recursive) = evaluate(&rules, resolve, &mut errors);
     for (rule_index, rule) in rules.iter().enumerate() {
-        if rule.is_default {
+        if rule.is_default && !recursive.contains(&rule_index) {
             let value = evaluated
                 .get(&rule_index)
                 .expect("Rule must have been evaluated");
@@ -96,63 +315,185 @@ pub fn analyze(module_ast: &ModuleAst) -> ValuesResult {
             }
         }
     }
-    for (l, rule_index) in lets {
+    for (range, rule_index) in lets {
+        if recursive.contains(&rule_index) {
+            continue;
+        }
         let value = evaluated
             .get(&rule_index)
             .expect("Rule must have been evaluated");
         if !value.is_known() {
-            let error = Error::new(ErrorMessage::UninitializedLet, l.info.to_token_range());
+            let error = Error::new(ErrorMessage::UninitializedLet, range);
             errors.push(error);
         }
     }
     let symbol_table = rule_table
-        .into_iter()
+        .iter()
         .map(|(ident, rule_index)| {
             (
-                ident,
+                ident.clone(),
                 evaluated
-                    .get(&rule_index)
+                    .get(rule_index)
                     .cloned()
                     .expect("Rule must have been evaluated"),
             )
         })
         .collect();
-    inits.iter().skip(1).for_each(|rule_index| {
-        let rule = rules.get(*rule_index).expect("Rule must exists");
-        let error = Error::new(ErrorMessage::MultipleInits, rule.info.to_token_range());
-        errors.push(error);
-    });
+    let context = Context {
+        module: &module_ast.name,
+        rules: &rules,
+        rule_table: &rule_table,
+        evaluated: &evaluated,
+        imported,
+    };
+    // hidden fields must be initialized as well
+    let mut check_initialized = |rule_index: &RuleIndex| {
+        if !recursive.contains(rule_index)
+            && !evaluated.get(rule_index).is_some_and(Value::is_known)
+        {
+            let rule = rules.get(*rule_index).expect("Rule must exists");
+            let error = Error::new(ErrorMessage::UninitializedInit, rule.info.to_token_range());
+            errors.push(error);
+        }
+    };
+    tests.iter().for_each(&mut check_initialized);
     let inits = inits
         .iter()
         .map(|rule_index| {
-            let value = evaluated
-                .get(rule_index)
-                .cloned()
-                .expect("Rule must have been evaluated");
-            if !value.is_known() {
-                let rule = rules.get(*rule_index).expect("Rule must exists");
-                let error = Error::new(ErrorMessage::UninitializedInit, rule.info.to_token_range());
-                errors.push(error);
-            }
-            value
+            check_initialized(rule_index);
+            emitted(&context, *rule_index)
+        })
+        .collect();
+    let defaults = rule_table
+        .iter()
+        .filter(|(_, rule_index)| defs.contains(rule_index))
+        .map(|(ident, rule_index)| (ident.clone(), emitted(&context, *rule_index)))
+        .collect();
+    let emit_paths = rule_table
+        .iter()
+        .map(|(ident, rule_index)| {
+            (
+                ident.clone(),
+                emit_paths(&context, *rule_index, &mut Vec::new()),
+            )
         })
         .collect();
 
+    // the alias of the init takes precedence over the one of the global it refers to
+    let init_name = init_names
+        .into_iter()
+        .next()
+        .and_then(|(init_name, referred)| {
+            init_name.or_else(|| {
+                let referred = referred.filter(|ident| imported.lookup(ident, &[]).is_none())?;
+                emit_names
+                    .get(&module_ast.name.clone().extend(referred.name))
+                    .cloned()
+            })
+        });
     ValuesResult {
         inits,
+        init_name,
         symbol_table,
+        defaults,
+        emit_paths,
         errors,
     }
 }
 
-fn evaluate(
-    module: GlobalIdent,
+/// Spread the defs and lets with an alias as a single field named by the alias,
+/// e.g. `...db` of `let db = { port = 80 } as "database"` like `...{ database = db }`.
+///
+/// Only direct references are wrapped, the fields of `...db::settings` are spread as usual.
+fn wrap_aliased_spreads(rules: &mut Vec<Rule>, emit_name: impl Fn(&Ident) -> Option<String>) {
+    let mut wrapped = Vec::new();
+    for (rule_index, rule) in rules.iter().enumerate() {
+        let ValueDescription::Spread(_, spread_indices) = &rule.value_description else {
+            continue;
+        };
+        for (position, spread_index) in spread_indices.iter().enumerate() {
+            if let ValueDescription::Ref(ident, inner_names) =
+                &rules[*spread_index].value_description
+            {
+                if let Some(name) = emit_name(ident).filter(|_| inner_names.is_empty()) {
+                    wrapped.push((rule_index, position, *spread_index, name));
+                }
+            }
+        }
+    }
+    for (rule_index, position, spread_index, name) in wrapped {
+        let field = (name, (spread_index, FieldEmit::Name));
+        rules.push(Rule {
+            value_description: ValueDescription::Struct(IndexMap::from([field])),
+            is_default: false,
+            info: rules[spread_index].info.clone(),
+        });
+        let struct_index = rules.len() - 1;
+        if let ValueDescription::Spread(_, spread_indices) =
+            &mut rules[rule_index].value_description
+        {
+            spread_indices[position] = struct_index;
+        }
+    }
+}
+
+/// Evaluate a single expression of the module, e.g. of a REPL or a hover,
+/// with the references resolved against the symbol table of the module.
+///
+/// Returns the value including its hidden fields.
+/// References to names, that are not in the symbol table, are reported as undefined.
+pub fn eval_expr(expr: &Expr, module: &GlobalIdent, symbol_table: &SymbolTable) -> (Value, Errors) {
+    let mut rules = Vec::new();
+    analysis::analyze(expr, &mut rules);
+    let mut errors = Errors::new();
+    let resolve = |ident: &Ident, _: &[Ident]| {
+        symbol_table
+            .get(&module.clone().extend(ident.name.clone()))
+            .map(|value| Resolved::Value(value, 0))
+    };
+    for rule in &rules {
+        if let ValueDescription::Ref(ident, inner_names) = &rule.value_description {
+            if resolve(ident, inner_names).is_none()
+                && BuiltInType::from_name(&ident.name).is_none()
+            {
+                errors.push(Error::new(
+                    ErrorMessage::UndefinedIdent(ident.name.clone()),
+                    ident.to_token_range(),
+                ));
+            }
+        }
+    }
+    let (mut evaluated, _) = evaluate(&rules, resolve, &mut errors);
+    // the value of the expression is described by its last rule
+    let value = rules
+        .len()
+        .checked_sub(1)
+        .and_then(|rule_index| evaluated.remove(&rule_index))
+        .unwrap_or(Value::Unknown);
+    (value, errors)
+}
+
+/// Target of a reference
+enum Resolved<'a> {
+    /// Rule of a global and whether the global is a def
+    Rule(RuleIndex, bool),
+    /// Value of a global, that was evaluated before,
+    /// and the number of inner names of the reference, that are part of the global's name
+    Value(&'a Value, usize),
+}
+
+/// Evaluate all rules, following references to the targets given by `resolve`.
+///
+/// Returns the values together with the rules, that are unknown because of a recursion,
+/// which was reported as [`ErrorMessage::RecursiveInit`].
+fn evaluate<'a>(
     rules: &[Rule],
-    rule_table: &HashMap<GlobalIdent, RuleIndex>,
+    resolve: impl Fn(&Ident, &[Ident]) -> Option<Resolved<'a>>,
     errors: &mut Vec<Error>,
-) -> HashMap<RuleIndex, Value> {
+) -> (HashMap<RuleIndex, Value>, HashSet<RuleIndex>) {
     let mut stack: Vec<RuleIndex> = Vec::new();
     let mut evaluated: HashMap<RuleIndex, Value> = HashMap::with_capacity(rules.len());
+    let mut recursive = HashSet::new();
     for rule_index in 0..rules.len() {
         if evaluated.contains_key(&rule_index) {
             continue;
@@ -165,100 +506,71 @@ fn evaluate(
 
             // prevent infinite loop
             if stack.contains(&rule_index) {
-                // a self reference is not necessarily illegal
-                // but it cannot be fully evaluated
+                // the reference, which closes the cycle,
+                // is either the rule itself or the rule, which pushed it.
+                // Defs may be recursive, since they describe types.
+                let closing = std::iter::once(rule_index)
+                    .chain(stack.last().copied())
+                    .map(|index| rules.get(index).expect("Rule must exist"))
+                    .find_map(|rule| match &rule.value_description {
+                        ValueDescription::Ref(ident, inner_names) => {
+                            Some((rule, ident, inner_names))
+                        }
+                        _ => None,
+                    });
+                if let Some((closing, _, _)) = closing.filter(|(_, ident, inner_names)| {
+                    matches!(resolve(ident, inner_names), Some(Resolved::Rule(_, false)))
+                }) {
+                    let error =
+                        Error::new(ErrorMessage::RecursiveInit, closing.info.to_token_range());
+                    errors.push(error);
+                    recursive.insert(rule_index);
+                }
                 evaluated.insert(rule_index, Value::Unknown);
                 continue;
             }
 
             // push dependencies to stack
             let rule = rules.get(rule_index).expect("Rule must exist");
-            match &rule.value_description {
-                ValueDescription::Union(indices) => {
-                    let unevaluated: Vec<_> = indices
-                        .iter()
-                        .filter(|index| !evaluated.contains_key(index))
-                        .collect();
-                    if !unevaluated.is_empty() {
-                        stack.push(rule_index);
-                        stack.extend(unevaluated);
-                        continue;
-                    }
-                }
-                ValueDescription::Struct(s) => {
-                    let unevaluated: Vec<_> = s
-                        .values()
-                        .filter(|index| !evaluated.contains_key(index))
-                        .collect();
-                    if !unevaluated.is_empty() {
-                        stack.push(rule_index);
-                        stack.extend(unevaluated);
-                        continue;
-                    }
-                }
-                ValueDescription::List(indices) => {
-                    let unevaluated: Vec<_> = indices
-                        .iter()
-                        .filter(|index| !evaluated.contains_key(index))
-                        .collect();
-                    if !unevaluated.is_empty() {
-                        stack.push(rule_index);
-                        stack.extend(unevaluated);
-                        continue;
-                    }
-                }
-                ValueDescription::Composed(i1, i2) => {
-                    match (evaluated.contains_key(i1), evaluated.contains_key(i2)) {
-                        (false, false) => {
-                            stack.push(rule_index);
-                            stack.push(*i1);
-                            stack.push(*i2);
-                            continue;
-                        }
-                        (false, true) => {
-                            stack.push(rule_index);
-                            stack.push(*i1);
-                            continue;
-                        }
-                        (true, false) => {
-                            stack.push(rule_index);
-                            stack.push(*i2);
-                            continue;
-                        }
-                        (true, true) => {}
-                    }
-                }
-                ValueDescription::Ref(ident) => {
-                    if ident.is_flattened() {
-                        // TODO: implement lookup
-                        errors.push(Error::new(
-                            ErrorMessage::Unsupported("module references".to_string()),
-                            ident.info.to_token_range(),
-                        ));
-                    }
-                    if let Some(ref_index) =
-                        rule_table.get(&module.clone().extend(ident.name.clone()))
-                    {
-                        if !evaluated.contains_key(ref_index) {
-                            stack.push(rule_index);
-                            stack.push(*ref_index);
-                            continue;
-                        }
-                    }
-                }
-                _ => {}
+            let dependencies = dependencies(&rule.value_description, &resolve);
+            let unevaluated: Vec<_> = dependencies
+                .iter()
+                .filter(|index| !evaluated.contains_key(index))
+                .collect();
+            if !unevaluated.is_empty() {
+                stack.push(rule_index);
+                stack.extend(unevaluated);
+                continue;
+            }
+            // a value, that depends on a reported recursion, is not reported again
+            if dependencies.iter().any(|index| recursive.contains(index)) {
+                recursive.insert(rule_index);
             }
 
             // evaluate
             match &rule.value_description {
-                ValueDescription::Union(_indices) => {
-                    // TODO: How to deal with unions?
-                    evaluated.insert(rule_index, Value::Unknown);
+                ValueDescription::Union(indices) => {
+                    // a union is determined by its only known alternative, e.g. `null` of
+                    // `String | null`, while the alternatives of `"a" | "b"` are ambiguous.
+                    // An alternative, that could not be parsed, might have been known as well.
+                    let is_recovered = indices.iter().any(|index| {
+                        let rule = rules.get(*index).expect("Rule must exist");
+                        matches!(rule.value_description, ValueDescription::Unknown)
+                    });
+                    let mut known = indices
+                        .iter()
+                        .filter_map(|index| evaluated.get(index))
+                        .filter(|value| value.is_known());
+                    let value = match (known.next(), known.next()) {
+                        (Some(value), None) if !is_recovered => value.clone(),
+                        _ => Value::Unknown,
+                    };
+                    evaluated.insert(rule_index, value);
                 }
                 ValueDescription::Struct(s) => {
                     let value = Value::Struct(
                         s.iter()
-                            .map(|(k, rule_index)| {
+                            .map(|(k, (rule_index, _))| {
                                 (
                                     k.clone(),
                                     evaluated.get(rule_index).cloned().unwrap_or(Value::Unknown),
@@ -287,19 +599,83 @@ fn evaluate(
                     own_value.merge_fields(super_value);
                     evaluated.insert(rule_index, own_value);
                 }
+                ValueDescription::Spread(own_index, spread_indices) => {
+                    // values, that are no structs, are reported by the type analysis
+                    let mut value = Value::Struct(IndexMap::new());
+                    for index in spread_indices.iter().chain(std::iter::once(own_index)) {
+                        match evaluated.get(index) {
+                            Some(spread @ Value::Struct(_)) => {
+                                value.override_fields(spread.clone())
+                            }
+                            Some(Value::Unknown) | None => value = Value::Unknown,
+                            Some(_) => {}
+                        }
+                    }
+                    evaluated.insert(rule_index, value);
+                }
                 ValueDescription::Primitive(value) => {
                     evaluated.insert(rule_index, value.clone());
                 }
-                ValueDescription::Ref(ident) => {
-                    if let Some(ref_index) =
-                        rule_table.get(&module.clone().extend(ident.name.clone()))
-                    {
-                        let value = evaluated
-                            .get(ref_index)
-                            .expect("Value must be present")
-                            .clone();
+                ValueDescription::Not(operand) => {
+                    // non-boolean operands are reported by the type analysis
+                    let value = match evaluated.get(operand) {
+                        Some(Value::Bool(b)) => Value::Bool(!b),
+                        _ => Value::Unknown,
+                    };
+                    evaluated.insert(rule_index, value);
+                }
+                ValueDescription::BoolOp(operator, lhs, rhs) => {
+                    let value = match (evaluated.get(lhs), evaluated.get(rhs)) {
+                        (Some(Value::Bool(lhs)), Some(Value::Bool(rhs))) => {
+                            Value::Bool(operator.apply(*lhs, *rhs))
+                        }
+                        _ => Value::Unknown,
+                    };
+                    evaluated.insert(rule_index, value);
+                }
+                ValueDescription::Neg(operand) => {
+                    // non-numeric operands are reported by the type analysis
+                    let value = evaluated.get(operand).map_or(Value::Unknown, Value::negate);
+                    evaluated.insert(rule_index, value);
+                }
+                ValueDescription::ArithOp(operator, lhs, rhs) => {
+                    let operands = evaluated
+                        .get(lhs)
+                        .and_then(Value::as_f64)
+                        .zip(evaluated.get(rhs).and_then(Value::as_f64));
+                    // non-numeric operands are reported by the type analysis
+                    let value = match operands.map(|(lhs, rhs)| operator.apply(lhs, rhs)) {
+                        Some(Some(n)) => Value::from_f64(n),
+                        Some(None) => {
+                            errors.push(Error::new(
+                                ErrorMessage::DivisionByZero,
+                                rule.info.to_token_range(),
+                            ));
+                            Value::Unknown
+                        }
+                        None => Value::Unknown,
+                    };
+                    evaluated.insert(rule_index, value);
+                }
+                ValueDescription::Ref(ident, segments) => {
+                    let value = match resolve(ident, segments) {
+                        Some(Resolved::Rule(ref_index, _)) => {
+                            Some((evaluated.get(&ref_index).expect("Value must be present"), 0))
+                        }
+                        Some(Resolved::Value(value, len)) => Some((value, len)),
+                        None => None,
+                    };
+                    if let Some((value, len)) = value {
+                        let value = project(value, &segments[len..], errors);
                         evaluated.insert(rule_index, value);
                     } else {
+                        if !segments.is_empty() {
+                            // TODO: implement lookup
+                            errors.push(Error::new(
+                                ErrorMessage::Unsupported("module references".to_string()),
+                                rule.info.to_token_range(),
+                            ));
+                        }
                         evaluated.insert(rule_index, Value::Unknown);
                     }
                 }
@@ -309,5 +685,255 @@ fn evaluate(
             }
         }
     }
-    evaluated
+    (evaluated, recursive)
+}
+
+/// Rules, whose values the value of the description is made of.
+fn dependencies<'a>(
+    value_description: &ValueDescription,
+    resolve: impl Fn(&Ident, &[Ident]) -> Option<Resolved<'a>>,
+) -> Vec<RuleIndex> {
+    match value_description {
+        ValueDescription::Union(indices) | ValueDescription::List(indices) => indices.clone(),
+        ValueDescription::Struct(s) => s.values().map(|(index, _)| *index).collect(),
+        ValueDescription::Not(operand) | ValueDescription::Neg(operand) => vec![*operand],
+        ValueDescription::BoolOp(_, lhs, rhs) | ValueDescription::ArithOp(_, lhs, rhs) => {
+            vec![*lhs, *rhs]
+        }
+        ValueDescription::Composed(own, super_rule) => vec![*own, *super_rule],
+        ValueDescription::Spread(own, spreads) => spreads
+            .iter()
+            .chain(std::iter::once(own))
+            .copied()
+            .collect(),
+        ValueDescription::Ref(ident, inner_names) => match resolve(ident, inner_names) {
+            Some(Resolved::Rule(ref_index, _)) => vec![ref_index],
+            _ => Vec::new(),
+        },
+        ValueDescription::Primitive(_) | ValueDescription::Empty | ValueDescription::Unknown => {
+            Vec::new()
+        }
+    }
+}
+
+/// Access the inner value described by the segments of a reference.
+/// Index segments (e.g. `hosts::0`) select list elements,
+/// other segments (e.g. `server::host`) select struct fields.
+fn project(value: &Value, segments: &[Ident], errors: &mut Vec<Error>) -> Value {
+    let mut value = value;
+    for segment in segments {
+        let error_message = match (value, segment.as_index()) {
+            (Value::Unknown, _) => return Value::Unknown,
+            (Value::List(list), Some(index)) => match list.get(index) {
+                Some(element) => {
+                    value = element;
+                    continue;
+                }
+                None => ErrorMessage::IndexOutOfBounds(index, list.len()),
+            },
+            (Value::Struct(fields), None) => match fields.get(&segment.name) {
+                Some(field) => {
+                    value = field;
+                    continue;
+                }
+                None => ErrorMessage::UndefinedField(segment.name.clone()),
+            },
+            (_, Some(_)) => ErrorMessage::NotIndexable,
+            (_, None) => ErrorMessage::FieldOfNonStruct,
+        };
+        errors.push(Error::new(error_message, segment.to_token_range()));
+        return Value::Unknown;
+    }
+    value.clone()
+}
+
+/// Everything needed to follow the rules after their evaluation.
+struct Context<'a> {
+    module: &'a GlobalIdent,
+    rules: &'a [Rule],
+    rule_table: &'a HashMap<GlobalIdent, RuleIndex>,
+    evaluated: &'a HashMap<RuleIndex, Value>,
+    imported: &'a Imported,
+}
+
+/// Value of the rule without its hidden fields and with its aliased fields renamed.
+fn emitted(context: &Context, rule_index: RuleIndex) -> Value {
+    let mut value = context
+        .evaluated
+        .get(&rule_index)
+        .cloned()
+        .expect("Rule must have been evaluated");
+    let mut paths = emit_paths(context, rule_index, &mut Vec::new());
+    for (path, emit) in &paths {
+        if *emit == FieldEmit::Hidden {
+            remove(&mut value, &path.segments);
+        }
+    }
+    // the paths name the fields by their names, so nested fields are renamed first
+    paths.sort_by_key(|(path, _)| std::cmp::Reverse(path.segments.len()));
+    for (path, emit) in &paths {
+        if let FieldEmit::Alias(alias) = emit {
+            rename(&mut value, &path.segments, alias);
+        }
+    }
+    value
+}
+
+/// Paths of all hidden and aliased fields inside of the value of the rule.
+///
+/// Fields hidden or aliased by a composed rule stay so, even if they are overwritten,
+/// e.g. a hidden field of a def is not emitted by an init of the def.
+/// If the own and the super rule alias a field, the alias of the own rule comes first.
+fn emit_paths(
+    context: &Context,
+    rule_index: RuleIndex,
+    visited: &mut Vec<RuleIndex>,
+) -> Vec<(ValuePath, FieldEmit)> {
+    // self references cannot be fully evaluated and are reported elsewhere
+    if visited.contains(&rule_index) {
+        return Vec::new();
+    }
+    let rule = context.rules.get(rule_index).expect("Rule must exist");
+    visited.push(rule_index);
+    let paths = match &rule.value_description {
+        ValueDescription::Struct(fields) => fields
+            .iter()
+            .flat_map(|(name, (field_index, emit))| {
+                let segment = PathSegment::Key(name.clone());
+                let field_path = (prefixed(segment.clone(), ValuePath::root()), emit.clone());
+                if *emit == FieldEmit::Hidden {
+                    return vec![field_path];
+                }
+                let mut paths: Vec<_> = Some(field_path)
+                    .filter(|_| *emit != FieldEmit::Name)
+                    .into_iter()
+                    .collect();
+                paths.extend(
+                    emit_paths(context, *field_index, visited)
+                        .into_iter()
+                        .map(|(path, emit)| (prefixed(segment.clone(), path), emit)),
+                );
+                paths
+            })
+            .collect(),
+        ValueDescription::List(indices) => indices
+            .iter()
+            .enumerate()
+            .flat_map(|(index, element_index)| {
+                emit_paths(context, *element_index, visited)
+                    .into_iter()
+                    .map(move |(path, emit)| (prefixed(PathSegment::Index(index), path), emit))
+            })
+            .collect(),
+        ValueDescription::Composed(own_index, super_index) => {
+            let mut paths = emit_paths(context, *own_index, visited);
+            // only struct fields are merged, other values replace the super value
+            if let Some(Value::Struct(_)) = context.evaluated.get(own_index) {
+                paths.extend(emit_paths(context, *super_index, visited));
+            }
+            paths
+        }
+        ValueDescription::Spread(own_index, spread_indices) => {
+            let mut paths = emit_paths(context, *own_index, visited);
+            for spread_index in spread_indices.iter().rev() {
+                paths.extend(emit_paths(context, *spread_index, visited));
+            }
+            paths
+        }
+        ValueDescription::Ref(ident, inner_names) => {
+            let (paths, len) = match context.imported.lookup(ident, inner_names) {
+                Some((global_ident, len)) => (
+                    context
+                        .imported
+                        .emit_paths
+                        .get(global_ident)
+                        .cloned()
+                        .unwrap_or_default(),
+                    len,
+                ),
+                None => (
+                    context
+                        .rule_table
+                        .get(&context.module.clone().extend(ident.name.clone()))
+                        .map(|ref_index| emit_paths(context, *ref_index, visited))
+                        .unwrap_or_default(),
+                    0,
+                ),
+            };
+            let segments: Vec<_> = inner_names[len..]
+                .iter()
+                .map(|segment| {
+                    segment.as_index().map_or_else(
+                        || PathSegment::Key(segment.name.clone()),
+                        PathSegment::Index,
+                    )
+                })
+                .collect();
+            paths
+                .into_iter()
+                .filter_map(|(path, emit)| {
+                    path.segments
+                        .strip_prefix(segments.as_slice())
+                        .filter(|rest| !rest.is_empty())
+                        .map(|rest| {
+                            let path = ValuePath {
+                                segments: rest.to_vec(),
+                            };
+                            (path, emit)
+                        })
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+    visited.pop();
+    paths
+}
+
+fn prefixed(segment: PathSegment, path: ValuePath) -> ValuePath {
+    let mut segments = vec![segment];
+    segments.extend(path.segments);
+    ValuePath { segments }
+}
+
+/// Rename the struct field at the path, if it exists, keeping its position.
+fn rename(value: &mut Value, segments: &[PathSegment], alias: &str) {
+    match (value, segments) {
+        (Value::Struct(fields), [PathSegment::Key(key)]) => {
+            if let Some((index, _, field)) = fields.shift_remove_full(key) {
+                fields.shift_insert(index, alias.to_string(), field);
+            }
+        }
+        (Value::Struct(fields), [PathSegment::Key(key), rest @ ..]) => {
+            if let Some(field) = fields.get_mut(key) {
+                rename(field, rest, alias);
+            }
+        }
+        (Value::List(elements), [PathSegment::Index(index), rest @ ..]) => {
+            if let Some(element) = elements.get_mut(*index) {
+                rename(element, rest, alias);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Remove the nested value at the path, if it exists.
+fn remove(value: &mut Value, segments: &[PathSegment]) {
+    match (value, segments) {
+        (Value::Struct(fields), [PathSegment::Key(key)]) => {
+            fields.shift_remove(key);
+        }
+        (Value::Struct(fields), [PathSegment::Key(key), rest @ ..]) => {
+            if let Some(field) = fields.get_mut(key) {
+                remove(field, rest);
+            }
+        }
+        (Value::List(elements), [PathSegment::Index(index), rest @ ..]) => {
+            if let Some(element) = elements.get_mut(*index) {
+                remove(element, rest);
+            }
+        }
+        _ => {}
+    }
 }
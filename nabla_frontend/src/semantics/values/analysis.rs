@@ -1,8 +1,12 @@
 use crate::{
-    ast::{Expr, List, Named, Single, Struct, StructField, StructOrList, Union, UnionAlternative},
+    ast::{
+        ArithOp, AstInfo, BoolOp, Expr, HasInfo, Ident, List, Named, Neg, Not, Single, Struct,
+        StructField, StructOrList, Union, UnionAlternative,
+    },
     eval::Eval,
-    semantics::values::{Rule, RuleIndex, ValueDescription},
+    semantics::values::{FieldEmit, Rule, RuleIndex, ValueDescription},
 };
+use indexmap::IndexMap;
 
 pub(super) fn analyze(expr: &Expr, rules: &mut Vec<Rule>) {
     expr.analyze(rules);
@@ -85,16 +89,95 @@ impl ValueAnalyzer for Single {
                 rules.push(rule);
                 rule_index(rules)
             }
+            Self::Not(not) => not.analyze(rules),
+            Self::BoolOp(bool_op) => bool_op.analyze(rules),
+            Self::Neg(neg) => neg.analyze(rules),
+            Self::ArithOp(arith_op) => arith_op.analyze(rules),
         }
     }
 }
 
+impl ValueAnalyzer for Not {
+    fn analyze(&self, rules: &mut Vec<Rule>) -> RuleIndex {
+        let operand = analyze_operand(self.single.as_deref(), &self.info, rules);
+        let rule = Rule {
+            value_description: ValueDescription::Not(operand),
+            is_default: false,
+            info: self.info.clone(),
+        };
+        rules.push(rule);
+        rule_index(rules)
+    }
+}
+
+impl ValueAnalyzer for BoolOp {
+    fn analyze(&self, rules: &mut Vec<Rule>) -> RuleIndex {
+        let lhs = self.lhs.analyze(rules);
+        let rhs = analyze_operand(self.rhs.as_deref(), &self.info, rules);
+        let rule = Rule {
+            value_description: ValueDescription::BoolOp(self.operator, lhs, rhs),
+            is_default: false,
+            info: self.info.clone(),
+        };
+        rules.push(rule);
+        rule_index(rules)
+    }
+}
+
+impl ValueAnalyzer for Neg {
+    fn analyze(&self, rules: &mut Vec<Rule>) -> RuleIndex {
+        let operand = analyze_operand(self.single.as_deref(), &self.info, rules);
+        let rule = Rule {
+            value_description: ValueDescription::Neg(operand),
+            is_default: false,
+            info: self.info.clone(),
+        };
+        rules.push(rule);
+        rule_index(rules)
+    }
+}
+
+impl ValueAnalyzer for ArithOp {
+    fn analyze(&self, rules: &mut Vec<Rule>) -> RuleIndex {
+        let lhs = self.lhs.analyze(rules);
+        let rhs = analyze_operand(self.rhs.as_deref(), &self.info, rules);
+        let rule = Rule {
+            value_description: ValueDescription::ArithOp(self.operator, lhs, rhs),
+            is_default: false,
+            info: self.info.clone(),
+        };
+        rules.push(rule);
+        rule_index(rules)
+    }
+}
+
+/// Analyze the operand of an operation, which is unknown if it is missing.
+fn analyze_operand(single: Option<&Single>, info: &AstInfo, rules: &mut Vec<Rule>) -> RuleIndex {
+    single
+        .map(|single| single.analyze(rules))
+        .unwrap_or_else(|| {
+            let rule = Rule {
+                value_description: ValueDescription::Unknown,
+                is_default: false,
+                info: info.clone(),
+            };
+            rules.push(rule);
+            rule_index(rules)
+        })
+}
+
 impl ValueAnalyzer for Named {
     fn analyze(&self, rules: &mut Vec<Rule>) -> RuleIndex {
-        let ident = self.flatten_name();
+        let segments: Option<Vec<Ident>> = self
+            .inner_names
+            .iter()
+            .map(|inner_name| inner_name.name.clone())
+            .collect();
         let ident_rule_index = {
-            let info = ident.info.clone();
-            let value_description = ValueDescription::Ref(ident);
+            let info = self.flatten_name().info;
+            let value_description = segments.map_or(ValueDescription::Unknown, |segments| {
+                ValueDescription::Ref(self.name.clone(), segments)
+            });
             let rule = Rule {
                 value_description,
                 is_default: false,
@@ -110,6 +193,7 @@ impl ValueAnalyzer for Named {
                 StructOrList::List(l) => l.analyze(rules),
             })
             .map_or(ident_rule_index, |expr_rule_index| {
+                // the literal overrides the defaults of the def
                 let value_description =
                     ValueDescription::Composed(expr_rule_index, ident_rule_index);
                 let rule = Rule {
@@ -124,17 +208,22 @@ impl ValueAnalyzer for Named {
 }
 
 impl ValueAnalyzer for Struct {
+    /// The last of duplicate fields wins and keeps the position of the first,
+    /// the duplicates are reported by the type analysis.
     fn analyze(&self, rules: &mut Vec<Rule>) -> RuleIndex {
-        let map = self
-            .fields
-            .iter()
-            .flatten()
-            .map(|field| {
-                let name = field.name.name.clone();
-                let index = field.analyze(rules);
-                (name, index)
-            })
-            .collect();
+        let mut map = IndexMap::new();
+        for field in self.fields.iter().flatten() {
+            let name = field.name.name.clone();
+            let index = field.analyze(rules);
+            let emit = if field.hidden {
+                FieldEmit::Hidden
+            } else if field.emit_name() != name {
+                FieldEmit::Alias(field.emit_name().to_string())
+            } else {
+                FieldEmit::Name
+            };
+            map.insert(name, (index, emit));
+        }
         let value_description = ValueDescription::Struct(map);
         let rule = Rule {
             value_description,
@@ -142,6 +231,21 @@ impl ValueAnalyzer for Struct {
             info: self.info.clone(),
         };
         rules.push(rule);
+        let own_index = rule_index(rules);
+        if self.spreads.is_empty() {
+            return own_index;
+        }
+        let spread_indices = self
+            .spreads
+            .iter()
+            .filter_map(|spread| spread.single.as_ref())
+            .map(|single| single.analyze(rules))
+            .collect();
+        rules.push(Rule {
+            value_description: ValueDescription::Spread(own_index, spread_indices),
+            is_default: false,
+            info: self.info.clone(),
+        });
         rule_index(rules)
     }
 }
@@ -169,6 +273,7 @@ impl ValueAnalyzer for StructField {
         let expr_index = self.expr.as_ref().map(|expr| expr.analyze(rules));
         match (type_expr_index, expr_index) {
             (Some(type_expr_index), Some(expr_index)) => {
+                // the default of the field overrides the defaults of its type
                 let value_description = ValueDescription::Composed(expr_index, type_expr_index);
                 let rule = Rule {
                     value_description,
@@ -1,22 +1,44 @@
+use crate::diagnostics::Severity;
+use std::{rc::Rc, sync::Arc};
 use thiserror::Error;
 
 pub const LBRACKET: &str = "[";
 pub const RBRACKET: &str = "]";
 pub const LCURLY: &str = "{";
 pub const RCURLY: &str = "}";
+pub const LPAREN: &str = "(";
+pub const RPAREN: &str = ")";
+pub const DOT_DOT: &str = "..";
+pub const SPREAD: &str = "...";
 pub const DOUBLE_COLON: &str = "::";
 pub const STAR: &str = "*";
 pub const PIPE: &str = "|";
+pub const OR: &str = "||";
+pub const AND: &str = "&&";
+pub const NOT: &str = "!";
+pub const PLUS: &str = "+";
+pub const MINUS: &str = "-";
+pub const SLASH: &str = "/";
 pub const EQ: &str = "=";
 pub const COLON: &str = ":";
 pub const USE: &str = "use";
 pub const DEF: &str = "def";
 pub const LET: &str = "let";
+/// Starts a test block, e.g. `test local { ... }`, but is an identifier everywhere else
+pub const TEST: &str = "test";
 pub const AS: &str = "as";
 pub const TRUE: &str = "true";
 pub const FALSE: &str = "false";
 pub const NULL: &str = "null";
 pub const EOF: &str = "";
+/// Words, that are lexed as identifiers, but might become keywords in the future
+pub const RESERVED: [&str; 4] = ["if", "match", "in", "priv"];
+
+/// Operators of other languages, that are written instead of `=`.
+/// They are lexed as one `TokenType::Unknown` and parsed as `=`.
+pub const EQ_LOOKALIKES: [&str; 3] = ["==", ":=", "=>"];
+/// UTF-8 byte order mark, as written by some editors at the start of a file
+pub const BOM: &str = "\u{feff}";
 
 pub type TextRange = std::ops::Range<usize>;
 pub type TokenRange = std::ops::Range<usize>;
@@ -36,9 +58,19 @@ pub enum TokenType {
     RBracket,
     LCurly,
     RCurly,
+    LParen,
+    RParen,
+    DotDot,
+    Spread,
     DoubleColon,
     Star,
     Pipe,
+    Or,
+    And,
+    Not,
+    Plus,
+    Minus,
+    Slash,
     Eq,
     Colon,
     Use,
@@ -48,13 +80,15 @@ pub enum TokenType {
     True,
     False,
     Null,
-    String(String),
-    Char(String),
+    String(Arc<str>),
+    Char(Arc<str>),
     Number(String),
     Ident(String),
     Whitespace(String),
     Comment(String),
     Unknown(String),
+    /// Leading byte order mark, kept so the original file can be reproduced
+    Bom,
     Eof,
 }
 
@@ -63,10 +97,37 @@ pub enum TokenType {
 pub enum ErrorMessage {
     #[error("Missing closing single quote")]
     MissingClosingSingleQuote,
+    #[error("Missing closing double quote before the end of the line")]
+    MissingClosingDoubleQuote,
     #[error("Missing decimals after comma")]
     MissingDecimals,
+    #[error("Missing digits of the exponent")]
+    MissingExponent,
+    #[error("`_` must be between two digits")]
+    MisplacedSeparator,
     #[error("Unknown character")]
     Unknown,
+    #[error("Unknown escape sequence `{0}`, expected one of `\\\"` `\\\\` `\\n` `\\t` `\\r` `\\u{{...}}`")]
+    InvalidEscape(String),
+    #[error("`{0}` is reserved for a future keyword")]
+    Reserved(String),
+    #[error("More than {0} tokens, the rest of the file is not analyzed")]
+    TooManyTokens(usize),
+    #[error("`==` is not valid nabla; use `=`")]
+    DoubleEq,
+    #[error("`:=` is not valid nabla; use `=`")]
+    ColonEq,
+    #[error("`=>` is not valid nabla; use `=`")]
+    FatArrow,
+}
+
+impl ErrorMessage {
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::Reserved(_) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
 }
 
 /// Lexical error
@@ -79,7 +140,7 @@ pub struct Error {
 }
 
 /// A token, defined by its token type and text range.
-/// Also contains errors that occurred during lexical analysis of the token.
+/// Errors that occurred during lexical analysis are collected in `LexerResult::errors`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Token {
     pub token_type: TokenType,
@@ -99,6 +160,19 @@ impl Error {
 }
 
 impl TokenType {
+    /// Whether the token carries no meaning for the parser and only ends up in a `Prelude`.
+    pub const fn is_trivia(&self) -> bool {
+        matches!(self, Self::Whitespace(_) | Self::Comment(_) | Self::Bom)
+    }
+
+    /// Whether the token is a keyword, e.g. `def`.
+    pub const fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            Self::Use | Self::Def | Self::Let | Self::As | Self::True | Self::False | Self::Null
+        )
+    }
+
     pub const fn as_static_str(&self) -> Option<&'static str> {
         use TokenType::*;
         match self {
@@ -106,9 +180,19 @@ impl TokenType {
             RBracket => Some(RBRACKET),
             LCurly => Some(LCURLY),
             RCurly => Some(RCURLY),
+            LParen => Some(LPAREN),
+            RParen => Some(RPAREN),
+            DotDot => Some(DOT_DOT),
+            Spread => Some(SPREAD),
             DoubleColon => Some(DOUBLE_COLON),
             Star => Some(STAR),
             Pipe => Some(PIPE),
+            Or => Some(OR),
+            And => Some(AND),
+            Not => Some(NOT),
+            Plus => Some(PLUS),
+            Minus => Some(MINUS),
+            Slash => Some(SLASH),
             Eq => Some(EQ),
             Colon => Some(COLON),
             Use => Some(USE),
@@ -118,6 +202,7 @@ impl TokenType {
             True => Some(TRUE),
             False => Some(FALSE),
             Null => Some(NULL),
+            Bom => Some(BOM),
             Eof => Some(EOF),
             _ => None,
         }
@@ -128,6 +213,9 @@ impl TokenType {
 pub struct TokenStream<'a> {
     tokens: &'a [Token],
     first_ptr: *const Token,
+    /// For every token, the offset of the first token at or after it, that is not trivia.
+    /// Computed once, so leading trivia does not have to be scanned by every token parser.
+    trivia_ends: Rc<[usize]>,
     pub error_buffer: Vec<ParserError>,
 }
 
@@ -154,6 +242,16 @@ impl TokenStream<'_> {
         self.tokens
     }
 
+    /// Offset of the first token, that is not trivia (whitespace, comments or a byte order mark).
+    pub fn trivia_end(&self) -> usize {
+        let offset = self.location_offset();
+        self.trivia_ends
+            .get(offset)
+            .copied()
+            .unwrap_or(offset)
+            .min(offset + self.tokens.len())
+    }
+
     pub fn append_error(&mut self, error: ParserError) {
         self.error_buffer.push(error);
     }
@@ -167,9 +265,18 @@ impl<'a> ToTokenRange for TokenStream<'a> {
 
 impl<'a> From<&'a [Token]> for TokenStream<'a> {
     fn from(tokens: &'a [Token]) -> Self {
+        let mut trivia_ends = vec![tokens.len(); tokens.len() + 1];
+        for (offset, token) in tokens.iter().enumerate().rev() {
+            if token.token_type.is_trivia() {
+                trivia_ends[offset] = trivia_ends[offset + 1];
+            } else {
+                trivia_ends[offset] = offset;
+            }
+        }
         Self {
             tokens,
             first_ptr: tokens.as_ptr(),
+            trivia_ends: trivia_ends.into(),
             error_buffer: Vec::new(),
         }
     }
@@ -271,9 +378,9 @@ impl<'a> nom::InputIter for TokenStream<'a> {
 impl std::fmt::Display for TokenType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use TokenType::*;
-        let token_string = match self {
-            Ident(s) | String(s) | Char(s) | Number(s) | Whitespace(s) | Comment(s)
-            | Unknown(s) => s,
+        let token_string: &str = match self {
+            String(s) | Char(s) => s,
+            Ident(s) | Number(s) | Whitespace(s) | Comment(s) | Unknown(s) => s,
             static_token => static_token
                 .as_static_str()
                 .expect("Static representation must be available"),
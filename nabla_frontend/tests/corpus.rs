@@ -0,0 +1,167 @@
+//! Compares the diagnostics of every file in `tests/corpus` with its golden file.
+//!
+//! Each `<name>.nabla` is paired with a `<name>.diagnostics` file,
+//! which contains one diagnostic per line: `<range> <severity> <code>: <message>`.
+//! The range is given as `line:col..line:col` (both starting at 1, columns in bytes)
+//! and the code is the name of the error message variant.
+//! Secondary spans follow their diagnostic as indented `note <range>: <message>` lines.
+//!
+//! Run with `NABLA_BLESS=1 cargo test -p nabla_frontend --test corpus`
+//! to regenerate the golden files after an intended change.
+
+use nabla_frontend::{
+    diagnostics::{self, Severity},
+    lexer::{self, LexerResult},
+    line_index::LineIndex,
+    parser::{self, ParserResult},
+    semantics::{self, SemanticsResult},
+    token::TextRange,
+    GlobalIdent, ModuleAst,
+};
+use std::{fmt::Debug, fs, path::Path};
+
+const BLESS_VAR: &str = "NABLA_BLESS";
+const SOURCE_EXTENSION: &str = "nabla";
+const GOLDEN_EXTENSION: &str = "diagnostics";
+
+struct Entry {
+    range: TextRange,
+    severity: Severity,
+    code: String,
+    message: String,
+    notes: Vec<(TextRange, String)>,
+}
+
+impl Entry {
+    fn new(range: TextRange, severity: Severity, message: &(impl Debug + ToString)) -> Self {
+        Self {
+            range,
+            severity,
+            code: code(message),
+            message: message.to_string(),
+            notes: Vec::new(),
+        }
+    }
+}
+
+/// Name of the enum variant, e.g. `UndefinedIdent` for `UndefinedIdent("x")`.
+fn code(message: &impl Debug) -> String {
+    let debug = format!("{:?}", message);
+    debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Run all stages on the source and render their diagnostics, sorted by position.
+fn render(src: &str) -> String {
+    let LexerResult { tokens, errors } = lexer::lex(src);
+    let mut entries: Vec<_> = errors
+        .iter()
+        .map(|error| {
+            Entry::new(
+                error.range.clone(),
+                error.message.severity(),
+                &error.message,
+            )
+        })
+        .collect();
+    let ParserResult { ast, errors } = parser::parse(&tokens);
+    entries.extend(errors.iter().map(|error| {
+        let range = diagnostics::to_text_range(&tokens, &error.range);
+        Entry::new(range, Severity::Error, &error.message)
+    }));
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let SemanticsResult { errors, .. } = semantics::analyze(&module_ast);
+    entries.extend(errors.iter().map(|error| {
+        let range = diagnostics::to_text_range(&tokens, &error.range);
+        let mut entry = Entry::new(range, error.message.severity(), &error.message);
+        entry.notes = error
+            .secondary
+            .iter()
+            .map(|span| {
+                let range = diagnostics::to_text_range(&tokens, &span.range);
+                (range, span.message.clone())
+            })
+            .collect();
+        entry
+    }));
+    entries.sort_by(|e1, e2| {
+        (e1.range.start, e1.range.end, &e1.code, &e1.message).cmp(&(
+            e2.range.start,
+            e2.range.end,
+            &e2.code,
+            &e2.message,
+        ))
+    });
+    let line_index = LineIndex::new(src);
+    let render_range = |range: &TextRange| {
+        let (start, end) = line_index.line_col_range(range);
+        format!(
+            "{}:{}..{}:{}",
+            start.line + 1,
+            start.col + 1,
+            end.line + 1,
+            end.col + 1
+        )
+    };
+    let mut output = String::new();
+    for entry in &entries {
+        let severity = match entry.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        output.push_str(&format!(
+            "{} {} {}: {}\n",
+            render_range(&entry.range),
+            severity,
+            entry.code,
+            entry.message
+        ));
+        for (range, message) in &entry.notes {
+            output.push_str(&format!("  note {}: {}\n", render_range(range), message));
+        }
+    }
+    output
+}
+
+#[test]
+fn corpus() {
+    let bless = std::env::var_os(BLESS_VAR).is_some();
+    let corpus_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut sources: Vec<_> = fs::read_dir(&corpus_dir)
+        .expect("Corpus directory must exist")
+        .map(|entry| entry.expect("Reading corpus entry failed").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == SOURCE_EXTENSION))
+        .collect();
+    sources.sort();
+    assert!(!sources.is_empty(), "Corpus must not be empty");
+    let mut failures = Vec::new();
+    for source in &sources {
+        let src = fs::read_to_string(source).expect("Reading corpus file failed");
+        let actual = render(&src);
+        let golden = source.with_extension(GOLDEN_EXTENSION);
+        if bless {
+            fs::write(&golden, &actual).expect("Writing golden file failed");
+            continue;
+        }
+        let expected = fs::read_to_string(&golden).unwrap_or_default();
+        if expected != actual {
+            failures.push(format!(
+                "{}:\n--- expected\n{}--- actual\n{}",
+                source.display(),
+                expected,
+                actual
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} corpus files differ (rerun with {}=1 to bless):\n\n{}",
+        failures.len(),
+        sources.len(),
+        BLESS_VAR,
+        failures.join("\n")
+    );
+}
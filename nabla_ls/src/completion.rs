@@ -0,0 +1,215 @@
+use nabla_frontend::{
+    compile::CompileResult,
+    semantics::{namespace::Binding, types, uses},
+    token::TokenType,
+    GlobalIdent,
+};
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind};
+
+#[cfg(test)]
+mod tests;
+
+const USE_KEYWORD: &str = "use";
+const BUILT_IN_TYPES: [&str; 3] = [types::STRING, types::NUMBER, types::BOOL];
+const KEYWORDS: [&str; 3] = ["true", "false", "null"];
+
+/// Position, identifiers are completed at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Context {
+    /// After the colon of a type annotation
+    Type,
+    /// At the start of an expression, e.g. after `=` or `[`
+    Expr,
+}
+
+/// Complete the module path of a use or the identifier, that ends at the offset.
+///
+/// The result is the last compilation of the text.
+pub fn compute(
+    text: &str,
+    result: &CompileResult,
+    offset: usize,
+    modules: &[GlobalIdent],
+) -> Vec<CompletionItem> {
+    match text.get(..offset).and_then(use_path) {
+        Some(path) => module_paths(&path, modules),
+        None => identifiers(text, result, offset),
+    }
+}
+
+/// Complete the module path of the use statement.
+///
+/// After `use `, the roots of the module trees are completed
+/// and after `::` the next segment of the modules starting with the path.
+/// Inside of braces, e.g. `use root::{a b::`, the path before the braces is prepended.
+/// Items of a module are not completed.
+fn module_paths(path: &str, modules: &[GlobalIdent]) -> Vec<CompletionItem> {
+    let (parents, partial) = path
+        .rsplit_once("::")
+        .map_or((Vec::new(), path), |(parents, partial)| {
+            (parents.split("::").collect(), partial)
+        });
+    let mut names: Vec<_> = modules
+        .iter()
+        .filter_map(|module| {
+            let mut segments = std::iter::once(&module.root)
+                .chain(&module.path)
+                .map(String::as_str);
+            parents
+                .iter()
+                .all(|parent| segments.next() == Some(parent))
+                .then(|| segments.next())
+                .flatten()
+        })
+        .filter(|name| name.starts_with(partial))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+        .into_iter()
+        .map(|name| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::MODULE),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Complete the names of the namespace, the built-in types and, in expressions, the keywords.
+///
+/// Types complete defs and imports, expressions lets as well.
+/// Defs are completed as classes and lets as variables, so editors render their icons.
+fn identifiers(text: &str, result: &CompileResult, offset: usize) -> Vec<CompletionItem> {
+    let Some((context, partial)) = context(text, result, offset) else {
+        return Vec::new();
+    };
+    let module = &result.module_ast.name;
+    let mut globals: Vec<_> = result
+        .semantics
+        .bindings
+        .iter()
+        .filter(|(ident, _)| ident.root == module.root && ident.path.len() == 1)
+        .map(|(ident, binding)| (ident.path[0].clone(), Some(binding.clone()), None))
+        .collect();
+    let (namespace, _, _) = uses::analyze(&result.module_ast, None);
+    globals.extend(namespace.into_iter().map(|(name, ident)| {
+        let binding = result.semantics.bindings.get(&ident).cloned();
+        (name, binding, Some(ident.to_string()))
+    }));
+    globals.retain(|(_, binding, _)| context == Context::Expr || binding != &Some(Binding::Let));
+    globals.sort_unstable_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    let keywords: &[_] = match context {
+        Context::Type => &[],
+        Context::Expr => &KEYWORDS,
+    };
+    globals
+        .into_iter()
+        .map(|(name, binding, detail)| CompletionItem {
+            label: name,
+            kind: Some(match binding {
+                Some(Binding::Def) => CompletionItemKind::CLASS,
+                Some(Binding::Let) => CompletionItemKind::VARIABLE,
+                None => CompletionItemKind::REFERENCE,
+            }),
+            detail,
+            ..Default::default()
+        })
+        .chain(BUILT_IN_TYPES.iter().map(|name| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some("built-in type".to_string()),
+            ..Default::default()
+        }))
+        .chain(keywords.iter().map(|keyword| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            ..Default::default()
+        }))
+        .filter(|item| item.label.starts_with(partial))
+        .collect()
+}
+
+/// Context of the identifier, that ends at the offset, and its part before the offset.
+///
+/// The context is decided by the token before the identifier.
+/// Inside of strings and comments, after `::` and at field names, nothing is completed.
+fn context<'a>(text: &'a str, result: &CompileResult, offset: usize) -> Option<(Context, &'a str)> {
+    let at = result
+        .tokens
+        .iter()
+        .position(|token| token.range.start < offset && offset <= token.range.end);
+    let (before, partial) = match at.map(|index| (index, &result.tokens[index])) {
+        Some((index, token)) => match &token.token_type {
+            TokenType::Ident(_) => (index, text.get(token.range.start..offset)?),
+            TokenType::Whitespace(_) => (index, ""),
+            TokenType::Comment(_) => return None,
+            _ if offset == token.range.end => (index + 1, ""),
+            _ => return None,
+        },
+        None => (
+            result
+                .tokens
+                .iter()
+                .take_while(|token| token.range.end <= offset)
+                .count(),
+            "",
+        ),
+    };
+    let previous = result.tokens[..before].iter().rev().find(|token| {
+        !matches!(
+            token.token_type,
+            TokenType::Whitespace(_) | TokenType::Comment(_) | TokenType::Bom
+        )
+    });
+    let context = match previous.map(|token| &token.token_type) {
+        Some(TokenType::Colon) => Context::Type,
+        Some(
+            TokenType::Eq
+            | TokenType::LBracket
+            | TokenType::LParen
+            | TokenType::Pipe
+            | TokenType::Or
+            | TokenType::And
+            | TokenType::Not
+            | TokenType::Plus
+            | TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash,
+        ) => Context::Expr,
+        _ => return None,
+    };
+    Some((context, partial))
+}
+
+/// Path of the unfinished use statement at the end of the text without whitespace,
+/// e.g. `root::a::` for `use root::{b a::`.
+fn use_path(text: &str) -> Option<String> {
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+    let (start, _) = text.rmatch_indices(USE_KEYWORD).find(|(index, _)| {
+        !text[..*index].ends_with(is_ident_char)
+            && text[index + USE_KEYWORD.len()..].starts_with(char::is_whitespace)
+    })?;
+    // paths before the open braces
+    let mut groups: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut is_closed = false;
+    for c in text[start + USE_KEYWORD.len()..].chars() {
+        match c {
+            c if c.is_whitespace() || c == ',' => {
+                if groups.is_empty() && !current.is_empty() {
+                    return None;
+                }
+                current.clear();
+            }
+            '{' if !is_closed => groups.push(std::mem::take(&mut current)),
+            '}' => {
+                groups.pop()?;
+                is_closed = groups.is_empty();
+                current.clear();
+            }
+            c if !is_closed && (is_ident_char(c) || c == ':') => current.push(c),
+            _ => return None,
+        }
+    }
+    Some(groups.concat() + &current)
+}
@@ -0,0 +1,118 @@
+use crate::completion::compute;
+use nabla_frontend::{compile, GlobalIdent};
+use pretty_assertions::assert_eq;
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind};
+
+fn modules() -> Vec<GlobalIdent> {
+    [&["config"][..], &["dir", "client"], &["dir", "server"]]
+        .iter()
+        .map(|path| {
+            GlobalIdent::default().extend_multiple(path.iter().map(|s| s.to_string()).collect())
+        })
+        .chain([GlobalIdent::new("lib".to_string()).extend("util".to_string())])
+        .collect()
+}
+
+/// Completions at the end of the source
+fn items(src: &str) -> Vec<CompletionItem> {
+    compute(src, &compile::compile(src), src.len(), &modules())
+}
+
+/// Labels of the completions at the end of the source
+fn labels(src: &str) -> Vec<String> {
+    items(src).into_iter().map(|item| item.label).collect()
+}
+
+#[test]
+fn module_roots() {
+    assert_eq!(vec!["lib", "root"], labels("use "));
+    assert_eq!(vec!["root"], labels("let x = 1\nuse r"));
+}
+
+#[test]
+fn path_segments() {
+    assert_eq!(vec!["config", "dir"], labels("use root::"));
+    assert_eq!(vec!["client", "server"], labels("use root::dir::"));
+    assert_eq!(vec!["server"], labels("use root::dir::s"));
+    assert_eq!(Vec::<String>::new(), labels("use root::nope::"));
+}
+
+#[test]
+fn inside_braces() {
+    assert_eq!(vec!["client", "server"], labels("use root::{config dir::"));
+    assert_eq!(vec!["client", "server"], labels("use root::dir::{server, "));
+}
+
+#[test]
+fn outside_of_use() {
+    assert_eq!(Vec::<String>::new(), labels("use"));
+    assert_eq!(Vec::<String>::new(), labels("reuse "));
+    assert_eq!(Vec::<String>::new(), labels("use root::config as c "));
+    assert_eq!(
+        vec!["config", "x", "String", "Number", "Bool", "true", "false", "null"],
+        labels("use root::{config}\nlet x = ")
+    );
+}
+
+#[test]
+fn type_annotation() {
+    let src = "use shared::Server\ndef Port = Number\nlet port = 80\ndef Config = { port: ";
+    assert_eq!(
+        vec!["Config", "Port", "Server", "String", "Number", "Bool"],
+        labels(src)
+    );
+    assert_eq!(vec!["Port"], labels("def Port = Number\nlet p: P"));
+}
+
+#[test]
+fn expression() {
+    let src = "def Port = Number\nlet port = 80\nlet list = [";
+    assert_eq!(
+        vec!["Port", "list", "port", "String", "Number", "Bool", "true", "false", "null"],
+        labels(src)
+    );
+    assert_eq!(vec!["port"], labels("let port = 80\ninit { a = p"));
+    assert_eq!(vec!["null"], labels("let a = n"));
+}
+
+#[test]
+fn kinds() {
+    let src = "use shared::Server\ndef Port = Number\nlet port = 80\nlet a = ";
+    let kinds: Vec<_> = items(src)
+        .into_iter()
+        .map(|item| (item.label, item.kind.unwrap(), item.detail))
+        .take(4)
+        .collect();
+    assert_eq!(
+        vec![
+            ("Port".to_string(), CompletionItemKind::CLASS, None),
+            (
+                "Server".to_string(),
+                CompletionItemKind::REFERENCE,
+                Some("shared::Server".to_string())
+            ),
+            ("a".to_string(), CompletionItemKind::VARIABLE, None),
+            ("port".to_string(), CompletionItemKind::VARIABLE, None),
+        ],
+        kinds
+    );
+}
+
+#[test]
+fn no_identifiers() {
+    let src = "def Port = Number\n";
+    // field names, strings, comments and inner names
+    assert_eq!(Vec::<String>::new(), labels(&format!("{}init {{ p", src)));
+    assert_eq!(Vec::<String>::new(), labels(&format!("{}let a = \"P", src)));
+    let commented = format!("{}let a = // P\n", src);
+    let result = compile::compile(&commented);
+    let offset = commented.len() - 1;
+    assert_eq!(
+        Vec::<CompletionItem>::new(),
+        compute(&commented, &result, offset, &modules())
+    );
+    assert_eq!(
+        Vec::<String>::new(),
+        labels(&format!("{}let a = Port::P", src))
+    );
+}
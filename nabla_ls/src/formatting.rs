@@ -0,0 +1,73 @@
+use crate::convert_text_range;
+use nabla_frontend::{
+    compile::CompileResult,
+    diagnostics::Stage,
+    format,
+    line_index::{LineCol, LineIndex},
+    token::TextRange,
+};
+use tower_lsp::lsp_types::{Position, Range, TextEdit};
+
+#[cfg(test)]
+mod tests;
+
+/// Edit, that replaces the whole text by its formatted text.
+///
+/// The result is the last compilation of the text.
+/// Texts with lexical or parse errors are not formatted, so a broken text is never mangled.
+/// A formatted text needs no edit.
+pub fn compute(text: &str, result: &CompileResult) -> Option<Vec<TextEdit>> {
+    if has_syntax_errors(result) {
+        return None;
+    }
+    let formatted = format::format(&result.module_ast.ast, text);
+    let line_index = LineIndex::new(text);
+    Some(edit(&line_index, text, 0..text.len(), formatted))
+}
+
+/// Edit, that formats the globals in the range, see [`compute`].
+pub fn compute_range(text: &str, result: &CompileResult, range: Range) -> Option<Vec<TextEdit>> {
+    if has_syntax_errors(result) {
+        return None;
+    }
+    let line_index = LineIndex::new(text);
+    let offset = |position: Position| {
+        line_index.position_offset(LineCol {
+            line: position.line as usize,
+            col: position.character as usize,
+        })
+    };
+    let range = offset(range.start)..offset(range.end);
+    let Some((replaced, formatted)) = format::format_range(&result.module_ast.ast, text, &range)
+    else {
+        return Some(Vec::new());
+    };
+    Some(edit(
+        &line_index,
+        &text[replaced.clone()],
+        replaced,
+        formatted,
+    ))
+}
+
+fn edit(
+    line_index: &LineIndex,
+    original: &str,
+    range: TextRange,
+    formatted: String,
+) -> Vec<TextEdit> {
+    if original == formatted {
+        return Vec::new();
+    }
+    vec![TextEdit {
+        range: convert_text_range(line_index, &range),
+        new_text: formatted,
+    }]
+}
+
+fn has_syntax_errors(result: &CompileResult) -> bool {
+    result
+        .errors
+        .iter()
+        .any(|error| error.is_error() && matches!(error.stage(), Stage::Lex | Stage::Parse))
+}
@@ -0,0 +1,110 @@
+use crate::{
+    formatting::{compute, compute_range},
+    NablaLS,
+};
+use nabla_frontend::compile;
+use pretty_assertions::assert_eq;
+use tower_lsp::{
+    lsp_types::{
+        DidOpenTextDocumentParams, DocumentFormattingParams, FormattingOptions, Position, Range,
+        TextDocumentIdentifier, TextDocumentItem, TextEdit, Url,
+    },
+    LanguageServer, LspService,
+};
+
+fn edit(range: Range, new_text: &str) -> TextEdit {
+    TextEdit {
+        range,
+        new_text: new_text.to_string(),
+    }
+}
+
+#[test]
+fn whole_document() {
+    let src = "let a=1\nlet b=2";
+    assert_eq!(
+        Some(vec![edit(
+            Range::new(Position::new(0, 0), Position::new(1, 7)),
+            "let a = 1\n\nlet b = 2\n"
+        )]),
+        compute(src, &compile::compile(src))
+    );
+}
+
+#[test]
+fn formatted_document() {
+    let src = "let a = 1\n";
+    assert_eq!(Some(Vec::new()), compute(src, &compile::compile(src)));
+}
+
+#[test]
+fn syntax_errors() {
+    for src in ["let a = ", "let a = 'b"] {
+        let result = compile::compile(src);
+        assert_eq!(None, compute(src, &result));
+        let range = Range::new(Position::new(0, 0), Position::new(0, 0));
+        assert_eq!(None, compute_range(src, &result, range));
+    }
+}
+
+#[test]
+fn semantic_errors_are_formatted() {
+    let src = "let a=b";
+    assert_eq!(
+        Some(vec![edit(
+            Range::new(Position::new(0, 0), Position::new(0, 7)),
+            "let a = b\n"
+        )]),
+        compute(src, &compile::compile(src))
+    );
+}
+
+#[test]
+fn range() {
+    let src = "let a=1\nlet b=2\nlet c=3\n";
+    let range = Range::new(Position::new(1, 2), Position::new(1, 2));
+    assert_eq!(
+        Some(vec![edit(
+            Range::new(Position::new(1, 0), Position::new(1, 7)),
+            "let b = 2"
+        )]),
+        compute_range(src, &compile::compile(src), range)
+    );
+}
+
+#[test]
+fn through_the_service() {
+    let (service, _) = LspService::new(NablaLS::new);
+    let uri = Url::parse("file:///config.nabla").unwrap();
+    let edits = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let server = service.inner();
+            server
+                .did_open(DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem::new(
+                        uri.clone(),
+                        "nabla".to_string(),
+                        1,
+                        "{a=1}".to_string(),
+                    ),
+                })
+                .await;
+            server
+                .formatting(DocumentFormattingParams {
+                    text_document: TextDocumentIdentifier::new(uri),
+                    options: FormattingOptions::default(),
+                    work_done_progress_params: Default::default(),
+                })
+                .await
+        })
+        .unwrap();
+    assert_eq!(
+        Some(vec![edit(
+            Range::new(Position::new(0, 0), Position::new(0, 5)),
+            "{\n    a = 1\n}\n"
+        )]),
+        edits
+    );
+}
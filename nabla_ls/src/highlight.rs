@@ -0,0 +1,36 @@
+use crate::convert_text_range;
+use nabla_frontend::{
+    indent,
+    lexer::{self, LexerResult},
+    line_index::LineIndex,
+    token::TextRange,
+};
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind};
+
+#[cfg(test)]
+mod tests;
+
+/// Highlight the curly or bracket at the offset together with its counterpart,
+/// so editors can show and auto-close the matching pair.
+///
+/// The offset may be right before or right after the curly or bracket.
+/// The innermost pair is preferred, e.g. at `}|}`.
+pub fn compute(text: &str, offset: usize) -> Vec<DocumentHighlight> {
+    let LexerResult { tokens, .. } = lexer::lex(text);
+    let touches = |range: &TextRange| range.start <= offset && offset <= range.end;
+    let line_index = LineIndex::new(text);
+    indent::bracket_pairs(&tokens)
+        .into_iter()
+        .filter(|(opening, closing)| touches(opening) || touches(closing))
+        .max_by_key(|(opening, _)| opening.start)
+        .map(|(opening, closing)| {
+            [opening, closing]
+                .iter()
+                .map(|range| DocumentHighlight {
+                    range: convert_text_range(&line_index, range),
+                    kind: Some(DocumentHighlightKind::TEXT),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
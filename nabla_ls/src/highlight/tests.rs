@@ -0,0 +1,43 @@
+use crate::highlight::compute;
+use pretty_assertions::assert_eq;
+use tower_lsp::lsp_types::{Position, Range};
+
+/// Highlighted ranges at the offset
+fn ranges(src: &str, offset: usize) -> Vec<Range> {
+    compute(src, offset)
+        .into_iter()
+        .map(|highlight| highlight.range)
+        .collect()
+}
+
+fn range(line: u32, col: u32) -> Range {
+    Range::new(Position::new(line, col), Position::new(line, col + 1))
+}
+
+#[test]
+fn matching_curly() {
+    let src = "Config {\n  port = 80\n}";
+    let expected = vec![range(0, 7), range(2, 0)];
+    assert_eq!(expected, ranges(src, 7));
+    assert_eq!(expected, ranges(src, src.len()));
+}
+
+#[test]
+fn innermost_pair() {
+    let src = "a = [{}]";
+    assert_eq!(vec![range(0, 5), range(0, 6)], ranges(src, 6));
+    assert_eq!(vec![range(0, 4), range(0, 7)], ranges(src, 4));
+}
+
+#[test]
+fn no_bracket() {
+    assert_eq!(Vec::<Range>::new(), ranges("a = [1 2]", 2));
+    assert_eq!(Vec::<Range>::new(), ranges("a = [1 2", 4));
+}
+
+#[test]
+fn utf16_columns() {
+    // `🦀` takes two UTF-16 code units
+    let src = "a = \"🦀\" [\r\n]";
+    assert_eq!(vec![range(0, 9), range(1, 0)], ranges(src, 11));
+}
@@ -0,0 +1,140 @@
+use crate::convert_text_range;
+use nabla_frontend::{
+    ast::{Def, Global, HasInfo, Ident, Let},
+    compile::CompileResult,
+    diagnostics,
+    line_index::LineIndex,
+    node::{Node, NodeKind, ToNode},
+    semantics::{namespace::Binding, uses},
+    token::{TokenRange, TokenType},
+    GlobalIdent,
+};
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
+
+#[cfg(test)]
+mod tests;
+
+/// Values longer than this number of characters are truncated
+pub const MAX_VALUE_WIDTH: usize = 200;
+
+/// Describe the global, whose name or reference is at the offset.
+///
+/// The hover shows the binding and the declared type of the global, e.g. `let port: Number`,
+/// or the inferred type for a let without annotation, followed by its value, if it is known.
+/// Imported globals are shown with their full name, e.g. `use shared::Server`.
+/// Field names, primitive types and everything else are not hovered.
+pub fn compute(text: &str, result: &CompileResult, offset: usize) -> Option<Hover> {
+    let index = result.tokens.iter().position(|token| {
+        matches!(token.token_type, TokenType::Ident(_))
+            && token.range.start <= offset
+            && offset <= token.range.end
+    })?;
+    let root = result.module_ast.ast.to_node();
+    let (parent, ident) = enclosing_ident(&root, index)?;
+    let name = ident.value.as_deref()?;
+    let local = result.module_ast.name.clone().extend(name.to_string());
+    let global = match parent.kind {
+        NodeKind::Def | NodeKind::Let => local,
+        NodeKind::Named | NodeKind::UseItem | NodeKind::Use => {
+            if result.semantics.bindings.contains_key(&local) {
+                local
+            } else {
+                let (namespace, _, _) = uses::analyze(&result.module_ast, None);
+                namespace.get(name)?.clone()
+            }
+        }
+        _ => return None,
+    };
+    let mut value = format!("```nabla\n{}\n```", signature(text, result, &global)?);
+    if let Some(known) = result
+        .semantics
+        .symbol_table
+        .get(&global)
+        .filter(|value| value.is_known())
+    {
+        value.push_str(&format!(
+            "\n\nValue: `{}`",
+            truncate(&known.to_string(), MAX_VALUE_WIDTH)
+        ));
+    }
+    let range = diagnostics::to_text_range(&result.tokens, &ident.range);
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(convert_text_range(&LineIndex::new(text), &range)),
+    })
+}
+
+/// Innermost identifier, that contains the token index, together with its parent.
+fn enclosing_ident(node: &Node, index: usize) -> Option<(&Node, &Node)> {
+    let child = node
+        .children
+        .iter()
+        .find(|child| child.range.contains(&index))?;
+    if child.kind == NodeKind::Ident {
+        Some((node, child))
+    } else {
+        enclosing_ident(child, index)
+    }
+}
+
+/// Binding and type of the global, e.g. `def Port = Number`.
+fn signature(text: &str, result: &CompileResult, global: &GlobalIdent) -> Option<String> {
+    if global.root != result.module_ast.name.root || global.path.len() != 1 {
+        return Some(format!("use {}", global));
+    }
+    let name = &global.path[0];
+    let is_named = |ident: &Option<Ident>| ident.as_ref().is_some_and(|ident| ident.name == *name);
+    let source = |range: &TokenRange| {
+        let range = diagnostics::to_text_range(&result.tokens, range);
+        text[range].trim().to_string()
+    };
+    match result.semantics.bindings.get(global)? {
+        Binding::Def => {
+            let def = result.module_ast.ast.globals.iter().find_map(|g| match g {
+                Global::Def(def @ Def { name, .. }) if is_named(name) => Some(def),
+                _ => None,
+            })?;
+            Some(match (&def.type_expr, &def.expr) {
+                (_, Some(expr)) => format!("def {} = {}", name, source(&expr.info().range)),
+                (Some(type_expr), None) => {
+                    format!("def {}: {}", name, source(&type_expr.info().range))
+                }
+                (None, None) => format!("def {}", name),
+            })
+        }
+        Binding::Let => {
+            let l = result.module_ast.ast.globals.iter().find_map(|g| match g {
+                Global::Let(l @ Let { name, .. }) if is_named(name) => Some(l),
+                _ => None,
+            })?;
+            let type_name = match &l.type_expr {
+                Some(type_expr) => Some(source(&type_expr.info().range)),
+                None => result
+                    .semantics
+                    .types
+                    .idents
+                    .get(global)
+                    .map(|rule_index| result.semantics.types.resolve(*rule_index))
+                    .filter(|resolved| resolved.is_known())
+                    .map(|resolved| resolved.to_string()),
+            };
+            Some(match type_name {
+                Some(type_name) => format!("let {}: {}", name, type_name),
+                None => format!("let {}", name),
+            })
+        }
+    }
+}
+
+/// Cut the text to the number of characters, marking the cut with `...`.
+fn truncate(text: &str, max_width: usize) -> String {
+    const ELLIPSIS: &str = "...";
+    if text.chars().count() <= max_width {
+        return text.to_string();
+    }
+    let kept = max_width.saturating_sub(ELLIPSIS.len());
+    text.chars().take(kept).chain(ELLIPSIS.chars()).collect()
+}
@@ -0,0 +1,77 @@
+use crate::hover::{compute, truncate};
+use nabla_frontend::compile;
+use pretty_assertions::assert_eq;
+use tower_lsp::lsp_types::{HoverContents, Position, Range};
+
+/// Markdown of the hover at the first occurrence of the pattern
+fn hover(src: &str, pattern: &str) -> Option<String> {
+    let offset = src.find(pattern).expect("pattern is in the source");
+    let result = compile::compile(src);
+    compute(src, &result, offset).map(|hover| match hover.contents {
+        HoverContents::Markup(markup) => markup.value,
+        contents => panic!("unexpected contents {:?}", contents),
+    })
+}
+
+#[test]
+fn def() {
+    let src = "def Port = Number\nlet port: Port = 80";
+    assert_eq!(
+        Some("```nabla\ndef Port = Number\n```".to_string()),
+        hover(src, "Port =")
+    );
+}
+
+#[test]
+fn let_with_type_and_value() {
+    let src = "def Port = Number\nlet port: Port = 80\ninit { a = port }";
+    let expected = Some("```nabla\nlet port: Port\n```\n\nValue: `80`".to_string());
+    assert_eq!(expected, hover(src, "port:"));
+    // references show the global they name
+    assert_eq!(expected, hover(src, "port }"));
+}
+
+#[test]
+fn inferred_type() {
+    let src = "let name = \"server\"";
+    assert_eq!(
+        Some("```nabla\nlet name: String\n```\n\nValue: `\"server\"`".to_string()),
+        hover(src, "name")
+    );
+}
+
+#[test]
+fn import() {
+    let src = "use shared::Server\ninit Server {}";
+    let expected = Some("```nabla\nuse shared::Server\n```".to_string());
+    assert_eq!(expected, hover(src, "Server {"));
+    assert_eq!(expected, hover(src, "Server\n"));
+}
+
+#[test]
+fn nothing_to_hover() {
+    let src = "def Config = { port: Number }\ninit Config { port = 80 }";
+    // primitive types and fields are no globals
+    assert_eq!(None, hover(src, "Number"));
+    assert_eq!(None, hover(src, "port ="));
+    assert_eq!(None, hover(src, "80"));
+}
+
+#[test]
+fn hover_range() {
+    let src = "let a = 1\nlet b = a";
+    let offset = src.rfind('a').unwrap();
+    let result = compile::compile(src);
+    let hover = compute(src, &result, offset + 1).unwrap();
+    assert_eq!(
+        Some(Range::new(Position::new(1, 8), Position::new(1, 9))),
+        hover.range
+    );
+}
+
+#[test]
+fn long_values() {
+    assert_eq!("abc", truncate("abc", 3));
+    assert_eq!("a...", truncate("abcdefg", 4));
+    assert_eq!("🦀...", truncate("🦀🦀🦀🦀🦀", 4));
+}
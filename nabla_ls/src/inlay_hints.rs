@@ -0,0 +1,178 @@
+use crate::convert_offset;
+use nabla_frontend::{
+    ast::{Expr, Global, Single, Struct, StructOrList},
+    diagnostics,
+    eval::Value,
+    lexer::{self, LexerResult},
+    line_index::LineIndex,
+    parser::{self, ParserResult},
+    semantics::{self, SemanticsResult},
+    token::{Token, TokenRange},
+    GlobalIdent, ModuleAst,
+};
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Range};
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum number of hints per request, so huge files stay responsive.
+pub const MAX_HINTS: usize = 1000;
+
+/// Hint at a text offset, before it is converted into its LSP representation.
+struct Hint {
+    offset: usize,
+    label: String,
+    kind: Option<InlayHintKind>,
+}
+
+/// Compute the inlay hints of a document within the range.
+///
+/// Lets without type annotation are hinted with their inferred type.
+/// Structs of a def inside of an init are hinted with the default values of fields,
+/// that are not set explicitly.
+/// At most `max_hints` hints are returned.
+pub fn compute(text: &str, range: Range, max_hints: usize) -> Vec<InlayHint> {
+    let LexerResult { tokens, .. } = lexer::lex(text);
+    let ParserResult { ast, .. } = parser::parse(&tokens);
+    let module_ast = ModuleAst::new(GlobalIdent::default(), ast);
+    let semantics_result = semantics::analyze(&module_ast);
+    let mut hints = Vec::new();
+    for global in &module_ast.ast.globals {
+        match global {
+            Global::Let(l) => {
+                if let (Some(name), None, Some(_)) = (&l.name, &l.type_expr, &l.expr) {
+                    let ident = module_ast.name.clone().extend(name.name.clone());
+                    if let Some(hint) =
+                        let_hint(&tokens, &semantics_result, &ident, &name.info.range)
+                    {
+                        hints.push(hint);
+                    }
+                }
+            }
+            Global::Init(init) => default_hints(
+                &tokens,
+                &module_ast,
+                &semantics_result,
+                &init.expr,
+                &mut hints,
+            ),
+            _ => {}
+        }
+    }
+    hints.sort_by_key(|hint| hint.offset);
+    let line_index = LineIndex::new(text);
+    hints
+        .into_iter()
+        .map(|hint| InlayHint {
+            position: convert_offset(&line_index, hint.offset),
+            label: InlayHintLabel::String(hint.label),
+            kind: hint.kind,
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(hint.kind.is_none()),
+            padding_right: Some(hint.kind.is_none()),
+            data: None,
+        })
+        .filter(|hint| range.start <= hint.position && hint.position <= range.end)
+        .take(max_hints)
+        .collect()
+}
+
+/// Hint the inferred type after the name of a let.
+fn let_hint(
+    tokens: &[Token],
+    semantics_result: &SemanticsResult,
+    ident: &GlobalIdent,
+    name_range: &TokenRange,
+) -> Option<Hint> {
+    let rule_index = semantics_result.types.idents.get(ident)?;
+    let resolved_type = semantics_result.types.resolve(*rule_index);
+    if !resolved_type.is_known() {
+        return None;
+    }
+    Some(Hint {
+        offset: diagnostics::to_text_range(tokens, name_range).end,
+        label: format!(": {}", resolved_type),
+        kind: Some(InlayHintKind::TYPE),
+    })
+}
+
+/// Hint the defaults of all structs of a def inside of the expression.
+fn default_hints(
+    tokens: &[Token],
+    module_ast: &ModuleAst,
+    semantics_result: &SemanticsResult,
+    expr: &Expr,
+    hints: &mut Vec<Hint>,
+) {
+    let Expr::Single(single) = expr else {
+        return;
+    };
+    match single {
+        Single::Named(named) => match &named.expr {
+            Some(StructOrList::Struct(s)) => {
+                let names = named.names();
+                if !names.is_empty() {
+                    let ident = module_ast
+                        .name
+                        .clone()
+                        .extend_multiple(names.into_iter().cloned().collect());
+                    if let Some(defaults) = semantics_result.symbol_table.get(&ident) {
+                        struct_default_hints(tokens, s, defaults, hints);
+                    }
+                }
+                struct_field_hints(tokens, module_ast, semantics_result, s, hints);
+            }
+            Some(StructOrList::List(l)) => l
+                .exprs
+                .iter()
+                .for_each(|expr| default_hints(tokens, module_ast, semantics_result, expr, hints)),
+            None => {}
+        },
+        Single::Struct(s) => struct_field_hints(tokens, module_ast, semantics_result, s, hints),
+        Single::List(l) => l
+            .exprs
+            .iter()
+            .for_each(|expr| default_hints(tokens, module_ast, semantics_result, expr, hints)),
+        _ => {}
+    }
+}
+
+fn struct_field_hints(
+    tokens: &[Token],
+    module_ast: &ModuleAst,
+    semantics_result: &SemanticsResult,
+    s: &Struct,
+    hints: &mut Vec<Hint>,
+) {
+    s.fields
+        .iter()
+        .flatten()
+        .flat_map(|field| field.expr.as_ref())
+        .for_each(|expr| default_hints(tokens, module_ast, semantics_result, expr, hints));
+}
+
+/// Hint the known default values, which are not overwritten by the struct,
+/// in front of its closing curly.
+fn struct_default_hints(tokens: &[Token], s: &Struct, defaults: &Value, hints: &mut Vec<Hint>) {
+    let (Value::Struct(defaults), Some(rcurly)) = (defaults, &s.rcurly) else {
+        return;
+    };
+    let defaults: Vec<_> = defaults
+        .iter()
+        .filter(|(name, value)| {
+            value.is_known()
+                && !s
+                    .fields
+                    .iter()
+                    .flatten()
+                    .any(|field| &&field.name.name == name)
+        })
+        .collect();
+    let offset = diagnostics::to_text_range(tokens, &rcurly.range).start;
+    hints.extend(defaults.into_iter().map(|(name, value)| Hint {
+        offset,
+        label: format!("{} = {}", name, value),
+        kind: None,
+    }));
+}
@@ -0,0 +1,67 @@
+use crate::inlay_hints::{compute, MAX_HINTS};
+use pretty_assertions::assert_eq;
+use tower_lsp::lsp_types::{InlayHint, InlayHintLabel, Position, Range};
+
+/// Range over the whole document
+fn full_range() -> Range {
+    Range::new(Position::new(0, 0), Position::new(u32::MAX, 0))
+}
+
+fn labels(hints: &[InlayHint]) -> Vec<(u32, u32, &str)> {
+    hints
+        .iter()
+        .map(|hint| match &hint.label {
+            InlayHintLabel::String(label) => {
+                (hint.position.line, hint.position.character, &**label)
+            }
+            InlayHintLabel::LabelParts(_) => panic!("Expected label string"),
+        })
+        .collect()
+}
+
+#[test]
+fn let_types() {
+    let src = r#"let port = 8080
+let host: String = "localhost"
+let hosts = [host]
+let broken = unknown
+"#;
+    let hints = compute(src, full_range(), MAX_HINTS);
+    assert_eq!(
+        vec![(0, 8, ": Number"), (2, 9, ": [String]")],
+        labels(&hints)
+    );
+}
+
+#[test]
+fn init_defaults() {
+    let src = r#"def Server = {
+    host: String
+    port: Number = 80
+}
+def Config = {
+    name: String = "app"
+    verbose: Bool = false
+    server: Server
+}
+Config {
+    verbose = true
+    server = Server { host = "localhost" }
+}
+"#;
+    let hints = compute(src, full_range(), MAX_HINTS);
+    assert_eq!(
+        vec![(11, 41, "port = 80"), (12, 0, "name = \"app\"")],
+        labels(&hints)
+    );
+}
+
+#[test]
+fn hints_in_range() {
+    let src = "let a = 1\nlet b = 2\nlet c = 3\nlet d = 4\n";
+    let range = Range::new(Position::new(1, 0), Position::new(3, 0));
+    let hints = compute(src, range, MAX_HINTS);
+    assert_eq!(vec![(1, 5, ": Number"), (2, 5, ": Number")], labels(&hints));
+    let hints = compute(src, full_range(), 3);
+    assert_eq!(3, hints.len());
+}
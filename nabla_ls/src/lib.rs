@@ -0,0 +1,404 @@
+use nabla_frontend::{
+    compile::{self, CompileOptions, CompileResult, Uses},
+    diagnostics::Diagnostic,
+    limits,
+    line_index::{LineCol, LineIndex},
+    semantics::{self, compat, structure::GlobalRanges, SemanticsResult},
+    token::TextRange,
+};
+use std::{collections::HashMap, sync::RwLock};
+use tower_lsp::{
+    jsonrpc::Result,
+    lsp_types::{
+        self, CompletionOptions, CompletionParams, CompletionResponse,
+        DidChangeConfigurationParams, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+        DidOpenTextDocumentParams, DocumentFormattingParams, DocumentHighlight,
+        DocumentHighlightParams, DocumentOnTypeFormattingOptions, DocumentOnTypeFormattingParams,
+        DocumentRangeFormattingParams, Hover, HoverParams, HoverProviderCapability,
+        InitializeParams, InitializeResult, InlayHint, InlayHintParams, MessageType, OneOf,
+        Position, Range, ServerCapabilities, ServerInfo, TextDocumentSyncCapability,
+        TextDocumentSyncKind, TextDocumentSyncOptions, TextEdit, Url,
+    },
+    Client, LanguageServer, LspService, Server,
+};
+use workspace::Workspace;
+
+mod completion;
+mod formatting;
+mod highlight;
+mod hover;
+mod inlay_hints;
+mod on_type_formatting;
+mod publish;
+mod workspace;
+
+/// Text of an open document and the result of its last compilation
+#[derive(Debug)]
+struct Document {
+    text: String,
+    result: CompileResult,
+}
+
+#[derive(Debug)]
+struct NablaLS {
+    client: Client,
+    /// Default target, inits are checked against.
+    /// Set by the `target` setting, e.g. `{ "target": "toml" }`.
+    target: RwLock<Option<compat::Target>>,
+    /// Forward references are reported, if set by the `strictOrder` setting.
+    strict_order: RwLock<bool>,
+    /// Maximum number of diagnostics published per document.
+    /// Set by the `maxDiagnostics` initialization option.
+    max_diagnostics: RwLock<usize>,
+    /// Latest state of all open documents
+    documents: RwLock<HashMap<Url, Document>>,
+    /// Modules of the workspace folder, uses are checked against.
+    /// Without a workspace folder, every use is accepted.
+    workspace: RwLock<Option<Workspace>>,
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for NablaLS {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        if let Some(options) = &params.initialization_options {
+            self.apply_settings(options).await;
+            if let Some(max_diagnostics) = options
+                .get("maxDiagnostics")
+                .and_then(|max_diagnostics| max_diagnostics.as_u64())
+            {
+                *self.max_diagnostics.write().expect("Lock is not poisoned") =
+                    max_diagnostics as usize;
+            }
+        }
+        #[allow(deprecated)]
+        let root_uri = params
+            .workspace_folders
+            .as_ref()
+            .and_then(|folders| folders.first())
+            .map(|folder| &folder.uri)
+            .or(params.root_uri.as_ref());
+        if let Some(root_dir) = root_uri.and_then(|uri| uri.to_file_path().ok()) {
+            *self.workspace.write().expect("Lock is not poisoned") =
+                Some(Workspace::scan(root_dir));
+        }
+        Ok(InitializeResult {
+            server_info: Some(ServerInfo {
+                name: "Nabla LS".to_string(),
+                version: Some("0.1".to_string()),
+            }),
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Options(
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::FULL),
+                        ..Default::default()
+                    },
+                )),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec![":".to_string()]),
+                    ..Default::default()
+                }),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: on_type_formatting::FIRST_TRIGGER_CHARACTER
+                        .to_string(),
+                    more_trigger_character: Some(
+                        on_type_formatting::MORE_TRIGGER_CHARACTERS
+                            .map(ToString::to_string)
+                            .to_vec(),
+                    ),
+                }),
+                document_highlight_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                ..Default::default()
+            },
+        })
+    }
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        self.client
+            .log_message(MessageType::INFO, "file opened!")
+            .await;
+        // the opened file may be new to the workspace
+        if let Some(workspace) = self
+            .workspace
+            .write()
+            .expect("Lock is not poisoned")
+            .as_mut()
+        {
+            workspace.rescan();
+        }
+        self.on_change(params.text_document.uri, params.text_document.text)
+            .await
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        self.client
+            .log_message(MessageType::INFO, "file changed!")
+            .await;
+        let mut changes = params.content_changes;
+        self.on_change(
+            params.text_document.uri,
+            std::mem::take(&mut changes[0].text),
+        )
+        .await
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        self.documents
+            .write()
+            .expect("Lock is not poisoned")
+            .remove(&params.text_document.uri);
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        // clients either send the settings of the server directly or nested by its name
+        let settings = params.settings.get("nabla").unwrap_or(&params.settings);
+        self.apply_settings(settings).await;
+        // the diagnostics of the open documents depend on the settings
+        let documents: Vec<_> = self
+            .documents
+            .read()
+            .expect("Lock is not poisoned")
+            .iter()
+            .map(|(uri, document)| (uri.clone(), document.text.clone()))
+            .collect();
+        for (uri, text) in documents {
+            self.on_change(uri, text).await;
+        }
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let documents = self.documents.read().expect("Lock is not poisoned");
+        Ok(documents.get(&params.text_document.uri).map(|document| {
+            inlay_hints::compute(&document.text, params.range, inlay_hints::MAX_HINTS)
+        }))
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let position = params.text_document_position;
+        let documents = self.documents.read().expect("Lock is not poisoned");
+        let workspace = self.workspace.read().expect("Lock is not poisoned");
+        // without a workspace folder, there are no modules to complete
+        let modules = workspace
+            .as_ref()
+            .map_or(&[][..], |workspace| &workspace.modules);
+        Ok(documents
+            .get(&position.text_document.uri)
+            .map(|Document { text, result }| {
+                let offset = LineIndex::new(text).position_offset(LineCol {
+                    line: position.position.line as usize,
+                    col: position.position.character as usize,
+                });
+                CompletionResponse::Array(completion::compute(text, result, offset, modules))
+            }))
+    }
+
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let position = params.text_document_position;
+        let documents = self.documents.read().expect("Lock is not poisoned");
+        Ok(documents.get(&position.text_document.uri).map(|document| {
+            on_type_formatting::compute(
+                &document.text,
+                position.position,
+                &params.ch,
+                &params.options,
+            )
+        }))
+    }
+
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let position = params.text_document_position_params;
+        let documents = self.documents.read().expect("Lock is not poisoned");
+        Ok(documents.get(&position.text_document.uri).map(|document| {
+            let offset = LineIndex::new(&document.text).position_offset(LineCol {
+                line: position.position.line as usize,
+                col: position.position.character as usize,
+            });
+            highlight::compute(&document.text, offset)
+        }))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params;
+        let documents = self.documents.read().expect("Lock is not poisoned");
+        Ok(documents
+            .get(&position.text_document.uri)
+            .and_then(|document| {
+                let offset = LineIndex::new(&document.text).position_offset(LineCol {
+                    line: position.position.line as usize,
+                    col: position.position.character as usize,
+                });
+                hover::compute(&document.text, &document.result, offset)
+            }))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        let documents = self.documents.read().expect("Lock is not poisoned");
+        Ok(documents
+            .get(&params.text_document.uri)
+            .and_then(|document| formatting::compute(&document.text, &document.result)))
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let documents = self.documents.read().expect("Lock is not poisoned");
+        Ok(documents
+            .get(&params.text_document.uri)
+            .and_then(|document| {
+                formatting::compute_range(&document.text, &document.result, params.range)
+            }))
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl NablaLS {
+    fn new(client: Client) -> Self {
+        Self {
+            client,
+            target: RwLock::new(None),
+            strict_order: RwLock::new(false),
+            max_diagnostics: RwLock::new(publish::DEFAULT_MAX_DIAGNOSTICS),
+            documents: RwLock::new(HashMap::new()),
+            workspace: RwLock::new(None),
+        }
+    }
+
+    async fn apply_settings(&self, settings: &lsp_types::LSPAny) {
+        self.set_target(settings).await;
+        let strict_order = settings
+            .get("strictOrder")
+            .and_then(|strict_order| strict_order.as_bool())
+            .unwrap_or_default();
+        *self.strict_order.write().expect("Lock is not poisoned") = strict_order;
+    }
+
+    /// Set the default target from the `target` setting.
+    /// Unknown targets are reported and disable the check.
+    async fn set_target(&self, settings: &lsp_types::LSPAny) {
+        let target = match settings.get("target").and_then(|target| target.as_str()) {
+            Some(name) => match name.parse() {
+                Ok(target) => Some(target),
+                Err(message) => {
+                    self.client.log_message(MessageType::WARNING, message).await;
+                    None
+                }
+            },
+            None => None,
+        };
+        *self.target.write().expect("Lock is not poisoned") = target;
+    }
+
+    async fn on_change(&self, uri: Url, text: String) {
+        if let Some(diagnostic) = publish::too_large(&text, limits::MAX_FILE_SIZE) {
+            // without the text, no inlay hints or hovers are computed either
+            self.documents
+                .write()
+                .expect("Lock is not poisoned")
+                .remove(&uri);
+            self.client
+                .publish_diagnostics(uri, vec![diagnostic], None)
+                .await;
+            return;
+        }
+        let options = semantics::Options {
+            strict_order: *self.strict_order.read().expect("Lock is not poisoned"),
+            ..Default::default()
+        };
+        let result = {
+            let workspace = self.workspace.read().expect("Lock is not poisoned");
+            let uses = match workspace.as_ref() {
+                Some(workspace) => Uses::Checked(workspace),
+                None => Uses::Unchecked,
+            };
+            compile::compile_with_options(
+                &text,
+                &CompileOptions {
+                    semantics: options,
+                    uses,
+                },
+            )
+        };
+        let CompileResult {
+            tokens,
+            module_ast,
+            semantics: SemanticsResult { inits, .. },
+            errors,
+        } = &result;
+        let globals = GlobalRanges::new(&module_ast.ast);
+        let mut diagnostics: Vec<_> = errors
+            .iter()
+            .map(|error| {
+                error.to_diagnostic(tokens).with_context(
+                    error
+                        .token_range()
+                        .and_then(|range| globals.enclosing(range)),
+                )
+            })
+            .collect();
+        let target = *self.target.read().expect("Lock is not poisoned");
+        if let (Some(target), Some(init)) = (target, inits.first()) {
+            diagnostics.extend(compat::check(module_ast, init, target, false).iter().map(
+                |error| {
+                    Diagnostic::from_semantic_error(tokens, error)
+                        .with_context(globals.enclosing(&error.range))
+                },
+            ));
+        }
+        let line_index = LineIndex::new(&text);
+        let max_diagnostics = *self.max_diagnostics.read().expect("Lock is not poisoned");
+        let diagnostics = publish::prepare(&uri, &line_index, diagnostics, max_diagnostics);
+        self.documents
+            .write()
+            .expect("Lock is not poisoned")
+            .insert(uri.clone(), Document { text, result });
+        self.client
+            .publish_diagnostics(uri, diagnostics, None)
+            .await;
+    }
+}
+
+/// Serve the language server over stdio until the client exits.
+///
+/// The tokio runtime is only started here,
+/// so binaries embedding the server stay synchronous otherwise.
+pub fn run() {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Building the tokio runtime failed")
+        .block_on(serve());
+}
+
+async fn serve() {
+    let stdin = tokio::io::stdin();
+    let stdout = tokio::io::stdout();
+
+    let (service, socket) = LspService::new(NablaLS::new);
+    Server::new(stdin, stdout, socket).serve(service).await;
+}
+
+fn convert_offset(line_index: &LineIndex, offset: usize) -> Position {
+    let position = line_index.position(offset);
+    Position::new(position.line as u32, position.col as u32)
+}
+
+fn convert_text_range(line_index: &LineIndex, range: &TextRange) -> Range {
+    let (start, end) = line_index.range(range);
+    Range {
+        start: Position::new(start.line as u32, start.col as u32),
+        end: Position::new(end.line as u32, end.col as u32),
+    }
+}
@@ -0,0 +1,58 @@
+use nabla_frontend::{
+    indent,
+    lexer::{self, LexerResult},
+    line_index::{LineCol, LineIndex},
+};
+use tower_lsp::lsp_types::{FormattingOptions, Position, Range, TextEdit};
+
+#[cfg(test)]
+mod tests;
+
+/// Characters, after which the current line is indented
+pub const FIRST_TRIGGER_CHARACTER: &str = "\n";
+pub const MORE_TRIGGER_CHARACTERS: [&str; 2] = ["}", "]"];
+
+/// Indent the line of the position after the character was typed.
+///
+/// A new line is indented to the level of the unclosed curlies and brackets before it.
+/// A line, which starts with the typed `}` or `]`, is dedented to the line of its opening token.
+/// Other lines are left unchanged.
+pub fn compute(
+    text: &str,
+    position: Position,
+    ch: &str,
+    options: &FormattingOptions,
+) -> Vec<TextEdit> {
+    let line_index = LineIndex::new(text);
+    let line_start = line_index.offset(LineCol {
+        line: position.line as usize,
+        col: 0,
+    });
+    let line = &text[line_start..];
+    let content = line.trim_start_matches([' ', '\t']);
+    if ch != FIRST_TRIGGER_CHARACTER && !content.starts_with(ch) {
+        return Vec::new();
+    }
+    let LexerResult { tokens, .. } = lexer::lex(text);
+    let expected = indentation(options).repeat(indent::line_depth(&tokens, line_start));
+    let current = &line[..line.len() - content.len()];
+    if current == expected {
+        return Vec::new();
+    }
+    vec![TextEdit {
+        range: Range::new(
+            Position::new(position.line, 0),
+            Position::new(position.line, current.len() as u32),
+        ),
+        new_text: expected,
+    }]
+}
+
+/// Whitespace of one indentation level.
+fn indentation(options: &FormattingOptions) -> String {
+    if options.insert_spaces {
+        " ".repeat(options.tab_size as usize)
+    } else {
+        "\t".to_string()
+    }
+}
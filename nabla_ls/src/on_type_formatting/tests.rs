@@ -0,0 +1,77 @@
+use crate::on_type_formatting::compute;
+use pretty_assertions::assert_eq;
+use tower_lsp::lsp_types::{FormattingOptions, Position, Range, TextEdit};
+
+fn spaces(tab_size: u32) -> FormattingOptions {
+    FormattingOptions {
+        tab_size,
+        insert_spaces: true,
+        ..Default::default()
+    }
+}
+
+fn edit(line: u32, end: u32, new_text: &str) -> TextEdit {
+    TextEdit {
+        range: Range::new(Position::new(line, 0), Position::new(line, end)),
+        new_text: new_text.to_string(),
+    }
+}
+
+#[test]
+fn newline_in_struct() {
+    let src = "def Config = {\n\n}";
+    assert_eq!(
+        vec![edit(1, 0, "    ")],
+        compute(src, Position::new(1, 0), "\n", &spaces(4))
+    );
+}
+
+#[test]
+fn newline_in_nested_list() {
+    let src = "Config {\n  hosts = [\n  \n}";
+    assert_eq!(
+        vec![edit(2, 2, "    ")],
+        compute(src, Position::new(2, 2), "\n", &spaces(2))
+    );
+}
+
+#[test]
+fn closing_curly_dedents() {
+    let src = "def Config = {\n  server: {\n    host: String\n    }";
+    assert_eq!(
+        vec![edit(3, 4, "  ")],
+        compute(src, Position::new(3, 5), "}", &spaces(2))
+    );
+}
+
+#[test]
+fn closing_after_content_is_unchanged() {
+    let src = "def Config = {\n  hosts: [String]";
+    assert_eq!(
+        Vec::<TextEdit>::new(),
+        compute(src, Position::new(1, 17), "]", &spaces(2))
+    );
+}
+
+#[test]
+fn correct_indentation_is_unchanged() {
+    let src = "Config {\n  port = 80\n}";
+    assert_eq!(
+        Vec::<TextEdit>::new(),
+        compute(src, Position::new(2, 1), "}", &spaces(2))
+    );
+}
+
+#[test]
+fn tabs() {
+    let src = "Config {\n  a = {\n";
+    let options = FormattingOptions {
+        tab_size: 4,
+        insert_spaces: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        vec![edit(2, 0, "\t\t")],
+        compute(src, Position::new(2, 0), "\n", &options)
+    );
+}
@@ -0,0 +1,103 @@
+use crate::convert_text_range;
+use nabla_frontend::{
+    diagnostics::{self, Diagnostic, Severity},
+    limits,
+    line_index::LineIndex,
+};
+use tower_lsp::lsp_types::{self, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Url};
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum number of diagnostics per document, if not configured otherwise.
+pub const DEFAULT_MAX_DIAGNOSTICS: usize = 100;
+
+/// Prepare the diagnostics of a document for publishing.
+///
+/// Diagnostics are merged and only the first `max_diagnostics` by position are kept,
+/// because cascading errors of an unfinished edit can be numerous.
+/// Suppressed diagnostics are summarized by an informational diagnostic
+/// at the location of the last kept one.
+pub fn prepare(
+    uri: &Url,
+    line_index: &LineIndex,
+    diagnostics: Vec<Diagnostic>,
+    max_diagnostics: usize,
+) -> Vec<lsp_types::Diagnostic> {
+    let mut diagnostics = diagnostics::merge(diagnostics);
+    let suppressed = diagnostics.len().saturating_sub(max_diagnostics);
+    diagnostics.truncate(max_diagnostics);
+    let mut diagnostics: Vec<_> = diagnostics
+        .into_iter()
+        .map(|diagnostic| convert_diagnostic(uri, line_index, diagnostic))
+        .collect();
+    if suppressed > 0 {
+        diagnostics.push(lsp_types::Diagnostic {
+            range: diagnostics
+                .last()
+                .map(|diagnostic| diagnostic.range)
+                .unwrap_or_default(),
+            message: format!("additional {} diagnostics suppressed", suppressed),
+            severity: Some(DiagnosticSeverity::INFORMATION),
+            ..Default::default()
+        });
+    }
+    diagnostics
+}
+
+/// Diagnostic, that replaces the analysis of a text larger than `max_size` bytes.
+///
+/// Huge documents, e.g. opened by accident, would otherwise exhaust the memory of the host.
+pub fn too_large(text: &str, max_size: usize) -> Option<lsp_types::Diagnostic> {
+    (text.len() > max_size).then(|| lsp_types::Diagnostic {
+        message: format!(
+            "file too large for analysis, limit {} MB",
+            max_size / limits::MEGABYTE
+        ),
+        severity: Some(DiagnosticSeverity::ERROR),
+        ..Default::default()
+    })
+}
+
+/// Convert a diagnostic into its LSP representation.
+/// Notes and merged diagnostics are added as related information
+/// and the enclosing global prefixes the message, like in the report of the CLI.
+fn convert_diagnostic(
+    uri: &Url,
+    line_index: &LineIndex,
+    diagnostic: Diagnostic,
+) -> lsp_types::Diagnostic {
+    let related_information = diagnostic
+        .notes
+        .into_iter()
+        .map(|note| (note.range, note.message))
+        .chain(
+            diagnostic
+                .related
+                .into_iter()
+                .map(|related| (related.range, related.message)),
+        )
+        .map(|(range, message)| DiagnosticRelatedInformation {
+            location: Location::new(uri.clone(), convert_text_range(line_index, &range)),
+            message,
+        })
+        .collect::<Vec<_>>();
+    lsp_types::Diagnostic {
+        range: convert_text_range(line_index, &diagnostic.range),
+        message: match diagnostic.context {
+            Some(context) => format!("[{}] {}", context, diagnostic.message),
+            None => diagnostic.message,
+        },
+        severity: Some(match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        source: Some("nabla".to_string()),
+        related_information: if related_information.is_empty() {
+            None
+        } else {
+            Some(related_information)
+        },
+        ..Default::default()
+    }
+}
@@ -0,0 +1,132 @@
+use crate::publish::{prepare, too_large, DEFAULT_MAX_DIAGNOSTICS};
+use nabla_frontend::{
+    diagnostics::{Diagnostic, Note, Stage},
+    lexer::{self, LexerResult},
+    limits,
+    line_index::LineIndex,
+    semantics::error::{Error, ErrorMessage},
+};
+use pretty_assertions::assert_eq;
+use tower_lsp::lsp_types::{
+    DiagnosticRelatedInformation, DiagnosticSeverity, Location, Position, Range, Url,
+};
+
+#[test]
+fn cap_diagnostics() {
+    let text = "x\n".repeat(1000);
+    let line_index = LineIndex::new(&text);
+    let uri = Url::parse("file:///config.nabla").unwrap();
+    // in reverse order, to check that the first ones by position are kept
+    let diagnostics = (0..1000)
+        .rev()
+        .map(|line| Diagnostic::new(Stage::Parse, "error".to_string(), 2 * line..2 * line + 1))
+        .collect();
+    let diagnostics = prepare(&uri, &line_index, diagnostics, DEFAULT_MAX_DIAGNOSTICS);
+    assert_eq!(DEFAULT_MAX_DIAGNOSTICS + 1, diagnostics.len());
+    assert_eq!(
+        Range::new(Position::new(99, 0), Position::new(99, 1)),
+        diagnostics[DEFAULT_MAX_DIAGNOSTICS - 1].range
+    );
+    let summary = diagnostics.last().unwrap();
+    assert_eq!("additional 900 diagnostics suppressed", summary.message);
+    assert_eq!(Some(DiagnosticSeverity::INFORMATION), summary.severity);
+    assert_eq!(
+        diagnostics[DEFAULT_MAX_DIAGNOSTICS - 1].range,
+        summary.range
+    );
+}
+
+#[test]
+fn no_summary_below_cap() {
+    let text = "x";
+    let line_index = LineIndex::new(text);
+    let uri = Url::parse("file:///config.nabla").unwrap();
+    let diagnostics = vec![Diagnostic::new(Stage::Lex, "error".to_string(), 0..1)];
+    let diagnostics = prepare(&uri, &line_index, diagnostics, 1);
+    assert_eq!(1, diagnostics.len());
+    assert_eq!("error", diagnostics[0].message);
+}
+
+#[test]
+fn notes_as_related_information() {
+    let text = "def a = {}\ndef a = {}\n";
+    let line_index = LineIndex::new(text);
+    let uri = Url::parse("file:///config.nabla").unwrap();
+    let mut diagnostic = Diagnostic::new(Stage::Semantics, "redeclared".to_string(), 15..16);
+    diagnostic.notes = vec![Note {
+        message: "first declared here".to_string(),
+        range: 4..5,
+    }];
+    let diagnostics = prepare(&uri, &line_index, vec![diagnostic], DEFAULT_MAX_DIAGNOSTICS);
+    assert_eq!(
+        Some(vec![DiagnosticRelatedInformation {
+            location: Location::new(uri, Range::new(Position::new(0, 4), Position::new(0, 5))),
+            message: "first declared here".to_string(),
+        }]),
+        diagnostics[0].related_information
+    );
+}
+
+#[test]
+fn context_in_message() {
+    let text = "def a = {}";
+    let line_index = LineIndex::new(text);
+    let uri = Url::parse("file:///config.nabla").unwrap();
+    let diagnostics = vec![
+        Diagnostic::new(Stage::Semantics, "error".to_string(), 8..10).with_context(Some("def a")),
+        Diagnostic::new(Stage::Lex, "error".to_string(), 0..1),
+    ];
+    let diagnostics = prepare(&uri, &line_index, diagnostics, DEFAULT_MAX_DIAGNOSTICS);
+    assert_eq!("error", diagnostics[0].message);
+    assert_eq!("[def a] error", diagnostics[1].message);
+    assert_eq!(Some("nabla".to_string()), diagnostics[1].source);
+}
+
+#[test]
+fn ranges_at_end_of_text() {
+    let text = "{ a = 1 }\n";
+    let LexerResult { tokens, .. } = lexer::lex(text);
+    let line_index = LineIndex::new(text);
+    let uri = Url::parse("file:///config.nabla").unwrap();
+    // token ranges past the last token and empty ranges, e.g. of errors at the end of the file
+    let diagnostics = [
+        tokens.len()..tokens.len() + 1,
+        tokens.len() + 5..tokens.len() + 5,
+        0..0,
+    ]
+    .into_iter()
+    .map(|range| {
+        let error = Error::new(ErrorMessage::MissingField("a".to_string()), range);
+        Diagnostic::from_semantic_error(&tokens, &error)
+    })
+    .collect();
+    let ranges: Vec<_> = prepare(&uri, &line_index, diagnostics, DEFAULT_MAX_DIAGNOSTICS)
+        .into_iter()
+        .map(|diagnostic| diagnostic.range)
+        .collect();
+    assert_eq!(
+        vec![
+            Range::new(Position::new(0, 0), Position::new(0, 0)),
+            Range::new(Position::new(1, 0), Position::new(1, 0)),
+        ],
+        ranges
+    );
+}
+
+#[test]
+fn too_large_text() {
+    let text = " ".repeat(limits::MAX_FILE_SIZE + 1);
+    let diagnostic = too_large(&text, limits::MAX_FILE_SIZE).expect("Text is too large");
+    assert_eq!(
+        "file too large for analysis, limit 32 MB",
+        diagnostic.message
+    );
+    assert_eq!(Some(DiagnosticSeverity::ERROR), diagnostic.severity);
+    assert_eq!(Range::default(), diagnostic.range);
+}
+
+#[test]
+fn text_at_size_limit() {
+    let text = " ".repeat(limits::MAX_FILE_SIZE);
+    assert_eq!(None, too_large(&text, limits::MAX_FILE_SIZE));
+}
@@ -0,0 +1,53 @@
+use nabla_frontend::{
+    resolver::{module_files, validate_module_name, ModuleResolver, ModuleSource, ResolveError},
+    GlobalIdent,
+};
+use std::path::PathBuf;
+
+const EXTENSION: &str = "nabla";
+
+/// Module files of the workspace folder, which are collected once and then cached.
+///
+/// The module `root::a::b` is the file `<root_dir>/a/b.nabla`.
+#[derive(Clone, Debug)]
+pub struct Workspace {
+    root_dir: PathBuf,
+    pub modules: Vec<GlobalIdent>,
+}
+
+impl Workspace {
+    pub fn scan(root_dir: PathBuf) -> Self {
+        let modules = module_files(&root_dir, EXTENSION);
+        Self { root_dir, modules }
+    }
+
+    /// Collect the module files again, e.g. after a file was added.
+    pub fn rescan(&mut self) {
+        self.modules = module_files(&self.root_dir, EXTENSION);
+    }
+}
+
+impl ModuleResolver for Workspace {
+    fn resolve(&self, ident: &GlobalIdent) -> Result<ModuleSource, ResolveError> {
+        validate_module_name(ident)?;
+        if !self.modules.contains(ident) {
+            return Err(ResolveError::NotFound(ident.clone()));
+        }
+        let mut path = self.root_dir.clone();
+        for segment in &ident.path {
+            path.push(segment);
+        }
+        path.set_extension(EXTENSION);
+        let src = std::fs::read_to_string(&path)
+            .map_err(|err| ResolveError::Io(ident.clone(), err.to_string()))?;
+        Ok(ModuleSource {
+            name: ident.clone(),
+            origin: path.display().to_string(),
+            src,
+        })
+    }
+
+    fn modules(&self) -> Vec<GlobalIdent> {
+        self.modules.clone()
+    }
+}